@@ -0,0 +1,83 @@
+// Movement and button updates on large synthetic maps, to measure the cost
+// of lookups and button scans as levels grow.
+use box_pushing_core::{
+    actor::Actor,
+    events::EventQueue,
+    map::{MapData, Tile, TileType},
+    pos::Pos,
+    Direction,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use std::hint::black_box;
+
+// A walled square room `side` cells across with a scattering of boxes,
+// buttons and doors, so it holds roughly `side * side` tiles.
+fn synthetic_map(side: i32) -> MapData {
+    let mut tiles = Vec::new();
+    for y in 0..side {
+        for x in 0..side {
+            let edge = y == 0 || x == 0 || y == side - 1 || x == side - 1;
+            let tile_type = if edge {
+                TileType::Wall1
+            } else {
+                match (y * 31 + x * 17) % 13 {
+                    0 => TileType::PushBox,
+                    1 => TileType::Button(((y + x) % 8) as u32),
+                    2 => TileType::Door(Some(((y * x) % 8) as u32), false),
+                    3 => TileType::WinPad,
+                    _ => TileType::Empty,
+                }
+            };
+            tiles.push(Tile::new(y, x, tile_type));
+        }
+    }
+    // Clear a corridor along row 1 for the player to walk.
+    tiles.retain(|t| t.pos().y != 1 || t.pos().x == 0 || t.pos().x == side - 1);
+    MapData::new(tiles, Pos::new(1, 1), None)
+}
+
+const SIDES: [i32; 3] = [50, 100, 200];
+
+fn move_actor(c: &mut Criterion) {
+    let mut group = c.benchmark_group("move_actor");
+    for side in SIDES {
+        let map = synthetic_map(side);
+        group.bench_with_input(BenchmarkId::from_parameter(side * side), &map, |b, map| {
+            let mut map = map.clone();
+            let mut player = Actor::player(map.player_spawn);
+            let mut events = EventQueue::new();
+            let mut direction = Direction::Right;
+            b.iter(|| {
+                // Walk the corridor back and forth.
+                if player.x == side - 2 {
+                    direction = Direction::Left;
+                } else if player.x == 1 {
+                    direction = Direction::Right;
+                }
+                black_box(map.move_actor(&mut player, direction, &mut events));
+                events.drain();
+            });
+        });
+    }
+    group.finish();
+}
+
+fn update_button_status(c: &mut Criterion) {
+    let mut group = c.benchmark_group("update_button_status");
+    for side in SIDES {
+        let map = synthetic_map(side);
+        group.bench_with_input(BenchmarkId::from_parameter(side * side), &map, |b, map| {
+            let mut map = map.clone();
+            let player = Actor::player(map.player_spawn);
+            let mut events = EventQueue::new();
+            b.iter(|| {
+                map.update_button_status(black_box(&player), &mut events);
+                events.drain();
+            });
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, move_actor, update_button_status);
+criterion_main!(benches);
@@ -0,0 +1,9 @@
+// Levels in their JSON form, as found in save files and snapshots.
+#![no_main]
+
+use box_pushing_core::map::MapData;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<MapData>(data);
+});
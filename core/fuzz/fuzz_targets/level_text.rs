@@ -0,0 +1,13 @@
+// Level files as community packs ship them: any text has to parse or give an
+// error, never panic.
+#![no_main]
+
+use box_pushing_core::level_file::{levels_to_text, parse_levels};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|text: &str| {
+    if let Ok(maps) = parse_levels(text) {
+        // Whatever parses has to survive being written back out.
+        let _ = levels_to_text(&maps);
+    }
+});
@@ -0,0 +1,12 @@
+// Level codes pasted in from chat, mangled or not.
+#![no_main]
+
+use box_pushing_core::share_code;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|code: &str| {
+    if let Ok(map) = share_code::decode(code) {
+        // A code that decodes has to encode again.
+        let _ = share_code::encode(&map);
+    }
+});
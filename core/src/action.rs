@@ -0,0 +1,73 @@
+// What the player asks the game to do, whatever key or button asked for it.
+// Front ends map their own input to actions (the terminal through the key
+// bindings in its config, the web page through key names), so the rules
+// never see a backend's input type.
+use crate::Direction;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum GameAction {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    // Moves of the second player in co-op. With no second player they move
+    // the only one, so either set of keys plays a solo game.
+    PartnerUp,
+    PartnerDown,
+    PartnerLeft,
+    PartnerRight,
+    // The next move runs: the player keeps going until something is in the
+    // way or underfoot.
+    Run,
+    // The next direction fires the grapple that way, on levels that give
+    // the player any.
+    Grapple,
+    // Power-ups from the inventory: a phase takes the next direction and
+    // goes through a wall that way; an extra undo takes back a move even
+    // once the level's undos have run out.
+    UsePhase,
+    UseUndo,
+    Undo,
+    Redo,
+    Restart,
+    Pause,
+    // Leave the game for the main menu.
+    Menu,
+    ShareCode,
+    ExportSolution,
+    // Write the board as it stands to a file, as a level starting there.
+    ExportBoard,
+    Editor,
+    // Ask the solver for the next push.
+    Hint,
+    // Turn sound off or back on.
+    Mute,
+    // Show the controls, tiles and rules.
+    Help,
+    // Say in words what is around the player.
+    Describe,
+    // Arrange the board by hand, in practice.
+    Practice,
+}
+
+impl GameAction {
+    pub fn direction(self) -> Option<Direction> {
+        match self {
+            Self::MoveUp => Some(Direction::Up),
+            Self::MoveDown => Some(Direction::Down),
+            Self::MoveLeft => Some(Direction::Left),
+            Self::MoveRight => Some(Direction::Right),
+            _ => None,
+        }
+    }
+    pub fn partner_direction(self) -> Option<Direction> {
+        match self {
+            Self::PartnerUp => Some(Direction::Up),
+            Self::PartnerDown => Some(Direction::Down),
+            Self::PartnerLeft => Some(Direction::Left),
+            Self::PartnerRight => Some(Direction::Right),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,95 @@
+// Things that move around a level. The player is an actor and boxes move as
+// actors when pushed; anything added later that walks (enemies, clones) gets a
+// kind of its own with its own movement rules, and moves through the same
+// `MapData::move_actor`.
+use crate::{pos::Pos, Direction};
+use serde::{Deserialize, Serialize};
+
+pub const PLAYER_GLYPH: char = 'X';
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum ActorKind {
+    Player,
+    Box,
+    SlipperyBox,
+}
+
+// What an actor may do when it moves.
+#[derive(Clone, Copy)]
+pub struct MoveRules {
+    // Shoves pushable things ahead of it.
+    pub pushes: bool,
+    // Can itself be shoved by an actor that pushes.
+    pub pushable: bool,
+    // Holds down buttons it stands on.
+    pub presses_buttons: bool,
+    // Once pushed, keeps going the same way until something stops it.
+    pub slides: bool,
+}
+
+impl ActorKind {
+    pub fn rules(self) -> MoveRules {
+        match self {
+            Self::Player => MoveRules {
+                pushes: true,
+                pushable: false,
+                presses_buttons: true,
+                slides: false,
+            },
+            Self::Box => MoveRules {
+                pushes: false,
+                pushable: true,
+                presses_buttons: true,
+                slides: false,
+            },
+            Self::SlipperyBox => MoveRules {
+                pushes: false,
+                pushable: true,
+                presses_buttons: true,
+                slides: true,
+            },
+        }
+    }
+    pub fn glyph(self) -> char {
+        match self {
+            Self::Player => PLAYER_GLYPH,
+            Self::Box => '@',
+            Self::SlipperyBox => '$',
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Actor {
+    pub y: i32,
+    pub x: i32,
+    pub glyph: char,
+    pub kind: ActorKind,
+}
+
+impl Actor {
+    pub fn new(kind: ActorKind, Pos { y, x }: Pos) -> Self {
+        Self {
+            y,
+            x,
+            glyph: kind.glyph(),
+            kind,
+        }
+    }
+    pub fn player(pos: Pos) -> Self {
+        Self::new(ActorKind::Player, pos)
+    }
+    pub fn pos(&self) -> Pos {
+        Pos::new(self.y, self.x)
+    }
+    pub fn set_pos(&mut self, pos: Pos) {
+        (self.y, self.x) = (pos.y, pos.x);
+    }
+    pub fn rules(&self) -> MoveRules {
+        self.kind.rules()
+    }
+    // Don't call this directly. MapData::move_actor checks what is in the way.
+    pub fn step(&mut self, direction: Direction) {
+        self.set_pos(self.pos() + direction);
+    }
+}
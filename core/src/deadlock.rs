@@ -0,0 +1,211 @@
+// Deadlock analysis: telling when a box, or the whole level, can no longer be
+// put right. Winning here means the player reaching a win pad, and boxes only
+// matter for holding buttons down and for what they stand in the way of. So:
+//
+// - a dead square, for a button id, is a cell no box can be pushed from onto
+//   any of that id's buttons, even with every box out of the way;
+// - a box is frozen when it can't be pushed along either axis, because of
+//   walls or other frozen boxes, so it will never move again (a box in a
+//   corner is the simplest case); a frozen box off any button is stuck;
+// - a position is lost when, with frozen boxes as walls and shut any closed
+//   door whose buttons too few boxes can still reach, the player can't get
+//   to a win pad.
+//
+// Everything uncertain is taken the hopeful way (doors open, other boxes
+// move aside), so a position called lost really is. Dead squares depend only
+// on the level and are worked out once in `Deadlocks::new`.
+use crate::{
+    map::{Id, MapData, TileType},
+    pos::{Bounds, Pos},
+    Direction,
+};
+use std::collections::{BTreeMap, HashSet};
+
+const AXES: [[Direction; 2]; 2] = [
+    [Direction::Up, Direction::Down],
+    [Direction::Left, Direction::Right],
+];
+
+pub struct Deadlocks {
+    bounds: Bounds,
+    // Cells nothing will ever stand on: walls and doors no button opens.
+    walls: HashSet<Pos>,
+    win_pads: Vec<Pos>,
+    buttons: BTreeMap<Id, Vec<Pos>>,
+    // Ids with step pads, whose doors are taken to open sooner or later.
+    pads: HashSet<Id>,
+    // For each button id, the cells a box can still be pushed from onto one
+    // of its buttons; every other cell is a dead square for it.
+    live: BTreeMap<Id, HashSet<Pos>>,
+    // Scripted tiles can open doors whatever the buttons say.
+    scripted: bool,
+}
+
+impl Deadlocks {
+    pub fn new(map: &MapData) -> Self {
+        let mut buttons: BTreeMap<Id, Vec<Pos>> = BTreeMap::new();
+        let mut win_pads = Vec::new();
+        // Step pads need no box, but can still open their doors.
+        let mut pads = HashSet::new();
+        for tile in map.tiles() {
+            match tile.tile_type {
+                TileType::Button(id) => buttons.entry(id).or_default().push(tile.pos()),
+                TileType::StepPad(id, ..) => {
+                    pads.insert(id);
+                }
+                TileType::WinPad => win_pads.push(tile.pos()),
+                _ => (),
+            }
+        }
+        let walls = map
+            .tiles()
+            .filter(|t| match t.tile_type {
+                TileType::Wall1 | TileType::Door(None, false) => true,
+                TileType::Door(Some(id), false) => {
+                    !buttons.contains_key(&id) && !pads.contains(&id)
+                }
+                _ => false,
+            })
+            .map(|t| t.pos())
+            .collect();
+        let mut deadlocks = Self {
+            bounds: map.bounds(),
+            walls,
+            win_pads,
+            buttons,
+            pads,
+            live: BTreeMap::new(),
+            scripted: map.scripts().is_some(),
+        };
+        deadlocks.live = deadlocks
+            .buttons
+            .iter()
+            .map(|(&id, buttons)| (id, deadlocks.pull_from(buttons)))
+            .collect();
+        deadlocks
+    }
+    fn is_wall(&self, pos: Pos) -> bool {
+        !self.bounds.contains(pos) || self.walls.contains(&pos)
+    }
+    // Every cell a box could be pushed from to reach one of `targets`, found
+    // by pulling boxes backwards from them over an empty level.
+    fn pull_from(&self, targets: &[Pos]) -> HashSet<Pos> {
+        let mut reached: HashSet<Pos> = targets.iter().copied().collect();
+        let mut stack = targets.to_vec();
+        while let Some(pos) = stack.pop() {
+            for direction in Direction::ALL {
+                // The box came from `from`, pushed by a player behind it.
+                let from = pos + direction;
+                let behind = from + direction;
+                if !self.is_wall(from) && !self.is_wall(behind) && reached.insert(from) {
+                    stack.push(from);
+                }
+            }
+        }
+        reached
+    }
+    // Whether a box at `pos` can never be pushed onto a button of `id`.
+    pub fn is_dead_square(&self, id: Id, pos: Pos) -> bool {
+        self.live.get(&id).is_none_or(|live| !live.contains(&pos))
+    }
+    // The boxes at `boxes` that can never move again.
+    pub fn frozen(&self, boxes: &HashSet<Pos>) -> HashSet<Pos> {
+        boxes
+            .iter()
+            .copied()
+            .filter(|&pos| self.is_frozen(pos, boxes, &mut HashSet::new()))
+            .collect()
+    }
+    // Boxes being checked count as walls while their neighbours are, so a
+    // pair that hold each other in place are both frozen.
+    fn is_frozen(&self, pos: Pos, boxes: &HashSet<Pos>, checking: &mut HashSet<Pos>) -> bool {
+        checking.insert(pos);
+        let frozen = AXES.iter().all(|axis| {
+            axis.iter().any(|&direction| {
+                let side = pos + direction;
+                self.is_wall(side)
+                    || checking.contains(&side)
+                    || (boxes.contains(&side) && self.is_frozen(side, boxes, checking))
+            })
+        });
+        checking.remove(&pos);
+        frozen
+    }
+    fn on_button(&self, pos: Pos) -> bool {
+        self.buttons.values().flatten().any(|&button| button == pos)
+    }
+    // Frozen boxes that aren't holding a button down, and so are of no more
+    // use to anyone.
+    pub fn stuck_boxes(&self, map: &MapData) -> Vec<Pos> {
+        let boxes = box_positions(map);
+        let mut stuck: Vec<Pos> = self
+            .frozen(&boxes)
+            .into_iter()
+            .filter(|&pos| !self.on_button(pos))
+            .collect();
+        stuck.sort();
+        stuck
+    }
+    // Whether the doors of `id` can still be opened with the boxes at
+    // `boxes`, the player holding one button. A step pad needs no box.
+    fn can_open(&self, id: Id, boxes: &HashSet<Pos>, frozen: &HashSet<Pos>) -> bool {
+        if self.pads.contains(&id) {
+            return true;
+        }
+        let Some(buttons) = self.buttons.get(&id) else {
+            return false;
+        };
+        let usable = boxes
+            .iter()
+            .filter(|&pos| {
+                buttons.contains(pos) || (!frozen.contains(pos) && !self.is_dead_square(id, *pos))
+            })
+            .count();
+        usable + 1 >= buttons.len()
+    }
+    // Whether the player at `player` can no longer reach a win pad, with
+    // boxes at `boxes` and `closed_doors` the closed doors that have a button.
+    pub fn is_lost(&self, player: Pos, boxes: &HashSet<Pos>, closed_doors: &[(Pos, Id)]) -> bool {
+        if self.win_pads.is_empty() {
+            return false;
+        }
+        let frozen = self.frozen(boxes);
+        let shut: HashSet<Pos> = closed_doors
+            .iter()
+            .filter(|&&(_, id)| !self.scripted && !self.can_open(id, boxes, &frozen))
+            .map(|&(pos, _)| pos)
+            .collect();
+        let mut reached = HashSet::from([player]);
+        let mut stack = vec![player];
+        while let Some(pos) = stack.pop() {
+            if self.win_pads.contains(&pos) {
+                return false;
+            }
+            for next in pos.neighbours() {
+                let blocked = self.is_wall(next) || frozen.contains(&next) || shut.contains(&next);
+                if !blocked && reached.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+        true
+    }
+    // `is_lost` for the level as it stands with the player at `player`.
+    pub fn is_map_lost(&self, map: &MapData, player: Pos) -> bool {
+        let closed: Vec<(Pos, Id)> = map
+            .tiles()
+            .filter_map(|t| match t.tile_type {
+                TileType::Door(Some(id), false) => Some((t.pos(), id)),
+                _ => None,
+            })
+            .collect();
+        self.is_lost(player, &box_positions(map), &closed)
+    }
+}
+
+fn box_positions(map: &MapData) -> HashSet<Pos> {
+    map.tiles()
+        .filter(|t| t.tile_type.is_pushable())
+        .map(|t| t.pos())
+        .collect()
+}
@@ -0,0 +1,32 @@
+// What is around the player, for describing the board in words to players
+// who can't see it. The front end turns this into a sentence in the player's
+// language; this only works out what there is to say.
+use crate::{
+    map::{Cell, MapData, TileType},
+    pos::Pos,
+    Direction,
+};
+
+pub struct Surroundings {
+    // The cell next to the player each way, leaving out bare floor.
+    pub sides: Vec<(Direction, Cell)>,
+    // Where the nearest win pad is from the player: rows down and columns
+    // right, negative for up and left. None if the level has none.
+    pub goal: Option<Pos>,
+}
+
+impl Surroundings {
+    pub fn new(map: &MapData, player: Pos) -> Self {
+        let sides = Direction::ALL
+            .into_iter()
+            .map(|direction| (direction, map.cell_at(player + direction)))
+            .filter(|(_, cell)| !cell.is_bare())
+            .collect();
+        let goal = map
+            .tiles()
+            .filter(|tile| tile.tile_type == TileType::WinPad)
+            .map(|tile| tile.pos() - player)
+            .min_by_key(|offset| (offset.y.abs() + offset.x.abs(), offset.y, offset.x));
+        Self { sides, goal }
+    }
+}
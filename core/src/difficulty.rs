@@ -0,0 +1,84 @@
+// Difficulty estimate from how hard the solver had to work: solution length
+// in pushes and moves, how much of the state space it searched and how many
+// pushes were on offer at each step.
+use crate::{
+    map::MapData,
+    solver::{solve, Solution, SolveResult, DEFAULT_MAX_STATES},
+};
+use std::fmt;
+
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+pub enum Rating {
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+            Self::Expert => "expert",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Difficulty {
+    pub score: u32,
+    pub pushes: usize,
+    pub moves: usize,
+    pub states: usize,
+    pub branching: f64,
+}
+
+impl Difficulty {
+    pub fn rating(&self) -> Rating {
+        match self.score {
+            0..=29 => Rating::Easy,
+            30..=59 => Rating::Medium,
+            60..=99 => Rating::Hard,
+            _ => Rating::Expert,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.rating(), self.score)
+    }
+}
+
+fn score(pushes: usize, moves: usize, states: usize, branching: f64) -> u32 {
+    let search = (states.max(1) as f64).log2();
+    (pushes as f64 * 3.0 + moves as f64 / 5.0 + search * 4.0 + branching * 2.0).round() as u32
+}
+
+// None if the level couldn't be solved within the solver's state limit.
+pub fn estimate(map: &MapData) -> Option<Difficulty> {
+    from_solution(&solve(map, DEFAULT_MAX_STATES))
+}
+
+pub fn from_solution(solution: &Solution) -> Option<Difficulty> {
+    let SolveResult::Solved(moves) = &solution.result else {
+        return None;
+    };
+    let stats = &solution.stats;
+    let branching = stats.branching();
+    Some(Difficulty {
+        score: score(
+            solution.pushes,
+            moves.len(),
+            stats.states_explored,
+            branching,
+        ),
+        pushes: solution.pushes,
+        moves: moves.len(),
+        states: stats.states_explored,
+        branching,
+    })
+}
@@ -0,0 +1,84 @@
+// The level editor's model: the level is held as rows of level-file
+// characters, so anything the file format can express can be placed, and a
+// finished edit is read back with the same parser that loads level files.
+use crate::{
+    level_file::{self, LevelFileError},
+    map::MapData,
+    pos::Pos,
+    transform::Transform,
+    Direction,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Editor {
+    pub rows: Vec<Vec<char>>,
+    pub cursor: Pos,
+    // The level's header lines, kept as they are.
+    header: String,
+}
+
+impl Editor {
+    pub fn new(map: &MapData) -> Self {
+        Self {
+            rows: level_file::level_grid(map),
+            cursor: map.player_spawn,
+            header: level_file::level_header(map),
+        }
+    }
+    // The character at `pos`, or ' ' outside the grid.
+    pub fn at(&self, Pos { y, x }: Pos) -> char {
+        if y < 0 || x < 0 {
+            return ' ';
+        }
+        self.rows
+            .get(y as usize)
+            .and_then(|row| row.get(x as usize))
+            .copied()
+            .unwrap_or(' ')
+    }
+    // The cursor can go anywhere right of and below the top-left corner; the
+    // grid grows when something is placed past its edge.
+    pub fn move_cursor(&mut self, direction: Direction) {
+        self.cursor = (self.cursor + direction).max(Pos::ZERO);
+    }
+    // Puts `c` under the cursor. A level has one spawn, so placing `P` clears
+    // the old one.
+    pub fn place(&mut self, c: char) {
+        if c == 'P' {
+            for cell in self.rows.iter_mut().flatten() {
+                if *cell == 'P' {
+                    *cell = ' ';
+                }
+            }
+        }
+        let (y, x) = (self.cursor.y as usize, self.cursor.x as usize);
+        if self.rows.len() <= y {
+            self.rows.resize(y + 1, Vec::new());
+        }
+        if self.rows[y].len() <= x {
+            self.rows[y].resize(x + 1, ' ');
+        }
+        self.rows[y][x] = c;
+    }
+    pub fn transform(&mut self, transform: Transform) -> Result<(), LevelFileError> {
+        let mut map = self.build()?;
+        map.transform(transform);
+        self.rows = level_file::level_grid(&map);
+        Ok(())
+    }
+    // Reads the grid back as a level, failing where a level file would.
+    pub fn build(&self) -> Result<MapData, LevelFileError> {
+        let mut text = self.header.clone();
+        for row in &self.rows {
+            if row.iter().all(|&c| c == ' ') {
+                // A blank line before the grid would be skipped, shifting every row.
+                text.push('.');
+            } else {
+                text.extend(row);
+            }
+            text.push('\n');
+        }
+        Ok(level_file::parse_levels(&text)?.remove(0))
+    }
+}
@@ -0,0 +1,169 @@
+// Things that happen during play. Movement, tiles and buttons push events onto
+// the game's queue as they happen; each frame the front end drains the queue
+// and hands the batch to whatever reacts to it (the status line, sound, ...).
+use crate::{
+    game::LevelError,
+    map::{Id, Item},
+    pos::Pos,
+    Direction,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::VecDeque, fmt};
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum GameEvent {
+    PlayerMoved { direction: Direction },
+    MoveBlocked { direction: Direction },
+    BoxPushed { from: Pos, to: Pos },
+    // The grapple pulled a box, or the player, from one cell to another.
+    Grappled { from: Pos, to: Pos },
+    ItemPickedUp { item: Item },
+    GemCollected,
+    // A button went down under the player or a box.
+    ButtonPressed { id: Id },
+    DoorOpened { id: Id },
+    // Doors with a close delay shut again after their buttons were let go.
+    DoorClosed { id: Id },
+    // The co-op doors opened or closed as their plates were held or let go.
+    HoldDoor { open: bool },
+    LevelWon { level: u32 },
+    // A warp sent the players ahead from one level to another.
+    Warped { from: u32, to: u32 },
+    // The player started the level over, or took back a move.
+    LevelRestarted,
+    MoveUndone,
+    // An action the player asked for did nothing.
+    Refused { reason: Refusal },
+    // In assist mode, a box was pushed where it can never move again.
+    BoxStuck { pos: Pos },
+    // The level couldn't be loaded, and the fallback map stands in for it.
+    LoadFailed { error: LevelError },
+    // The watched level file changed and was read in again, or couldn't be
+    // (the watcher keeps the reason).
+    LevelsReloaded,
+    ReloadFailed,
+}
+
+// Why an action did nothing. The front end puts it into words.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Refusal {
+    // A clicked cell can't be walked to.
+    NoPath,
+    NothingToGrapple,
+    NoGrapplesLeft,
+    NothingToPhase,
+    NoPhasesLeft,
+    NoUndosLeft,
+    NoExtraUndosLeft,
+    NothingToUndo,
+    NothingToRedo,
+}
+
+impl fmt::Display for Refusal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::NoPath => write!(f, "can't walk there"),
+            Self::NothingToGrapple => write!(f, "nothing to grapple"),
+            Self::NoGrapplesLeft => write!(f, "no grapples left"),
+            Self::NothingToPhase => write!(f, "nothing to phase through"),
+            Self::NoPhasesLeft => write!(f, "no phases left"),
+            Self::NoUndosLeft => write!(f, "no undos left"),
+            Self::NoExtraUndosLeft => write!(f, "no extra undos left"),
+            Self::NothingToUndo => write!(f, "nothing to undo"),
+            Self::NothingToRedo => write!(f, "nothing to redo"),
+        }
+    }
+}
+
+impl fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::PlayerMoved { .. } => write!(f, "moved"),
+            Self::MoveBlocked { .. } => write!(f, "blocked"),
+            Self::BoxPushed { .. } => write!(f, "box pushed"),
+            Self::Grappled { .. } => write!(f, "grappled"),
+            Self::ItemPickedUp { item } => write!(f, "picked up {}", item.name()),
+            Self::GemCollected => write!(f, "gem collected"),
+            Self::ButtonPressed { id } => write!(f, "button {} pressed", id),
+            Self::DoorOpened { id } => write!(f, "door {} opened", id),
+            Self::DoorClosed { id } => write!(f, "door {} closed", id),
+            Self::HoldDoor { open: true } => write!(f, "hold doors opened"),
+            Self::HoldDoor { open: false } => write!(f, "hold doors closed"),
+            Self::LevelWon { level } => write!(f, "level {} won", level),
+            Self::Warped { from, to } => write!(f, "warped from level {} to {}", from, to),
+            Self::LevelRestarted => write!(f, "level restarted"),
+            Self::MoveUndone => write!(f, "move undone"),
+            Self::Refused { reason } => write!(f, "{}", reason),
+            Self::BoxStuck { pos } => write!(f, "box stuck at {}", pos),
+            Self::LoadFailed { error } => write!(f, "{}", error),
+            Self::LevelsReloaded => write!(f, "reloaded"),
+            Self::ReloadFailed => write!(f, "reload failed"),
+        }
+    }
+}
+
+// The events a level can attach a message to with an `on` header (see
+// `level_file`). The first box pushed, a button or door of an id, a gem or a
+// power-up picked up.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Serialize, Deserialize)]
+pub enum Trigger {
+    Push,
+    Button(Id),
+    Door(Id),
+    Gem,
+    Item,
+}
+
+impl Trigger {
+    pub fn of(event: &GameEvent) -> Option<Self> {
+        match *event {
+            GameEvent::BoxPushed { .. } => Some(Self::Push),
+            GameEvent::ButtonPressed { id } => Some(Self::Button(id)),
+            GameEvent::DoorOpened { id } => Some(Self::Door(id)),
+            GameEvent::GemCollected => Some(Self::Gem),
+            GameEvent::ItemPickedUp { .. } => Some(Self::Item),
+            _ => None,
+        }
+    }
+}
+
+// As written in a level file: `push`, `button ID`, `door ID`, `gem` or `item`.
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Push => write!(f, "push"),
+            Self::Button(id) => write!(f, "button {}", id),
+            Self::Door(id) => write!(f, "door {}", id),
+            Self::Gem => write!(f, "gem"),
+            Self::Item => write!(f, "item"),
+        }
+    }
+}
+
+#[derive(Default, Clone, Serialize, Deserialize)]
+pub struct EventQueue {
+    events: VecDeque<GameEvent>,
+}
+
+impl EventQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn push(&mut self, event: GameEvent) {
+        self.events.push_back(event);
+    }
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+    pub fn iter(&self) -> impl Iterator<Item = &GameEvent> {
+        self.events.iter()
+    }
+    // Takes every queued event, oldest first. Events are logged here rather
+    // than when pushed, so the solver's scratch queues stay out of the log.
+    pub fn drain(&mut self) -> Vec<GameEvent> {
+        for event in &self.events {
+            log::debug!("event: {}", event);
+        }
+        self.events.drain(..).collect()
+    }
+}
@@ -0,0 +1,850 @@
+use crate::{
+    action::GameAction,
+    actor::{Actor, ActorKind},
+    deadlock::Deadlocks,
+    editor::Editor,
+    events::{EventQueue, GameEvent, Refusal, Trigger},
+    history::{History, Snapshot},
+    hot_reload::LevelWatcher,
+    level_file::level_to_text,
+    lurd,
+    map::{fallback_map, Exit, Item, MapData, MoveOutcome, TileType},
+    pathfind,
+    pos::Pos,
+    practice::Arranging,
+    Direction,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeSet, fmt};
+
+// The game advances in fixed ticks whether or not a key is pressed.
+pub const TICK_MS: u64 = 50;
+// How many ticks pass between checks of a watched level file.
+const RELOAD_POLL_TICKS: u64 = 5;
+// How many moves an event message stands in for the flavor text.
+pub const EVENT_FLAVOR_MOVES: usize = 5;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum LevelError {
+    Missing { level: u32, count: usize },
+    // The level has co-op tiles and this is a game for one.
+    NeedsPartner { level: u32 },
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Missing { level, count: 0 } => {
+                write!(f, "cannot load level {}: there are no levels", level)
+            }
+            Self::Missing { level, count } => write!(
+                f,
+                "cannot load level {}: there are only {} levels",
+                level, count
+            ),
+            Self::NeedsPartner { level } => {
+                write!(f, "cannot load level {}: it needs two players", level)
+            }
+        }
+    }
+}
+
+// A level to go to with `goto_level`. `Index` is clamped to the levels there
+// are; `Next` past the last level finishes the campaign and `Previous` stops
+// at the first, while the wrapping forms go round to the other end instead.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LevelId {
+    Index(u32),
+    Next,
+    Previous,
+    NextWrapping,
+    PreviousWrapping,
+}
+
+// Where `goto_level` ended up.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LevelChange {
+    Loaded(u32),
+    // There was no level after the last one; the state is now GameOver.
+    CampaignFinished,
+}
+
+// What the game is doing, which decides how keys are read and what is drawn.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub enum GameState {
+    // Back at the menus; the front end stops running this game.
+    MainMenu,
+    Playing,
+    Paused,
+    // `level` was just won and the next level is waiting.
+    LevelComplete { level: u32 },
+    // The last level has been won.
+    GameOver,
+    // The current level is being edited; play resumes on the edited copy.
+    Editor(Editor),
+    // The board is being arranged by hand in practice.
+    Arranging(Arranging),
+}
+
+// Serializes as the whole session except the level file watcher, which
+// belongs to this process and is left off.
+#[derive(Serialize, Deserialize)]
+pub struct GameContext {
+    pub player: Actor,
+    // The second player in co-op, or None when playing alone.
+    #[serde(default)]
+    pub partner: Option<Actor>,
+    // The level being played, or the fallback map if it could not be loaded.
+    pub map_data: MapData,
+    pub map_list: Vec<MapData>,
+    pub level: u32,
+    #[serde(skip)]
+    pub watcher: Option<LevelWatcher>,
+    pub status: Option<String>,
+    pub state: GameState,
+    // LURD moves made since the level was (re)started.
+    pub moves: String,
+    // The level most recently won and the moves that won it.
+    pub last_solution: Option<(u32, String)>,
+    // The named spawn point the level is entered at, if not the usual one,
+    // and where the win pad the level was last won on leads.
+    #[serde(default)]
+    pub spawn: Option<String>,
+    #[serde(default)]
+    exit: Option<Exit>,
+    // Filled as the game is played; the front end drains it every frame.
+    pub events: EventQueue,
+    // Ticks since the game started.
+    pub ticks: u64,
+    pub history: History,
+    // Set by a run action, so the next move runs instead of stepping.
+    #[serde(skip)]
+    run_next: bool,
+    // Set by a grapple action, so the next direction aims the grapple.
+    #[serde(skip)]
+    grapple_next: bool,
+    // Set by using a phase, so the next move goes through a wall.
+    #[serde(skip)]
+    phase_next: bool,
+    // Undos taken on this level, for levels that allow only so many. Not
+    // part of the history, so undoing doesn't give them back.
+    #[serde(default)]
+    undos_used: u32,
+    // Solver hints taken on this level, for levels that allow only so many.
+    // Kept until the level is left, so restarting doesn't give them back.
+    #[serde(default)]
+    hints_used: u32,
+    // The level's message for the last event that had one and the turn it
+    // came on, and the triggers already shown since the level was started,
+    // since each is only shown the first time.
+    #[serde(default)]
+    event_flavor: Option<(String, usize)>,
+    #[serde(default)]
+    triggered: BTreeSet<Trigger>,
+    // Assist mode, set by the front end: the level's undo limit is lifted.
+    #[serde(skip)]
+    pub assist: bool,
+    // A debugging aid: the player walks through walls and boxes.
+    #[serde(skip)]
+    pub noclip: bool,
+    // Set once the board has been arranged by hand, until the level is
+    // left or restarted. Wins while practising count for nothing.
+    #[serde(default)]
+    pub practice: bool,
+}
+
+impl GameContext {
+    pub fn new(map_list: Vec<MapData>, watcher: Option<LevelWatcher>) -> Self {
+        let mut game = Self {
+            player: Actor::player(Pos::new(5, 5)),
+            partner: None,
+            map_data: fallback_map(),
+            map_list,
+            level: 0,
+            watcher,
+            status: None,
+            state: GameState::Playing,
+            moves: String::new(),
+            last_solution: None,
+            spawn: None,
+            exit: None,
+            events: EventQueue::new(),
+            ticks: 0,
+            history: History::new(),
+            run_next: false,
+            grapple_next: false,
+            phase_next: false,
+            undos_used: 0,
+            hints_used: 0,
+            event_flavor: None,
+            triggered: BTreeSet::new(),
+            assist: false,
+            noclip: false,
+            practice: false,
+        };
+        game.restart_level();
+        game
+    }
+    // Makes this a co-op game, restarting the level with a second player
+    // next to the first. Every level after starts them that way too.
+    pub fn add_partner(&mut self) {
+        self.partner = Some(Actor::player(self.map_data.player_spawn));
+        // Any complaint that the level needs two players no longer holds.
+        self.status = None;
+        self.restart_level();
+    }
+    // Loads the current level, or the fallback map if there is no such level.
+    pub fn load_current_level(&mut self) -> Result<(), LevelError> {
+        let result = match self.map_list.get(self.level as usize) {
+            Some(map) if map.needs_two_players() && self.partner.is_none() => {
+                let error = LevelError::NeedsPartner { level: self.level };
+                log::warn!("{}; using the fallback map", error);
+                self.map_data = fallback_map();
+                Err(error)
+            }
+            Some(map) => {
+                log::info!("loaded level {} ({} tiles)", self.level, map.tile_count());
+                self.map_data = map.clone();
+                if let Some(name) = &self.spawn {
+                    match map.spawns.get(name) {
+                        Some(&pos) => self.map_data.player_spawn = pos,
+                        None => log::warn!("level {} has no spawn point {}", self.level, name),
+                    }
+                }
+                Ok(())
+            }
+            None => {
+                let error = LevelError::Missing {
+                    level: self.level,
+                    count: self.map_list.len(),
+                };
+                log::warn!("{}; using the fallback map", error);
+                self.map_data = fallback_map();
+                Err(error)
+            }
+        };
+        self.player.set_pos(self.map_data.player_spawn);
+        if let Some(partner) = &mut self.partner {
+            partner.set_pos(partner_spawn(&self.map_data));
+        }
+        self.moves.clear();
+        self.history.clear();
+        self.undos_used = 0;
+        self.event_flavor = None;
+        self.triggered.clear();
+        self.practice = false;
+        result
+    }
+    // Like load_current_level, but a failure is reported as an event.
+    pub fn restart_level(&mut self) {
+        if let Err(error) = self.load_current_level() {
+            self.events.push(GameEvent::LoadFailed { error });
+        }
+    }
+    // Swaps in a freshly loaded level list, staying on the same level index.
+    pub fn reload_maps(&mut self, maps: Vec<MapData>) {
+        self.map_list = maps;
+        let spawn = self.spawn.clone();
+        self.goto_level_at(LevelId::Index(self.level), spawn);
+    }
+    // Whether anything runs on its own time, so the front end has to keep
+    // ticking while no keys are pressed.
+    pub fn needs_ticks(&self) -> bool {
+        self.watcher.is_some()
+    }
+    // Advances everything that runs on its own time rather than on moves.
+    // Returns whether anything changed.
+    pub fn tick(&mut self) -> bool {
+        self.ticks += 1;
+        self.ticks.is_multiple_of(RELOAD_POLL_TICKS) && self.check_for_reload()
+    }
+    // Returns whether the level file had changed.
+    pub fn check_for_reload(&mut self) -> bool {
+        let result = match self.watcher.as_mut() {
+            Some(watcher) => watcher.poll(),
+            None => return false,
+        };
+        match result {
+            Some(Ok(maps)) => {
+                log::info!("level file changed; reloaded {} levels", maps.len());
+                self.reload_maps(maps);
+                self.events.push(GameEvent::LevelsReloaded);
+            }
+            Some(Err(e)) => {
+                log::warn!("reload failed: {}", e);
+                self.events.push(GameEvent::ReloadFailed);
+            }
+            None => return false,
+        }
+        true
+    }
+    // Moves to another level and loads it. With no levels at all the
+    // fallback map is loaded as level 0.
+    pub fn goto_level(&mut self, id: LevelId) -> LevelChange {
+        self.goto_level_at(id, None)
+    }
+    // Like goto_level, entering the level at the named spawn point if it has
+    // one by that name.
+    pub fn goto_level_at(&mut self, id: LevelId, spawn: Option<String>) -> LevelChange {
+        let count = self.map_list.len() as u32;
+        let last = count.saturating_sub(1);
+        let level = match id {
+            LevelId::Index(level) => level.min(last),
+            LevelId::Next if self.level >= last => {
+                self.state = GameState::GameOver;
+                return LevelChange::CampaignFinished;
+            }
+            LevelId::Next => self.level + 1,
+            LevelId::Previous => self.level.saturating_sub(1).min(last),
+            LevelId::NextWrapping if self.level >= last => 0,
+            LevelId::NextWrapping => self.level + 1,
+            LevelId::PreviousWrapping if self.level == 0 || self.level > last => last,
+            LevelId::PreviousWrapping => self.level - 1,
+        };
+        if level != self.level {
+            self.hints_used = 0;
+        }
+        self.level = level;
+        self.spawn = spawn;
+        self.restart_level();
+        LevelChange::Loaded(level)
+    }
+    pub fn player_movement(&mut self, direction: Direction) -> MoveOutcome {
+        let before = self.snapshot();
+        let partner = self.partner.as_ref().map(Actor::pos);
+        let outcome = if self.noclip {
+            self.ghost_step(direction)
+        } else if self.blocked_by(self.player.pos(), partner, direction) {
+            MoveOutcome::Blocked
+        } else {
+            self.map_data
+                .move_actor(&mut self.player, direction, &mut self.events)
+        };
+        log::debug!(
+            "move {:?} from {:?}: {:?}",
+            direction,
+            before.player.pos(),
+            outcome
+        );
+        if outcome != MoveOutcome::Blocked {
+            self.moves
+                .push(lurd::move_char(direction, outcome == MoveOutcome::Pushed));
+            self.history.record(before);
+        }
+        outcome
+    }
+    // Moves the second player, or the only one if there is no second.
+    // Partner moves are undoable but stay out of `moves`, which is player
+    // one's LURD record.
+    pub fn partner_movement(&mut self, direction: Direction) -> MoveOutcome {
+        let Some(mut partner) = self.partner.take() else {
+            return self.player_movement(direction);
+        };
+        let mut before = self.snapshot();
+        before.partner = Some(partner.clone());
+        let outcome = if self.blocked_by(partner.pos(), Some(self.player.pos()), direction) {
+            MoveOutcome::Blocked
+        } else {
+            self.map_data
+                .move_actor(&mut partner, direction, &mut self.events)
+        };
+        self.partner = Some(partner);
+        if outcome != MoveOutcome::Blocked {
+            self.history.record(before);
+        }
+        outcome
+    }
+    // Whether a move from `from` would walk into the other player at
+    // `other`, or push a box onto them or slide one through them.
+    fn blocked_by(&self, from: Pos, other: Option<Pos>, direction: Direction) -> bool {
+        let Some(other) = other else {
+            return false;
+        };
+        let ahead = from + direction;
+        ahead == other || self.map_data.push_path(ahead, direction).contains(&other)
+    }
+    // Where each player stands, for holding down buttons.
+    fn player_positions(&self) -> Vec<Pos> {
+        let partner = self.partner.as_ref().map(Actor::pos);
+        std::iter::once(self.player.pos()).chain(partner).collect()
+    }
+    // A noclip step: anywhere inside the map goes, and nothing is pushed.
+    fn ghost_step(&mut self, direction: Direction) -> MoveOutcome {
+        if !self
+            .map_data
+            .bounds()
+            .contains(self.player.pos() + direction)
+        {
+            return MoveOutcome::Blocked;
+        }
+        self.player.step(direction);
+        self.events.push(GameEvent::PlayerMoved { direction });
+        MoveOutcome::Moved
+    }
+    // Puts the player at `pos` for debugging. It can be undone, but since it
+    // is no move it leaves no mark in `moves`.
+    pub fn teleport(&mut self, pos: Pos) -> Result<(), String> {
+        if !self.map_data.bounds().contains(pos) {
+            return Err(format!("{} is outside the map", pos));
+        }
+        self.history.record(self.snapshot());
+        self.player.set_pos(pos);
+        let players = self.player_positions();
+        self.map_data
+            .update_buttons_held(&players, &mut self.events);
+        Ok(())
+    }
+    fn refuse(&mut self, reason: Refusal) {
+        self.events.push(GameEvent::Refused { reason });
+    }
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            player: self.player.clone(),
+            partner: self.partner.clone(),
+            map_data: self.map_data.clone(),
+            moves: self.moves.clone(),
+        }
+    }
+    fn restore(&mut self, snapshot: Snapshot) {
+        self.player = snapshot.player;
+        self.partner = snapshot.partner;
+        self.map_data = snapshot.map_data;
+        self.moves = snapshot.moves;
+    }
+    // Walks the player along the shortest path to `target`, one recorded
+    // move at a time. Stops short instead of pushing a box, and on a win pad.
+    pub fn walk_to(&mut self, target: Pos) {
+        let Some(path) = pathfind::find_path(&self.map_data, self.player.pos(), target) else {
+            self.refuse(Refusal::NoPath);
+            return;
+        };
+        for direction in path {
+            if !self.step_without_pushing(direction)
+                || self.map_data.is_win_pad_at(self.player.pos())
+            {
+                break;
+            }
+        }
+    }
+    // Keeps moving the player in `direction` until a wall or box is in the
+    // way or it steps onto anything but bare floor.
+    pub fn run(&mut self, direction: Direction) {
+        while self.step_without_pushing(direction)
+            && self.map_data.cell_at(self.player.pos()).floor_type() == TileType::Empty
+        {}
+    }
+    // One recorded move that refuses to push, for walking several moves at
+    // once. Doors are updated after the move rather than at the end of the
+    // frame, since the next move may depend on them. Returns whether the
+    // player moved.
+    fn step_without_pushing(&mut self, direction: Direction) -> bool {
+        let ahead = self.map_data.cell_at(self.player.pos() + direction);
+        if ahead.has_pushable() || self.player_movement(direction) == MoveOutcome::Blocked {
+            return false;
+        }
+        let players = self.player_positions();
+        self.map_data
+            .update_buttons_held(&players, &mut self.events);
+        true
+    }
+    // Fires player one's grapple towards `direction`. It can be undone, but
+    // LURD has no letter for it, so like a teleport it leaves no mark in
+    // `moves`.
+    pub fn grapple(&mut self, direction: Direction) {
+        let before = self.snapshot();
+        let partner: Vec<Pos> = self.partner.iter().map(Actor::pos).collect();
+        if self
+            .map_data
+            .grapple(&mut self.player, &partner, direction, &mut self.events)
+        {
+            log::debug!("grapple {:?} from {:?}", direction, before.player.pos());
+            self.history.record(before);
+        } else {
+            self.refuse(Refusal::NothingToGrapple);
+        }
+    }
+    // Moves player one through the wall ahead with a phase. Like a grapple
+    // it leaves no mark in `moves`.
+    pub fn phase(&mut self, direction: Direction) {
+        let before = self.snapshot();
+        let partner: Vec<Pos> = self.partner.iter().map(Actor::pos).collect();
+        if self
+            .map_data
+            .phase(&mut self.player, &partner, direction, &mut self.events)
+        {
+            log::debug!("phase {:?} from {:?}", direction, before.player.pos());
+            self.history.record(before);
+        } else {
+            self.refuse(Refusal::NothingToPhase);
+        }
+    }
+    // How many more undos the level allows, if it limits them. Nothing is
+    // limited in assist mode.
+    pub fn undos_left(&self) -> Option<u32> {
+        if self.assist {
+            return None;
+        }
+        let allowed = self.map_data.undos?;
+        Some(allowed.saturating_sub(self.undos_used))
+    }
+    // How many more of the `allowed` hints there are on this level. Nothing
+    // is limited in assist mode.
+    pub fn hints_left(&self, allowed: u32) -> Option<u32> {
+        (!self.assist).then(|| allowed.saturating_sub(self.hints_used))
+    }
+    pub fn count_hint(&mut self) {
+        self.hints_used += 1;
+    }
+    // Takes back the last move, or says that there is none or that the
+    // level's undos have run out.
+    pub fn undo(&mut self) {
+        if self.undos_left() == Some(0) {
+            self.refuse(Refusal::NoUndosLeft);
+            return;
+        }
+        if self.take_back() {
+            self.undos_used += 1;
+        }
+    }
+    // Spends an extra undo to take back the last move, whatever the level
+    // allows. The one spent stays spent, though the move taken back is
+    // restored with the inventory it had.
+    pub fn extra_undo(&mut self) {
+        let left = self.map_data.items(Item::Undo);
+        if left == 0 {
+            self.refuse(Refusal::NoExtraUndosLeft);
+            return;
+        }
+        if self.take_back() {
+            let held = self.map_data.items(Item::Undo).min(left - 1);
+            self.map_data.inventory.insert(Item::Undo, held);
+        }
+    }
+    // Restores the state before the last move, returning whether there was
+    // one.
+    fn take_back(&mut self) -> bool {
+        let current = self.snapshot();
+        match self.history.undo(current) {
+            Some(previous) => {
+                log::debug!("undo to {} moves", previous.moves.len());
+                self.restore(previous);
+                self.events.push(GameEvent::MoveUndone);
+                true
+            }
+            None => {
+                self.refuse(Refusal::NothingToUndo);
+                false
+            }
+        }
+    }
+    pub fn redo(&mut self) {
+        let current = self.snapshot();
+        match self.history.redo(current) {
+            Some(next) => {
+                log::debug!("redo to {} moves", next.moves.len());
+                self.restore(next);
+            }
+            None => self.refuse(Refusal::NothingToRedo),
+        }
+    }
+    // Carries out `action` during play. Sharing a code and exporting a
+    // solution are left to the front end, so they do nothing here.
+    pub fn perform(&mut self, action: GameAction) {
+        // Alone, the partner's keys are the player's too, run and all.
+        let solo = action
+            .partner_direction()
+            .filter(|_| self.partner.is_none());
+        if let Some(direction) = action.direction().or(solo) {
+            if std::mem::take(&mut self.grapple_next) {
+                self.grapple(direction);
+            } else if std::mem::take(&mut self.phase_next) {
+                self.phase(direction);
+            } else if std::mem::take(&mut self.run_next) {
+                self.run(direction);
+            } else {
+                self.player_movement(direction);
+            }
+            return;
+        }
+        if let Some(direction) = action.partner_direction() {
+            self.run_next = false;
+            self.grapple_next = false;
+            self.phase_next = false;
+            self.partner_movement(direction);
+            return;
+        }
+        self.run_next = false;
+        self.grapple_next = false;
+        self.phase_next = false;
+        match action {
+            GameAction::Run => self.run_next = true,
+            GameAction::Grapple if self.map_data.grapples_left() == 0 => {
+                self.refuse(Refusal::NoGrapplesLeft);
+            }
+            GameAction::Grapple => self.grapple_next = true,
+            GameAction::UsePhase if self.map_data.items(Item::Phase) == 0 => {
+                self.refuse(Refusal::NoPhasesLeft);
+            }
+            GameAction::UsePhase => self.phase_next = true,
+            GameAction::UseUndo => self.extra_undo(),
+            GameAction::Undo => self.undo(),
+            GameAction::Redo => self.redo(),
+            GameAction::Restart => {
+                self.restart_level();
+                self.events.push(GameEvent::LevelRestarted);
+            }
+            GameAction::Pause => self.state = GameState::Paused,
+            GameAction::Menu => self.state = GameState::MainMenu,
+            GameAction::Editor => self.open_editor(),
+            GameAction::Practice => self.arrange(),
+            _ => (),
+        }
+    }
+    // Returns the index of the level that was just won, if any.
+    // Either player reaching a win pad wins in co-op, or taking a warp
+    // warps them both. Warps do nothing in practice, where they would unlock
+    // levels ahead.
+    pub fn update_all(&mut self) -> Option<u32> {
+        let players = self.player_positions();
+        let last = self.map_list.len().saturating_sub(1) as u32;
+        let warp = players
+            .iter()
+            .find_map(|&pos| self.map_data.warp_at(pos))
+            .filter(|_| !self.practice);
+        if let Some(to) = warp.map(|levels| (self.level + levels).min(last)) {
+            if to > self.level {
+                log::info!("warped from level {} to {}", self.level, to);
+                let from = self.level;
+                self.goto_level(LevelId::Index(to));
+                self.events.push(GameEvent::Warped { from, to });
+                return None;
+            }
+        }
+        if let Some(&pad) = players
+            .iter()
+            .find(|&&pos| self.map_data.is_win_pad_at(pos))
+        {
+            let won = self.level;
+            self.exit = self.map_data.exits.get(&pad).cloned();
+            log::info!(
+                "level {} won in {} moves: {}",
+                won,
+                self.moves.len(),
+                self.moves
+            );
+            self.events.push(GameEvent::LevelWon { level: won });
+            // Player one's moves alone don't solve a co-op level, and LURD
+            // has no way to write down a grapple or phase. Every win replaces
+            // the last solution, so none is left over from an earlier level.
+            let recorded = self.partner.is_none() && self.map_data.is_recorded() && !self.practice;
+            self.last_solution = recorded.then(|| (won, self.moves.clone()));
+            let leads_on = self.exit.as_ref().is_some_and(|exit| exit.level.is_some());
+            self.state = if leads_on || self.level as usize + 1 < self.map_list.len() {
+                GameState::LevelComplete { level: won }
+            } else {
+                GameState::GameOver
+            };
+            return Some(won);
+        }
+        self.map_data
+            .update_buttons_held(&players, &mut self.events);
+        self.note_triggers();
+        if self.assist {
+            self.note_stuck_boxes();
+        }
+        None
+    }
+    // Assist mode's warning, for any box just pushed where it can never move
+    // again.
+    fn note_stuck_boxes(&mut self) {
+        let pushed: Vec<Pos> = self
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                GameEvent::BoxPushed { to, .. } => Some(*to),
+                _ => None,
+            })
+            .collect();
+        if pushed.is_empty() {
+            return;
+        }
+        let stuck = Deadlocks::new(&self.map_data).stuck_boxes(&self.map_data);
+        for pos in pushed.into_iter().filter(|pos| stuck.contains(pos)) {
+            self.events.push(GameEvent::BoxStuck { pos });
+        }
+    }
+    // Picks up the level's message for any queued event seen for the first
+    // time.
+    fn note_triggers(&mut self) {
+        for trigger in self.events.iter().filter_map(Trigger::of) {
+            if !self.triggered.insert(trigger) {
+                continue;
+            }
+            if let Some(message) = self.map_data.event_flavor.get(&trigger) {
+                self.event_flavor = Some((message.clone(), self.map_data.turn()));
+            }
+        }
+    }
+    // The board as it stands, as a level that starts from here: the boxes
+    // where they are now, the player where they stand and the doors and
+    // turn count as they are, with the moves that led here noted above it.
+    pub fn board_text(&self) -> String {
+        let mut map = self.map_data.clone();
+        map.player_spawn = self.player.pos();
+        let mut text = format!(
+            "; level {} after {} moves: {}\n",
+            self.level,
+            self.moves.len(),
+            self.moves
+        );
+        if let Some(partner) = &self.partner {
+            let Pos { y, x } = partner.pos();
+            text.push_str(&format!("; partner at row {} column {}\n", y, x));
+        }
+        text.push_str(&level_to_text(&map));
+        text
+    }
+    // The line above the board: a recent event's message, or else the
+    // level's flavor text in `language`.
+    pub fn flavor(&self, language: &str) -> Option<&str> {
+        match &self.event_flavor {
+            Some((message, turn)) if self.map_data.turn() < turn + EVENT_FLAVOR_MOVES => {
+                Some(message)
+            }
+            _ => self.map_data.flavor(language),
+        }
+    }
+    // Leaves the level-complete screen for the next level.
+    pub fn next_level(&mut self) {
+        if let GameState::LevelComplete { .. } = self.state {
+            // Through an exit to where it leads, or else on to the next.
+            let (id, spawn) = match self.exit.take() {
+                Some(Exit {
+                    level: Some(level),
+                    spawn,
+                }) => (LevelId::Index(level), Some(spawn)),
+                Some(Exit { level: None, spawn }) => (LevelId::Next, Some(spawn)),
+                None => (LevelId::Next, None),
+            };
+            if self.goto_level_at(id, spawn) != LevelChange::CampaignFinished {
+                self.state = GameState::Playing;
+            }
+        }
+    }
+    // Starts practice on this level, or goes back to arranging the board,
+    // with the cursor on the player.
+    pub fn arrange(&mut self) {
+        self.practice = true;
+        self.state = GameState::Arranging(Arranging::new(self.player.pos()));
+    }
+    // The arranging key, at the cursor: sets down the box picked up, or
+    // picks up the box there, or else adds a box there. Each change can be
+    // undone like a move.
+    pub fn arrange_box(&mut self) {
+        let GameState::Arranging(mut arranging) = self.state else {
+            return;
+        };
+        let cursor = arranging.cursor;
+        if self.player_positions().contains(&cursor) {
+            return;
+        }
+        let before = self.snapshot();
+        let changed = match arranging.held {
+            Some(from) if from == cursor => {
+                arranging.held = None;
+                false
+            }
+            Some(from) => {
+                let shifted = self.map_data.shift_box(from, cursor);
+                if shifted {
+                    arranging.held = None;
+                }
+                shifted
+            }
+            None if self.map_data.cell_at(cursor).has_pushable() => {
+                arranging.held = Some(cursor);
+                false
+            }
+            None => self.map_data.add_box(cursor),
+        };
+        if changed {
+            self.arranged(before);
+        }
+        self.state = GameState::Arranging(arranging);
+    }
+    // Takes away the box at the cursor.
+    pub fn arrange_remove(&mut self) {
+        let GameState::Arranging(arranging) = &mut self.state else {
+            return;
+        };
+        let cursor = arranging.cursor;
+        if arranging.held == Some(cursor) {
+            arranging.held = None;
+        }
+        let before = self.snapshot();
+        if self.map_data.remove_box(cursor) {
+            self.arranged(before);
+        }
+    }
+    // Puts the player at the cursor, if they could stand there.
+    pub fn arrange_player(&mut self) {
+        let GameState::Arranging(arranging) = &self.state else {
+            return;
+        };
+        let cursor = arranging.cursor;
+        let partner = self.partner.as_ref().map(Actor::pos);
+        if !self.map_data.is_walkable(cursor, self.player.kind)
+            || self.map_data.cell_at(cursor).has_pushable()
+            || partner == Some(cursor)
+        {
+            return;
+        }
+        let before = self.snapshot();
+        self.player.set_pos(cursor);
+        self.arranged(before);
+    }
+    // Keeps the board from before a change for undo, and brings the doors
+    // up to date with where the boxes and players are now.
+    fn arranged(&mut self, before: Snapshot) {
+        self.history.record(before);
+        let players = self.player_positions();
+        self.map_data
+            .update_buttons_held(&players, &mut self.events);
+    }
+    pub fn open_editor(&mut self) {
+        self.state = GameState::Editor(Editor::new(&self.map_data));
+    }
+    // Plays the edited level in place of the current one. If it doesn't
+    // parse, the editor stays open with the reason on the status line.
+    pub fn apply_edit(&mut self) {
+        let GameState::Editor(editor) = &self.state else {
+            return;
+        };
+        match editor.build() {
+            Ok(map) => {
+                match self.map_list.get_mut(self.level as usize) {
+                    Some(slot) => *slot = map,
+                    None => {
+                        self.level = self.map_list.len() as u32;
+                        self.map_list.push(map);
+                    }
+                }
+                self.status = None;
+                self.restart_level();
+                self.state = GameState::Playing;
+            }
+            Err(e) => self.status = Some(e.to_string()),
+        }
+    }
+}
+
+// Where the second player starts: the first free floor next to the spawn, or
+// on it if there is none.
+fn partner_spawn(map: &MapData) -> Pos {
+    let spawn = map.player_spawn;
+    Direction::ALL
+        .into_iter()
+        .map(|direction| spawn + direction)
+        .find(|&pos| map.is_walkable(pos, ActorKind::Player) && !map.cell_at(pos).has_pushable())
+        .unwrap_or(spawn)
+}
@@ -0,0 +1,263 @@
+// Seeded level generator. A walled room is split in two by a wall with a door;
+// the door's buttons, some boxes and the player go on one side and the win pad
+// on the other. Symmetric levels instead mirror every feature across the
+// middle of the room, or rotate it half a turn about the centre. Every
+// candidate is run through the solver and only solvable levels are returned.
+use crate::{
+    difficulty::{from_solution, Difficulty},
+    map::{MapData, TileType},
+    map_builder::MapBuilder,
+    pos::Pos,
+    solver::solve,
+};
+use std::collections::HashSet;
+
+const MAX_ATTEMPTS: usize = 200;
+// Candidates that take longer than this to settle are thrown away rather
+// than searched to the end.
+const MAX_SOLVER_STATES: usize = 5_000;
+
+// splitmix64: small, fast and good enough for level layouts.
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    // Uniform in `lo..=hi`.
+    pub fn range(&mut self, lo: i32, hi: i32) -> i32 {
+        lo + (self.next_u64() % (hi - lo + 1) as u64) as i32
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Symmetry {
+    None,
+    // Mirrored left to right.
+    Horizontal,
+    // Unchanged by a half turn about the centre.
+    Rotational,
+}
+
+struct Layout {
+    builder: MapBuilder,
+    taken: HashSet<Pos>,
+    height: i32,
+    width: i32,
+    symmetry: Symmetry,
+}
+
+impl Layout {
+    fn new(height: i32, width: i32, symmetry: Symmetry) -> Self {
+        let mut builder = MapBuilder::new();
+        builder.room(Pos::ZERO, Pos::new(height - 1, width - 1));
+        Self {
+            builder,
+            taken: HashSet::new(),
+            height,
+            width,
+            symmetry,
+        }
+    }
+    // Where the symmetry sends a cell.
+    fn image(&self, pos: Pos) -> Pos {
+        let last = Pos::new(self.height - 1, self.width - 1);
+        match self.symmetry {
+            Symmetry::None => pos,
+            Symmetry::Horizontal => Pos::new(pos.y, last.x - pos.x),
+            Symmetry::Rotational => last - pos,
+        }
+    }
+    // Places a tile and its symmetric twin, picking a cell in the rectangle
+    // where both it and its twin are free.
+    fn place_pair(&mut self, rng: &mut Rng, top_left: Pos, bottom_right: Pos, tile_type: TileType) {
+        loop {
+            let cell = self.free_cell(rng, top_left, bottom_right);
+            let twin = self.image(cell);
+            if cell != twin && !self.taken.contains(&twin) {
+                self.place(cell.y, cell.x, tile_type);
+                self.place(twin.y, twin.x, tile_type);
+                return;
+            }
+        }
+    }
+    fn place(&mut self, y: i32, x: i32, tile_type: TileType) {
+        self.builder.place(Pos::new(y, x), tile_type);
+        self.taken.insert(Pos::new(y, x));
+    }
+    fn into_map(mut self, player_spawn: Pos) -> MapData {
+        self.builder.spawn(player_spawn).build()
+    }
+    // Picks a free cell inside the rectangle from `top_left` to
+    // `bottom_right` inclusive.
+    fn free_cell(&self, rng: &mut Rng, top_left: Pos, bottom_right: Pos) -> Pos {
+        loop {
+            let cell = Pos::new(
+                rng.range(top_left.y, bottom_right.y),
+                rng.range(top_left.x, bottom_right.x),
+            );
+            if !self.taken.contains(&cell) {
+                return cell;
+            }
+        }
+    }
+}
+
+fn candidate(rng: &mut Rng) -> MapData {
+    let height = rng.range(7, 11);
+    let width = rng.range(16, 26);
+    let mut layout = Layout::new(height, width, Symmetry::None);
+
+    // The dividing wall and its door.
+    let split = rng.range(width / 2, width - 4);
+    let door_y = rng.range(1, height - 2);
+    for y in 1..height - 1 {
+        if y == door_y {
+            layout.place(y, split, TileType::Door(Some(0), false));
+        } else {
+            layout.place(y, split, TileType::Wall1);
+        }
+    }
+
+    // Keep the outer ring of the left room clear of boxes so few are stuck
+    // against a wall from the start.
+    let left_room = (Pos::new(1, 1), Pos::new(height - 2, split - 1));
+    let box_area = (Pos::new(2, 2), Pos::new(height - 3, split - 2));
+    for _ in 0..rng.range(0, 4) {
+        let Pos { y, x } = layout.free_cell(rng, left_room.0, left_room.1);
+        layout.place(y, x, TileType::Wall1);
+    }
+    // The player can hold one button, so every extra button needs a box.
+    let buttons = rng.range(2, 3);
+    for _ in 0..buttons {
+        let Pos { y, x } = layout.free_cell(rng, left_room.0, left_room.1);
+        layout.place(y, x, TileType::Button(0));
+    }
+    for _ in 0..buttons - 1 + rng.range(0, 1) {
+        let Pos { y, x } = layout.free_cell(rng, box_area.0, box_area.1);
+        layout.place(y, x, TileType::PushBox);
+    }
+    let player_spawn = layout.free_cell(rng, left_room.0, left_room.1);
+    layout.taken.insert(player_spawn);
+
+    let Pos { y, x } =
+        layout.free_cell(rng, Pos::new(1, split + 1), Pos::new(height - 2, width - 2));
+    layout.place(y, x, TileType::WinPad);
+
+    layout.into_map(player_spawn)
+}
+
+// A room mirrored left to right: the win pad sits in a strip along the top
+// behind a door in the middle, and the player starts on the centre line.
+fn mirrored_candidate(rng: &mut Rng) -> MapData {
+    let height = rng.range(9, 12);
+    let width = rng.range(7, 11) * 2 + 1;
+    let center = width / 2;
+    let mut layout = Layout::new(height, width, Symmetry::Horizontal);
+
+    let split = rng.range(3, 4);
+    for x in 1..width - 1 {
+        if x == center {
+            layout.place(split, x, TileType::Door(Some(0), false));
+        } else {
+            layout.place(split, x, TileType::Wall1);
+        }
+    }
+    layout.place(rng.range(1, split - 1), center, TileType::WinPad);
+
+    let left_half = (Pos::new(split + 1, 1), Pos::new(height - 2, center - 1));
+    let box_area = (Pos::new(split + 2, 2), Pos::new(height - 3, center - 1));
+    fill_symmetric(&mut layout, rng, left_half, box_area);
+    let player_spawn = layout.free_cell(
+        rng,
+        Pos::new(split + 1, center),
+        Pos::new(height - 2, center),
+    );
+    layout.into_map(player_spawn)
+}
+
+// A room unchanged by a half turn: the win pad is locked in a small vault in
+// the centre with a door above and below.
+fn rotational_candidate(rng: &mut Rng) -> MapData {
+    let height = rng.range(4, 6) * 2 + 1;
+    let width = rng.range(7, 11) * 2 + 1;
+    let (cy, cx) = (height / 2, width / 2);
+    let mut layout = Layout::new(height, width, Symmetry::Rotational);
+
+    for y in cy - 1..=cy + 1 {
+        for x in cx - 1..=cx + 1 {
+            let tile_type = match (y - cy, x - cx) {
+                (0, 0) => TileType::WinPad,
+                (_, 0) => TileType::Door(Some(0), false),
+                _ => TileType::Wall1,
+            };
+            layout.place(y, x, tile_type);
+        }
+    }
+
+    // Features go in the top half and are rotated into the bottom half.
+    let top_half = (Pos::new(1, 1), Pos::new(cy - 1, width - 2));
+    let box_area = (Pos::new(2, 2), Pos::new(cy - 1, width - 3));
+    fill_symmetric(&mut layout, rng, top_half, box_area);
+    let player_spawn = layout.free_cell(rng, Pos::new(1, 1), Pos::new(height - 2, width - 2));
+    layout.into_map(player_spawn)
+}
+
+// Pillars, buttons and boxes in symmetric pairs. Every button needs a box on
+// it except the one the player stands on, and boxes come in pairs, so there
+// is always one box to spare.
+fn fill_symmetric(
+    layout: &mut Layout,
+    rng: &mut Rng,
+    (top_left, bottom_right): (Pos, Pos),
+    (box_top_left, box_bottom_right): (Pos, Pos),
+) {
+    for _ in 0..rng.range(0, 2) {
+        layout.place_pair(rng, top_left, bottom_right, TileType::Wall1);
+    }
+    let pairs = rng.range(1, 2);
+    for _ in 0..pairs {
+        layout.place_pair(rng, top_left, bottom_right, TileType::Button(0));
+    }
+    for _ in 0..pairs {
+        layout.place_pair(rng, box_top_left, box_bottom_right, TileType::PushBox);
+    }
+}
+
+// Returns the first solvable level for `seed`, or None if none turned up
+// within the attempt budget.
+pub fn generate(seed: u64, symmetry: Symmetry) -> Option<MapData> {
+    generate_rated(seed, symmetry).map(|(map, _)| map)
+}
+
+fn generate_rated(seed: u64, symmetry: Symmetry) -> Option<(MapData, Difficulty)> {
+    let mut rng = Rng::new(seed);
+    for _ in 0..MAX_ATTEMPTS {
+        let mut map = match symmetry {
+            Symmetry::None => candidate(&mut rng),
+            Symmetry::Horizontal => mirrored_candidate(&mut rng),
+            Symmetry::Rotational => rotational_candidate(&mut rng),
+        };
+        if let Some(difficulty) = from_solution(&solve(&map, MAX_SOLVER_STATES)) {
+            map.flavor_text = Some(format!("Generated level (seed {}, {})", seed, difficulty));
+            return Some((map, difficulty));
+        }
+    }
+    None
+}
+
+// Generates `count` levels from consecutive seeds, easiest first.
+pub fn generate_set(seed: u64, count: u64, symmetry: Symmetry) -> Vec<MapData> {
+    let mut levels: Vec<(MapData, Difficulty)> = (0..count)
+        .filter_map(|i| generate_rated(seed.wrapping_add(i), symmetry))
+        .collect();
+    levels.sort_by_key(|(_, difficulty)| difficulty.score);
+    levels.into_iter().map(|(map, _)| map).collect()
+}
@@ -0,0 +1,131 @@
+use crate::pos::Pos;
+
+// At most one item per cell, with constant-time lookups. The grid grows
+// to fit whatever is put into it; looking outside it finds nothing. Iteration
+// is row by row.
+#[derive(Clone)]
+pub struct Grid<T> {
+    top: i32,
+    left: i32,
+    height: i32,
+    width: i32,
+    cells: Vec<Option<T>>,
+    len: usize,
+}
+
+impl<T> Default for Grid<T> {
+    fn default() -> Self {
+        Self {
+            top: 0,
+            left: 0,
+            height: 0,
+            width: 0,
+            cells: Vec::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<T> Grid<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    fn index(&self, pos: Pos) -> Option<usize> {
+        let (row, col) = (pos.y - self.top, pos.x - self.left);
+        if row < 0 || col < 0 || row >= self.height || col >= self.width {
+            return None;
+        }
+        Some((row * self.width + col) as usize)
+    }
+    fn pos(&self, index: usize) -> Pos {
+        let index = index as i32;
+        Pos::new(
+            self.top + index / self.width,
+            self.left + index % self.width,
+        )
+    }
+    pub fn at(&self, pos: Pos) -> Option<&T> {
+        self.cells[self.index(pos)?].as_ref()
+    }
+    pub fn at_mut(&mut self, pos: Pos) -> Option<&mut T> {
+        let i = self.index(pos)?;
+        self.cells[i].as_mut()
+    }
+    // Puts `item` at `pos`, returning whatever was there before.
+    pub fn insert(&mut self, pos: Pos, item: T) -> Option<T> {
+        let i = match self.index(pos) {
+            Some(i) => i,
+            None => {
+                self.grow_to(pos);
+                self.index(pos).unwrap()
+            }
+        };
+        let old = self.cells[i].replace(item);
+        if old.is_none() {
+            self.len += 1;
+        }
+        old
+    }
+    pub fn remove(&mut self, pos: Pos) -> Option<T> {
+        let i = self.index(pos)?;
+        let old = self.cells[i].take();
+        if old.is_some() {
+            self.len -= 1;
+        }
+        old
+    }
+    pub fn iter(&self) -> impl Iterator<Item = (Pos, &T)> {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter_map(|(i, cell)| Some((self.pos(i), cell.as_ref()?)))
+    }
+    // Re-lays the cells out over a rectangle that also covers `pos`.
+    fn grow_to(&mut self, Pos { y, x }: Pos) {
+        let (top, left, bottom, right) = if self.width == 0 {
+            (y, x, y, x)
+        } else {
+            (
+                self.top.min(y),
+                self.left.min(x),
+                (self.top + self.height - 1).max(y),
+                (self.left + self.width - 1).max(x),
+            )
+        };
+        let (height, width) = (bottom - top + 1, right - left + 1);
+        let mut cells: Vec<Option<T>> = (0..height * width).map(|_| None).collect();
+        let old = std::mem::take(&mut self.cells);
+        for (i, cell) in old.into_iter().enumerate() {
+            let Pos { y, x } = self.pos(i);
+            cells[((y - top) * width + x - left) as usize] = cell;
+        }
+        (self.top, self.left, self.height, self.width) = (top, left, height, width);
+        self.cells = cells;
+    }
+}
+
+impl<T> FromIterator<(Pos, T)> for Grid<T> {
+    fn from_iter<I: IntoIterator<Item = (Pos, T)>>(iter: I) -> Self {
+        let items: Vec<(Pos, T)> = iter.into_iter().collect();
+        let mut grid = Self::new();
+        // Size the grid for every item up front rather than growing per item.
+        if let Some(&(first, _)) = items.first() {
+            let (top_left, bottom_right) =
+                items.iter().fold((first, first), |(tl, br), &(pos, _)| {
+                    (tl.min(pos), br.max(pos))
+                });
+            grid.grow_to(top_left);
+            grid.grow_to(bottom_right);
+        }
+        for (pos, item) in items {
+            grid.insert(pos, item);
+        }
+        grid
+    }
+}
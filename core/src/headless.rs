@@ -0,0 +1,96 @@
+// Runs a level with nothing attached to the terminal: feed it moves and read
+// back what each one did and how the board ended up.
+use crate::{
+    events::GameEvent,
+    game::{GameContext, GameState},
+    lurd,
+    map::{MapData, MoveOutcome},
+    thumbnail::thumbnail,
+    Direction,
+};
+use std::fmt;
+
+pub struct Step {
+    pub direction: Direction,
+    pub outcome: MoveOutcome,
+    // Everything the move set off, in order.
+    pub events: Vec<GameEvent>,
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let pushed = self.outcome == MoveOutcome::Pushed;
+        write!(f, "{} ", lurd::move_char(self.direction, pushed))?;
+        match self.outcome {
+            MoveOutcome::Blocked => write!(f, "blocked")?,
+            MoveOutcome::Moved => write!(f, "moved")?,
+            MoveOutcome::Pushed => write!(f, "pushed")?,
+        }
+        for event in &self.events {
+            match event {
+                GameEvent::PlayerMoved { .. }
+                | GameEvent::MoveBlocked { .. }
+                | GameEvent::BoxPushed { .. } => (),
+                _ => write!(f, ", {}", event)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+pub struct Simulation {
+    pub game: GameContext,
+    pub steps: Vec<Step>,
+}
+
+impl Simulation {
+    pub fn new(map: MapData) -> Self {
+        Self {
+            game: GameContext::new(vec![map], None),
+            steps: Vec::new(),
+        }
+    }
+    // Plays every move in order, stopping early if the level is won.
+    pub fn run(map: MapData, moves: &[Direction]) -> Self {
+        let mut simulation = Self::new(map);
+        for &direction in moves {
+            if simulation.step(direction).is_none() {
+                break;
+            }
+        }
+        simulation
+    }
+    // Returns None once the level has been won.
+    pub fn step(&mut self, direction: Direction) -> Option<&Step> {
+        if self.game.state != GameState::Playing {
+            return None;
+        }
+        let outcome = self.game.player_movement(direction);
+        self.game.update_all();
+        self.steps.push(Step {
+            direction,
+            outcome,
+            events: self.game.events.drain(),
+        });
+        self.steps.last()
+    }
+    pub fn won(&self) -> bool {
+        matches!(
+            self.game.state,
+            GameState::LevelComplete { .. } | GameState::GameOver
+        )
+    }
+    // The moves played, in LURD notation.
+    pub fn lurd(&self) -> String {
+        self.steps
+            .iter()
+            .map(|step| lurd::move_char(step.direction, step.outcome == MoveOutcome::Pushed))
+            .collect()
+    }
+    // The board as text, one row per line, with the player drawn on it.
+    pub fn board(&self) -> Vec<String> {
+        let mut map = self.game.map_data.clone();
+        map.player_spawn = self.game.player.pos();
+        thumbnail(&map, usize::MAX, usize::MAX)
+    }
+}
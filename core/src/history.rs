@@ -0,0 +1,62 @@
+// Undo and redo for moves. Each move saves a snapshot of everything it can
+// change: the players, the map (boxes, buttons and doors) and the LURD record.
+// The terrain is shared between snapshots, so they stay cheap.
+use crate::{actor::Actor, map::MapData};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub player: Actor,
+    #[serde(default)]
+    pub partner: Option<Actor>,
+    pub map_data: MapData,
+    pub moves: String,
+}
+
+// Unbounded unless given a limit, past which the oldest moves are forgotten.
+// It is cleared whenever a level is (re)loaded.
+#[derive(Default, Serialize, Deserialize)]
+pub struct History {
+    undo: VecDeque<Snapshot>,
+    redo: Vec<Snapshot>,
+    limit: Option<usize>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn set_limit(&mut self, limit: Option<usize>) {
+        self.limit = limit;
+        self.trim();
+    }
+    fn trim(&mut self) {
+        if let Some(limit) = self.limit {
+            while self.undo.len() > limit {
+                self.undo.pop_front();
+            }
+        }
+    }
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+    // Records the state before a new move. A new move ends any redo chain.
+    pub fn record(&mut self, before: Snapshot) {
+        self.undo.push_back(before);
+        self.redo.clear();
+        self.trim();
+    }
+    // Steps back from `current`, returning the state to restore.
+    pub fn undo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let previous = self.undo.pop_back()?;
+        self.redo.push(current);
+        Some(previous)
+    }
+    pub fn redo(&mut self, current: Snapshot) -> Option<Snapshot> {
+        let next = self.redo.pop()?;
+        self.undo.push_back(current);
+        Some(next)
+    }
+}
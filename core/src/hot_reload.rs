@@ -0,0 +1,48 @@
+use crate::{
+    level_file::{load_level_file, LevelFileError},
+    map::MapData,
+};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+// Watches a level file by polling its modification time.
+pub struct LevelWatcher {
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+    // Why the last reload failed, if it did.
+    error: Option<String>,
+}
+
+impl LevelWatcher {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            last_modified: modified_time(path),
+            error: None,
+        }
+    }
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+    // Returns the re-parsed levels if the file changed since the last poll.
+    pub fn poll(&mut self) -> Option<Result<Vec<MapData>, LevelFileError>> {
+        let modified = modified_time(&self.path);
+        if modified.is_none() || modified == self.last_modified {
+            return None;
+        }
+        self.last_modified = modified;
+        let result = load_level_file(&self.path);
+        self.error = result.as_ref().err().map(ToString::to_string);
+        Some(result)
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|m| m.modified()).ok()
+}
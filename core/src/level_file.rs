@@ -0,0 +1,681 @@
+// Plain-text level files.
+//
+// A file holds one or more levels separated by a line containing only `---`.
+// Each level starts with optional `key: value` header lines followed by the
+// map grid, one character per tile:
+//
+//   B        wall
+//   @        push box
+//   $        slippery box, sliding on when pushed until something stops it
+//   &        phase box, solid after an even number of moves, and after an
+//            odd number passable to the player (never to boxes)
+//   #        win pad
+//   P        player spawn (exactly one per level)
+//   0-9      button with that id
+//   a-z      closed door controlled by button id 0-25 (a = 0)
+//   D        door with no button
+//   =        plate, held down only by a player (co-op)
+//   |        hold door, open only while every plate is held (co-op)
+//   !        phase power-up, picked up by stepping onto it
+//   +        extra undo power-up, likewise
+//   *        gem, collected for points in scoring mode
+//   ~        warp, hidden during play, sending the player levels ahead
+//   ' ' / .  empty floor
+//
+// The headers are `flavor`, and `flavor.LANG` for the flavor text translated
+// into language LANG (e.g. `flavor.fr: Bienvenue`), and `par: MOVES/PUSHES`
+// for the level's official par, which `--par` works out with the solver.
+// `pad: ID COUNT/EVERY` makes the buttons with that id step pads: rather
+// than being held down, they open their doors when the player steps onto
+// one on a move whose number, counting from 1, is COUNT modulo EVERY (so
+// `pad: 2 0/2` wants an even move). `delay: ID MOVES` makes the doors with
+// that id close again once their buttons have been let go for MOVES moves,
+// rather than staying open for good; they wait for their doorways to clear.
+// `grapples: N` lets the player fire the grapple N times on the level.
+// `item: KIND COUNT` starts the player with COUNT power-ups of KIND (`phase`
+// or `undo`) in the inventory, and `undos: N` allows only N undos on the
+// level, besides any extra undos.
+// `spawn: NAME ROW COL` adds a spawn point called NAME at that row and column
+// of the grid (counting from 0), for entering the level other than at `P`,
+// and `exit: ROW COL LEVEL SPAWN` makes the win pad there lead to spawn
+// point SPAWN of level LEVEL (counting from 0), or of the next level if
+// LEVEL is `next`. Together they let a hub level be left and come back to
+// from different sides. `warp: N` makes the level's warps send the player N
+// levels ahead rather than `DEFAULT_WARP`.
+// `on: TRIGGER MESSAGE` shows MESSAGE in place of the flavor text for a few
+// moves the first time TRIGGER happens: `push` for the first box pushed,
+// `button ID` and `door ID` for a button of that id pressed or its doors
+// opening, `gem` and `item` for a gem or power-up picked up.
+// `turn: N` starts the level as if N moves had been made, for step pads and
+// phase boxes, and `open: ID ...` starts the doors of those ids open. An
+// exported board uses them to go on from where the game stood.
+//
+// Levels with plates or hold doors need two players at once, so they only
+// load in co-op.
+//
+// Levels in a pack may also use the glyphs of the pack's custom tiles (see
+// `scripting`).
+//
+// Lines starting with `;` are comments.
+//
+// A file may open with a `version: N` line giving the format version it was
+// written in; files without one are version 1. Files from a newer version
+// are refused rather than misread. Version 1 is the only format so far; when
+// the format changes, older files get converted before parsing.
+use crate::{
+    events::Trigger,
+    map::{Exit, Id, Item, MapData, Par, Tile, TileType, MAX_LEVEL_SIZE},
+    paths::data_dir,
+    pos::Pos,
+    scripting::TileScripts,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub const LEVEL_SEPARATOR: &str = "---";
+pub const LEVEL_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug)]
+pub enum LevelFileError {
+    Io(std::io::Error),
+    Parse {
+        level: usize,
+        line: usize,
+        msg: String,
+    },
+}
+
+impl fmt::Display for LevelFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Parse { level, line, msg } => {
+                write!(f, "level {}, line {}: {}", level, line, msg)
+            }
+        }
+    }
+}
+
+impl From<std::io::Error> for LevelFileError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+pub fn boards_dir() -> PathBuf {
+    data_dir().join("boards")
+}
+
+// Writes a board exported mid-level (see `GameContext::board_text`) and
+// returns the file it went to. Boards of one level are told apart by how many
+// moves in they were taken.
+pub fn save_board(level: u32, moves: usize, text: &str) -> io::Result<PathBuf> {
+    let dir = boards_dir();
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("level-{}-move-{}.txt", level, moves));
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+pub fn load_level_file(path: &Path) -> Result<Vec<MapData>, LevelFileError> {
+    let text = fs::read_to_string(path)?;
+    parse_levels(&text)
+}
+
+pub fn parse_levels(text: &str) -> Result<Vec<MapData>, LevelFileError> {
+    parse_levels_with(text, None)
+}
+
+// Like `parse_levels`, also accepting the custom tiles in `scripts`.
+pub fn parse_levels_with(
+    text: &str,
+    scripts: Option<&Arc<TileScripts>>,
+) -> Result<Vec<MapData>, LevelFileError> {
+    let version_line = read_version(text)?;
+    let mut levels = Vec::new();
+    let mut chunk: Vec<(usize, &str)> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        if Some(i) == version_line {
+            continue;
+        }
+        if line.trim_end() == LEVEL_SEPARATOR {
+            levels.push(parse_level(levels.len(), &chunk, scripts)?);
+            chunk.clear();
+        } else {
+            chunk.push((i + 1, line));
+        }
+    }
+    if chunk.iter().any(|(_, l)| !l.trim().is_empty()) {
+        levels.push(parse_level(levels.len(), &chunk, scripts)?);
+    }
+    if levels.is_empty() {
+        return Err(LevelFileError::Parse {
+            level: 0,
+            line: 0,
+            msg: "file contains no levels".to_string(),
+        });
+    }
+    Ok(levels)
+}
+
+// Checks the `version: N` line, if the file has one, and returns its index.
+fn read_version(text: &str) -> Result<Option<usize>, LevelFileError> {
+    let Some((i, line)) = text
+        .lines()
+        .enumerate()
+        .find(|(_, l)| !l.trim().is_empty() && !l.starts_with(';'))
+    else {
+        return Ok(None);
+    };
+    let Some(("version", value)) = line.split_once(':').map(|(k, v)| (k.trim(), v.trim())) else {
+        return Ok(None);
+    };
+    let err = |msg: String| LevelFileError::Parse {
+        level: 0,
+        line: i + 1,
+        msg,
+    };
+    let version: u32 = value
+        .parse()
+        .map_err(|_| err(format!("`{}` is not a format version", value)))?;
+    if version > LEVEL_FORMAT_VERSION {
+        return Err(err(format!(
+            "file is format version {}, but this game only reads up to {}",
+            version, LEVEL_FORMAT_VERSION
+        )));
+    }
+    Ok(Some(i))
+}
+
+fn parse_level(
+    level: usize,
+    lines: &[(usize, &str)],
+    scripts: Option<&Arc<TileScripts>>,
+) -> Result<MapData, LevelFileError> {
+    let err = |line: usize, msg: String| LevelFileError::Parse { level, line, msg };
+
+    let mut flavor_text = None;
+    let mut flavor_translations = BTreeMap::new();
+    let mut par = None;
+    let mut pads = BTreeMap::new();
+    let mut close_delays = BTreeMap::new();
+    let mut grapples = 0;
+    let mut inventory = BTreeMap::new();
+    let mut undos = None;
+    let mut spawns = BTreeMap::new();
+    let mut exits = BTreeMap::new();
+    let mut warp = DEFAULT_WARP;
+    let mut event_flavor = BTreeMap::new();
+    let mut turn = 0;
+    let mut open = BTreeSet::new();
+    let mut tile_map = Vec::new();
+    let mut player_spawn = None;
+    let mut y = 0;
+    let mut in_grid = false;
+
+    for &(line_no, line) in lines {
+        if line.starts_with(';') {
+            continue;
+        }
+        if !in_grid {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some((key, value)) = line.split_once(':') {
+                match key.trim() {
+                    "flavor" => flavor_text = Some(value.trim().to_string()),
+                    "par" => par = Some(parse_par(value).map_err(|msg| err(line_no, msg))?),
+                    "pad" => {
+                        let (id, count, every) =
+                            parse_pad(value).map_err(|msg| err(line_no, msg))?;
+                        pads.insert(id, (count, every));
+                    }
+                    "delay" => {
+                        let (id, moves) = parse_delay(value).map_err(|msg| err(line_no, msg))?;
+                        close_delays.insert(id, moves);
+                    }
+                    "item" => {
+                        let (item, count) = parse_item(value).map_err(|msg| err(line_no, msg))?;
+                        inventory.insert(item, count);
+                    }
+                    "undos" => {
+                        undos = Some(value.trim().parse().map_err(|_| {
+                            err(line_no, format!("`{}` is not an undo count", value.trim()))
+                        })?);
+                    }
+                    "spawn" => {
+                        let (name, pos) = parse_spawn(value).map_err(|msg| err(line_no, msg))?;
+                        spawns.insert(name, pos);
+                    }
+                    "exit" => {
+                        let (pos, exit) = parse_exit(value).map_err(|msg| err(line_no, msg))?;
+                        exits.insert(pos, exit);
+                    }
+                    "on" => {
+                        let (trigger, message) =
+                            parse_on(value).map_err(|msg| err(line_no, msg))?;
+                        event_flavor.insert(trigger, message);
+                    }
+                    "warp" => {
+                        warp = value.trim().parse().map_err(|_| {
+                            err(
+                                line_no,
+                                format!("`{}` is not a number of levels", value.trim()),
+                            )
+                        })?;
+                    }
+                    "turn" => {
+                        turn = value.trim().parse().map_err(|_| {
+                            err(line_no, format!("`{}` is not a move count", value.trim()))
+                        })?;
+                    }
+                    "open" => open = parse_open(value).map_err(|msg| err(line_no, msg))?,
+                    "grapples" => {
+                        grapples = value.trim().parse().map_err(|_| {
+                            err(
+                                line_no,
+                                format!("`{}` is not a grapple count", value.trim()),
+                            )
+                        })?;
+                    }
+                    other => match other.strip_prefix("flavor.") {
+                        Some(language) if !language.is_empty() => {
+                            flavor_translations
+                                .insert(language.to_string(), value.trim().to_string());
+                        }
+                        _ => return Err(err(line_no, format!("unknown header `{}`", other))),
+                    },
+                }
+                continue;
+            }
+            in_grid = true;
+        }
+        if y >= MAX_LEVEL_SIZE || line.chars().count() > MAX_LEVEL_SIZE as usize {
+            return Err(err(
+                line_no,
+                format!("level is bigger than {0} by {0} cells", MAX_LEVEL_SIZE),
+            ));
+        }
+        for (x, c) in line.chars().enumerate() {
+            let x = x as i32;
+            let tile_type = match c {
+                ' ' | '.' => continue,
+                'P' => {
+                    if player_spawn.is_some() {
+                        return Err(err(line_no, "more than one player spawn".to_string()));
+                    }
+                    player_spawn = Some(Pos::new(y, x));
+                    continue;
+                }
+                _ if scripts.is_some_and(|s| s.has(c)) => TileType::Custom(c),
+                _ => match tile_from_char(c) {
+                    Some(TileType::Button(id)) => match pads.get(&id) {
+                        Some(&(count, every)) => TileType::StepPad(id, count, every),
+                        None => TileType::Button(id),
+                    },
+                    Some(TileType::Warp(_)) => TileType::Warp(warp),
+                    Some(TileType::Door(Some(id), _)) => {
+                        TileType::Door(Some(id), open.contains(&id))
+                    }
+                    Some(tile_type) => tile_type,
+                    None => return Err(err(line_no, format!("unknown tile `{}`", c))),
+                },
+            };
+            tile_map.push(Tile::new(y, x, tile_type));
+        }
+        y += 1;
+    }
+
+    let player_spawn = player_spawn.ok_or_else(|| {
+        err(
+            lines.first().map(|l| l.0).unwrap_or(0),
+            "missing player spawn `P`".to_string(),
+        )
+    })?;
+    let mut map = MapData::new(tile_map, player_spawn, flavor_text)
+        .with_scripts(scripts.cloned())
+        .with_turn(turn);
+    map.flavor_translations = flavor_translations;
+    map.par = par;
+    map.close_delays = close_delays;
+    map.grapples = grapples;
+    map.inventory = inventory;
+    map.undos = undos;
+    map.spawns = spawns;
+    map.event_flavor = event_flavor;
+    map.exits = exits;
+    Ok(map)
+}
+
+fn parse_par(value: &str) -> Result<Par, String> {
+    let bad = || format!("`{}` is not a par (MOVES/PUSHES)", value.trim());
+    let (moves, pushes) = value.split_once('/').ok_or_else(bad)?;
+    Ok(Par {
+        moves: moves.trim().parse().map_err(|_| bad())?,
+        pushes: pushes.trim().parse().map_err(|_| bad())?,
+    })
+}
+
+fn parse_pad(value: &str) -> Result<(Id, u32, u32), String> {
+    let bad = || format!("`{}` is not a step pad (ID COUNT/EVERY)", value.trim());
+    let (id, rule) = value.trim().split_once(' ').ok_or_else(bad)?;
+    let (count, every) = rule.split_once('/').ok_or_else(bad)?;
+    let id: Id = id.parse().map_err(|_| bad())?;
+    let count: u32 = count.trim().parse().map_err(|_| bad())?;
+    let every: u32 = every.trim().parse().map_err(|_| bad())?;
+    if id > 9 {
+        return Err(format!("pad {} has no button character (0-9)", id));
+    }
+    if count >= every {
+        return Err(format!(
+            "a step pad's count must be below its modulus, not {}/{}",
+            count, every
+        ));
+    }
+    Ok((id, count, every))
+}
+
+fn parse_delay(value: &str) -> Result<(Id, u32), String> {
+    let bad = || format!("`{}` is not a door close delay (ID MOVES)", value.trim());
+    let (id, moves) = value.trim().split_once(' ').ok_or_else(bad)?;
+    let id: Id = id.parse().map_err(|_| bad())?;
+    let moves: u32 = moves.trim().parse().map_err(|_| bad())?;
+    if id > 25 {
+        return Err(format!("door {} has no door character (a-z)", id));
+    }
+    Ok((id, moves))
+}
+
+fn parse_open(value: &str) -> Result<BTreeSet<Id>, String> {
+    value
+        .split_whitespace()
+        .map(|id| match id.parse::<Id>() {
+            Ok(id) if id <= 25 => Ok(id),
+            Ok(id) => Err(format!("door {} has no door character (a-z)", id)),
+            Err(_) => Err(format!("`{}` is not a door id", id)),
+        })
+        .collect()
+}
+
+// How many levels ahead a warp sends the player, unless the level says.
+pub const DEFAULT_WARP: u32 = 3;
+
+fn parse_item(value: &str) -> Result<(Item, u32), String> {
+    let bad = || format!("`{}` is not a power-up count (KIND COUNT)", value.trim());
+    let (name, count) = value.trim().split_once(' ').ok_or_else(bad)?;
+    let item = Item::ALL
+        .into_iter()
+        .find(|item| item.name() == name)
+        .ok_or_else(|| format!("unknown power-up `{}` (phase or undo)", name))?;
+    Ok((item, count.trim().parse().map_err(|_| bad())?))
+}
+
+fn parse_on(value: &str) -> Result<(Trigger, String), String> {
+    let bad = || {
+        format!(
+            "`{}` is not an event message (TRIGGER MESSAGE)",
+            value.trim()
+        )
+    };
+    let (kind, rest) = value.trim().split_once(' ').ok_or_else(bad)?;
+    let with_id = |rest: &str| -> Result<(Id, String), String> {
+        let (id, message) = rest.trim().split_once(' ').ok_or_else(bad)?;
+        Ok((id.parse().map_err(|_| bad())?, message.to_string()))
+    };
+    let (trigger, message) = match kind {
+        "push" => (Trigger::Push, rest.to_string()),
+        "gem" => (Trigger::Gem, rest.to_string()),
+        "item" => (Trigger::Item, rest.to_string()),
+        "button" => {
+            let (id, message) = with_id(rest)?;
+            (Trigger::Button(id), message)
+        }
+        "door" => {
+            let (id, message) = with_id(rest)?;
+            (Trigger::Door(id), message)
+        }
+        other => {
+            return Err(format!(
+                "unknown trigger `{}` (push, button, door, gem or item)",
+                other
+            ))
+        }
+    };
+    let message = message.trim();
+    if message.is_empty() {
+        return Err(bad());
+    }
+    Ok((trigger, message.to_string()))
+}
+
+// A row and column, as in `spawn` and `exit` headers.
+fn parse_cell(row: &str, col: &str) -> Option<Pos> {
+    let (y, x): (i32, i32) = (row.parse().ok()?, col.parse().ok()?);
+    let fits = |n: i32| (0..MAX_LEVEL_SIZE).contains(&n);
+    (fits(y) && fits(x)).then(|| Pos::new(y, x))
+}
+
+fn parse_spawn(value: &str) -> Result<(String, Pos), String> {
+    let bad = || format!("`{}` is not a spawn point (NAME ROW COL)", value.trim());
+    let &[name, row, col] = value.split_whitespace().collect::<Vec<_>>().as_slice() else {
+        return Err(bad());
+    };
+    Ok((name.to_string(), parse_cell(row, col).ok_or_else(bad)?))
+}
+
+fn parse_exit(value: &str) -> Result<(Pos, Exit), String> {
+    let bad = || format!("`{}` is not an exit (ROW COL LEVEL SPAWN)", value.trim());
+    let &[row, col, level, spawn] = value.split_whitespace().collect::<Vec<_>>().as_slice() else {
+        return Err(bad());
+    };
+    let level = match level {
+        "next" => None,
+        level => Some(level.parse().map_err(|_| bad())?),
+    };
+    let exit = Exit {
+        level,
+        spawn: spawn.to_string(),
+    };
+    Ok((parse_cell(row, col).ok_or_else(bad)?, exit))
+}
+
+fn par_line(par: Par) -> String {
+    format!("par: {}/{}", par.moves, par.pushes)
+}
+
+// Rewrites the `par` header of each level in `text` that has Some par in
+// `pars`, leaving everything else in the file, comments included, as it was.
+pub fn with_pars(text: &str, pars: &[Option<Par>]) -> String {
+    let mut out = String::new();
+    let mut level = 0;
+    let mut in_grid = false;
+    for line in text.lines() {
+        if line.trim_end() == LEVEL_SEPARATOR {
+            level += 1;
+            in_grid = false;
+        } else if !in_grid && !line.starts_with(';') && !line.trim().is_empty() {
+            let new_par = pars.get(level).copied().flatten();
+            match line.split_once(':') {
+                Some((key, _)) if key.trim() == "par" && new_par.is_some() => continue,
+                Some(_) => (),
+                None => {
+                    in_grid = true;
+                    if let Some(par) = new_par {
+                        out.push_str(&par_line(par));
+                        out.push('\n');
+                    }
+                }
+            }
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+// The tile a grid character stands for. Empty floor and the player spawn are
+// not tiles, so they give None like any unknown character.
+pub fn tile_from_char(c: char) -> Option<TileType> {
+    Some(match c {
+        'B' => TileType::Wall1,
+        '@' => TileType::PushBox,
+        '$' => TileType::SlipperyBox,
+        '&' => TileType::PhaseBox,
+        '#' => TileType::WinPad,
+        'D' => TileType::Door(None, false),
+        '=' => TileType::Plate,
+        '|' => TileType::HoldDoor(false),
+        '!' => TileType::Pickup(Item::Phase),
+        '+' => TileType::Pickup(Item::Undo),
+        '*' => TileType::Gem,
+        '~' => TileType::Warp(DEFAULT_WARP),
+        '0'..='9' => TileType::Button(c as u32 - '0' as u32),
+        'a'..='z' => TileType::Door(Some(c as u32 - 'a' as u32), false),
+        _ => return None,
+    })
+}
+
+pub fn tile_char(tile_type: TileType) -> char {
+    match tile_type {
+        TileType::Empty => ' ',
+        TileType::Wall1 => 'B',
+        TileType::PushBox => '@',
+        TileType::SlipperyBox => '$',
+        TileType::PhaseBox => '&',
+        TileType::WinPad => '#',
+        TileType::Custom(glyph) => glyph,
+        TileType::Plate => '=',
+        TileType::HoldDoor(_) => '|',
+        TileType::Pickup(_) | TileType::Gem => tile_type.glyph(),
+        TileType::Warp(_) => '~',
+        TileType::Button(id) | TileType::StepPad(id, ..) if id < 10 => char::from(b'0' + id as u8),
+        TileType::Door(Some(id), _) if id < 26 => char::from(b'a' + id as u8),
+        TileType::Button(_) | TileType::StepPad(..) | TileType::Door(..) => 'D',
+    }
+}
+
+// Writes several levels as one file, marked with the current format version.
+pub fn levels_to_text(maps: &[MapData]) -> String {
+    let levels: Vec<String> = maps.iter().map(level_to_text).collect();
+    format!(
+        "version: {}\n{}",
+        LEVEL_FORMAT_VERSION,
+        levels.join(&format!("{}\n", LEVEL_SEPARATOR))
+    )
+}
+
+// The map grid of a level, one character per cell, without any header.
+pub fn level_grid(map: &MapData) -> Vec<Vec<char>> {
+    let mut grid: Vec<Vec<char>> = Vec::new();
+    let mut put = |Pos { y, x }: Pos, c: char| {
+        if y < 0 || x < 0 {
+            return;
+        }
+        let (y, x) = (y as usize, x as usize);
+        if grid.len() <= y {
+            grid.resize(y + 1, Vec::new());
+        }
+        if grid[y].len() <= x {
+            grid[y].resize(x + 1, ' ');
+        }
+        grid[y][x] = c;
+    };
+    for tile in map.tiles() {
+        put(tile.pos(), tile_char(tile.tile_type));
+    }
+    put(map.player_spawn, 'P');
+    grid
+}
+
+// The header lines of a level, each ending in a newline.
+pub fn level_header(map: &MapData) -> String {
+    let mut text = String::new();
+    if let Some(flavor) = &map.flavor_text {
+        text.push_str(&format!("flavor: {}\n", flavor));
+    }
+    for (language, flavor) in &map.flavor_translations {
+        text.push_str(&format!("flavor.{}: {}\n", language, flavor));
+    }
+    if let Some(par) = map.par {
+        text.push_str(&par_line(par));
+        text.push('\n');
+    }
+    let pads: BTreeMap<Id, (u32, u32)> = map
+        .tiles()
+        .filter_map(|t| match t.tile_type {
+            TileType::StepPad(id, count, every) => Some((id, (count, every))),
+            _ => None,
+        })
+        .collect();
+    for (id, (count, every)) in pads {
+        text.push_str(&format!("pad: {} {}/{}\n", id, count, every));
+    }
+    for (id, moves) in &map.close_delays {
+        text.push_str(&format!("delay: {} {}\n", id, moves));
+    }
+    if map.grapples > 0 {
+        text.push_str(&format!("grapples: {}\n", map.grapples));
+    }
+    for (item, count) in &map.inventory {
+        text.push_str(&format!("item: {} {}\n", item.name(), count));
+    }
+    if let Some(undos) = map.undos {
+        text.push_str(&format!("undos: {}\n", undos));
+    }
+    let warp = map.tiles().find_map(|t| match t.tile_type {
+        TileType::Warp(levels) => Some(levels),
+        _ => None,
+    });
+    if let Some(levels) = warp.filter(|&levels| levels != DEFAULT_WARP) {
+        text.push_str(&format!("warp: {}\n", levels));
+    }
+    for (name, pos) in &map.spawns {
+        text.push_str(&format!("spawn: {} {} {}\n", name, pos.y, pos.x));
+    }
+    for (pos, exit) in &map.exits {
+        let level = exit
+            .level
+            .map_or("next".to_string(), |level| level.to_string());
+        text.push_str(&format!(
+            "exit: {} {} {} {}\n",
+            pos.y, pos.x, level, exit.spawn
+        ));
+    }
+    for (trigger, message) in &map.event_flavor {
+        text.push_str(&format!("on: {} {}\n", trigger, message));
+    }
+    if map.turn() > 0 {
+        text.push_str(&format!("turn: {}\n", map.turn()));
+    }
+    let open: BTreeSet<Id> = map
+        .tiles()
+        .filter_map(|t| match t.tile_type {
+            TileType::Door(Some(id), true) => Some(id),
+            _ => None,
+        })
+        .collect();
+    if !open.is_empty() {
+        let open: Vec<String> = open.iter().map(Id::to_string).collect();
+        text.push_str(&format!("open: {}\n", open.join(" ")));
+    }
+    text
+}
+
+// Writes a single level in the same format `parse_levels` reads.
+pub fn level_to_text(map: &MapData) -> String {
+    let mut text = level_header(map);
+    for row in level_grid(map) {
+        if row.iter().all(|&c| c == ' ') {
+            // A blank line before the grid would be skipped, shifting every row.
+            text.push('.');
+        } else {
+            text.extend(row);
+        }
+        text.push('\n');
+    }
+    text
+}
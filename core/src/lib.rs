@@ -0,0 +1,64 @@
+// Game rules and level handling for button trial, with no terminal code:
+// maps and movement, level files and codes, the solver and generator.
+use pos::Pos;
+use serde::{Deserialize, Serialize};
+
+pub mod action;
+pub mod actor;
+pub mod deadlock;
+pub mod describe;
+pub mod difficulty;
+pub mod editor;
+pub mod events;
+pub mod game;
+pub mod generator;
+pub mod grid;
+pub mod headless;
+pub mod history;
+pub mod hot_reload;
+pub mod level_file;
+pub mod lint;
+pub mod lurd;
+pub mod map;
+pub mod map_builder;
+pub mod pathfind;
+pub mod paths;
+pub mod pos;
+pub mod practice;
+pub mod save;
+pub mod score;
+pub mod scripting;
+pub mod share_code;
+pub mod solver;
+pub mod thumbnail;
+pub mod transform;
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    // The one-cell step this direction moves by.
+    pub fn delta(self) -> Pos {
+        match self {
+            Self::Up => Pos::new(-1, 0),
+            Self::Down => Pos::new(1, 0),
+            Self::Left => Pos::new(0, -1),
+            Self::Right => Pos::new(0, 1),
+        }
+    }
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Up => Self::Down,
+            Self::Down => Self::Up,
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
@@ -0,0 +1,316 @@
+// Design checks for level files: catches mistakes that parse fine but make a
+// level broken or confusing to play.
+use crate::{
+    map::{MapData, TileType},
+    pos::Pos,
+    Direction,
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+};
+
+pub struct Lint {
+    pub pos: Option<Pos>,
+    pub message: String,
+}
+
+impl fmt::Display for Lint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.pos {
+            Some(pos) => write!(f, "{}: {}", pos, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+fn lint(pos: Pos, message: String) -> Lint {
+    Lint {
+        pos: Some(pos),
+        message,
+    }
+}
+
+pub fn lint_level(map: &MapData) -> Vec<Lint> {
+    let mut lints = Vec::new();
+    check_links(map, &mut lints);
+    check_overlaps(map, &mut lints);
+    check_win_pads(map, &mut lints);
+    check_stuck_boxes(map, &mut lints);
+    check_box_reach(map, &mut lints);
+    check_spawns_and_exits(map, &mut lints);
+    lints
+}
+
+fn check_spawns_and_exits(map: &MapData, lints: &mut Vec<Lint>) {
+    for (name, &pos) in &map.spawns {
+        let cell = map.cell_at(pos);
+        if cell.is_solid() || cell.has_pushable() {
+            lints.push(lint(
+                pos,
+                format!("spawn point {} is inside a solid tile", name),
+            ));
+        }
+    }
+    for &pos in map.exits.keys() {
+        if !map.is_win_pad_at(pos) {
+            lints.push(lint(pos, "exit is not on a win pad".to_string()));
+        }
+    }
+}
+
+fn check_links(map: &MapData, lints: &mut Vec<Lint>) {
+    let mut button_ids = HashSet::new();
+    let mut door_ids = HashSet::new();
+    for tile in map.tiles() {
+        match tile.tile_type {
+            TileType::Button(id) | TileType::StepPad(id, ..) => {
+                button_ids.insert(id);
+            }
+            TileType::Door(Some(id), _) => {
+                door_ids.insert(id);
+            }
+            _ => (),
+        }
+    }
+    for tile in map.tiles() {
+        match tile.tile_type {
+            TileType::Button(id) if !door_ids.contains(&id) => {
+                lints.push(lint(tile.pos(), format!("button {} controls no door", id)));
+            }
+            TileType::StepPad(id, ..) if !door_ids.contains(&id) => {
+                lints.push(lint(tile.pos(), format!("step pad {} opens no door", id)));
+            }
+            TileType::Door(Some(id), false) if !button_ids.contains(&id) => {
+                lints.push(lint(tile.pos(), format!("door {} has no button", id)));
+            }
+            TileType::Door(None, false) => {
+                lints.push(lint(tile.pos(), "door has no button".to_string()));
+            }
+            TileType::Plate if !has(map, |t| matches!(t, TileType::HoldDoor(_))) => {
+                lints.push(lint(tile.pos(), "plate opens no hold door".to_string()));
+            }
+            TileType::HoldDoor(false) if !has(map, |t| t == TileType::Plate) => {
+                lints.push(lint(tile.pos(), "hold door has no plate".to_string()));
+            }
+            _ => (),
+        }
+    }
+}
+
+fn has(map: &MapData, wanted: impl Fn(TileType) -> bool) -> bool {
+    map.tiles().any(|t| wanted(t.tile_type))
+}
+
+// A box may sit on a button, win pad or open door; a wall may not sit on
+// anything, and nothing may sit on a closed door.
+fn check_overlaps(map: &MapData, lints: &mut Vec<Lint>) {
+    for (pos, cell) in map.cells() {
+        let (Some(floor), Some(object)) = (cell.floor, cell.object) else {
+            continue;
+        };
+        if object.tile_type == TileType::Wall1 || floor.tile_type.is_solid() {
+            let names = [floor, object].map(|t| tile_name(t.tile_type));
+            lints.push(lint(
+                pos,
+                format!("overlapping tiles: {}", names.join(", ")),
+            ));
+        }
+    }
+    let spawn = map.cell_at(map.player_spawn);
+    if spawn.is_solid() || spawn.has_pushable() {
+        lints.push(lint(
+            map.player_spawn,
+            "player spawns inside a solid tile".to_string(),
+        ));
+    }
+}
+
+fn tile_name(tile_type: TileType) -> String {
+    match tile_type {
+        TileType::Empty => "empty".to_string(),
+        TileType::Wall1 => "wall".to_string(),
+        TileType::PushBox => "box".to_string(),
+        TileType::SlipperyBox => "slippery box".to_string(),
+        TileType::PhaseBox => "phase box".to_string(),
+        TileType::Pickup(item) => format!("{} power-up", item.name()),
+        TileType::Gem => "gem".to_string(),
+        TileType::Warp(_) => "warp".to_string(),
+        TileType::Button(id) => format!("button {}", id),
+        TileType::Door(Some(id), _) => format!("door {}", id),
+        TileType::Door(None, _) => "door".to_string(),
+        TileType::WinPad => "win pad".to_string(),
+        TileType::Custom(glyph) => format!("custom tile {}", glyph),
+        TileType::Plate => "plate".to_string(),
+        TileType::HoldDoor(_) => "hold door".to_string(),
+        TileType::StepPad(id, ..) => format!("step pad {}", id),
+    }
+}
+
+// Cells that can never be entered: walls and doors nothing can open.
+fn permanent_walls(map: &MapData) -> HashSet<Pos> {
+    let button_ids: HashSet<u32> = map
+        .tiles()
+        .filter_map(|t| match t.tile_type {
+            TileType::Button(id) | TileType::StepPad(id, ..) => Some(id),
+            _ => None,
+        })
+        .collect();
+    let plates = has(map, |t| t == TileType::Plate);
+    map.tiles()
+        .filter(|t| match t.tile_type {
+            TileType::Wall1 => true,
+            TileType::HoldDoor(false) => !plates,
+            TileType::Door(Some(id), false) => !button_ids.contains(&id),
+            TileType::Door(None, false) => true,
+            _ => false,
+        })
+        .map(|t| t.pos())
+        .collect()
+}
+
+// Win pads the player can't get to even if every door opens and every box
+// gets out of the way. Nothing moves outside the map's bounds, so neither
+// does the search.
+fn check_win_pads(map: &MapData, lints: &mut Vec<Lint>) {
+    let walls = permanent_walls(map);
+    let bounds = map.bounds();
+    let mut reachable = HashSet::from([map.player_spawn]);
+    let mut stack = vec![map.player_spawn];
+    while let Some(pos) = stack.pop() {
+        for next in pos.neighbours() {
+            if bounds.contains(next) && !walls.contains(&next) && reachable.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    let mut any_pad = false;
+    for tile in map.tiles() {
+        if tile.tile_type == TileType::WinPad {
+            any_pad = true;
+            if !reachable.contains(&tile.pos()) {
+                lints.push(lint(tile.pos(), "win pad is unreachable".to_string()));
+            }
+        }
+    }
+    if !any_pad {
+        lints.push(Lint {
+            pos: None,
+            message: "level has no win pad".to_string(),
+        });
+    }
+}
+
+// A box wedged into a corner of walls can never be pushed again, which only
+// matters if it isn't already holding a button down.
+fn check_stuck_boxes(map: &MapData, lints: &mut Vec<Lint>) {
+    let walls = permanent_walls(map);
+    let is_wall = |pos: Pos, direction: Direction| walls.contains(&(pos + direction));
+    for tile in map.tiles() {
+        let pos = tile.pos();
+        let on_button = matches!(map.cell_at(pos).floor_type(), TileType::Button(_));
+        if !tile.tile_type.is_pushable() || on_button {
+            continue;
+        }
+        let vertical = is_wall(pos, Direction::Up) || is_wall(pos, Direction::Down);
+        let horizontal = is_wall(pos, Direction::Left) || is_wall(pos, Direction::Right);
+        if vertical && horizontal {
+            lints.push(lint(
+                pos,
+                "box starts in a corner and can never move".to_string(),
+            ));
+        }
+    }
+}
+
+// Cells the player can walk to from `from` with a box at `box_pos`, if every
+// door opens and the other boxes get out of the way.
+fn walkable_region(map: &MapData, walls: &HashSet<Pos>, from: Pos, box_pos: Pos) -> HashSet<Pos> {
+    let bounds = map.bounds();
+    let mut region = HashSet::from([from]);
+    let mut stack = vec![from];
+    while let Some(pos) = stack.pop() {
+        for next in pos.neighbours() {
+            let open = bounds.contains(next) && !walls.contains(&next) && next != box_pos;
+            if open && region.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    region
+}
+
+// Every cell the box starting at `start` can be pushed to, alone in the
+// level. Unlike plain connectivity this follows the pushes: the player has
+// to get behind the box first, and a box against a wall can only slide
+// along it.
+fn push_reach(map: &MapData, walls: &HashSet<Pos>, start: Pos) -> HashSet<Pos> {
+    let bounds = map.bounds();
+    let mut reached = HashSet::new();
+    // The box and the top-left cell the player can reach, which stands for
+    // the whole region they can walk around in.
+    let mut seen = HashSet::new();
+    let mut stack = vec![(start, map.player_spawn)];
+    while let Some((box_pos, player)) = stack.pop() {
+        let region = walkable_region(map, walls, player, box_pos);
+        let Some(&corner) = region.iter().min() else {
+            continue;
+        };
+        if !seen.insert((box_pos, corner)) {
+            continue;
+        }
+        reached.insert(box_pos);
+        for direction in Direction::ALL {
+            let behind = box_pos + direction.opposite();
+            let to = box_pos + direction;
+            if region.contains(&behind) && bounds.contains(to) && !walls.contains(&to) {
+                stack.push((to, box_pos));
+            }
+        }
+    }
+    reached
+}
+
+// Doors that can never open because too few boxes can be pushed onto their
+// buttons, the player holding down one button themselves.
+fn check_box_reach(map: &MapData, lints: &mut Vec<Lint>) {
+    let walls = permanent_walls(map);
+    let mut buttons: BTreeMap<u32, Vec<Pos>> = BTreeMap::new();
+    let mut doors: BTreeMap<u32, Pos> = BTreeMap::new();
+    let mut boxes = Vec::new();
+    for tile in map.tiles() {
+        match tile.tile_type {
+            TileType::Button(id) => buttons.entry(id).or_default().push(tile.pos()),
+            TileType::Door(Some(id), false) => {
+                doors.entry(id).or_insert(tile.pos());
+            }
+            // A slippery box can stop in fewer places, but never more.
+            TileType::PushBox | TileType::SlipperyBox => boxes.push(tile.pos()),
+            _ => (),
+        }
+    }
+    let reach: Vec<HashSet<Pos>> = boxes
+        .iter()
+        .map(|&pos| push_reach(map, &walls, pos))
+        .collect();
+    for (id, door) in doors {
+        let Some(buttons) = buttons.get(&id) else {
+            continue;
+        };
+        let usable = reach
+            .iter()
+            .filter(|cells| buttons.iter().any(|button| cells.contains(button)))
+            .count();
+        if usable + 1 < buttons.len() {
+            lints.push(lint(
+                door,
+                format!(
+                    "door {} can never open: it needs {} box(es) on its buttons, but only {} can get there",
+                    id,
+                    buttons.len() - 1,
+                    usable
+                ),
+            ));
+        }
+    }
+}
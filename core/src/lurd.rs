@@ -0,0 +1,82 @@
+// Solutions in the standard LURD notation: one letter per step, lowercase for
+// a plain move and uppercase for a push.
+use crate::{paths::data_dir, Direction};
+use std::{fs, io, path::PathBuf};
+
+pub fn move_char(direction: Direction, pushed: bool) -> char {
+    let c = match direction {
+        Direction::Left => 'l',
+        Direction::Up => 'u',
+        Direction::Right => 'r',
+        Direction::Down => 'd',
+    };
+    if pushed {
+        c.to_ascii_uppercase()
+    } else {
+        c
+    }
+}
+
+pub fn solutions_dir() -> PathBuf {
+    data_dir().join("solutions")
+}
+
+// Where the solution for `level` goes. Each world, named as the progress file
+// keys it, gets a folder of its own so the level numbers of different packs
+// and level files don't clash; a level from no world, such as one from a
+// share code, goes at the top.
+pub fn solution_path(world: Option<&str>, level: u32) -> PathBuf {
+    let mut path = solutions_dir();
+    if let Some(world) = world {
+        let folder: String = world
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        path.push(folder);
+    }
+    path.join(format!("level-{}.lurd", level))
+}
+
+// Writes the solution for `level` of `world` and returns the file it went to.
+pub fn save_solution(world: Option<&str>, level: u32, lurd: &str) -> io::Result<PathBuf> {
+    let path = solution_path(world, level);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(&path, format!("{}\n", lurd))?;
+    Ok(path)
+}
+
+// The direction of one LURD move, whether a push or not.
+pub fn move_direction(c: char) -> Option<Direction> {
+    match c.to_ascii_lowercase() {
+        'l' => Some(Direction::Left),
+        'u' => Some(Direction::Up),
+        'r' => Some(Direction::Right),
+        'd' => Some(Direction::Down),
+        _ => None,
+    }
+}
+
+// Reads a LURD string into moves. Case (push vs. move) is ignored since the
+// rules decide whether a step pushes; whitespace is skipped.
+pub fn parse(text: &str) -> Result<Vec<Direction>, String> {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| move_direction(c).ok_or_else(|| format!("`{}` is not a LURD move", c)))
+        .collect()
+}
+
+// Accepts either a path to a solution file or the LURD moves themselves.
+pub fn load(path_or_moves: &str) -> Result<Vec<Direction>, String> {
+    match fs::read_to_string(path_or_moves) {
+        Ok(text) => parse(&text),
+        Err(_) => parse(path_or_moves),
+    }
+}
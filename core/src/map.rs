@@ -0,0 +1,1042 @@
+use crate::{
+    actor::{Actor, ActorKind},
+    events::{EventQueue, GameEvent, Trigger},
+    grid::Grid,
+    map_builder::MapBuilder,
+    pos::{Bounds, Pos},
+    scripting::{Effect, TileScripts},
+    Direction,
+};
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, sync::Arc};
+
+pub type Id = u32;
+
+#[derive(PartialEq, Clone, Copy, Serialize, Deserialize)]
+pub enum TileType {
+    Empty,
+    Wall1,
+    PushBox,
+    // A box that slides on when pushed until it runs into something.
+    SlipperyBox,
+    Button(Id),             // button-door id
+    Door(Option<Id>, bool), // button-door id and open status
+    WinPad,
+    // A level pack's own floor tile, behaving as its script says.
+    Custom(char),
+    // Co-op tiles. Plates are held down only by players, never boxes, and
+    // hold doors stand open (the bool) only while every plate of the level
+    // has a player on it, so one player can't get through alone.
+    Plate,
+    HoldDoor(bool),
+    // A button only the player presses, and only by stepping onto it on a
+    // move whose number is the first count modulo the second: the button-door
+    // id, then that count and modulus.
+    StepPad(Id, u32, u32),
+    // A box that fades in and out with the turns: after an even number of
+    // moves it is as solid as a wall, after an odd number the player can
+    // walk through it. Boxes can never be pushed into it.
+    PhaseBox,
+    // A power-up lying on the floor, taken into the inventory when the
+    // player steps onto it.
+    Pickup(Item),
+    // A gem, worth points in scoring mode, collected by stepping onto it.
+    Gem,
+    // A secret shortcut, drawn as bare floor, that sends the player this
+    // many levels ahead.
+    Warp(u32),
+}
+
+// Power-ups the player carries, each used with its own key.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Item {
+    // The next move goes through one wall or closed door to the cell beyond.
+    Phase,
+    // Takes back a move even once the level's undos have run out.
+    Undo,
+}
+
+impl Item {
+    pub const ALL: [Self; 2] = [Self::Phase, Self::Undo];
+
+    // The name in `item` headers.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Phase => "phase",
+            Self::Undo => "undo",
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum MoveOutcome {
+    Blocked,
+    Moved,
+    Pushed,
+}
+
+impl TileType {
+    pub fn glyph(self) -> char {
+        match self {
+            Self::Empty => ' ',
+            Self::Wall1 => 'B',
+            Self::PushBox => '@',
+            Self::SlipperyBox => '$',
+            Self::Button(..) => '^',
+            Self::Door(..) => 'D',
+            Self::WinPad => '#',
+            Self::Custom(glyph) => glyph,
+            Self::Plate => '=',
+            Self::HoldDoor(..) => '|',
+            Self::StepPad(..) => '%',
+            Self::PhaseBox => '&',
+            Self::Pickup(Item::Phase) => '!',
+            Self::Pickup(Item::Undo) => '+',
+            Self::Gem => '*',
+            Self::Warp(_) => ' ',
+        }
+    }
+    pub fn is_solid(self) -> bool {
+        matches!(
+            self,
+            Self::Wall1 | Self::Door(_, false) | Self::HoldDoor(false)
+        )
+    }
+    // Walls and boxes sit on top of a cell's floor; everything else is floor.
+    pub fn is_object(self) -> bool {
+        matches!(self, Self::Wall1 | Self::PushBox | Self::SlipperyBox)
+    }
+    // Tiles that can change during play, as opposed to fixed terrain.
+    pub fn is_entity(self) -> bool {
+        matches!(
+            self,
+            Self::PushBox
+                | Self::SlipperyBox
+                | Self::Door(..)
+                | Self::HoldDoor(..)
+                | Self::Pickup(_)
+                | Self::Gem
+        )
+    }
+    // Tiles that need two players at once, so only load in co-op.
+    pub fn is_coop_only(self) -> bool {
+        matches!(self, Self::Plate | Self::HoldDoor(..))
+    }
+    pub fn is_pushable(self) -> bool {
+        matches!(self, Self::PushBox | Self::SlipperyBox)
+    }
+    // The kind of actor a pushable tile moves as.
+    pub fn pushed_as(self) -> ActorKind {
+        match self {
+            Self::SlipperyBox => ActorKind::SlipperyBox,
+            _ => ActorKind::Box,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct Tile {
+    y: i32,
+    x: i32,
+    pub tile_type: TileType,
+}
+
+impl Tile {
+    pub fn new(y: i32, x: i32, tile_type: TileType) -> Self {
+        Self { y, x, tile_type }
+    }
+    pub fn pos(&self) -> Pos {
+        Pos::new(self.y, self.x)
+    }
+}
+
+// What one cell holds: a floor tile (button, win pad, door) with at most one
+// object (wall or box) on top. Floors are drawn first, then objects.
+#[derive(Clone, Copy, Default)]
+pub struct Cell {
+    pub floor: Option<Tile>,
+    pub object: Option<Tile>,
+}
+
+impl Cell {
+    // A later tile on the same layer replaces the earlier one.
+    fn place(&mut self, tile: Tile) {
+        if tile.tile_type.is_object() {
+            self.object = Some(tile);
+        } else {
+            self.floor = Some(tile);
+        }
+    }
+    fn is_empty(&self) -> bool {
+        self.floor.is_none() && self.object.is_none()
+    }
+    // Entity layers override the terrain beneath them.
+    fn over(self, below: Cell) -> Cell {
+        Cell {
+            floor: self.floor.or(below.floor),
+            object: self.object.or(below.object),
+        }
+    }
+    pub fn tiles(&self) -> impl Iterator<Item = Tile> {
+        self.floor.into_iter().chain(self.object)
+    }
+    pub fn floor_type(&self) -> TileType {
+        self.floor.map_or(TileType::Empty, |t| t.tile_type)
+    }
+    // Nothing but floor, as far as the player can tell: warps are secret.
+    pub fn is_bare(&self) -> bool {
+        self.object.is_none() && matches!(self.floor_type(), TileType::Empty | TileType::Warp(_))
+    }
+    pub fn is_solid(&self) -> bool {
+        self.tiles().any(|t| t.tile_type.is_solid())
+    }
+    pub fn has_pushable(&self) -> bool {
+        self.object.is_some_and(|t| t.tile_type.is_pushable())
+    }
+    // The kind of actor the object here moves as when pushed, if it can be.
+    fn pushable_kind(&self) -> Option<ActorKind> {
+        self.object
+            .filter(|t| t.tile_type.is_pushable())
+            .map(|t| t.tile_type.pushed_as())
+    }
+}
+
+// Levels are split in two. Terrain (walls, buttons, win pads) never changes
+// during play, so copies of a level share it; entities (boxes and doors) are
+// everything a move can change, and are all a copy actually duplicates.
+// Serialized as a plain tile list, which is rebuilt into layers on load.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(try_from = "MapRecord", into = "MapRecord")]
+pub struct MapData {
+    terrain: Arc<Grid<Cell>>,
+    entities: BTreeMap<Pos, Cell>,
+    links: Arc<Links>,
+    // How many of each id's buttons have a box on them, kept up to date as
+    // boxes are pushed.
+    boxed_buttons: BTreeMap<Id, usize>,
+    // The smallest rectangle holding every tile and the spawn point. Nothing
+    // can be moved outside it, so a level without enclosing walls just stops
+    // at its edge.
+    bounds: Bounds,
+    pub player_spawn: Pos,
+    pub flavor_text: Option<String>,
+    // Translations of the flavor text, by language code.
+    pub flavor_translations: BTreeMap<String, String>,
+    // The official par, if the level has one.
+    pub par: Option<Par>,
+    // For the doors that close again, by id: how many moves they stay open
+    // after their buttons are let go. Other doors stay open for good.
+    pub close_delays: BTreeMap<Id, u32>,
+    // The turn at which the doors of each id whose buttons were let go, or
+    // that a script closed, will close.
+    closing: BTreeMap<Id, usize>,
+    // How many times the player may use the grapple on this level, and how
+    // many of those have been used.
+    pub grapples: u32,
+    grapples_used: u32,
+    // The power-ups the player holds, and how many undos the level allows if
+    // it limits them.
+    pub inventory: BTreeMap<Item, u32>,
+    pub undos: Option<u32>,
+    // Whether a grapple or phase has moved the player in a way LURD can't
+    // write down.
+    unrecorded: bool,
+    // Gems collected so far.
+    gems: u32,
+    // Spawn points besides `player_spawn`, by name, for levels entered from
+    // more than one way, and the win pads that lead to one of them.
+    pub spawns: BTreeMap<String, Pos>,
+    pub exits: BTreeMap<Pos, Exit>,
+    // Messages that stand in for the flavor text for a while after their
+    // events first happen.
+    pub event_flavor: BTreeMap<Trigger, String>,
+    // Moves made by players on this copy of the level so far. Undoing puts
+    // it back with the rest of the copy.
+    turn: usize,
+    // Behaviour of the custom tiles, if the level has any. Not serialized:
+    // whoever loads a saved map attaches the pack's scripts again.
+    scripts: Option<Arc<TileScripts>>,
+}
+
+// The most rows or columns a level can have.
+pub const MAX_LEVEL_SIZE: i32 = 1000;
+
+// The moves and pushes of the solver's best solution of a level: the fewest
+// pushes possible, and the moves that solution takes.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Par {
+    pub moves: usize,
+    pub pushes: usize,
+}
+
+// Where winning on a win pad leads: to a level, or else the next one, entered
+// at one of its named spawn points.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Exit {
+    pub level: Option<u32>,
+    pub spawn: String,
+}
+
+// Where the buttons and doors of each id are. Neither ever moves, so this is
+// built once per level and shared between copies like the terrain.
+#[derive(Default)]
+struct Links {
+    buttons: BTreeMap<Id, Vec<Pos>>,
+    doors: BTreeMap<Id, Vec<Pos>>,
+    plates: Vec<Pos>,
+    hold_doors: Vec<Pos>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MapRecord {
+    tiles: Vec<Tile>,
+    player_spawn: Pos,
+    flavor_text: Option<String>,
+    #[serde(default)]
+    flavor_translations: BTreeMap<String, String>,
+    #[serde(default)]
+    par: Option<Par>,
+    #[serde(default)]
+    close_delays: BTreeMap<Id, u32>,
+    #[serde(default)]
+    closing: BTreeMap<Id, usize>,
+    #[serde(default)]
+    grapples: u32,
+    #[serde(default)]
+    grapples_used: u32,
+    #[serde(default)]
+    inventory: BTreeMap<Item, u32>,
+    #[serde(default)]
+    undos: Option<u32>,
+    #[serde(default)]
+    unrecorded: bool,
+    #[serde(default)]
+    gems: u32,
+    #[serde(default)]
+    spawns: BTreeMap<String, Pos>,
+    // A list, since a map keyed by position has no plain serialized form.
+    #[serde(default)]
+    exits: Vec<(Pos, Exit)>,
+    #[serde(default)]
+    event_flavor: Vec<(Trigger, String)>,
+    #[serde(default)]
+    turn: usize,
+}
+
+impl TryFrom<MapRecord> for MapData {
+    type Error = String;
+
+    fn try_from(record: MapRecord) -> Result<Self, Self::Error> {
+        let positions = record.tiles.iter().map(Tile::pos);
+        if !fits_level_size(positions.chain([record.player_spawn])) {
+            return Err(format!(
+                "level is bigger than {0} by {0} cells",
+                MAX_LEVEL_SIZE
+            ));
+        }
+        let mut map = Self::new(record.tiles, record.player_spawn, record.flavor_text);
+        map.flavor_translations = record.flavor_translations;
+        map.par = record.par;
+        map.close_delays = record.close_delays;
+        map.closing = record.closing;
+        map.grapples = record.grapples;
+        map.grapples_used = record.grapples_used;
+        map.inventory = record.inventory;
+        map.undos = record.undos;
+        map.unrecorded = record.unrecorded;
+        map.gems = record.gems;
+        map.spawns = record.spawns;
+        map.exits = record.exits.into_iter().collect();
+        map.event_flavor = record.event_flavor.into_iter().collect();
+        map.turn = record.turn;
+        Ok(map)
+    }
+}
+
+// Whether points all fit in a level of at most `MAX_LEVEL_SIZE` each way.
+// Levels are stored densely, so anything bigger isn't worth loading.
+fn fits_level_size(points: impl IntoIterator<Item = Pos>) -> bool {
+    let (mut ys, mut xs) = ((i32::MAX, i32::MIN), (i32::MAX, i32::MIN));
+    for Pos { y, x } in points {
+        ys = (ys.0.min(y), ys.1.max(y));
+        xs = (xs.0.min(x), xs.1.max(x));
+    }
+    let span = |(low, high): (i32, i32)| high as i64 - low as i64 + 1;
+    span(ys) <= MAX_LEVEL_SIZE as i64 && span(xs) <= MAX_LEVEL_SIZE as i64
+}
+
+impl From<MapData> for MapRecord {
+    fn from(map: MapData) -> Self {
+        Self {
+            tiles: map.tiles().collect(),
+            player_spawn: map.player_spawn,
+            flavor_text: map.flavor_text,
+            flavor_translations: map.flavor_translations,
+            par: map.par,
+            close_delays: map.close_delays,
+            closing: map.closing,
+            grapples: map.grapples,
+            grapples_used: map.grapples_used,
+            inventory: map.inventory,
+            undos: map.undos,
+            unrecorded: map.unrecorded,
+            gems: map.gems,
+            spawns: map.spawns,
+            exits: map.exits.into_iter().collect(),
+            event_flavor: map.event_flavor.into_iter().collect(),
+            turn: map.turn,
+        }
+    }
+}
+
+impl MapData {
+    pub fn new(tiles: Vec<Tile>, player_spawn: Pos, flavor_text: Option<String>) -> Self {
+        let mut terrain: BTreeMap<Pos, Cell> = BTreeMap::new();
+        let mut entities: BTreeMap<Pos, Cell> = BTreeMap::new();
+        for tile in tiles {
+            let layer = if tile.tile_type.is_entity() {
+                &mut entities
+            } else {
+                &mut terrain
+            };
+            layer.entry(tile.pos()).or_default().place(tile);
+        }
+        let mut links = Links::default();
+        for (&pos, cell) in &terrain {
+            match cell.floor_type() {
+                TileType::Button(id) => links.buttons.entry(id).or_default().push(pos),
+                TileType::Plate => links.plates.push(pos),
+                _ => (),
+            }
+        }
+        for (&pos, cell) in &entities {
+            match cell.floor_type() {
+                TileType::Door(Some(id), _) => links.doors.entry(id).or_default().push(pos),
+                TileType::HoldDoor(_) => links.hold_doors.push(pos),
+                _ => (),
+            }
+        }
+        let positions = terrain.keys().chain(entities.keys()).copied();
+        let bounds = Bounds::around(positions.chain([player_spawn]));
+        let mut map = Self {
+            terrain: Arc::new(terrain.into_iter().collect()),
+            entities,
+            links: Arc::new(links),
+            boxed_buttons: BTreeMap::new(),
+            bounds,
+            player_spawn,
+            flavor_text,
+            flavor_translations: BTreeMap::new(),
+            par: None,
+            close_delays: BTreeMap::new(),
+            closing: BTreeMap::new(),
+            grapples: 0,
+            grapples_used: 0,
+            inventory: BTreeMap::new(),
+            undos: None,
+            unrecorded: false,
+            gems: 0,
+            spawns: BTreeMap::new(),
+            exits: BTreeMap::new(),
+            event_flavor: BTreeMap::new(),
+            turn: 0,
+            scripts: None,
+        };
+        let boxed: Vec<Id> = map
+            .entities
+            .iter()
+            .filter(|(_, cell)| cell.has_pushable())
+            .filter_map(|(&pos, _)| map.button_at(pos))
+            .collect();
+        for id in boxed {
+            *map.boxed_buttons.entry(id).or_default() += 1;
+        }
+        map
+    }
+    fn button_at(&self, pos: Pos) -> Option<Id> {
+        match self.terrain.at(pos)?.floor_type() {
+            TileType::Button(id) => Some(id),
+            _ => None,
+        }
+    }
+    // The flavor text in `language`, or untranslated if there is no
+    // translation for it.
+    pub fn flavor(&self, language: &str) -> Option<&str> {
+        self.flavor_translations
+            .get(language)
+            .or(self.flavor_text.as_ref())
+            .map(String::as_str)
+    }
+    pub fn with_scripts(mut self, scripts: Option<Arc<TileScripts>>) -> Self {
+        self.scripts = scripts;
+        self
+    }
+    pub fn scripts(&self) -> Option<&Arc<TileScripts>> {
+        self.scripts.as_ref()
+    }
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+    // Starts the level as if `turn` moves had been made already.
+    pub fn with_turn(mut self, turn: usize) -> Self {
+        self.turn = turn;
+        self
+    }
+    pub fn grapples_left(&self) -> u32 {
+        self.grapples.saturating_sub(self.grapples_used)
+    }
+    // Whether every move so far on this copy can be written in LURD.
+    pub fn is_recorded(&self) -> bool {
+        !self.unrecorded
+    }
+    // The gems collected so far, and how many the level has in all.
+    pub fn gems(&self) -> (u32, u32) {
+        let left = self
+            .entities
+            .values()
+            .filter(|cell| cell.floor_type() == TileType::Gem)
+            .count() as u32;
+        (self.gems, self.gems + left)
+    }
+    pub fn items(&self, item: Item) -> u32 {
+        self.inventory.get(&item).copied().unwrap_or(0)
+    }
+    // Whether phase boxes are solid to the player this turn.
+    pub fn phase_boxes_solid(&self) -> bool {
+        self.turn.is_multiple_of(2)
+    }
+    pub fn cell_at(&self, pos: Pos) -> Cell {
+        let terrain = self.terrain.at(pos).copied().unwrap_or_default();
+        match self.entities.get(&pos) {
+            Some(entities) => entities.over(terrain),
+            None => terrain,
+        }
+    }
+    // Every non-empty cell: the terrain's row by row, then any entities that
+    // have strayed off it.
+    pub fn cells(&self) -> impl Iterator<Item = (Pos, Cell)> + '_ {
+        let on_terrain = self.terrain.iter().map(|(pos, _)| (pos, self.cell_at(pos)));
+        let off_terrain = self
+            .entities
+            .iter()
+            .filter(|(pos, _)| self.terrain.at(**pos).is_none())
+            .map(|(pos, cell)| (*pos, *cell));
+        on_terrain.chain(off_terrain)
+    }
+    // Every tile, cell by cell, floor before object.
+    pub fn tiles(&self) -> impl Iterator<Item = Tile> + '_ {
+        self.cells().flat_map(|(_, cell)| cell.tiles())
+    }
+    pub fn tile_count(&self) -> usize {
+        self.tiles().count()
+    }
+    // Whether the level has co-op tiles, and so can only be played by two.
+    pub fn needs_two_players(&self) -> bool {
+        !self.links.plates.is_empty() || !self.links.hold_doors.is_empty()
+    }
+    fn press_buttons_at(&self, pos: Pos, events: &mut EventQueue) {
+        if let TileType::Button(id) = self.cell_at(pos).floor_type() {
+            events.push(GameEvent::ButtonPressed { id });
+        }
+    }
+    // Everything that happens to the player for arriving at `pos`, however
+    // they got there.
+    fn player_arrives(&mut self, pos: Pos, events: &mut EventQueue) {
+        self.step_on_pad(pos, events);
+        self.pick_up(pos, events);
+    }
+    // Takes any power-up at `pos` into the inventory, or collects a gem.
+    fn pick_up(&mut self, pos: Pos, events: &mut EventQueue) {
+        let Some(cell) = self.entities.get_mut(&pos) else {
+            return;
+        };
+        let found = cell.floor.map(|t| t.tile_type);
+        if !matches!(found, Some(TileType::Pickup(_) | TileType::Gem)) {
+            return;
+        }
+        cell.floor = None;
+        if cell.is_empty() {
+            self.entities.remove(&pos);
+        }
+        if let Some(TileType::Pickup(item)) = found {
+            *self.inventory.entry(item).or_default() += 1;
+            events.push(GameEvent::ItemPickedUp { item });
+        } else {
+            self.gems += 1;
+            events.push(GameEvent::GemCollected);
+        }
+    }
+    // Spends a phase to move `player` through the wall or closed door ahead
+    // onto the cell beyond it, which has to be free. Returns whether they
+    // went.
+    pub fn phase(
+        &mut self,
+        player: &mut Actor,
+        others: &[Pos],
+        direction: Direction,
+        events: &mut EventQueue,
+    ) -> bool {
+        let wall = player.pos() + direction;
+        let beyond = wall + direction;
+        let cell = self.cell_at(beyond);
+        if self.items(Item::Phase) == 0
+            || !self.blocks(&self.cell_at(wall), player.kind)
+            || !self.is_walkable(beyond, player.kind)
+            || cell.has_pushable()
+            || others.contains(&beyond)
+        {
+            return false;
+        }
+        *self.inventory.entry(Item::Phase).or_default() -= 1;
+        self.unrecorded = true;
+        self.turn += 1;
+        player.set_pos(beyond);
+        events.push(GameEvent::PlayerMoved { direction });
+        self.player_arrives(beyond, events);
+        self.press_buttons_at(beyond, events);
+        true
+    }
+    // Opens the doors of a step pad the player just stepped onto at `pos`,
+    // if this turn is the one it counts.
+    fn step_on_pad(&mut self, pos: Pos, events: &mut EventQueue) {
+        let TileType::StepPad(id, count, every) = self.cell_at(pos).floor_type() else {
+            return;
+        };
+        if self.turn % every.max(1) as usize != count as usize {
+            return;
+        }
+        events.push(GameEvent::ButtonPressed { id });
+        if self.set_doors(id, true) {
+            events.push(GameEvent::DoorOpened { id });
+        }
+    }
+    // Moves the object at `pos` one step as an actor of `kind`, or further
+    // if it slides, returning where it stopped.
+    fn push_object(
+        &mut self,
+        pos: Pos,
+        kind: ActorKind,
+        direction: Direction,
+        events: &mut EventQueue,
+    ) -> Pos {
+        let mut to = pos + direction;
+        while kind.rules().slides && self.has_room(to + direction, kind) {
+            to = to + direction;
+        }
+        self.move_object(pos, to, kind, events);
+        if let (Some(scripts), TileType::Custom(glyph)) =
+            (&self.scripts, self.cell_at(to).floor_type())
+        {
+            // Only players push, and the move pushing is not counted yet.
+            let effects = scripts.on_pushed(glyph, direction, self.turn + 1);
+            self.apply_effects(&effects, events);
+        }
+        to
+    }
+    // Puts the object at `from` down at `to`, keeping the button counts up
+    // to date.
+    fn move_object(&mut self, from: Pos, to: Pos, kind: ActorKind, events: &mut EventQueue) {
+        let tile = self.take_object(from).unwrap();
+        self.put_object(tile, to);
+        if kind.rules().presses_buttons {
+            self.press_buttons_at(to, events);
+        }
+    }
+    fn take_object(&mut self, pos: Pos) -> Option<Tile> {
+        let cell = self.entities.get_mut(&pos)?;
+        let tile = cell.object.take()?;
+        if cell.is_empty() {
+            self.entities.remove(&pos);
+        }
+        if let Some(id) = self.button_at(pos) {
+            *self.boxed_buttons.entry(id).or_default() -= 1;
+        }
+        Some(tile)
+    }
+    fn put_object(&mut self, mut tile: Tile, pos: Pos) {
+        (tile.y, tile.x) = (pos.y, pos.x);
+        self.entities.entry(pos).or_default().object = Some(tile);
+        if let Some(id) = self.button_at(pos) {
+            *self.boxed_buttons.entry(id).or_default() += 1;
+        }
+    }
+    // Whether a box could be set down at `pos` in practice: open floor
+    // inside the level with nothing on it.
+    pub fn has_room_for_box(&self, pos: Pos) -> bool {
+        let cell = self.cell_at(pos);
+        self.bounds.contains(pos) && cell.object.is_none() && !cell.is_solid()
+    }
+    // Practice changes to the board, which are no moves: a box is added,
+    // taken away or set down somewhere else by hand. Each returns whether
+    // it could be done. Afterwards the moves can't be replayed as LURD.
+    pub fn add_box(&mut self, pos: Pos) -> bool {
+        if !self.has_room_for_box(pos) {
+            return false;
+        }
+        self.put_object(Tile::new(pos.y, pos.x, TileType::PushBox), pos);
+        self.unrecorded = true;
+        true
+    }
+    pub fn remove_box(&mut self, pos: Pos) -> bool {
+        if !self.cell_at(pos).has_pushable() {
+            return false;
+        }
+        self.take_object(pos);
+        self.unrecorded = true;
+        true
+    }
+    pub fn shift_box(&mut self, from: Pos, to: Pos) -> bool {
+        if !self.cell_at(from).has_pushable() || !self.has_room_for_box(to) {
+            return false;
+        }
+        let tile = self.take_object(from).unwrap();
+        self.put_object(tile, to);
+        self.unrecorded = true;
+        true
+    }
+    // Fires the grapple from `player` towards `direction`. It latches onto
+    // the first box or solid cell in line: a box is pulled back to the
+    // player, as close as it can get; against anything else the player is
+    // pulled up to it. Uses up a charge and counts as a move, unless there
+    // was nothing to latch onto or it was already within reach. Any other
+    // players, at `others`, count as solid. Returns whether anything moved.
+    pub fn grapple(
+        &mut self,
+        player: &mut Actor,
+        others: &[Pos],
+        direction: Direction,
+        events: &mut EventQueue,
+    ) -> bool {
+        if self.grapples_left() == 0 {
+            return false;
+        }
+        let mut target = player.pos() + direction;
+        loop {
+            if !self.bounds.contains(target) {
+                return false;
+            }
+            let cell = self.cell_at(target);
+            if cell.has_pushable() || self.blocks(&cell, player.kind) || others.contains(&target) {
+                break;
+            }
+            target = target + direction;
+        }
+        let back = direction.opposite();
+        match self.cell_at(target).pushable_kind() {
+            Some(kind) => {
+                let mut to = target;
+                while to + back != player.pos()
+                    && self.has_room(to + back, kind)
+                    && !others.contains(&(to + back))
+                {
+                    to = to + back;
+                }
+                if to == target {
+                    return false;
+                }
+                self.grapples_used += 1;
+                self.unrecorded = true;
+                self.turn += 1;
+                events.push(GameEvent::Grappled { from: target, to });
+                self.move_object(target, to, kind, events);
+            }
+            None => {
+                let to = target + back;
+                if to == player.pos() {
+                    return false;
+                }
+                self.grapples_used += 1;
+                self.unrecorded = true;
+                self.turn += 1;
+                events.push(GameEvent::Grappled {
+                    from: player.pos(),
+                    to,
+                });
+                player.set_pos(to);
+                self.player_arrives(to, events);
+                self.press_buttons_at(to, events);
+            }
+        }
+        true
+    }
+    // The cells the object at `pos` would pass through if pushed towards
+    // `direction`: the one beyond it, or for a slippery box every cell up to
+    // where it stops. Empty if it can't move.
+    pub fn push_path(&self, pos: Pos, direction: Direction) -> Vec<Pos> {
+        let Some(kind) = self.cell_at(pos).pushable_kind() else {
+            return Vec::new();
+        };
+        let mut path = Vec::new();
+        let mut next = pos + direction;
+        while self.has_room(next, kind) {
+            path.push(next);
+            if !kind.rules().slides {
+                break;
+            }
+            next = next + direction;
+        }
+        path
+    }
+    // Whether a pushed actor of `kind` could move into `pos`.
+    fn has_room(&self, pos: Pos, kind: ActorKind) -> bool {
+        let cell = self.cell_at(pos);
+        self.bounds.contains(pos) && !self.blocks(&cell, kind) && !cell.has_pushable()
+    }
+    // Whether `cell` stops an actor of `kind`, asking the script of a custom
+    // floor tile.
+    fn blocks(&self, cell: &Cell, kind: ActorKind) -> bool {
+        if cell.is_solid() {
+            return true;
+        }
+        match (&self.scripts, cell.floor_type()) {
+            (Some(scripts), TileType::Custom(glyph)) => scripts.is_solid(glyph, kind),
+            (_, TileType::PhaseBox) => kind != ActorKind::Player || self.phase_boxes_solid(),
+            _ => false,
+        }
+    }
+    fn apply_effects(&mut self, effects: &[Effect], events: &mut EventQueue) {
+        for &effect in effects {
+            match effect {
+                Effect::OpenDoor(id) => {
+                    self.closing.remove(&id);
+                    if self.set_doors(id, true) {
+                        events.push(GameEvent::DoorOpened { id });
+                    }
+                }
+                // Closed along with the doors that close by themselves, once
+                // everyone is where they end up this move, so never on anyone
+                // or anything in the doorway.
+                Effect::CloseDoor(id) => {
+                    self.closing.insert(id, self.turn);
+                }
+            }
+        }
+    }
+    // Moves an actor one step if its rules allow, pushing whatever is ahead
+    // of it if it pushes. Only the player's moves are reported as events.
+    pub fn move_actor(
+        &mut self,
+        actor: &mut Actor,
+        direction: Direction,
+        events: &mut EventQueue,
+    ) -> MoveOutcome {
+        let rules = actor.rules();
+        let ahead_pos = actor.pos() + direction;
+        let beyond_pos = ahead_pos + direction;
+        let ahead = self.cell_at(ahead_pos);
+
+        let mut outcome = MoveOutcome::Moved;
+        if !self.bounds.contains(ahead_pos) || self.blocks(&ahead, actor.kind) {
+            outcome = MoveOutcome::Blocked;
+        } else if let Some(pushed) = ahead.pushable_kind() {
+            // Pushable tiles are boxes; they move as box actors.
+            let room = self.has_room(beyond_pos, pushed);
+            if rules.pushes && pushed.rules().pushable && room {
+                let to = self.push_object(ahead_pos, pushed, direction, events);
+                events.push(GameEvent::BoxPushed {
+                    from: ahead_pos,
+                    to,
+                });
+                outcome = MoveOutcome::Pushed;
+            } else {
+                outcome = MoveOutcome::Blocked;
+            }
+        }
+        let is_player = actor.kind == ActorKind::Player;
+        if outcome == MoveOutcome::Blocked {
+            if is_player {
+                events.push(GameEvent::MoveBlocked { direction });
+            }
+            return outcome;
+        }
+        actor.step(direction);
+        if is_player {
+            self.turn += 1;
+            events.push(GameEvent::PlayerMoved { direction });
+            self.player_arrives(actor.pos(), events);
+        }
+        if rules.presses_buttons {
+            self.press_buttons_at(actor.pos(), events);
+        }
+        if let (Some(scripts), TileType::Custom(glyph), true) = (
+            &self.scripts,
+            self.cell_at(actor.pos()).floor_type(),
+            is_player,
+        ) {
+            let effects = scripts.on_stood_on(glyph, self.turn);
+            self.apply_effects(&effects, events);
+        }
+        outcome
+    }
+    // Whether an actor of `kind` could stand at `pos`, if nothing were in
+    // the way. Boxes don't count.
+    pub fn is_walkable(&self, pos: Pos, kind: ActorKind) -> bool {
+        self.bounds.contains(pos) && !self.blocks(&self.cell_at(pos), kind)
+    }
+    pub fn bounds(&self) -> Bounds {
+        self.bounds
+    }
+    // How many levels ahead a warp at `pos` sends the player, if there is one.
+    pub fn warp_at(&self, pos: Pos) -> Option<u32> {
+        match self.cell_at(pos).floor_type() {
+            TileType::Warp(levels) => Some(levels),
+            _ => None,
+        }
+    }
+    pub fn is_win_pad_at(&self, pos: Pos) -> bool {
+        self.cell_at(pos).floor_type() == TileType::WinPad
+    }
+    // Closes the doors of `id` once `delay` moves have been made since their
+    // buttons were let go, though not while anyone or anything stands in a
+    // doorway.
+    fn count_down(&mut self, id: Id, delay: u32, players: &[Pos], events: &mut EventQueue) {
+        let doors = self.links.doors.get(&id).cloned().unwrap_or_default();
+        let open = doors
+            .iter()
+            .any(|&pos| self.cell_at(pos).floor_type() == TileType::Door(Some(id), true));
+        if !open {
+            self.closing.remove(&id);
+            return;
+        }
+        let closes_at = *self.closing.entry(id).or_insert(self.turn + delay as usize);
+        let blocked = doors
+            .iter()
+            .any(|pos| players.contains(pos) || self.cell_at(*pos).has_pushable());
+        if self.turn >= closes_at && !blocked {
+            self.set_doors(id, false);
+            self.closing.remove(&id);
+            events.push(GameEvent::DoorClosed { id });
+        }
+    }
+    // Opens or closes every door of `id`, returning whether any was opened.
+    fn set_doors(&mut self, id: Id, open: bool) -> bool {
+        let mut opened = false;
+        for pos in self.links.doors.get(&id).into_iter().flatten() {
+            let Some(door) = self.entities.get_mut(pos).and_then(|c| c.floor.as_mut()) else {
+                continue;
+            };
+            if let TileType::Door(_, was_open) = door.tile_type {
+                door.tile_type = TileType::Door(Some(id), open);
+                opened |= open && !was_open;
+            }
+        }
+        opened
+    }
+    // Opens the doors of `id` whatever their buttons say, returning whether
+    // there were any.
+    pub fn force_open(&mut self, id: Id, events: &mut EventQueue) -> bool {
+        if !self.links.doors.contains_key(&id) {
+            return false;
+        }
+        if self.set_doors(id, true) {
+            events.push(GameEvent::DoorOpened { id });
+        }
+        true
+    }
+    // Opens the doors of every id whose buttons are all held down by boxes
+    // or the player. Doors only close again if they have a close delay or a
+    // script closes them.
+    pub fn update_button_status(&mut self, player: &Actor, events: &mut EventQueue) {
+        self.update_buttons_held(&[player.pos()], events);
+    }
+    // Like update_button_status, with buttons held by players at `players`.
+    pub fn update_buttons_held(&mut self, players: &[Pos], events: &mut EventQueue) {
+        let links = Arc::clone(&self.links);
+        for (&id, buttons) in &links.buttons {
+            if !links.doors.contains_key(&id) {
+                continue;
+            }
+            let boxed = self.boxed_buttons.get(&id).copied().unwrap_or(0);
+            let stood_on = players
+                .iter()
+                .filter(|&&pos| self.button_at(pos) == Some(id))
+                .count();
+            let held = boxed + stood_on;
+            if held == buttons.len() {
+                self.closing.remove(&id);
+                if self.set_doors(id, true) {
+                    events.push(GameEvent::DoorOpened { id });
+                }
+            } else if let Some(&delay) = self.close_delays.get(&id) {
+                self.count_down(id, delay, players, events);
+            }
+        }
+        // Doors a script closed that nothing counts down above shut as soon as
+        // their doorways are clear.
+        let scripted: Vec<Id> = self
+            .closing
+            .keys()
+            .copied()
+            .filter(|id| !links.buttons.contains_key(id) || !self.close_delays.contains_key(id))
+            .collect();
+        for id in scripted {
+            self.count_down(id, 0, players, events);
+        }
+        // Unlike other doors, hold doors close again when a plate is let go,
+        // though not on anyone or anything standing in the doorway.
+        let held = !links.plates.is_empty() && links.plates.iter().all(|p| players.contains(p));
+        for &pos in &links.hold_doors {
+            let blocked = players.contains(&pos) || self.cell_at(pos).has_pushable();
+            let Some(door) = self.entities.get_mut(&pos).and_then(|c| c.floor.as_mut()) else {
+                continue;
+            };
+            let TileType::HoldDoor(was_open) = door.tile_type else {
+                continue;
+            };
+            let open = held || (was_open && blocked);
+            if open != was_open {
+                door.tile_type = TileType::HoldDoor(open);
+                events.push(GameEvent::HoldDoor { open });
+            }
+        }
+    }
+}
+
+// Stands in for a level that could not be loaded, so there is always
+// something to draw.
+pub fn fallback_map() -> MapData {
+    MapBuilder::new()
+        .room(Pos::new(0, 0), Pos::new(4, 11))
+        .spawn(Pos::new(2, 5))
+        .flavor("This level could not be loaded. Press q to leave.")
+        .build()
+}
+
+pub fn get_maps() -> Vec<MapData> {
+    vec![
+        // Level 1
+        MapBuilder::new()
+            .wall(Pos::new(0, 0), Direction::Right, 30)
+            .wall(Pos::new(0, 0), Direction::Down, 7)
+            .wall(Pos::new(6, 0), Direction::Right, 23)
+            .wall(Pos::new(6, 23), Direction::Down, 10)
+            .wall(Pos::new(16, 23), Direction::Right, 7)
+            .wall(Pos::new(16, 29), Direction::Up, 17)
+            .place(Pos::new(13, 26), TileType::WinPad)
+            .spawn(Pos::new(3, 3))
+            .flavor("Welcome")
+            .build(),
+        // Level 2
+        MapBuilder::new()
+            .room(Pos::new(0, 0), Pos::new(9, 34))
+            .room(Pos::new(9, 5), Pos::new(15, 34))
+            .wall(Pos::new(10, 17), Direction::Down, 5)
+            .link(Pos::new(10, 11), Pos::new(12, 17))
+            .link(Pos::new(14, 33), Pos::new(9, 31))
+            .place(Pos::new(7, 2), TileType::WinPad)
+            .spawn(Pos::new(14, 6))
+            .flavor("Buttons? What do they do?")
+            .build(),
+        // Level 3
+        MapBuilder::new()
+            .room(Pos::new(0, 0), Pos::new(6, 39))
+            .wall(Pos::new(0, 28), Direction::Down, 7)
+            .link(Pos::new(2, 24), Pos::new(3, 28))
+            .link(Pos::new(4, 24), Pos::new(3, 28))
+            .place(Pos::new(3, 10), TileType::PushBox)
+            .place(Pos::new(3, 35), TileType::WinPad)
+            .spawn(Pos::new(3, 3))
+            .flavor("You must activate both buttons at once.")
+            .build(),
+    ]
+}
@@ -0,0 +1,106 @@
+// Builds levels in code. Rooms, corridors and wall lines go down in order,
+// with a later tile replacing whatever was at its cell, and `link` wires a
+// button to a door so the pair share an id without numbering them by hand:
+//
+//     MapBuilder::new()
+//         .room(Pos::new(0, 0), Pos::new(6, 39))
+//         .wall(Pos::new(0, 28), Direction::Down, 7)
+//         .link(Pos::new(2, 24), Pos::new(3, 28))
+//         .spawn(Pos::new(3, 3))
+//         .build();
+use crate::{
+    map::{Id, MapData, Tile, TileType},
+    pos::Pos,
+    Direction,
+};
+use std::collections::BTreeMap;
+
+#[derive(Clone, Default)]
+pub struct MapBuilder {
+    tiles: BTreeMap<Pos, TileType>,
+    player_spawn: Pos,
+    flavor_text: Option<String>,
+    next_id: Id,
+}
+
+impl MapBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn place(&mut self, pos: Pos, tile_type: TileType) -> &mut Self {
+        self.tiles.insert(pos, tile_type);
+        self
+    }
+    // A straight line of `len` walls starting at `start`.
+    pub fn wall(&mut self, start: Pos, direction: Direction, len: i32) -> &mut Self {
+        for i in 0..len {
+            self.place(start + direction.delta() * i, TileType::Wall1);
+        }
+        self
+    }
+    // A solid block of walls with the given corners.
+    pub fn wall_rect(&mut self, top_left: Pos, bottom_right: Pos) -> &mut Self {
+        for y in top_left.y..=bottom_right.y {
+            for x in top_left.x..=bottom_right.x {
+                self.place(Pos::new(y, x), TileType::Wall1);
+            }
+        }
+        self
+    }
+    // Walls around the edge of the rectangle with the given corners, leaving
+    // the inside as it was.
+    pub fn room(&mut self, top_left: Pos, bottom_right: Pos) -> &mut Self {
+        let (height, width) = (bottom_right.y - top_left.y, bottom_right.x - top_left.x);
+        self.wall(top_left, Direction::Right, width + 1)
+            .wall(top_left, Direction::Down, height + 1)
+            .wall(bottom_right, Direction::Left, width + 1)
+            .wall(bottom_right, Direction::Up, height + 1)
+    }
+    // A passage one cell wide running `len` cells from `start`, walled on
+    // both sides. Walls on the path itself are knocked through, so a corridor
+    // can open a way into a room; side cells that already hold something are
+    // left alone.
+    pub fn corridor(&mut self, start: Pos, direction: Direction, len: i32) -> &mut Self {
+        let side = direction.delta().rotate_cw();
+        for i in 0..len {
+            let pos = start + direction.delta() * i;
+            if self.tiles.get(&pos) == Some(&TileType::Wall1) {
+                self.tiles.remove(&pos);
+            }
+            for wall in [pos + side, pos - side] {
+                self.tiles.entry(wall).or_insert(TileType::Wall1);
+            }
+        }
+        self
+    }
+    // Puts a button at `button` and a closed door at `door` that it opens.
+    // Linking another button to a door that is already linked makes both
+    // buttons needed to open it.
+    pub fn link(&mut self, button: Pos, door: Pos) -> &mut Self {
+        let id = match self.tiles.get(&door) {
+            Some(TileType::Door(Some(id), _)) => *id,
+            _ => {
+                self.next_id += 1;
+                self.next_id - 1
+            }
+        };
+        self.place(button, TileType::Button(id))
+            .place(door, TileType::Door(Some(id), false))
+    }
+    pub fn spawn(&mut self, pos: Pos) -> &mut Self {
+        self.player_spawn = pos;
+        self
+    }
+    pub fn flavor(&mut self, text: &str) -> &mut Self {
+        self.flavor_text = Some(text.to_string());
+        self
+    }
+    pub fn build(&self) -> MapData {
+        let tiles = self
+            .tiles
+            .iter()
+            .map(|(pos, &tile_type)| Tile::new(pos.y, pos.x, tile_type))
+            .collect();
+        MapData::new(tiles, self.player_spawn, self.flavor_text.clone())
+    }
+}
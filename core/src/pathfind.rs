@@ -0,0 +1,49 @@
+// Shortest walks across a level, for walking the player to a clicked cell.
+// The search is A* with the Manhattan distance as its estimate. Boxes are
+// walked through rather than around: whoever follows the path stops at the
+// first one instead of pushing it.
+use crate::{actor::ActorKind, map::MapData, pos::Pos, Direction};
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+};
+
+fn distance(a: Pos, b: Pos) -> i32 {
+    let d = a - b;
+    d.y.abs() + d.x.abs()
+}
+
+// The moves from `from` to `to`, or None if walls or closed doors cut it off.
+pub fn find_path(map: &MapData, from: Pos, to: Pos) -> Option<Vec<Direction>> {
+    if !map.is_walkable(to, ActorKind::Player) {
+        return None;
+    }
+    let mut best = HashMap::from([(from, 0)]);
+    let mut came_from: HashMap<Pos, (Pos, Direction)> = HashMap::new();
+    let mut open = BinaryHeap::from([Reverse((distance(from, to), 0, from))]);
+    while let Some(Reverse((_, steps, pos))) = open.pop() {
+        if pos == to {
+            let mut path = Vec::new();
+            let mut at = to;
+            while let Some(&(previous, direction)) = came_from.get(&at) {
+                path.push(direction);
+                at = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        if steps > best[&pos] {
+            continue;
+        }
+        for direction in Direction::ALL {
+            let next = pos + direction;
+            let better = best.get(&next).is_none_or(|&known| steps + 1 < known);
+            if better && map.is_walkable(next, ActorKind::Player) {
+                best.insert(next, steps + 1);
+                came_from.insert(next, (pos, direction));
+                open.push(Reverse((steps + 1 + distance(next, to), steps + 1, next)));
+            }
+        }
+    }
+    None
+}
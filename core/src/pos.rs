@@ -0,0 +1,154 @@
+// A cell on the map, or the offset between two cells. `y` grows downwards
+// and `x` to the right, and positions order row by row. Serialized as a
+// `(y, x)` pair.
+use crate::Direction;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+#[derive(
+    Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default, Debug, Serialize, Deserialize,
+)]
+#[serde(from = "(i32, i32)", into = "(i32, i32)")]
+pub struct Pos {
+    pub y: i32,
+    pub x: i32,
+}
+
+impl Pos {
+    pub const ZERO: Self = Self { y: 0, x: 0 };
+
+    pub const fn new(y: i32, x: i32) -> Self {
+        Self { y, x }
+    }
+    // A quarter turn clockwise about the origin, as seen on screen.
+    pub fn rotate_cw(self) -> Self {
+        Self::new(self.x, -self.y)
+    }
+    pub fn rotate_ccw(self) -> Self {
+        Self::new(-self.x, self.y)
+    }
+    // The smallest and largest coordinates of both, as top-left and
+    // bottom-right corners.
+    pub fn min(self, other: Self) -> Self {
+        Self::new(self.y.min(other.y), self.x.min(other.x))
+    }
+    pub fn max(self, other: Self) -> Self {
+        Self::new(self.y.max(other.y), self.x.max(other.x))
+    }
+    pub fn neighbours(self) -> [Self; 4] {
+        Direction::ALL.map(|d| self + d)
+    }
+}
+
+impl From<(i32, i32)> for Pos {
+    fn from((y, x): (i32, i32)) -> Self {
+        Self::new(y, x)
+    }
+}
+
+impl From<Pos> for (i32, i32) {
+    fn from(pos: Pos) -> Self {
+        (pos.y, pos.x)
+    }
+}
+
+impl From<Direction> for Pos {
+    fn from(direction: Direction) -> Self {
+        direction.delta()
+    }
+}
+
+impl fmt::Display for Pos {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.y, self.x)
+    }
+}
+
+impl Add for Pos {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self::new(self.y + other.y, self.x + other.x)
+    }
+}
+
+// A step from a position in a direction.
+impl Add<Direction> for Pos {
+    type Output = Self;
+
+    fn add(self, direction: Direction) -> Self {
+        self + direction.delta()
+    }
+}
+
+impl AddAssign for Pos {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Pos {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.y - other.y, self.x - other.x)
+    }
+}
+
+impl SubAssign for Pos {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Mul<i32> for Pos {
+    type Output = Self;
+
+    fn mul(self, scale: i32) -> Self {
+        Self::new(self.y * scale, self.x * scale)
+    }
+}
+
+impl Neg for Pos {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::new(-self.y, -self.x)
+    }
+}
+
+// The rectangle from `top_left` to `bottom_right`, both included.
+#[derive(Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub struct Bounds {
+    pub top_left: Pos,
+    pub bottom_right: Pos,
+}
+
+impl Bounds {
+    // The smallest rectangle holding every point, or a single cell at the
+    // origin if there are none.
+    pub fn around(points: impl IntoIterator<Item = Pos>) -> Self {
+        let mut points = points.into_iter();
+        let first = points.next().unwrap_or(Pos::ZERO);
+        points.fold(
+            Self {
+                top_left: first,
+                bottom_right: first,
+            },
+            |bounds, pos| Self {
+                top_left: bounds.top_left.min(pos),
+                bottom_right: bounds.bottom_right.max(pos),
+            },
+        )
+    }
+    // Height and width.
+    pub fn size(self) -> Pos {
+        self.bottom_right - self.top_left + Pos::new(1, 1)
+    }
+    pub fn contains(self, pos: Pos) -> bool {
+        pos.min(self.top_left) == self.top_left && pos.max(self.bottom_right) == self.bottom_right
+    }
+}
@@ -0,0 +1,29 @@
+// Practice mode's cursor. In practice the player can stop to arrange the
+// board by hand, adding, taking away and moving boxes and putting the
+// player somewhere else, then play on from there to try out an idea. Wins
+// in practice set no records.
+use crate::{
+    pos::{Bounds, Pos},
+    Direction,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Arranging {
+    pub cursor: Pos,
+    // Where the box picked up to be moved is, until it is set down.
+    pub held: Option<Pos>,
+}
+
+impl Arranging {
+    pub fn new(cursor: Pos) -> Self {
+        Self { cursor, held: None }
+    }
+    // The cursor stays inside the level, where anything can be arranged.
+    pub fn move_cursor(&mut self, direction: Direction, bounds: Bounds) {
+        let to = self.cursor + direction;
+        if bounds.contains(to) {
+            self.cursor = to;
+        }
+    }
+}
@@ -0,0 +1,97 @@
+// Saved games: the serialized `GameContext` as JSON, wrapped with the version
+// of the format it was written in:
+//
+//   {"version": 1, "game": {...}}
+//
+// Whenever a change to the game state would stop old saves from loading (a
+// field renamed or reshaped, a tile type reworked), bump `SAVE_VERSION` and
+// add a step to `MIGRATIONS` that rewrites the JSON of the version before.
+// Loading runs an old save through every step it is behind, so saves keep
+// working. Fields that are only added need no step if they have a serde
+// default.
+use crate::game::GameContext;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::{fmt, fs, io, path::Path};
+
+pub const SAVE_VERSION: u64 = 1;
+
+// `MIGRATIONS[n]` turns a version `n` save into a version `n + 1` one.
+const MIGRATIONS: [fn(Value) -> Value; SAVE_VERSION as usize] = [wrap_unversioned];
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Parse(String),
+    // Written by a newer version of the game.
+    TooNew(u64),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Parse(msg) => write!(f, "bad save: {}", msg),
+            Self::TooNew(version) => write!(
+                f,
+                "save is format version {}, but this game only reads up to {}",
+                version, SAVE_VERSION
+            ),
+        }
+    }
+}
+
+impl From<io::Error> for SaveError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[derive(Serialize)]
+struct SaveRef<'a> {
+    version: u64,
+    game: &'a GameContext,
+}
+
+pub fn to_string(game: &GameContext) -> String {
+    let save = SaveRef {
+        version: SAVE_VERSION,
+        game,
+    };
+    serde_json::to_string(&save).expect("game state always serializes")
+}
+
+pub fn from_str(text: &str) -> Result<GameContext, SaveError> {
+    let value: Value = serde_json::from_str(text).map_err(|e| SaveError::Parse(e.to_string()))?;
+    let mut value = migrate(value)?;
+    serde_json::from_value(value["game"].take()).map_err(|e| SaveError::Parse(e.to_string()))
+}
+
+// Brings a save of any older version up to `SAVE_VERSION`.
+fn migrate(mut value: Value) -> Result<Value, SaveError> {
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(0);
+    if version > SAVE_VERSION {
+        return Err(SaveError::TooNew(version));
+    }
+    for step in &MIGRATIONS[version as usize..] {
+        value = step(value);
+    }
+    Ok(value)
+}
+
+// Version 0 is the bare game state, as serialized before saves had a version.
+fn wrap_unversioned(game: Value) -> Value {
+    json!({ "version": 1, "game": game })
+}
+
+pub fn save(path: &Path, game: &GameContext) -> Result<(), SaveError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, to_string(game))?;
+    Ok(())
+}
+
+pub fn load(path: &Path) -> Result<GameContext, SaveError> {
+    from_str(&fs::read_to_string(path)?)
+}
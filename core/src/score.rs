@@ -0,0 +1,40 @@
+// Points for a won level in scoring mode: gems collected, a bonus for
+// meeting the level's par and a bonus for finishing quickly. A pack's score
+// is the sum of its levels' best.
+use crate::map::Par;
+use std::time::Duration;
+
+const GEM_POINTS: u32 = 100;
+// The par bonus in full for matching par, less a little for each move over.
+const PAR_BONUS: u32 = 500;
+const PAR_BONUS_PER_MOVE: u32 = 10;
+// The time bonus in full for an instant win, less a little for each second.
+const TIME_BONUS: u32 = 500;
+const TIME_BONUS_PER_SECOND: u32 = 5;
+
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub struct Score {
+    pub gems: u32,
+    pub par_bonus: u32,
+    pub time_bonus: u32,
+}
+
+impl Score {
+    // A level won in `moves` and `time` with `gems` collected. Levels with no
+    // par give no par bonus.
+    pub fn new(gems: u32, moves: usize, par: Option<Par>, time: Duration) -> Self {
+        let par_bonus = par.map_or(0, |par| {
+            let over = moves.saturating_sub(par.moves) as u32;
+            PAR_BONUS.saturating_sub(over.saturating_mul(PAR_BONUS_PER_MOVE))
+        });
+        let seconds = time.as_secs().min(u32::MAX as u64) as u32;
+        Self {
+            gems: gems * GEM_POINTS,
+            par_bonus,
+            time_bonus: TIME_BONUS.saturating_sub(seconds.saturating_mul(TIME_BONUS_PER_SECOND)),
+        }
+    }
+    pub fn total(&self) -> u32 {
+        self.gems + self.par_bonus + self.time_bonus
+    }
+}
@@ -0,0 +1,139 @@
+// Custom tile types for level packs, with their behaviour written in rhai.
+// A pack maps a glyph to a script, and levels use that glyph like any other
+// tile. A script may define any of these functions; missing ones fall back to
+// plain floor behaviour:
+//
+//   fn is_solid(actor)        // "player" or "box"; true blocks it
+//   fn on_stood_on()          // the player stepped onto the tile
+//   fn on_pushed(direction)   // a box was pushed onto the tile, moving
+//                             // "up", "down", "left" or "right"
+//
+// The two `on_` hooks return an effect, an array of effects or nothing:
+// `open_door(id)` and `close_door(id)`. They can call `turn()` for the
+// number of the move being made, counting from 1 since the level started.
+//
+// Scripts only run during play. The solver, generator and lint see custom
+// tiles as plain floor.
+use crate::{actor::ActorKind, map::Id, Direction};
+use rhai::{Array, Dynamic, Engine, Scope, AST};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+// Keeps a runaway script from hanging the game.
+const MAX_OPERATIONS: u64 = 100_000;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Effect {
+    OpenDoor(Id),
+    CloseDoor(Id),
+}
+
+#[derive(Debug)]
+pub struct ScriptError {
+    pub glyph: char,
+    pub msg: String,
+}
+
+impl fmt::Display for ScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "script for tile `{}`: {}", self.glyph, self.msg)
+    }
+}
+
+pub struct TileScripts {
+    engine: Engine,
+    tiles: BTreeMap<char, AST>,
+    // What `turn()` gives the hook being run.
+    turn: Arc<AtomicUsize>,
+}
+
+impl Default for TileScripts {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TileScripts {
+    pub fn new() -> Self {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        let turn = Arc::new(AtomicUsize::new(0));
+        let read_turn = Arc::clone(&turn);
+        engine
+            .register_fn("turn", move || read_turn.load(Ordering::Relaxed) as i64)
+            .register_type_with_name::<Effect>("Effect")
+            .register_fn("open_door", |id: i64| Effect::OpenDoor(id as Id))
+            .register_fn("close_door", |id: i64| Effect::CloseDoor(id as Id));
+        Self {
+            engine,
+            tiles: BTreeMap::new(),
+            turn,
+        }
+    }
+    pub fn add(&mut self, glyph: char, source: &str) -> Result<(), ScriptError> {
+        let ast = self.engine.compile(source).map_err(|e| ScriptError {
+            glyph,
+            msg: e.to_string(),
+        })?;
+        self.tiles.insert(glyph, ast);
+        Ok(())
+    }
+    pub fn has(&self, glyph: char) -> bool {
+        self.tiles.contains_key(&glyph)
+    }
+    pub fn is_solid(&self, glyph: char, actor: ActorKind) -> bool {
+        let actor = match actor {
+            ActorKind::Player => "player",
+            ActorKind::Box | ActorKind::SlipperyBox => "box",
+        };
+        self.call(glyph, "is_solid", (actor.to_string(),))
+            .and_then(|result| result.as_bool().ok())
+            .unwrap_or(false)
+    }
+    pub fn on_stood_on(&self, glyph: char, turn: usize) -> Vec<Effect> {
+        self.turn.store(turn, Ordering::Relaxed);
+        effects(self.call(glyph, "on_stood_on", ()))
+    }
+    pub fn on_pushed(&self, glyph: char, direction: Direction, turn: usize) -> Vec<Effect> {
+        self.turn.store(turn, Ordering::Relaxed);
+        let direction = match direction {
+            Direction::Up => "up",
+            Direction::Down => "down",
+            Direction::Left => "left",
+            Direction::Right => "right",
+        };
+        effects(self.call(glyph, "on_pushed", (direction.to_string(),)))
+    }
+    // Runs a hook if the tile's script defines it. A script that fails at
+    // run time acts as if the hook were missing.
+    fn call(&self, glyph: char, name: &str, args: impl rhai::FuncArgs) -> Option<Dynamic> {
+        let ast = self.tiles.get(&glyph)?;
+        if !ast.iter_functions().any(|f| f.name == name) {
+            return None;
+        }
+        self.engine
+            .call_fn::<Dynamic>(&mut Scope::new(), ast, name, args)
+            .ok()
+    }
+}
+
+fn effects(result: Option<Dynamic>) -> Vec<Effect> {
+    let Some(result) = result else {
+        return Vec::new();
+    };
+    if let Some(effect) = result.clone().try_cast::<Effect>() {
+        return vec![effect];
+    }
+    result
+        .try_cast::<Array>()
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|item| item.try_cast::<Effect>())
+        .collect()
+}
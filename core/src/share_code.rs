@@ -0,0 +1,129 @@
+// Compact level codes for pasting into chat: the level's text form, run-length
+// encoded and then base64'd (URL-safe alphabet, no padding).
+use crate::{
+    level_file::{level_to_text, parse_levels},
+    map::MapData,
+};
+use std::fmt;
+
+const CODE_PREFIX: &str = "BT1:";
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+// Level text is ASCII, so this byte can never appear in it literally.
+const RUN_MARKER: u8 = 0xff;
+const MIN_RUN: usize = 4;
+
+#[derive(Debug)]
+pub enum ShareCodeError {
+    MissingPrefix,
+    BadBase64,
+    BadRunLength,
+    BadLevel(String),
+}
+
+impl fmt::Display for ShareCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::MissingPrefix => {
+                write!(f, "not a level code (should start with {})", CODE_PREFIX)
+            }
+            Self::BadBase64 => write!(f, "level code is corrupted"),
+            Self::BadRunLength => write!(f, "level code is truncated"),
+            Self::BadLevel(e) => write!(f, "level code contains a bad level: {}", e),
+        }
+    }
+}
+
+pub fn encode(map: &MapData) -> String {
+    let text = level_to_text(map);
+    format!(
+        "{}{}",
+        CODE_PREFIX,
+        base64_encode(&rle_encode(text.as_bytes()))
+    )
+}
+
+pub fn decode(code: &str) -> Result<MapData, ShareCodeError> {
+    let body = code
+        .trim()
+        .strip_prefix(CODE_PREFIX)
+        .ok_or(ShareCodeError::MissingPrefix)?;
+    let bytes = rle_decode(&base64_decode(body)?)?;
+    let text = String::from_utf8(bytes).map_err(|_| ShareCodeError::BadBase64)?;
+    let mut levels = parse_levels(&text).map_err(|e| ShareCodeError::BadLevel(e.to_string()))?;
+    Ok(levels.remove(0))
+}
+
+fn rle_encode(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len());
+    let mut i = 0;
+    while i < data.len() {
+        let byte = data[i];
+        let mut run = 1;
+        while i + run < data.len() && data[i + run] == byte && run < u8::MAX as usize {
+            run += 1;
+        }
+        if run >= MIN_RUN {
+            out.extend([RUN_MARKER, run as u8, byte]);
+        } else {
+            out.extend(std::iter::repeat_n(byte, run));
+        }
+        i += run;
+    }
+    out
+}
+
+fn rle_decode(data: &[u8]) -> Result<Vec<u8>, ShareCodeError> {
+    let mut out = Vec::with_capacity(data.len() * 2);
+    let mut iter = data.iter();
+    while let Some(&byte) = iter.next() {
+        if byte == RUN_MARKER {
+            let run = *iter.next().ok_or(ShareCodeError::BadRunLength)?;
+            let value = *iter.next().ok_or(ShareCodeError::BadRunLength)?;
+            out.extend(std::iter::repeat_n(value, run as usize));
+        } else {
+            out.push(byte);
+        }
+    }
+    Ok(out)
+}
+
+fn base64_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len() * 4 / 3 + 3);
+    for chunk in data.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3f) as usize] as char);
+        }
+    }
+    out
+}
+
+fn base64_decode(text: &str) -> Result<Vec<u8>, ShareCodeError> {
+    let mut out = Vec::with_capacity(text.len() * 3 / 4);
+    let values = text
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|b| {
+            ALPHABET
+                .iter()
+                .position(|&a| a == b)
+                .ok_or(ShareCodeError::BadBase64)
+        })
+        .collect::<Result<Vec<usize>, _>>()?;
+    for chunk in values.chunks(4) {
+        if chunk.len() == 1 {
+            return Err(ShareCodeError::BadBase64);
+        }
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &v)| n | (v as u32) << (18 - 6 * i));
+        for i in 0..chunk.len() - 1 {
+            out.push((n >> (16 - 8 * i)) as u8);
+        }
+    }
+    Ok(out)
+}
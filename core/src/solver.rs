@@ -0,0 +1,521 @@
+// Breadth-first search over pushes. A search state is the set of cells the
+// player can walk to, the box positions and which doors are open; walking
+// between pushes is free, so the first solution found uses the fewest pushes.
+// Pushes into a position that `deadlock` finds lost aren't searched further.
+//
+// The search works on a compact copy of the level, but the final move list is
+// rebuilt by stepping the real `MapData` rules, so what it returns is exactly
+// what the game will accept.
+use crate::{
+    actor::Actor,
+    deadlock::Deadlocks,
+    events::EventQueue,
+    map::{MapData, MoveOutcome, TileType},
+    pos::Pos,
+    Direction,
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+pub const DEFAULT_MAX_STATES: usize = 200_000;
+const DIRECTIONS: [Direction; 4] = [
+    Direction::Up,
+    Direction::Right,
+    Direction::Down,
+    Direction::Left,
+];
+
+// The next push towards a solution, for a hint.
+pub enum Hint {
+    // Push the box at `at` one step `direction`.
+    Push { at: Pos, direction: Direction },
+    // The win pad can be reached without pushing anything.
+    WalkToWin,
+    // As for `SolveResult`.
+    Unsolvable,
+    GaveUp,
+}
+
+pub struct Solution {
+    pub result: SolveResult,
+    // Pushes in the solution, which is also its depth in the search.
+    pub pushes: usize,
+    pub stats: SearchStats,
+}
+
+pub enum SolveResult {
+    Solved(Vec<Direction>),
+    Unsolvable,
+    // The state limit was hit before the search finished, or the search ran
+    // out on a level with doors it can't open.
+    GaveUp,
+}
+
+#[derive(Default)]
+pub struct SearchStats {
+    pub states_explored: usize,
+    pub nodes_expanded: usize,
+    // Valid pushes found from expanded states, before removing repeats.
+    pub successors: usize,
+    // New states left unexplored because the level was lost in them.
+    pub deadlocks: usize,
+}
+
+impl SearchStats {
+    // Average number of pushes available from an expanded state.
+    pub fn branching(&self) -> f64 {
+        if self.nodes_expanded == 0 {
+            0.0
+        } else {
+            self.successors as f64 / self.nodes_expanded as f64
+        }
+    }
+}
+
+// The static parts of a level, flattened to a grid.
+struct Board {
+    top: i32,
+    left: i32,
+    width: i32,
+    height: i32,
+    walls: Vec<bool>,
+    // Cells only boxes can't enter.
+    box_walls: Vec<bool>,
+    win: Vec<bool>,
+    // Door cells and the button id that opens them.
+    doors: Vec<(usize, u32)>,
+    // Index into `doors` for each cell holding a door.
+    door_at: Vec<Option<usize>>,
+    // Neighbouring cell in each direction, indexed by `Direction as usize`
+    // (the same order as `DIRECTIONS`).
+    neighbours: Vec<[Option<usize>; 4]>,
+    buttons: HashMap<u32, Vec<usize>>,
+    // Ids of the doors that close again. The search takes them to be open
+    // only while boxes hold all their buttons, as the player can't be sure
+    // of getting through before they shut.
+    closing: HashSet<u32>,
+    // Whether a door has step pads, which the search never opens. Running
+    // out of states then doesn't show the level can't be solved.
+    pad_doors: bool,
+}
+
+impl Board {
+    fn new(map: &MapData) -> Self {
+        let positions: Vec<Pos> = map
+            .tiles()
+            .map(|t| t.pos())
+            .chain([map.player_spawn])
+            .collect();
+        let top = positions.iter().map(|p| p.y).min().unwrap_or(0);
+        let left = positions.iter().map(|p| p.x).min().unwrap_or(0);
+        let height = positions.iter().map(|p| p.y).max().unwrap_or(0) - top + 1;
+        let width = positions.iter().map(|p| p.x).max().unwrap_or(0) - left + 1;
+        let cells = (width * height) as usize;
+        let mut board = Self {
+            top,
+            left,
+            width,
+            height,
+            walls: vec![false; cells],
+            box_walls: vec![false; cells],
+            win: vec![false; cells],
+            doors: Vec::new(),
+            door_at: vec![None; cells],
+            neighbours: Vec::new(),
+            buttons: HashMap::new(),
+            closing: map.close_delays.keys().copied().collect(),
+            pad_doors: false,
+        };
+        let mut pads = HashSet::new();
+        board.neighbours = (0..cells)
+            .map(|cell| DIRECTIONS.map(|d| board.offset(cell, d)))
+            .collect();
+        for tile in map.tiles() {
+            let cell = board.cell(tile.pos()).unwrap();
+            match tile.tile_type {
+                // The solver plays alone, so a hold door never opens for it.
+                TileType::Wall1 | TileType::Door(None, _) | TileType::HoldDoor(_) => {
+                    board.walls[cell] = true
+                }
+                // Alone, the player only ever arrives on a cell after a number
+                // of moves of the same parity, so each phase box is always
+                // solid or always passable to them.
+                TileType::PhaseBox => {
+                    let Pos { y, x } = tile.pos() - map.player_spawn;
+                    if (map.turn() as i32 + y + x) % 2 != 0 {
+                        board.walls[cell] = true
+                    } else {
+                        board.box_walls[cell] = true
+                    }
+                }
+                TileType::Door(Some(id), _) => {
+                    board.door_at[cell] = Some(board.doors.len());
+                    board.doors.push((cell, id));
+                }
+                TileType::Button(id) => board.buttons.entry(id).or_default().push(cell),
+                TileType::WinPad => board.win[cell] = true,
+                // The search doesn't count moves, so step pads are plain floor
+                // to it, and doors with only step pads never open.
+                TileType::StepPad(id, ..) => {
+                    pads.insert(id);
+                }
+                TileType::PushBox
+                | TileType::SlipperyBox
+                | TileType::Custom(_)
+                | TileType::Empty
+                | TileType::Plate
+                | TileType::Pickup(_)
+                | TileType::Gem
+                | TileType::Warp(_) => (),
+            }
+        }
+        board.pad_doors = board.doors.iter().any(|(_, id)| pads.contains(id));
+        board
+    }
+    fn cell(&self, pos: Pos) -> Option<usize> {
+        let (y, x) = (pos.y - self.top, pos.x - self.left);
+        if y < 0 || x < 0 || y >= self.height || x >= self.width {
+            return None;
+        }
+        Some((y * self.width + x) as usize)
+    }
+    fn pos(&self, cell: usize) -> Pos {
+        let cell = cell as i32;
+        Pos::new(cell / self.width + self.top, cell % self.width + self.left)
+    }
+    fn offset(&self, cell: usize, direction: Direction) -> Option<usize> {
+        self.cell(self.pos(cell) + direction)
+    }
+    fn step(&self, cell: usize, direction: Direction) -> Option<usize> {
+        self.neighbours[cell][direction as usize]
+    }
+    // Whether a cell blocks both walking and pushing, ignoring boxes.
+    fn blocked(&self, cell: usize, open: &[bool]) -> bool {
+        self.walls[cell] || self.door_at[cell].is_some_and(|door| !open[door])
+    }
+    // Asks `deadlocks` whether the level is lost with the player at `player`.
+    fn is_lost(
+        &self,
+        deadlocks: &Deadlocks,
+        player: usize,
+        boxes: &[usize],
+        open: &[bool],
+    ) -> bool {
+        let boxes = boxes.iter().map(|&cell| self.pos(cell)).collect();
+        let closed: Vec<(Pos, u32)> = self
+            .doors
+            .iter()
+            .zip(open)
+            .filter(|(_, &open)| !open)
+            .map(|(&(cell, id), _)| (self.pos(cell), id))
+            .collect();
+        deadlocks.is_lost(self.pos(player), &boxes, &closed)
+    }
+    // Flood-fills the player's reachable cells, opening doors whose buttons the
+    // player can complete on the way, until nothing more opens.
+    fn explore(&self, start: usize, boxes: &[usize], open: &mut [bool]) -> Vec<bool> {
+        let mut has_box = vec![false; self.walls.len()];
+        for &b in boxes {
+            has_box[b] = true;
+        }
+        for (i, &(_, id)) in self.doors.iter().enumerate() {
+            if self.closing.contains(&id) {
+                open[i] = self
+                    .buttons
+                    .get(&id)
+                    .is_some_and(|buttons| buttons.iter().all(|&b| has_box[b]));
+            }
+        }
+        loop {
+            let mut region = vec![false; self.walls.len()];
+            let mut stack = vec![start];
+            region[start] = true;
+            while let Some(cell) = stack.pop() {
+                for next in self.neighbours[cell].into_iter().flatten() {
+                    if !region[next] && !self.blocked(next, open) && !has_box[next] {
+                        region[next] = true;
+                        stack.push(next);
+                    }
+                }
+            }
+            let mut opened = false;
+            for (i, &(_, id)) in self.doors.iter().enumerate() {
+                if open[i] || self.closing.contains(&id) {
+                    continue;
+                }
+                let Some(buttons) = self.buttons.get(&id) else {
+                    continue;
+                };
+                let uncovered: Vec<usize> =
+                    buttons.iter().copied().filter(|&b| !has_box[b]).collect();
+                // The player can hold down one button themselves.
+                if uncovered.is_empty() || (uncovered.len() == 1 && region[uncovered[0]]) {
+                    open[i] = true;
+                    opened = true;
+                }
+            }
+            if !opened {
+                return region;
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Hash)]
+struct StateKey {
+    // Smallest reachable cell, standing in for the whole region.
+    player: usize,
+    boxes: Vec<usize>,
+    slippery: Vec<usize>,
+    open: Vec<bool>,
+}
+
+struct Node {
+    boxes: Vec<usize>,
+    // The cells of `boxes` holding slippery boxes.
+    slippery: Vec<usize>,
+    // Doors open once the player has walked everywhere they can.
+    open: Vec<bool>,
+    // Parent node and the push (player cell before it, direction) that led here.
+    parent: Option<(usize, usize, Direction)>,
+}
+
+pub fn solve(map: &MapData, max_states: usize) -> Solution {
+    let board = Board::new(map);
+    let mut boxes: Vec<usize> = map
+        .tiles()
+        .filter(|t| t.tile_type.is_pushable())
+        .map(|t| board.cell(t.pos()).unwrap())
+        .collect();
+    boxes.sort_unstable();
+    let mut slippery: Vec<usize> = map
+        .tiles()
+        .filter(|t| t.tile_type == TileType::SlipperyBox)
+        .map(|t| board.cell(t.pos()).unwrap())
+        .collect();
+    slippery.sort_unstable();
+    let player = board.cell(map.player_spawn).unwrap();
+    let mut open = open_doors(map);
+    let region = board.explore(player, &boxes, &mut open);
+    let deadlocks = Deadlocks::new(map);
+    let mut stats = SearchStats::default();
+    if board.is_lost(&deadlocks, player, &boxes, &open) {
+        stats.deadlocks = 1;
+        return Solution {
+            result: SolveResult::Unsolvable,
+            pushes: 0,
+            stats,
+        };
+    }
+    let mut seen = HashSet::from([state_key(&region, &boxes, &slippery, &open)]);
+    let mut nodes = vec![Node {
+        boxes,
+        slippery,
+        open,
+        parent: None,
+    }];
+    // Regions are only kept while a node waits in the queue.
+    let mut queue = VecDeque::from([(0, region)]);
+
+    while let Some((index, region)) = queue.pop_front() {
+        if region.iter().zip(&board.win).any(|(&r, &w)| r && w) {
+            let (moves, pushes) = replay_pushes(map, &board, &nodes, index);
+            stats.states_explored = seen.len();
+            return Solution {
+                result: SolveResult::Solved(moves),
+                pushes,
+                stats,
+            };
+        }
+        stats.nodes_expanded += 1;
+        for b in 0..nodes[index].boxes.len() {
+            for direction in DIRECTIONS {
+                let node = &nodes[index];
+                let box_cell = node.boxes[b];
+                let (Some(from), Some(mut to)) = (
+                    board.step(box_cell, direction.opposite()),
+                    board.step(box_cell, direction),
+                ) else {
+                    continue;
+                };
+                let free = |cell: usize| {
+                    !board.blocked(cell, &node.open)
+                        && !board.box_walls[cell]
+                        && !node.boxes.contains(&cell)
+                };
+                if !region[from] || !free(to) {
+                    continue;
+                }
+                let mut slippery = node.slippery.clone();
+                if let Some(s) = slippery.iter().position(|&cell| cell == box_cell) {
+                    while let Some(next) = board.step(to, direction).filter(|&next| free(next)) {
+                        to = next;
+                    }
+                    slippery[s] = to;
+                    slippery.sort_unstable();
+                }
+                let mut boxes = node.boxes.clone();
+                boxes[b] = to;
+                boxes.sort_unstable();
+                stats.successors += 1;
+                let mut open = node.open.clone();
+                let child_region = board.explore(box_cell, &boxes, &mut open);
+                if !seen.insert(state_key(&child_region, &boxes, &slippery, &open)) {
+                    continue;
+                }
+                if board.is_lost(&deadlocks, box_cell, &boxes, &open) {
+                    stats.deadlocks += 1;
+                    continue;
+                }
+                if seen.len() >= max_states {
+                    stats.states_explored = seen.len();
+                    return Solution {
+                        result: SolveResult::GaveUp,
+                        pushes: 0,
+                        stats,
+                    };
+                }
+                queue.push_back((nodes.len(), child_region));
+                nodes.push(Node {
+                    boxes,
+                    slippery,
+                    open,
+                    parent: Some((index, from, direction)),
+                });
+            }
+        }
+    }
+    stats.states_explored = seen.len();
+    Solution {
+        result: if board.pad_doors {
+            SolveResult::GaveUp
+        } else {
+            SolveResult::Unsolvable
+        },
+        pushes: 0,
+        stats,
+    }
+}
+
+// Solves `map` as it stands with the player at `player`, and finds the first
+// push of the solution by playing it.
+pub fn hint(map: &MapData, player: Pos, max_states: usize) -> Hint {
+    let mut map = map.clone();
+    map.player_spawn = player;
+    let moves = match solve(&map, max_states).result {
+        SolveResult::Solved(moves) => moves,
+        SolveResult::Unsolvable => return Hint::Unsolvable,
+        SolveResult::GaveUp => return Hint::GaveUp,
+    };
+    let mut player = Actor::player(player);
+    let mut events = EventQueue::new();
+    for direction in moves {
+        let at = player.pos() + direction;
+        if map.move_actor(&mut player, direction, &mut events) == MoveOutcome::Pushed {
+            return Hint::Push { at, direction };
+        }
+        map.update_button_status(&player, &mut events);
+    }
+    Hint::WalkToWin
+}
+
+fn state_key(region: &[bool], boxes: &[usize], slippery: &[usize], open: &[bool]) -> StateKey {
+    StateKey {
+        player: region.iter().position(|&r| r).unwrap(),
+        boxes: boxes.to_vec(),
+        slippery: slippery.to_vec(),
+        open: open.to_vec(),
+    }
+}
+
+// Turns the chain of pushes ending at `index` into individual moves by
+// walking between them with the game's own movement rules.
+// Also returns the number of pushes.
+fn replay_pushes(
+    map: &MapData,
+    board: &Board,
+    nodes: &[Node],
+    mut index: usize,
+) -> (Vec<Direction>, usize) {
+    let mut pushes = Vec::new();
+    while let Some((parent, from, direction)) = nodes[index].parent {
+        // Any door the search opened by walking over buttons before this push
+        // must really be opened on the way to it.
+        pushes.push((board.pos(from), direction, nodes[parent].open.clone()));
+        index = parent;
+    }
+    pushes.reverse();
+    let push_count = pushes.len();
+
+    let mut map = map.clone();
+    let mut player = Actor::player(map.player_spawn);
+    let mut moves = Vec::new();
+    for (from, direction, open) in pushes {
+        walk(&mut map, &mut player, &mut moves, |m, p| {
+            p.pos() == from && open_doors(m).iter().zip(&open).all(|(&m, &o)| m || !o)
+        });
+        map.move_actor(&mut player, direction, &mut EventQueue::new());
+        map.update_button_status(&player, &mut EventQueue::new());
+        moves.push(direction);
+    }
+    walk(&mut map, &mut player, &mut moves, is_won);
+    (moves, push_count)
+}
+
+// Open state of every door that has a button, in tile order.
+fn open_doors(map: &MapData) -> Vec<bool> {
+    map.tiles()
+        .filter_map(|t| match t.tile_type {
+            TileType::Door(Some(_), open) => Some(open),
+            _ => None,
+        })
+        .collect()
+}
+
+fn is_won(map: &MapData, player: &Actor) -> bool {
+    map.is_win_pad_at(player.pos())
+}
+
+// Moves the player, without pushing anything, along the shortest path to a
+// position satisfying `arrived`. Doors may open along the way, so the path
+// search tracks door states as well as position.
+fn walk<F>(map: &mut MapData, player: &mut Actor, moves: &mut Vec<Direction>, arrived: F)
+where
+    F: Fn(&MapData, &Actor) -> bool,
+{
+    if arrived(map, player) {
+        return;
+    }
+    let mut states = vec![(map.clone(), player.clone(), None::<(usize, Direction)>)];
+    let mut seen = HashSet::from([(player.pos(), open_doors(map))]);
+    let mut queue = VecDeque::from([0]);
+    while let Some(index) = queue.pop_front() {
+        for direction in DIRECTIONS {
+            let (mut next_map, mut next_player, _) = states[index].clone();
+            let mut events = EventQueue::new();
+            if next_map.move_actor(&mut next_player, direction, &mut events) != MoveOutcome::Moved {
+                continue;
+            }
+            next_map.update_button_status(&next_player, &mut events);
+            let done = arrived(&next_map, &next_player);
+            if !seen.insert((next_player.pos(), open_doors(&next_map))) {
+                continue;
+            }
+            states.push((next_map, next_player, Some((index, direction))));
+            if done {
+                let mut path = Vec::new();
+                let mut at = states.len() - 1;
+                while let Some((parent, direction)) = states[at].2 {
+                    path.push(direction);
+                    at = parent;
+                }
+                path.reverse();
+                moves.extend(path);
+                let (end_map, end_player, _) = states.pop().unwrap();
+                *map = end_map;
+                *player = end_player;
+                return;
+            }
+            queue.push_back(states.len() - 1);
+        }
+    }
+}
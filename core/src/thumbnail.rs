@@ -0,0 +1,56 @@
+// Miniature previews of a level: the map is split into blocks and each block
+// is drawn as the glyph of its most interesting tile.
+use crate::{
+    actor::PLAYER_GLYPH,
+    map::{MapData, TileType},
+    pos::Pos,
+};
+
+// Higher wins when several tiles share one thumbnail cell.
+fn priority(tile_type: TileType) -> u8 {
+    match tile_type {
+        TileType::Empty | TileType::Warp(_) => 0,
+        TileType::Wall1 | TileType::PhaseBox => 1,
+        TileType::PushBox | TileType::SlipperyBox => 2,
+        TileType::Button(_) | TileType::Plate | TileType::StepPad(..) => 3,
+        TileType::Door(..) | TileType::HoldDoor(_) => 4,
+        TileType::Custom(_) | TileType::Pickup(_) | TileType::Gem => 3,
+        TileType::WinPad => 5,
+    }
+}
+
+// Returns at most `height` rows of at most `width` characters.
+pub fn thumbnail(map: &MapData, width: usize, height: usize) -> Vec<String> {
+    let positions = map.tiles().map(|t| t.pos()).chain([map.player_spawn]);
+    let Pos { y: max_y, x: max_x } = positions.fold(Pos::ZERO, Pos::max);
+    let (map_h, map_w) = (max_y.max(0) as usize + 1, max_x.max(0) as usize + 1);
+    // Round up so the whole map fits.
+    let scale_y = map_h.div_ceil(height.max(1));
+    let scale_x = map_w.div_ceil(width.max(1));
+    let rows = map_h.div_ceil(scale_y);
+    let cols = map_w.div_ceil(scale_x);
+
+    let mut cells: Vec<Vec<Option<TileType>>> = vec![vec![None; cols]; rows];
+    for tile in map.tiles() {
+        let Pos { y, x } = tile.pos();
+        if y < 0 || x < 0 {
+            continue;
+        }
+        let cell = &mut cells[y as usize / scale_y][x as usize / scale_x];
+        if cell.is_none_or(|c| priority(tile.tile_type) > priority(c)) {
+            *cell = Some(tile.tile_type);
+        }
+    }
+    let mut lines: Vec<Vec<char>> = cells
+        .iter()
+        .map(|row| row.iter().map(|c| c.map_or(' ', |t| t.glyph())).collect())
+        .collect();
+    let Pos {
+        y: spawn_y,
+        x: spawn_x,
+    } = map.player_spawn;
+    if spawn_y >= 0 && spawn_x >= 0 {
+        lines[spawn_y as usize / scale_y][spawn_x as usize / scale_x] = PLAYER_GLYPH;
+    }
+    lines.into_iter().map(|l| l.into_iter().collect()).collect()
+}
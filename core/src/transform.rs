@@ -0,0 +1,91 @@
+// Whole-level transformations. Rotations and mirrors happen in place: the
+// level's bounding box keeps its top-left corner. No tile points anywhere,
+// so tiles are only moved, never turned.
+use crate::{
+    map::{MapData, Tile},
+    pos::Pos,
+};
+use std::str::FromStr;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Transform {
+    RotateCw,
+    RotateCcw,
+    Rotate180,
+    // Left and right swap.
+    MirrorHorizontal,
+    // Top and bottom swap.
+    MirrorVertical,
+    Translate(i32, i32),
+}
+
+impl Transform {
+    // `size` is the height and width of the bounding box and `pos` is
+    // relative to its top-left corner.
+    fn apply_pos(self, pos: Pos, size: Pos) -> Pos {
+        // The far corner, where mirrored coordinates are measured from.
+        let last = size - Pos::new(1, 1);
+        match self {
+            Self::RotateCw => Pos::new(pos.x, last.y - pos.y),
+            Self::RotateCcw => Pos::new(last.x - pos.x, pos.y),
+            Self::Rotate180 => last - pos,
+            Self::MirrorHorizontal => Pos::new(pos.y, last.x - pos.x),
+            Self::MirrorVertical => Pos::new(last.y - pos.y, pos.x),
+            Self::Translate(dy, dx) => pos + Pos::new(dy, dx),
+        }
+    }
+}
+
+impl FromStr for Transform {
+    type Err = String;
+
+    // Accepts rotate-cw, rotate-ccw, rotate-180, mirror-h, mirror-v and
+    // translate:DY:DX.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rotate-cw" => Ok(Self::RotateCw),
+            "rotate-ccw" => Ok(Self::RotateCcw),
+            "rotate-180" => Ok(Self::Rotate180),
+            "mirror-h" => Ok(Self::MirrorHorizontal),
+            "mirror-v" => Ok(Self::MirrorVertical),
+            _ => {
+                let bad = || format!("unknown transform `{}`", s);
+                let rest = s.strip_prefix("translate:").ok_or_else(bad)?;
+                let (dy, dx) = rest.split_once(':').ok_or_else(bad)?;
+                Ok(Self::Translate(
+                    dy.parse().map_err(|_| bad())?,
+                    dx.parse().map_err(|_| bad())?,
+                ))
+            }
+        }
+    }
+}
+
+// Parses a comma-separated list, applied left to right.
+pub fn parse_transforms(list: &str) -> Result<Vec<Transform>, String> {
+    list.split(',').map(|t| t.trim().parse()).collect()
+}
+
+impl MapData {
+    pub fn transform(&mut self, transform: Transform) {
+        let bounds = self.bounds();
+        let top_left = bounds.top_left;
+        let apply = |pos: Pos| transform.apply_pos(pos - top_left, bounds.size()) + top_left;
+        let tiles = self
+            .tiles()
+            .map(|tile| {
+                let Pos { y, x } = apply(tile.pos());
+                Tile::new(y, x, tile.tile_type)
+            })
+            .collect();
+        let flavor_text = self.flavor_text.take();
+        let flavor_translations = std::mem::take(&mut self.flavor_translations);
+        let scripts = self.scripts().cloned();
+        let par = self.par;
+        let event_flavor = std::mem::take(&mut self.event_flavor);
+        *self = MapData::new(tiles, apply(self.player_spawn), flavor_text).with_scripts(scripts);
+        self.flavor_translations = flavor_translations;
+        self.par = par;
+        self.event_flavor = event_flavor;
+    }
+}
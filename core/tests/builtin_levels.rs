@@ -0,0 +1,64 @@
+// The built-in levels played through with known solutions, checking each is
+// still won the same way, and still the same level read back from its level
+// file text.
+mod harness;
+
+use box_pushing_core::{
+    events::GameEvent::{ButtonPressed, DoorOpened, LevelWon},
+    level_file::level_to_text,
+    map::{self, MapData},
+};
+use harness::{level, notable_events, play};
+
+fn builtin(index: usize) -> MapData {
+    level(&level_to_text(&map::get_maps()[index]))
+}
+
+#[test]
+fn level_0_walks_to_the_win_pad() {
+    let simulation = play(builtin(0), "rrrrrrrrrrrrrrrrrrrrrrrdddddddddd");
+    assert!(simulation.won());
+    assert_eq!(notable_events(&simulation), [(33, LevelWon { level: 0 })]);
+}
+
+#[test]
+fn level_1_opens_both_doors_from_their_buttons() {
+    let simulation = play(
+        builtin(1),
+        "uuuurrrrrrrrrrddrrrrrrrrrrrrrrrrrdduuuulluuulllllllllllllllllllllllllllll",
+    );
+    assert!(simulation.won());
+    assert_eq!(
+        notable_events(&simulation),
+        [
+            (9, ButtonPressed { id: 0 }),
+            (9, DoorOpened { id: 0 }),
+            (35, ButtonPressed { id: 1 }),
+            (35, DoorOpened { id: 1 }),
+            (73, LevelWon { level: 0 }),
+        ]
+    );
+}
+
+#[test]
+fn level_2_holds_one_button_with_a_box() {
+    let simulation = play(builtin(2), "rrrrrrdrUluRRRRRRRRRRRRRRdrdurrrrrrrrrrr");
+    assert!(simulation.won());
+    assert_eq!(
+        notable_events(&simulation),
+        [
+            (25, ButtonPressed { id: 0 }),
+            (28, ButtonPressed { id: 0 }),
+            (28, DoorOpened { id: 0 }),
+            (40, LevelWon { level: 0 }),
+        ]
+    );
+}
+
+#[test]
+fn level_2_stays_shut_with_one_button_down() {
+    // Standing on one button, with the box left where it started.
+    let simulation = play(builtin(2), "urrrrrrrrrrrrrrrrrrrrr");
+    assert!(!simulation.won());
+    assert_eq!(notable_events(&simulation), [(22, ButtonPressed { id: 0 })]);
+}
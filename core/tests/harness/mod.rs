@@ -0,0 +1,70 @@
+// Plays a LURD script through the real game logic and reports what happened,
+// for regression tests of whole levels. Each letter must do what its case
+// says: an uppercase move has to push a box and a lowercase one must not,
+// so a change in the rules shows up at the move where it starts to matter.
+use box_pushing_core::{
+    events::GameEvent,
+    headless::Simulation,
+    level_file::{level_to_text, parse_levels},
+    lurd,
+    map::{MapData, MoveOutcome},
+};
+
+// The first level in `text`. Every level built this way is also checked to
+// survive being written out and read back in.
+pub fn level(text: &str) -> MapData {
+    let map = parse_levels(text).unwrap().remove(0);
+    assert_round_trips(&level_to_text(&map));
+    map
+}
+
+// Checks that level text reads back as a level written out the same way.
+pub fn assert_round_trips(text: &str) {
+    assert_eq!(level_to_text(&parse_levels(text).unwrap()[0]), text);
+}
+
+pub fn play(map: MapData, script: &str) -> Simulation {
+    let moves = lurd::parse(script).unwrap_or_else(|e| panic!("bad script: {}", e));
+    let mut simulation = Simulation::new(map);
+    for (i, c) in script.chars().filter(|c| !c.is_whitespace()).enumerate() {
+        let Some(step) = simulation.step(moves[i]) else {
+            panic!("move {} `{}` comes after the level was won", i + 1, c);
+        };
+        let pushed = step.outcome == MoveOutcome::Pushed;
+        assert_ne!(
+            step.outcome,
+            MoveOutcome::Blocked,
+            "move {} `{}` was blocked",
+            i + 1,
+            c
+        );
+        assert_eq!(
+            pushed,
+            c.is_ascii_uppercase(),
+            "move {} `{}` {} a box",
+            i + 1,
+            c,
+            if pushed { "pushed" } else { "didn't push" }
+        );
+    }
+    simulation
+}
+
+// The events besides moving and pushing, with the number of the move that
+// set each off.
+pub fn notable_events(simulation: &Simulation) -> Vec<(usize, GameEvent)> {
+    simulation
+        .steps
+        .iter()
+        .enumerate()
+        .flat_map(|(i, step)| step.events.iter().map(move |&event| (i + 1, event)))
+        .filter(|(_, event)| {
+            !matches!(
+                event,
+                GameEvent::PlayerMoved { .. }
+                    | GameEvent::MoveBlocked { .. }
+                    | GameEvent::BoxPushed { .. }
+            )
+        })
+        .collect()
+}
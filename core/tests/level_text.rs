@@ -0,0 +1,177 @@
+// Level file text: levels written out read back in as the same level, boards
+// exported partway through, the headers only some levels use, and the
+// linter's warnings about what a level says.
+mod harness;
+
+use box_pushing_core::{
+    events::GameEvent::{ButtonPressed, DoorOpened, LevelWon},
+    level_file::{level_to_text, parse_levels},
+    lint::{lint_level, Lint},
+    map::{MapData, Tile, TileType},
+    pos::Pos,
+};
+use harness::{assert_round_trips, level, notable_events, play};
+
+// Levels written out as level file text read back in as the same level.
+
+#[test]
+fn blank_first_row_keeps_its_place() {
+    // A row holding only bare floor is written as spaces.
+    let mut tiles: Vec<Tile> = level("BBBBB\nBP.#B\nBBBBB")
+        .tiles()
+        .map(|tile| {
+            let Pos { y, x } = tile.pos();
+            Tile::new(y + 1, x, tile.tile_type)
+        })
+        .collect();
+    tiles.push(Tile::new(0, 2, TileType::Empty));
+    let text = level_to_text(&MapData::new(tiles, Pos::new(2, 1), None));
+    assert_round_trips(&text);
+    let map = level(&text);
+    assert_eq!(map.player_spawn, Pos::new(2, 1));
+    let simulation = play(map, "rr");
+    assert_eq!(notable_events(&simulation), [(2, LevelWon { level: 0 })]);
+}
+
+// An exported board is a level starting where the game stood, doors and turn
+// count included, with the moves so far noted above it.
+
+const BOARD: &str = "BBBBBBBB\nBP@.0a#B\nBBBBBBBB";
+
+#[test]
+fn board_loads_as_the_position_reached() {
+    let map = level(BOARD);
+    let simulation = play(map, "RR");
+    assert_eq!(
+        notable_events(&simulation),
+        [(2, ButtonPressed { id: 0 }), (2, DoorOpened { id: 0 })]
+    );
+    let text = simulation.game.board_text();
+    assert!(
+        text.starts_with("; level 0 after 2 moves: RR\n"),
+        "{}",
+        text
+    );
+    assert!(text.contains("turn: 2\nopen: 0\n"), "{}", text);
+    let board = level(&text);
+    assert_eq!(board.player_spawn, Pos::new(1, 3));
+    assert_eq!(board.turn(), 2);
+    assert!(board
+        .tiles()
+        .any(|tile| tile.tile_type == TileType::Door(Some(0), true)));
+    let boxes: Vec<Pos> = board
+        .tiles()
+        .filter(|tile| tile.tile_type == TileType::PushBox)
+        .map(|tile| tile.pos())
+        .collect();
+    assert_eq!(boxes, [Pos::new(1, 4)]);
+}
+
+#[test]
+fn board_saved_at_an_odd_turn_keeps_its_step_pad_timing() {
+    // The pad only counts even moves, and is one step away after the first.
+    let simulation = play(level("pad: 0 0/2\nBBBBBBBB\nBP.0a.#B\nBBBBBBBB"), "r");
+    let board = level(&simulation.game.board_text());
+    assert_eq!(board.turn(), 1);
+    let simulation = play(board, "rrrr");
+    assert_eq!(
+        notable_events(&simulation),
+        [
+            (1, ButtonPressed { id: 0 }),
+            (1, DoorOpened { id: 0 }),
+            (4, LevelWon { level: 0 }),
+        ]
+    );
+}
+
+// Levels can put a message in place of the flavor text for a few moves the
+// first time an event happens.
+
+// A button under the first step, then a box to push down the corridor.
+fn corridor() -> MapData {
+    let text = "flavor: Hello\non: push Nice push\non: button 0 Click\n\
+                BBBBBBBBBBBBB\nBP0@.......#B\nBBBBBBBBBBBBB\n";
+    level(text)
+}
+
+fn flavor(script: &str) -> String {
+    let simulation = play(corridor(), script);
+    simulation.game.flavor("").unwrap_or("").to_string()
+}
+
+#[test]
+fn message_replaces_the_flavor_text() {
+    let simulation = play(corridor(), "r");
+    assert_eq!(notable_events(&simulation), [(1, ButtonPressed { id: 0 })]);
+    assert_eq!(flavor("r"), "Click");
+    assert_eq!(flavor("rR"), "Nice push");
+}
+
+#[test]
+fn flavor_text_comes_back_after_a_few_moves() {
+    assert_eq!(flavor("rRRRRR"), "Nice push");
+    assert_eq!(flavor("rRRRRRR"), "Hello");
+}
+
+#[test]
+fn each_message_shows_only_once() {
+    assert_eq!(flavor("rRRRRRlllll"), "Hello");
+}
+
+#[test]
+fn messages_survive_the_level_text() {
+    let text = level_to_text(&corridor());
+    assert!(
+        text.contains("on: push Nice push\non: button 0 Click\n"),
+        "{}",
+        text
+    );
+    assert_round_trips(&text);
+}
+
+#[test]
+fn unknown_triggers_are_refused() {
+    assert!(parse_levels("on: explosion Boom\nBBB\nBP#\nBBB").is_err());
+    assert!(parse_levels("on: button x Click\nBBB\nBP#\nBBB").is_err());
+}
+
+// The level linter's warnings about boxes. A box in a corner can never be
+// pushed again, which is only a problem if it isn't already on a button.
+
+// The lints about a box at `pos`.
+fn box_lints(map: &MapData, pos: Pos) -> Vec<Lint> {
+    lint_level(map)
+        .into_iter()
+        .filter(|lint| lint.pos == Some(pos))
+        .collect()
+}
+
+#[test]
+fn box_in_a_corner_is_stuck() {
+    let map = level("BBBBBBB\nB@..P#B\nBBBBBBB");
+    let lints = box_lints(&map, Pos::new(1, 1));
+    assert!(
+        lints.iter().any(|lint| lint.message.contains("corner")),
+        "{:?}",
+        lints.iter().map(Lint::to_string).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn box_in_a_corner_on_its_button_is_fine() {
+    let mut tiles: Vec<Tile> = level("BBBBBBB\nB@.Pa#B\nBBBBBBB").tiles().collect();
+    tiles.push(Tile::new(1, 1, TileType::Button(0)));
+    let map = MapData::new(tiles, Pos::new(1, 3), None);
+    let lints = box_lints(&map, Pos::new(1, 1));
+    assert!(
+        lints.is_empty(),
+        "{:?}",
+        lints.iter().map(Lint::to_string).collect::<Vec<_>>()
+    );
+    // The box holds the door open from the start.
+    let simulation = play(map, "lrrr");
+    assert_eq!(
+        notable_events(&simulation),
+        [(1, DoorOpened { id: 0 }), (4, LevelWon { level: 0 })]
+    );
+}
@@ -0,0 +1,341 @@
+// Practice, assist, co-op and scoring, the ways to play besides the plain
+// game, and the words describe mode puts the board into.
+mod harness;
+
+use box_pushing_core::{
+    action::GameAction,
+    describe::Surroundings,
+    events::GameEvent::{
+        BoxStuck, ButtonPressed, DoorOpened, GemCollected, Grappled, LevelWon, Warped,
+    },
+    game::{GameContext, GameState},
+    level_file::{parse_levels, tile_char},
+    map::{Par, TileType},
+    pos::Pos,
+    score::Score,
+    Direction,
+};
+use harness::{level, notable_events, play};
+use std::time::Duration;
+
+// Performs the actions the way their keys do, then lets the board settle.
+fn perform(game: &mut GameContext, actions: &[GameAction]) {
+    for &action in actions {
+        game.perform(action);
+    }
+    game.update_all();
+}
+
+// Practice mode: the board arranged by hand, boxes added, moved and taken
+// away and the player put elsewhere, with play going on from there and a
+// win leaving no solution behind.
+
+fn boxes(game: &GameContext) -> Vec<Pos> {
+    game.map_data
+        .tiles()
+        .filter(|tile| tile.tile_type == TileType::PushBox)
+        .map(|tile| tile.pos())
+        .collect()
+}
+
+fn door_open(game: &GameContext) -> bool {
+    game.map_data
+        .tiles()
+        .any(|tile| tile.tile_type == TileType::Door(Some(0), true))
+}
+
+fn move_cursor(game: &mut GameContext, to: Pos) {
+    let GameState::Arranging(arranging) = &mut game.state else {
+        panic!("not arranging");
+    };
+    arranging.cursor = to;
+}
+
+#[test]
+fn a_box_added_on_a_button_opens_its_door() {
+    let mut simulation = play(level("BBBBBBBB\nBP..0a#B\nBBBBBBBB"), "r");
+    let game = &mut simulation.game;
+    game.perform(GameAction::Practice);
+    assert!(game.practice);
+    move_cursor(game, Pos::new(1, 4));
+    game.arrange_box();
+    assert_eq!(boxes(game), [Pos::new(1, 4)]);
+    assert!(door_open(game));
+    assert_eq!(game.events.drain(), [DoorOpened { id: 0 }]);
+    game.undo();
+    assert!(boxes(game).is_empty());
+}
+
+#[test]
+fn boxes_are_picked_up_set_down_and_taken_away() {
+    let mut simulation = play(level("BBBBBBB\nBP@.0.B\nBBBBBBB"), "");
+    let game = &mut simulation.game;
+    game.arrange();
+    move_cursor(game, Pos::new(1, 2));
+    game.arrange_box();
+    move_cursor(game, Pos::new(1, 4));
+    game.arrange_box();
+    assert_eq!(boxes(game), [Pos::new(1, 4)]);
+    // Nothing can be set down in a wall or under the player.
+    move_cursor(game, Pos::new(0, 4));
+    game.arrange_box();
+    move_cursor(game, Pos::new(1, 1));
+    game.arrange_box();
+    assert_eq!(boxes(game), [Pos::new(1, 4)]);
+    move_cursor(game, Pos::new(1, 4));
+    game.arrange_remove();
+    assert!(boxes(game).is_empty());
+    game.undo();
+    assert_eq!(boxes(game), [Pos::new(1, 4)]);
+}
+
+#[test]
+fn warps_do_nothing_in_practice() {
+    let levels = ["BBBBBB\nBP~.#B\nBBBBBB\n"; 4].join("---\n");
+    let mut game = GameContext::new(parse_levels(&levels).unwrap(), None);
+    game.arrange();
+    game.state = GameState::Playing;
+    perform(&mut game, &[GameAction::MoveRight]);
+    assert_eq!(game.level, 0);
+    assert_eq!(game.player.pos(), Pos::new(1, 2));
+    assert!(!game
+        .events
+        .drain()
+        .iter()
+        .any(|event| matches!(event, Warped { .. })));
+}
+
+#[test]
+fn a_win_in_practice_leaves_no_solution() {
+    let mut simulation = play(level("BBBBBBB\nBP...#B\nBBBBBBB"), "");
+    let game = &mut simulation.game;
+    game.arrange();
+    move_cursor(game, Pos::new(1, 4));
+    game.arrange_player();
+    game.state = GameState::Playing;
+    simulation.step(Direction::Right);
+    assert!(simulation.won());
+    assert_eq!(notable_events(&simulation), [(1, LevelWon { level: 0 })]);
+    assert_eq!(simulation.game.last_solution, None);
+}
+
+#[test]
+fn a_win_in_practice_leaves_no_earlier_solution_behind() {
+    let maps = vec![level("BBBBB\nBP.#B\nBBBBB"), level("BBBBB\nBP.#B\nBBBBB")];
+    let mut game = GameContext::new(maps, None);
+    for _ in 0..2 {
+        game.player_movement(Direction::Right);
+        game.update_all();
+    }
+    assert_eq!(game.last_solution, Some((0, "rr".to_string())));
+    game.next_level();
+    game.arrange();
+    game.state = GameState::Playing;
+    for _ in 0..2 {
+        game.player_movement(Direction::Right);
+        game.update_all();
+    }
+    assert!(game.practice);
+    assert_eq!(game.state, GameState::GameOver);
+    assert_eq!(game.last_solution, None);
+}
+
+// Assist mode lifts the level's limits on undos and hints, and warns as soon
+// as a push leaves a box where it can never move again.
+
+#[test]
+fn assist_mode_lifts_the_undo_limit() {
+    let mut simulation = play(level("undos: 1\nBBBBBB\nBP...B\nBBBBBB"), "rrr");
+    simulation.game.assist = true;
+    assert_eq!(simulation.game.undos_left(), None);
+    perform(&mut simulation.game, &[GameAction::Undo, GameAction::Undo]);
+    assert_eq!(simulation.game.player.pos(), Pos::new(1, 2));
+}
+
+#[test]
+fn hints_run_out_unless_assisted() {
+    let mut simulation = play(level("BBBBBBB\nBP.@.#B\nBBBBBBB"), "");
+    assert_eq!(simulation.game.hints_left(2), Some(2));
+    simulation.game.count_hint();
+    // Restarting doesn't give a hint back.
+    simulation.game.perform(GameAction::Restart);
+    simulation.game.count_hint();
+    assert_eq!(simulation.game.hints_left(2), Some(0));
+    simulation.game.assist = true;
+    assert_eq!(simulation.game.hints_left(2), None);
+}
+
+#[test]
+fn assist_mode_warns_of_a_stuck_box() {
+    let map = level("BBBBBBB\nB.@P.#B\nBBBBBBB");
+    let mut simulation = play(map.clone(), "");
+    simulation.game.assist = true;
+    simulation.step(Direction::Left);
+    assert_eq!(
+        notable_events(&simulation),
+        [(
+            1,
+            BoxStuck {
+                pos: Pos::new(1, 1)
+            }
+        )]
+    );
+    // Without assist the push goes by without a word.
+    assert_eq!(notable_events(&play(map, "L")), []);
+}
+
+#[test]
+fn a_box_cornered_on_its_button_is_not_stuck() {
+    let mut simulation = play(level("BBBBBBBB\nB#a.P@0B\nBBBBBBBB"), "");
+    simulation.game.assist = true;
+    simulation.step(Direction::Right);
+    for _ in 0..4 {
+        simulation.step(Direction::Left);
+    }
+    assert_eq!(
+        notable_events(&simulation),
+        [
+            (1, ButtonPressed { id: 0 }),
+            (1, DoorOpened { id: 0 }),
+            (5, LevelWon { level: 0 })
+        ]
+    );
+}
+
+// The second player's keys: in co-op they move the partner alone, and in a
+// solo game they are the player's own, so they run, grapple and phase too.
+
+#[test]
+fn solo_partner_keys_run() {
+    let mut simulation = play(level("BBBBBBBB\nBP....#B\nBBBBBBBB"), "r");
+    assert_eq!(notable_events(&simulation), []);
+    let game = &mut simulation.game;
+    perform(game, &[GameAction::Run, GameAction::PartnerRight]);
+    assert_eq!(game.player.pos(), Pos::new(1, 6));
+    assert!(game.events.drain().contains(&LevelWon { level: 0 }));
+}
+
+#[test]
+fn solo_partner_keys_aim_the_grapple() {
+    let mut simulation = play(level("grapples: 1\nBBBBBBB\nBP...@B\nB#....B\nBBBBBBB"), "");
+    let game = &mut simulation.game;
+    perform(game, &[GameAction::Grapple, GameAction::PartnerRight]);
+    assert_eq!(game.player.pos(), Pos::new(1, 1));
+    assert_eq!(game.map_data.grapples_left(), 0);
+    assert!(game
+        .events
+        .drain()
+        .iter()
+        .any(|event| matches!(event, Grappled { .. })));
+}
+
+#[test]
+fn a_co_op_win_leaves_no_earlier_solution_behind() {
+    let maps = vec![
+        level("BBBBB\nBP.#B\nBBBBB"),
+        level("BBBBBB\nBBBB.B\nB#..PB\nBBBBBB"),
+    ];
+    let mut game = GameContext::new(maps, None);
+    perform(&mut game, &[GameAction::MoveRight]);
+    perform(&mut game, &[GameAction::MoveRight]);
+    assert_eq!(game.last_solution, Some((0, "rr".to_string())));
+    game.next_level();
+    game.add_partner();
+    for _ in 0..3 {
+        perform(&mut game, &[GameAction::MoveLeft]);
+    }
+    assert_eq!(game.state, GameState::GameOver);
+    assert_eq!(game.last_solution, None);
+}
+
+#[test]
+fn partner_keys_move_only_the_partner() {
+    let mut game = GameContext::new(vec![level("BBBBBBB\nBP....B\nB....#B\nBBBBBBB")], None);
+    game.add_partner();
+    let partner = game.partner.as_ref().unwrap().pos();
+    perform(&mut game, &[GameAction::Run, GameAction::PartnerDown]);
+    assert_eq!(game.player.pos(), Pos::new(1, 1));
+    assert_eq!(
+        game.partner.as_ref().unwrap().pos(),
+        partner + Pos::new(1, 0)
+    );
+}
+
+// Scoring adds up gems, the par bonus and the time bonus of a win.
+
+#[test]
+fn gems_are_collected_on_the_way() {
+    let map = level("BBBBBBB\nBP*.*#B\nB...*.B\nBBBBBBB");
+    assert_eq!(map.gems(), (0, 3));
+    let simulation = play(map, "rrrr");
+    assert_eq!(
+        notable_events(&simulation),
+        [
+            (1, GemCollected),
+            (3, GemCollected),
+            (4, LevelWon { level: 0 }),
+        ]
+    );
+    assert_eq!(simulation.game.map_data.gems(), (2, 3));
+}
+
+#[test]
+fn bonuses_shrink_past_par_and_with_time() {
+    let par = Some(Par {
+        moves: 10,
+        pushes: 2,
+    });
+    let on_par = Score::new(2, 10, par, Duration::from_secs(20));
+    assert_eq!(
+        on_par,
+        Score {
+            gems: 200,
+            par_bonus: 500,
+            time_bonus: 400
+        }
+    );
+    assert_eq!(on_par.total(), 1100);
+    let slow = Score::new(0, 70, par, Duration::from_secs(1000));
+    assert_eq!(slow.total(), 0);
+    assert_eq!(Score::new(0, 5, None, Duration::ZERO).par_bonus, 0);
+}
+
+// The surroundings describe mode puts into words: what is next to the player
+// each way, and how far off the nearest win pad is. Tiles are compared by
+// their level file characters.
+
+fn sides(surroundings: &Surroundings) -> Vec<(Direction, char, char)> {
+    surroundings
+        .sides
+        .iter()
+        .map(|(direction, cell)| {
+            let object = cell.object.map_or(TileType::Empty, |tile| tile.tile_type);
+            (*direction, tile_char(object), tile_char(cell.floor_type()))
+        })
+        .collect()
+}
+
+#[test]
+fn bare_floor_is_left_out() {
+    let map = level("BBBBBBB\nB.....B\nB.P@0.B\nB...#.B\nBBBBBBB");
+    let surroundings = Surroundings::new(&map, map.player_spawn);
+    assert_eq!(sides(&surroundings), [(Direction::Right, '@', ' ')]);
+    assert_eq!(surroundings.goal, Some(Pos::new(1, 2)));
+}
+
+#[test]
+fn box_on_a_button_is_both() {
+    let simulation = play(level("BBBBBBB\nBP@0.#B\nBBBBBBB"), "R");
+    assert_eq!(notable_events(&simulation), [(1, ButtonPressed { id: 0 })]);
+    let game = &simulation.game;
+    let surroundings = Surroundings::new(&game.map_data, game.player.pos());
+    assert_eq!(
+        sides(&surroundings),
+        [
+            (Direction::Up, 'B', ' '),
+            (Direction::Right, '@', '0'),
+            (Direction::Down, 'B', ' '),
+        ]
+    );
+    assert_eq!(surroundings.goal, Some(Pos::new(0, 3)));
+}
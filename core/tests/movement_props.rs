@@ -0,0 +1,118 @@
+// Movement rules checked against random levels and random moves, played
+// through the headless simulation the way the game plays them:
+//
+// - a box never shares a cell with a wall, a closed door or another box;
+// - the player never ends a move inside anything solid or a box;
+// - undoing a move puts back exactly the state before it, and redoing it
+//   exactly the state after.
+use box_pushing_core::{
+    game::GameContext,
+    headless::Simulation,
+    level_file::parse_levels,
+    map::{MapData, MoveOutcome, TileType},
+    pos::Pos,
+    Direction,
+};
+use proptest::prelude::*;
+use std::collections::BTreeMap;
+
+// A random level as level-file text, up to 8 cells each way, with the
+// player spawning on an empty cell. Doors and buttons share two ids so some
+// doors open; the weights favour floor, walls and boxes.
+fn level() -> impl Strategy<Value = String> {
+    const CELLS: &str = "......BBB@@@01ab#";
+    let cell = prop::sample::select(CELLS.chars().collect::<Vec<_>>());
+    (3..9usize, 3..9usize)
+        .prop_flat_map(move |(height, width)| {
+            let cells = height * width;
+            (
+                Just(width),
+                prop::collection::vec(cell.clone(), cells),
+                0..cells,
+            )
+        })
+        .prop_map(|(width, mut cells, spawn)| {
+            cells[spawn] = 'P';
+            let rows: Vec<String> = cells.chunks(width).map(String::from_iter).collect();
+            rows.join("\n")
+        })
+}
+
+fn parse(level: &str) -> MapData {
+    parse_levels(level).unwrap().remove(0)
+}
+
+fn moves() -> impl Strategy<Value = Vec<Direction>> {
+    prop::collection::vec(prop::sample::select(Direction::ALL.to_vec()), 0..60)
+}
+
+// Everything a move can change, in a form that can be compared.
+fn state(game: &GameContext) -> (String, Pos, String) {
+    let map = serde_json::to_string(&game.map_data).unwrap();
+    (map, game.player.pos(), game.moves.clone())
+}
+
+fn tiles_by_pos(map: &MapData) -> BTreeMap<Pos, Vec<TileType>> {
+    let mut cells: BTreeMap<Pos, Vec<TileType>> = BTreeMap::new();
+    for tile in map.tiles() {
+        cells.entry(tile.pos()).or_default().push(tile.tile_type);
+    }
+    cells
+}
+
+proptest! {
+    #[test]
+    fn boxes_never_overlap_solids(map in level(), moves in moves()) {
+        let simulation = Simulation::run(parse(&map), &moves);
+        for (pos, tiles) in tiles_by_pos(&simulation.game.map_data) {
+            let boxes = tiles.iter().filter(|t| t.is_pushable()).count();
+            prop_assert!(boxes <= 1, "{} boxes at {:?}", boxes, pos);
+            if boxes == 1 {
+                prop_assert!(
+                    !tiles.iter().any(|t| t.is_solid()),
+                    "box in something solid at {:?}",
+                    pos
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn player_never_ends_inside_a_wall(map in level(), moves in moves()) {
+        let mut simulation = Simulation::new(parse(&map));
+        for direction in moves {
+            if simulation.step(direction).is_none() {
+                break;
+            }
+            let pos = simulation.game.player.pos();
+            let cells = tiles_by_pos(&simulation.game.map_data);
+            let tiles = cells.get(&pos).map_or(&[][..], Vec::as_slice);
+            prop_assert!(
+                !tiles.iter().any(|t| t.is_solid() || t.is_pushable()),
+                "player inside {} at {:?}",
+                tiles.iter().map(|t| t.glyph()).collect::<String>(),
+                pos
+            );
+        }
+    }
+
+    #[test]
+    fn undo_restores_the_exact_state(map in level(), moves in moves()) {
+        let mut simulation = Simulation::new(parse(&map));
+        for direction in moves {
+            let before = state(&simulation.game);
+            let Some(step) = simulation.step(direction) else {
+                break;
+            };
+            if step.outcome == MoveOutcome::Blocked {
+                prop_assert_eq!(&state(&simulation.game), &before);
+                continue;
+            }
+            let after = state(&simulation.game);
+            simulation.game.undo();
+            prop_assert_eq!(&state(&simulation.game), &before);
+            simulation.game.redo();
+            prop_assert_eq!(&state(&simulation.game), &after);
+        }
+    }
+}
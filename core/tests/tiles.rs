@@ -0,0 +1,615 @@
+// The special tiles and what comes with them, played through the real game
+// logic: what each does, how the solver and deadlock checks see it, and that
+// it survives being written out as level text.
+mod harness;
+
+use box_pushing_core::{
+    action::GameAction,
+    deadlock::Deadlocks,
+    events::{
+        GameEvent::{
+            self, BoxPushed, ButtonPressed, DoorClosed, DoorOpened, Grappled, ItemPickedUp,
+            LevelWon, Refused, Warped,
+        },
+        Refusal,
+    },
+    game::{GameContext, GameState},
+    headless::Simulation,
+    level_file::{level_to_text, parse_levels, parse_levels_with},
+    lint::lint_level,
+    map::{Item, MapData, MoveOutcome},
+    pos::Pos,
+    scripting::TileScripts,
+    solver::{self, SolveResult},
+    thumbnail::thumbnail,
+    Direction,
+};
+use harness::{assert_round_trips, level, notable_events, play};
+use std::sync::Arc;
+
+// Performs the actions the way their keys do, then lets the board settle.
+fn perform(game: &mut GameContext, actions: &[GameAction]) {
+    for &action in actions {
+        game.perform(action);
+    }
+    game.update_all();
+}
+
+// Step pads open their doors only when the player steps onto them on the
+// right move, and the move count behind that goes back with an undo.
+
+// The pad is three moves from the spawn, so the player can only reach it on
+// odd moves.
+fn corridor(pad: &str) -> MapData {
+    let text = format!("pad: {}\nBBBBBBBB\nBP..0a#B\nBBBBBBBB\n", pad);
+    level(&text)
+}
+
+#[test]
+fn pad_opens_its_door_on_the_counted_move() {
+    // Onto the pad on moves 3 and 5 does nothing; move 7 is 1 modulo 3.
+    let simulation = play(corridor("0 1/3"), "rrrlrlrrr");
+    assert!(simulation.won());
+    assert_eq!(
+        notable_events(&simulation),
+        [
+            (7, ButtonPressed { id: 0 }),
+            (7, DoorOpened { id: 0 }),
+            (9, LevelWon { level: 0 }),
+        ]
+    );
+}
+
+#[test]
+fn pad_wanting_the_wrong_parity_never_opens() {
+    let mut simulation = play(corridor("0 0/2"), "rrrlrlrlr");
+    let step = simulation.step(Direction::Right).unwrap();
+    assert_eq!(step.outcome, MoveOutcome::Blocked);
+}
+
+#[test]
+fn undo_takes_back_the_move_count() {
+    let mut simulation = Simulation::new(corridor("0 1/3"));
+    for _ in 0..3 {
+        simulation.step(Direction::Right);
+    }
+    simulation.game.undo();
+    assert_eq!(simulation.game.map_data.turn(), 2);
+    for direction in [Direction::Right, Direction::Left, Direction::Right] {
+        simulation.step(direction);
+    }
+    assert_eq!(simulation.game.map_data.turn(), 5);
+}
+
+#[test]
+fn a_door_with_only_pads_is_not_lost() {
+    let map = corridor("0 1/3");
+    assert!(!Deadlocks::new(&map).is_map_lost(&map, map.player_spawn));
+    // The search can't time the pad, but mustn't call the level unsolvable.
+    let solution = solver::solve(&map, solver::DEFAULT_MAX_STATES);
+    assert!(matches!(solution.result, SolveResult::GaveUp));
+}
+
+#[test]
+fn pads_survive_the_level_text() {
+    let text = level_to_text(&corridor("0 2/5"));
+    assert!(text.contains("pad: 0 2/5\n"), "{}", text);
+    assert_round_trips(&text);
+}
+
+// Slippery boxes slide on after a push until something stops them, and the
+// solver plans with that.
+
+fn pushes(simulation: &Simulation) -> Vec<(Pos, Pos)> {
+    simulation
+        .steps
+        .iter()
+        .flat_map(|step| &step.events)
+        .filter_map(|event| match *event {
+            BoxPushed { from, to } => Some((from, to)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn slides_until_a_wall() {
+    let simulation = play(level("BBBBBBBBB\nBP$.....B\nBBBBBBBBB"), "R");
+    assert_eq!(pushes(&simulation), [(Pos::new(1, 2), Pos::new(1, 7))]);
+}
+
+#[test]
+fn stops_short_of_another_box() {
+    let simulation = play(level("BBBBBBBBB\nBP$..@..B\nBBBBBBBBB"), "R");
+    assert_eq!(pushes(&simulation), [(Pos::new(1, 2), Pos::new(1, 4))]);
+}
+
+// One push sends the box all the way onto the button.
+const ONE_PUSH: &str = "BBBBBBB\nBP$..0B\nBBBBaBB\nBBBB#BB\nBBBBBBB";
+
+#[test]
+fn slides_onto_a_button() {
+    let simulation = play(level(ONE_PUSH), "Rrrdd");
+    assert!(simulation.won());
+    assert_eq!(
+        notable_events(&simulation),
+        [
+            (1, ButtonPressed { id: 0 }),
+            (1, DoorOpened { id: 0 }),
+            (5, LevelWon { level: 0 }),
+        ]
+    );
+}
+
+#[test]
+fn solver_counts_a_slide_as_one_push() {
+    let solution = solver::solve(&level(ONE_PUSH), solver::DEFAULT_MAX_STATES);
+    let SolveResult::Solved(moves) = solution.result else {
+        panic!("no solution");
+    };
+    assert_eq!(solution.pushes, 1);
+    assert!(Simulation::run(level(ONE_PUSH), &moves).won());
+}
+
+// Phase boxes let the player through only after an odd number of moves, and
+// never let a box in.
+
+#[test]
+fn passable_after_an_odd_number_of_moves() {
+    let simulation = play(level("BBBBBB\nBP.&#B\nBBBBBB"), "rrr");
+    assert_eq!(notable_events(&simulation), [(3, LevelWon { level: 0 })]);
+}
+
+#[test]
+fn solid_after_an_even_number_of_moves() {
+    let map = level("BBBBBB\nB.P&#B\nBBBBBB");
+    let mut simulation = play(map.clone(), "lr");
+    let step = simulation.step(Direction::Right).unwrap();
+    assert_eq!(step.outcome, MoveOutcome::Blocked);
+    // Every way back to the spawn takes an even number of moves.
+    let solution = solver::solve(&map, solver::DEFAULT_MAX_STATES);
+    assert!(matches!(solution.result, SolveResult::Unsolvable));
+}
+
+#[test]
+fn boxes_are_never_let_in() {
+    let mut simulation = play(level("BBBBBBBB\nBP.@&.#B\nBBBBBBBB"), "r");
+    assert!(!simulation.game.map_data.phase_boxes_solid());
+    let step = simulation.step(Direction::Right).unwrap();
+    assert_eq!(step.outcome, MoveOutcome::Blocked);
+}
+
+#[test]
+fn solver_times_its_way_through() {
+    let map = level("BBBBBBB\nBP.&..B\nB.BBB#B\nBBBBBBB");
+    let SolveResult::Solved(moves) = solver::solve(&map, solver::DEFAULT_MAX_STATES).result else {
+        panic!("no solution");
+    };
+    assert!(Simulation::run(map, &moves).won());
+}
+
+// Doors with a close delay shut again a number of moves after their buttons
+// are let go, and a tile script can shut a door too, but never on someone or
+// something in the doorway.
+
+// The player holds the button, then has to sprint for the door.
+fn sprint(delay: u32) -> MapData {
+    let text = format!("delay: 0 {}\nBBBBBBBB\nBP0.a.#B\nBBBBBBBB\n", delay);
+    level(&text)
+}
+
+#[test]
+fn door_closes_behind_the_player() {
+    // Let go on move 2, so the door is due to shut after move 4.
+    let simulation = play(sprint(2), "rrrrr");
+    assert_eq!(
+        notable_events(&simulation),
+        [
+            (1, ButtonPressed { id: 0 }),
+            (1, DoorOpened { id: 0 }),
+            (4, DoorClosed { id: 0 }),
+            (5, LevelWon { level: 0 }),
+        ]
+    );
+}
+
+#[test]
+fn door_waits_for_the_doorway_to_clear() {
+    // Due after move 3, with the player standing in the doorway.
+    let simulation = play(sprint(1), "rrrrr");
+    assert!(notable_events(&simulation).contains(&(4, DoorClosed { id: 0 })));
+}
+
+#[test]
+fn door_without_time_shuts_at_once() {
+    let mut simulation = play(sprint(0), "rr");
+    let step = simulation.step(Direction::Right).unwrap();
+    assert_eq!(step.outcome, MoveOutcome::Blocked);
+}
+
+#[test]
+fn delays_survive_the_level_text() {
+    let text = level_to_text(&sprint(3));
+    assert!(text.contains("delay: 0 3\n"), "{}", text);
+    assert_round_trips(&text);
+}
+
+#[test]
+fn solver_keeps_doors_open_under_boxes() {
+    let map = level("delay: 0 0\nBBBBBBB\nBP@0BBB\nB....a#\nBBBBBBB");
+    let SolveResult::Solved(moves) = solver::solve(&map, solver::DEFAULT_MAX_STATES).result else {
+        panic!("no solution");
+    };
+    assert!(Simulation::run(map, &moves).won());
+}
+
+// `O` opens door 0 when stepped on and `X` closes it.
+fn scripted(text: &str) -> MapData {
+    let mut scripts = TileScripts::new();
+    scripts
+        .add('O', "fn on_stood_on() { open_door(0) }")
+        .unwrap();
+    scripts
+        .add('X', "fn on_stood_on() { close_door(0) }")
+        .unwrap();
+    parse_levels_with(text, Some(&Arc::new(scripts)))
+        .unwrap()
+        .remove(0)
+}
+
+#[test]
+fn script_closes_a_door_once_the_doorway_is_clear() {
+    let map = scripted("BBBBBBB\nBPO@aXB\nB.....B\nBBBBBBB");
+    // Around to `X` with the box in the doorway, then back through it.
+    let simulation = play(map, "rRdrruLd");
+    assert_eq!(
+        notable_events(&simulation),
+        [(1, DoorOpened { id: 0 }), (8, DoorClosed { id: 0 })]
+    );
+}
+
+#[test]
+fn script_closing_a_shut_door_says_nothing() {
+    let simulation = play(scripted("BBBBBB\nBPXa#B\nBBBBBB"), "r");
+    assert_eq!(notable_events(&simulation), []);
+}
+
+// Power-ups are picked up into the inventory and used from it: a phase goes
+// through one wall, and an extra undo takes back a move past the level's
+// undo limit.
+
+#[test]
+fn stepping_on_a_power_up_picks_it_up() {
+    let simulation = play(level("BBBBBB\nBP!.+B\nBBBBBB"), "rrr");
+    assert_eq!(
+        notable_events(&simulation),
+        [
+            (1, ItemPickedUp { item: Item::Phase }),
+            (3, ItemPickedUp { item: Item::Undo }),
+        ]
+    );
+    let map = &simulation.game.map_data;
+    assert_eq!((map.items(Item::Phase), map.items(Item::Undo)), (1, 1));
+    // It is gone from the floor.
+    assert!(!simulation.board()[1].contains('!'));
+}
+
+#[test]
+fn phase_goes_through_one_wall() {
+    let mut simulation = Simulation::new(level("BBBBBB\nBP!B#B\nBBBBBB"));
+    simulation.step(Direction::Right);
+    perform(
+        &mut simulation.game,
+        &[GameAction::UsePhase, GameAction::MoveRight],
+    );
+    assert_eq!(simulation.game.player.pos(), Pos::new(1, 4));
+    assert!(simulation.won());
+    assert_eq!(simulation.game.map_data.items(Item::Phase), 0);
+    // LURD can't write the phase down, so there is no solution to keep.
+    assert_eq!(simulation.game.last_solution, None);
+}
+
+#[test]
+fn a_phase_win_leaves_no_earlier_solution_behind() {
+    let maps = vec![
+        level("BBBBB\nBP.#B\nBBBBB"),
+        level("item: phase 1\nBBBBB\nBPB#B\nBBBBB"),
+    ];
+    let mut simulation = Simulation {
+        game: GameContext::new(maps, None),
+        steps: Vec::new(),
+    };
+    simulation.step(Direction::Right);
+    simulation.step(Direction::Right);
+    assert_eq!(simulation.game.last_solution, Some((0, "rr".to_string())));
+    simulation.game.next_level();
+    perform(
+        &mut simulation.game,
+        &[GameAction::UsePhase, GameAction::MoveRight],
+    );
+    assert_eq!(simulation.game.state, GameState::GameOver);
+    assert_eq!(simulation.game.last_solution, None);
+}
+
+#[test]
+fn phase_needs_a_free_cell_beyond() {
+    let mut simulation = Simulation::new(level("item: phase 1\nBBBBB\nBPBBB\nBBBBB"));
+    perform(
+        &mut simulation.game,
+        &[GameAction::UsePhase, GameAction::MoveRight],
+    );
+    assert_eq!(simulation.game.player.pos(), Pos::new(1, 1));
+    assert_eq!(simulation.game.map_data.items(Item::Phase), 1);
+}
+
+#[test]
+fn undo_limit_and_extra_undos() {
+    let text = "undos: 1\nitem: undo 1\nBBBBBB\nBP...B\nBBBBBB";
+    let mut simulation = play(level(text), "rrr");
+    perform(&mut simulation.game, &[GameAction::Undo, GameAction::Undo]);
+    assert_eq!(simulation.game.player.pos(), Pos::new(1, 3));
+    let refused = Refused {
+        reason: Refusal::NoUndosLeft,
+    };
+    assert!(simulation.game.events.drain().contains(&refused));
+    perform(&mut simulation.game, &[GameAction::UseUndo]);
+    assert_eq!(simulation.game.player.pos(), Pos::new(1, 2));
+    assert_eq!(simulation.game.map_data.items(Item::Undo), 0);
+    perform(&mut simulation.game, &[GameAction::UseUndo]);
+    assert_eq!(simulation.game.player.pos(), Pos::new(1, 2));
+}
+
+#[test]
+fn undo_and_redo_with_nothing_to_take_back_are_refused() {
+    let mut simulation = Simulation::new(level("BBBBB\nBP.#B\nBBBBB"));
+    perform(&mut simulation.game, &[GameAction::Undo]);
+    simulation.game.redo();
+    let refusals = [Refusal::NothingToUndo, Refusal::NothingToRedo];
+    assert_eq!(
+        simulation.game.events.drain(),
+        refusals.map(|reason| Refused { reason })
+    );
+}
+
+#[test]
+fn power_ups_survive_the_level_text() {
+    let text = level_to_text(&level("undos: 5\nitem: phase 2\nBBBBB\nBP+#B\nBBBBB"));
+    assert!(text.contains("item: phase 2\n"), "{}", text);
+    assert!(text.contains("undos: 5\n"), "{}", text);
+    assert_round_trips(&text);
+    let simulation = play(level(&text), "rr");
+    assert!(notable_events(&simulation).contains(&(2, LevelWon { level: 0 })));
+}
+
+// The grapple pulls a box to the player, or the player up to a wall, as many
+// times as the level allows.
+
+// Fires the grapple the way the keys do, returning what happened.
+fn fire(simulation: &mut Simulation, aim: GameAction) -> Vec<GameEvent> {
+    perform(&mut simulation.game, &[GameAction::Grapple, aim]);
+    simulation.game.events.drain()
+}
+
+#[test]
+fn pulls_a_box_to_the_player() {
+    // The box is against the wall, so no push could ever move it.
+    let mut simulation = Simulation::new(level("grapples: 1\nBBBBBBB\nBP...@B\nBBBBBBB"));
+    let events = fire(&mut simulation, GameAction::MoveRight);
+    assert_eq!(
+        events,
+        [Grappled {
+            from: Pos::new(1, 5),
+            to: Pos::new(1, 2)
+        }]
+    );
+    assert_eq!(simulation.game.map_data.grapples_left(), 0);
+}
+
+#[test]
+fn pulls_the_player_up_to_a_wall() {
+    let map = level("grapples: 2\nBBBBBBB\nBP..0.B\nBBBBaBB\nBBBB#BB\nBBBBBBB");
+    let mut simulation = Simulation::new(map);
+    fire(&mut simulation, GameAction::MoveRight);
+    assert_eq!(simulation.game.player.pos(), Pos::new(1, 5));
+    // Back onto the button, then down through its door.
+    for direction in [Direction::Left, Direction::Down, Direction::Down] {
+        simulation.step(direction);
+    }
+    assert_eq!(
+        notable_events(&simulation),
+        [
+            (1, ButtonPressed { id: 0 }),
+            (1, DoorOpened { id: 0 }),
+            (3, LevelWon { level: 0 }),
+        ]
+    );
+    // The level was won, but LURD can't say how.
+    assert_eq!(simulation.game.last_solution, None);
+}
+
+#[test]
+fn a_grapple_win_leaves_no_earlier_solution_behind() {
+    let maps = vec![
+        level("BBBBB\nBP.#B\nBBBBB"),
+        level("grapples: 2\nBBBBBBB\nBP..0.B\nBBBBaBB\nBBBB#BB\nBBBBBBB"),
+    ];
+    let mut game = GameContext::new(maps, None);
+    for _ in 0..2 {
+        game.player_movement(Direction::Right);
+        game.update_all();
+    }
+    assert_eq!(game.last_solution, Some((0, "rr".to_string())));
+    game.next_level();
+    let mut simulation = Simulation {
+        game,
+        steps: Vec::new(),
+    };
+    fire(&mut simulation, GameAction::MoveRight);
+    for direction in [Direction::Left, Direction::Down, Direction::Down] {
+        simulation.step(direction);
+    }
+    assert_eq!(simulation.game.state, GameState::GameOver);
+    assert_eq!(simulation.game.last_solution, None);
+}
+
+#[test]
+fn pulled_box_presses_a_button() {
+    let mut simulation = Simulation::new(level("grapples: 1\nBBBBBBB\nBP0..@B\nBaBBBBB\nBBBBBBB"));
+    let events = fire(&mut simulation, GameAction::MoveRight);
+    assert!(events.contains(&ButtonPressed { id: 0 }), "{:?}", events);
+    assert!(events.contains(&DoorOpened { id: 0 }), "{:?}", events);
+}
+
+#[test]
+fn does_nothing_without_charges() {
+    let mut simulation = Simulation::new(level("BBBBBBB\nBP...@B\nBBBBBBB"));
+    let events = fire(&mut simulation, GameAction::MoveRight);
+    let refused = Refused {
+        reason: Refusal::NoGrapplesLeft,
+    };
+    assert!(events.contains(&refused), "{:?}", events);
+    // The direction was an ordinary move.
+    assert_eq!(simulation.game.player.pos(), Pos::new(1, 2));
+    assert!(!events.iter().any(|e| matches!(e, Grappled { .. })));
+}
+
+#[test]
+fn nothing_within_reach_keeps_the_charge() {
+    let mut simulation = Simulation::new(level("grapples: 1\nBBBBB\nBP@.B\nBBBBB"));
+    assert_eq!(
+        fire(&mut simulation, GameAction::MoveRight),
+        [Refused {
+            reason: Refusal::NothingToGrapple
+        }]
+    );
+    assert_eq!(simulation.game.map_data.grapples_left(), 1);
+}
+
+#[test]
+fn undo_gives_the_charge_back() {
+    let mut simulation = Simulation::new(level("grapples: 1\nBBBBBBB\nBP...@B\nBBBBBBB"));
+    fire(&mut simulation, GameAction::MoveRight);
+    simulation.game.undo();
+    assert_eq!(simulation.game.map_data.grapples_left(), 1);
+    assert_eq!(simulation.game.map_data.turn(), 0);
+}
+
+#[test]
+fn grapples_survive_the_level_text() {
+    let text = level_to_text(&level("grapples: 3\nBBBBB\nBP.#B\nBBBBB"));
+    assert!(text.contains("grapples: 3\n"), "{}", text);
+    assert_round_trips(&text);
+    assert!(play(level(&text), "rr").won());
+}
+
+// Hidden warps send the player levels ahead without winning the level they
+// are on.
+
+const WARP_LEVEL: &str = "BBBBBB\nBP~.#B\nBBBBBB\n";
+
+// `count` copies of the level, each with `header` above it.
+fn campaign(count: usize, header: &str) -> GameContext {
+    let levels = vec![format!("{}{}", header, WARP_LEVEL); count];
+    GameContext::new(parse_levels(&levels.join("---\n")).unwrap(), None)
+}
+
+#[test]
+fn warp_skips_ahead() {
+    let mut game = campaign(6, "");
+    perform(&mut game, &[GameAction::MoveRight]);
+    assert_eq!(game.level, 3);
+    assert!(game.events.drain().contains(&Warped { from: 0, to: 3 }));
+    assert_eq!(game.player.pos(), game.map_data.player_spawn);
+    assert_eq!(game.last_solution, None);
+}
+
+#[test]
+fn warp_stops_at_the_last_level() {
+    let mut game = campaign(6, "warp: 9\n");
+    perform(&mut game, &[GameAction::MoveRight]);
+    assert_eq!(game.level, 5);
+    // From there it has nowhere to go, so it is just floor.
+    perform(&mut game, &[GameAction::MoveRight]);
+    assert_eq!(game.level, 5);
+}
+
+#[test]
+fn warp_on_the_only_level_is_floor() {
+    let map = level(WARP_LEVEL);
+    let simulation = play(map, "rrr");
+    assert_eq!(notable_events(&simulation), [(3, LevelWon { level: 0 })]);
+}
+
+#[test]
+fn warps_are_hidden_but_kept_in_the_level_text() {
+    let map = level(&format!("warp: 2\n{}", WARP_LEVEL));
+    assert_eq!(thumbnail(&map, 10, 10)[1], "BX  #B");
+    let text = level_to_text(&map);
+    assert!(text.starts_with("warp: 2\n"), "{}", text);
+    assert!(text.contains("BP~"), "{}", text);
+    assert_round_trips(&text);
+}
+
+// Named spawn points, and exits that lead to them: a hub level left by one
+// win pad and come back to by another route.
+
+// The hub's west pad leads on to the next level as usual; its east pad leads
+// to that level's `back` spawn. The second level's pad leads home to the
+// hub's `east` spawn.
+const HUB: &str = "\
+spawn: east 1 5
+exit: 1 6 next back
+BBBBBBBB
+B#.P..#B
+BBBBBBBB
+---
+spawn: back 1 5
+exit: 1 1 0 east
+BBBBBBB
+B#P...B
+BBBBBBB
+";
+
+fn hub() -> GameContext {
+    GameContext::new(parse_levels(HUB).unwrap(), None)
+}
+
+#[test]
+fn exit_leads_to_its_spawn_point() {
+    let mut game = hub();
+    game.teleport(Pos::new(1, 6)).unwrap();
+    assert_eq!(game.update_all(), Some(0));
+    game.next_level();
+    assert_eq!((game.level, game.player.pos()), (1, Pos::new(1, 5)));
+    // And back into the hub beside the pad it was left by.
+    game.teleport(Pos::new(1, 1)).unwrap();
+    assert_eq!(game.update_all(), Some(1));
+    assert_eq!(game.state, GameState::LevelComplete { level: 1 });
+    game.next_level();
+    assert_eq!((game.level, game.player.pos()), (0, Pos::new(1, 5)));
+    // A restart keeps to the spawn point it came in by.
+    game.restart_level();
+    assert_eq!(game.player.pos(), Pos::new(1, 5));
+}
+
+#[test]
+fn plain_win_pad_enters_at_the_usual_spawn() {
+    let simulation = play(level(HUB), "ll");
+    assert_eq!(notable_events(&simulation), [(2, LevelWon { level: 0 })]);
+    let mut game = hub();
+    game.teleport(Pos::new(1, 1)).unwrap();
+    game.update_all();
+    game.next_level();
+    assert_eq!(game.player.pos(), Pos::new(1, 2));
+}
+
+#[test]
+fn spawns_and_exits_survive_the_level_text() {
+    let maps = parse_levels(HUB).unwrap();
+    for map in &maps {
+        let text = level_to_text(map);
+        assert_round_trips(&text);
+        assert!(lint_level(map).is_empty(), "{}", text);
+    }
+    assert!(level_to_text(&maps[0]).contains("exit: 1 6 next back\n"));
+}
@@ -0,0 +1,172 @@
+use crate::renderer::Backend;
+use box_pushing_core::transform::{parse_transforms, Transform};
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+usage: button_trial [LEVEL_FILE] [OPTIONS]
+  --fetch URL              download and install a level pack
+  --lint FILE              check a level file for design problems
+  --solve FILE             print a solution of each level in FILE (or of --level
+                           alone) with the fewest pushes, in LURD notation
+  --par PATH               solve every level of a level file, or of a pack
+                           directory, and write their pars into the files
+  --max-states N           states --solve and --par search before giving up on a level
+                           (default 200000)
+  --export-builtin FILE    write the built-in levels as a level file (- for stdout)
+  --transform OPS          print LEVEL_FILE transformed by a comma-separated list of
+                           rotate-cw, rotate-ccw, rotate-180, mirror-h, mirror-v
+                           and translate:DY:DX
+  --export-solutions       print solutions of won levels on exit
+  --playback FILE          watch a LURD solution file (with --level), or a
+                           replay file, checking it wins the level it was
+                           recorded on
+  --export-replay FILE     write the fastest run of --level as a replay file
+                           (- for stdout)
+  --headless FILE          play a LURD solution file without the UI and print each
+                           move and the final board; exits 1 if it does not win
+  --level N                level the solution belongs to (default 0)
+  --renderer NAME          terminal backend: curses, crossterm, or stub to read
+                           key names from stdin and print the final screen
+  --config FILE            read settings from FILE instead of the usual config.toml
+  --set KEY=VALUE          override one config setting, e.g. --set undo_limit=50
+  --log FILE               write the log to FILE instead of the data directory
+                           (RUST_LOG sets the level, e.g. RUST_LOG=debug)
+  --record-keys FILE       write every key pressed to FILE, with timings
+  --replay-keys FILE       press the keys recorded in FILE before reading the
+                           keyboard
+  --profile NAME           play as profile NAME, making it if it is new
+  --broadcast ADDR         let others watch this game with --spectate, taking
+                           viewers on ADDR (port 7879 if none is given)
+  --spectate ADDR          watch the game broadcasting at ADDR
+  --relay ADDR             run a lobby on ADDR (port 7880 if none is given) that
+                           network games can meet through, by giving RELAY/ROOM
+                           as the address to host or join
+  --debug                  let `:` open a command line in play (level N,
+                           teleport Y X, give key N, noclip)";
+
+#[derive(Default)]
+pub struct Args {
+    pub level_file: Option<PathBuf>,
+    pub fetch_url: Option<String>,
+    pub lint_file: Option<PathBuf>,
+    pub solve_file: Option<PathBuf>,
+    pub par_path: Option<PathBuf>,
+    pub max_states: Option<usize>,
+    pub export_builtin: Option<String>,
+    pub transforms: Option<Vec<Transform>>,
+    // Print the LURD solution of every level won to stdout on exit.
+    pub export_solutions: bool,
+    pub playback_file: Option<String>,
+    pub export_replay: Option<String>,
+    pub headless_file: Option<String>,
+    pub level: Option<u32>,
+    pub renderer: Option<Backend>,
+    pub config_file: Option<PathBuf>,
+    // `KEY=VALUE` config overrides, applied in order.
+    pub config_overrides: Vec<String>,
+    pub log_file: Option<PathBuf>,
+    pub record_keys: Option<PathBuf>,
+    pub replay_keys: Option<PathBuf>,
+    pub profile: Option<String>,
+    pub broadcast: Option<String>,
+    pub spectate: Option<String>,
+    pub relay: Option<String>,
+    pub debug: bool,
+}
+
+pub fn parse_args<I: Iterator<Item = String>>(mut args: I) -> Result<Args, String> {
+    let mut parsed = Args::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--fetch" => {
+                parsed.fetch_url = Some(args.next().ok_or("--fetch needs a URL")?);
+            }
+            "--lint" => {
+                parsed.lint_file = Some(PathBuf::from(args.next().ok_or("--lint needs a file")?));
+            }
+            "--solve" => {
+                parsed.solve_file = Some(PathBuf::from(args.next().ok_or("--solve needs a file")?));
+            }
+            "--par" => {
+                parsed.par_path = Some(PathBuf::from(
+                    args.next().ok_or("--par needs a level file or pack")?,
+                ));
+            }
+            "--max-states" => {
+                let states = args.next().ok_or("--max-states needs a number")?;
+                parsed.max_states = Some(
+                    states
+                        .parse()
+                        .map_err(|_| format!("bad state count {}", states))?,
+                );
+            }
+            "--export-builtin" => {
+                parsed.export_builtin =
+                    Some(args.next().ok_or("--export-builtin needs a file or -")?);
+            }
+            "--transform" => {
+                let ops = args
+                    .next()
+                    .ok_or("--transform needs a list of operations")?;
+                parsed.transforms = Some(parse_transforms(&ops)?);
+            }
+            "--export-solutions" => parsed.export_solutions = true,
+            "--debug" => parsed.debug = true,
+            "--playback" => {
+                parsed.playback_file = Some(args.next().ok_or("--playback needs a file")?);
+            }
+            "--export-replay" => {
+                parsed.export_replay =
+                    Some(args.next().ok_or("--export-replay needs a file or -")?);
+            }
+            "--headless" => {
+                parsed.headless_file = Some(args.next().ok_or("--headless needs a file")?);
+            }
+            "--level" => {
+                let level = args.next().ok_or("--level needs a number")?;
+                parsed.level = Some(level.parse().map_err(|_| format!("bad level {}", level))?);
+            }
+            "--renderer" => {
+                parsed.renderer = Some(args.next().ok_or("--renderer needs a name")?.parse()?);
+            }
+            "--config" => {
+                parsed.config_file =
+                    Some(PathBuf::from(args.next().ok_or("--config needs a file")?));
+            }
+            "--log" => {
+                parsed.log_file = Some(PathBuf::from(args.next().ok_or("--log needs a file")?));
+            }
+            "--record-keys" => {
+                parsed.record_keys = Some(PathBuf::from(
+                    args.next().ok_or("--record-keys needs a file")?,
+                ));
+            }
+            "--replay-keys" => {
+                parsed.replay_keys = Some(PathBuf::from(
+                    args.next().ok_or("--replay-keys needs a file")?,
+                ));
+            }
+            "--broadcast" => {
+                parsed.broadcast = Some(args.next().ok_or("--broadcast needs an address")?);
+            }
+            "--spectate" => {
+                parsed.spectate = Some(args.next().ok_or("--spectate needs an address")?);
+            }
+            "--relay" => {
+                parsed.relay = Some(args.next().ok_or("--relay needs an address")?);
+            }
+            "--profile" => {
+                parsed.profile = Some(args.next().ok_or("--profile needs a name")?);
+            }
+            "--set" => {
+                let setting = args.next().ok_or("--set needs KEY=VALUE")?;
+                parsed.config_overrides.push(setting);
+            }
+            "-h" | "--help" => return Err(USAGE.to_string()),
+            _ if arg.starts_with("--") => return Err(format!("unknown option {}\n{}", arg, USAGE)),
+            _ if parsed.level_file.is_none() => parsed.level_file = Some(PathBuf::from(arg)),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    Ok(parsed)
+}
@@ -0,0 +1,654 @@
+// Player settings from `config.toml` in the config directory, or in the
+// profile's own directory for a profile other than the default. Every value is
+// optional; anything left out keeps its default. `--set KEY=VALUE` on the
+// command line overrides single values, with KEY in dotted form and VALUE
+// written as in the file, e.g. `--set undo_limit=50` or
+// `--set 'glyphs.player="O"'`.
+//
+//   renderer = "crossterm"   # curses, crossterm or stub
+//   theme = "mono"           # color or mono
+//   animation_ms = 250       # playback step time
+//   reduced_motion = true    # no moving effects: story text appears
+//                            # whole instead of being typed out
+//   undo_limit = 100         # moves kept for undo; unlimited if left out
+//   repeat_ms = 100          # fastest a held key repeats; 0 for the
+//                            # terminal's own rate
+//   input_buffer = 8         # key presses kept waiting to be played, one
+//                            # per tick; more are dropped
+//   one_key = true           # ignore presses until the last one has been
+//                            # played, instead of queueing them
+//   min_move_ms = 250        # with one_key, the least time between moves
+//   confirm = "prompt"       # before restart or quit throws away moves:
+//                            # prompt asks y/n, hold wants the key held
+//                            # down for a second, off doesn't ask
+//   autosave_moves = 10      # autosave the level every this many moves,
+//                            # to offer back after a crash; 0 for never
+//   ghost = true             # race a ghost of your fastest run of each
+//                            # level (see `ghost`)
+//   scoring = true           # score each win for gems, par and time,
+//                            # keeping the best of each level
+//   describe = true          # describe the player's surroundings in
+//                            # words after every move, for screen
+//                            # readers (see `describe`)
+//   assist = true            # assist mode: unlimited undos and hints and
+//                            # a warning when a box gets stuck; wins
+//                            # are marked assisted and set no records
+//   hints_per_level = 3      # solver hints allowed on each level; 0 for
+//                            # none (see `hint`)
+//   language = "fr"          # UI and flavor text language (see `i18n`)
+//   leaderboard_url = "https://example.org/board"
+//                            # send wins to this online leaderboard and
+//                            # show its rankings (see `online`)
+//
+//   [keys]
+//   up = "w"                 # a character, or up, down, left, right,
+//   undo = "u"               # enter, backspace or escape
+//   run = "."                # then a direction to run until stopped
+//   grapple = "g"            # then a direction to fire the grapple
+//   use_phase = "1"          # power-ups: a phase, then a direction to
+//   use_undo = "2"           # go through a wall; an extra undo
+//   help = ["?", "f1"]       # the help screen (see `help`)
+//   practice = "P"           # practice: arrange the board by hand,
+//                            # setting no records (see `practice`)
+//   left = ["left", "h"]     # any action can take a list; moves also
+//                            # take hjkl by default, and the second
+//   partner_up = "w"         # player's partner_* moves WASD, which
+//                            # move the only player in a solo game
+//
+// Keys can also be changed on the controls screen, which writes `[keys]`
+// back to this file.
+//
+//   [glyphs]
+//   wall = "#"
+//
+//   [sound]
+//   output = "bell"          # bell rings the terminal bell, tones beeps
+//                            # (in builds with the `sound` feature), off
+//                            # is silent (see `sound`)
+//   [sound.volume]           # 0 to 100 for each sound, 0 muting it; these
+//   step = 0                 # are the defaults
+//   bump = 0
+//   push = 0
+//   button = 50
+//   door = 50
+//   win = 80
+//   music = 40               # level pack music (see `music`)
+use crate::{
+    confirm::ConfirmMode,
+    i18n::{Strings, DEFAULT_LANGUAGE},
+    profile::Profile,
+    renderer::{Backend, Key, Theme},
+    sound::SoundOutput,
+};
+use box_pushing_core::{
+    action::GameAction,
+    actor::PLAYER_GLYPH,
+    map::{Item, TileType},
+    paths::config_dir,
+};
+use serde::{Deserialize, Deserializer};
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+const CONFIG_FILE: &str = "config.toml";
+
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(PathBuf, io::Error),
+    Parse(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            Self::Parse(msg) => write!(f, "bad config: {}", msg),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    #[serde(deserialize_with = "parsed_option")]
+    pub renderer: Option<Backend>,
+    #[serde(deserialize_with = "parsed")]
+    pub theme: Theme,
+    pub animation_ms: i32,
+    pub reduced_motion: bool,
+    pub undo_limit: Option<usize>,
+    pub repeat_ms: u64,
+    pub input_buffer: usize,
+    pub one_key: bool,
+    pub min_move_ms: u64,
+    #[serde(deserialize_with = "parsed")]
+    pub confirm: ConfirmMode,
+    pub autosave_moves: u32,
+    pub ghost: bool,
+    pub scoring: bool,
+    pub describe: bool,
+    pub assist: bool,
+    pub hints_per_level: u32,
+    pub language: String,
+    pub leaderboard_url: Option<String>,
+    pub keys: Keys,
+    pub glyphs: Glyphs,
+    pub sound: SoundConfig,
+    // The text table for `language`, loaded along with the config.
+    #[serde(skip)]
+    pub strings: Strings,
+    // Set by `--debug` rather than the file.
+    #[serde(skip)]
+    pub debug: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            renderer: None,
+            theme: Theme::Color,
+            animation_ms: 250,
+            reduced_motion: false,
+            undo_limit: None,
+            repeat_ms: 100,
+            input_buffer: 8,
+            one_key: false,
+            min_move_ms: 250,
+            confirm: ConfirmMode::Prompt,
+            autosave_moves: 10,
+            ghost: false,
+            scoring: false,
+            describe: false,
+            assist: false,
+            hints_per_level: 3,
+            language: DEFAULT_LANGUAGE.to_string(),
+            leaderboard_url: None,
+            keys: Keys::default(),
+            glyphs: Glyphs::default(),
+            sound: SoundConfig::default(),
+            strings: Strings::default(),
+            debug: false,
+        }
+    }
+}
+
+// Every action takes a list of keys, or a single key on its own.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Keys {
+    #[serde(deserialize_with = "parsed_list")]
+    pub up: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub down: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub left: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub right: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub partner_up: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub partner_down: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub partner_left: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub partner_right: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub run: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub grapple: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub use_phase: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub use_undo: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub restart: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub undo: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub redo: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub pause: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub quit: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub share_code: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub export_solution: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub export_board: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub editor: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub hint: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub mute: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub help: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub describe: Vec<Key>,
+    #[serde(deserialize_with = "parsed_list")]
+    pub practice: Vec<Key>,
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Self {
+            up: vec![Key::Up, Key::Char('k')],
+            down: vec![Key::Down, Key::Char('j')],
+            left: vec![Key::Left, Key::Char('h')],
+            right: vec![Key::Right, Key::Char('l')],
+            partner_up: vec![Key::Char('w')],
+            partner_down: vec![Key::Char('s')],
+            partner_left: vec![Key::Char('a')],
+            partner_right: vec![Key::Char('d')],
+            run: vec![Key::Char('.')],
+            grapple: vec![Key::Char('g')],
+            use_phase: vec![Key::Char('1')],
+            use_undo: vec![Key::Char('2')],
+            restart: vec![Key::Char('r')],
+            undo: vec![Key::Char('z')],
+            redo: vec![Key::Char('y')],
+            pause: vec![Key::Char('p')],
+            quit: vec![Key::Char('q')],
+            share_code: vec![Key::Char('c')],
+            export_solution: vec![Key::Char('e')],
+            export_board: vec![Key::Char('b')],
+            editor: vec![Key::Char('E')],
+            hint: vec![Key::Char('H')],
+            mute: vec![Key::Char('m')],
+            help: vec![Key::Char('?'), Key::Function(1)],
+            describe: vec![Key::Char('x')],
+            practice: vec![Key::Char('P')],
+        }
+    }
+}
+
+impl Keys {
+    // Every bindable action and its name in `[keys]`, in the order the
+    // controls screen lists them.
+    pub const ACTIONS: [(GameAction, &'static str); 26] = [
+        (GameAction::MoveUp, "up"),
+        (GameAction::MoveDown, "down"),
+        (GameAction::MoveLeft, "left"),
+        (GameAction::MoveRight, "right"),
+        (GameAction::PartnerUp, "partner_up"),
+        (GameAction::PartnerDown, "partner_down"),
+        (GameAction::PartnerLeft, "partner_left"),
+        (GameAction::PartnerRight, "partner_right"),
+        (GameAction::Run, "run"),
+        (GameAction::Grapple, "grapple"),
+        (GameAction::UsePhase, "use_phase"),
+        (GameAction::UseUndo, "use_undo"),
+        (GameAction::Restart, "restart"),
+        (GameAction::Undo, "undo"),
+        (GameAction::Redo, "redo"),
+        (GameAction::Pause, "pause"),
+        (GameAction::Menu, "quit"),
+        (GameAction::ShareCode, "share_code"),
+        (GameAction::ExportSolution, "export_solution"),
+        (GameAction::ExportBoard, "export_board"),
+        (GameAction::Editor, "editor"),
+        (GameAction::Hint, "hint"),
+        (GameAction::Mute, "mute"),
+        (GameAction::Help, "help"),
+        (GameAction::Describe, "describe"),
+        (GameAction::Practice, "practice"),
+    ];
+
+    pub fn bound(&self, action: GameAction) -> &[Key] {
+        match action {
+            GameAction::MoveUp => &self.up,
+            GameAction::MoveDown => &self.down,
+            GameAction::MoveLeft => &self.left,
+            GameAction::MoveRight => &self.right,
+            GameAction::PartnerUp => &self.partner_up,
+            GameAction::PartnerDown => &self.partner_down,
+            GameAction::PartnerLeft => &self.partner_left,
+            GameAction::PartnerRight => &self.partner_right,
+            GameAction::Run => &self.run,
+            GameAction::Grapple => &self.grapple,
+            GameAction::UsePhase => &self.use_phase,
+            GameAction::UseUndo => &self.use_undo,
+            GameAction::Restart => &self.restart,
+            GameAction::Undo => &self.undo,
+            GameAction::Redo => &self.redo,
+            GameAction::Pause => &self.pause,
+            GameAction::Menu => &self.quit,
+            GameAction::ShareCode => &self.share_code,
+            GameAction::ExportSolution => &self.export_solution,
+            GameAction::ExportBoard => &self.export_board,
+            GameAction::Editor => &self.editor,
+            GameAction::Hint => &self.hint,
+            GameAction::Mute => &self.mute,
+            GameAction::Help => &self.help,
+            GameAction::Describe => &self.describe,
+            GameAction::Practice => &self.practice,
+        }
+    }
+    fn bound_mut(&mut self, action: GameAction) -> &mut Vec<Key> {
+        match action {
+            GameAction::MoveUp => &mut self.up,
+            GameAction::MoveDown => &mut self.down,
+            GameAction::MoveLeft => &mut self.left,
+            GameAction::MoveRight => &mut self.right,
+            GameAction::PartnerUp => &mut self.partner_up,
+            GameAction::PartnerDown => &mut self.partner_down,
+            GameAction::PartnerLeft => &mut self.partner_left,
+            GameAction::PartnerRight => &mut self.partner_right,
+            GameAction::Run => &mut self.run,
+            GameAction::Grapple => &mut self.grapple,
+            GameAction::UsePhase => &mut self.use_phase,
+            GameAction::UseUndo => &mut self.use_undo,
+            GameAction::Restart => &mut self.restart,
+            GameAction::Undo => &mut self.undo,
+            GameAction::Redo => &mut self.redo,
+            GameAction::Pause => &mut self.pause,
+            GameAction::Menu => &mut self.quit,
+            GameAction::ShareCode => &mut self.share_code,
+            GameAction::ExportSolution => &mut self.export_solution,
+            GameAction::ExportBoard => &mut self.export_board,
+            GameAction::Editor => &mut self.editor,
+            GameAction::Hint => &mut self.hint,
+            GameAction::Mute => &mut self.mute,
+            GameAction::Help => &mut self.help,
+            GameAction::Describe => &mut self.describe,
+            GameAction::Practice => &mut self.practice,
+        }
+    }
+    // The keys of `action` for showing to the player, such as `up, w, k`.
+    pub fn describe(&self, action: GameAction) -> String {
+        let keys: Vec<String> = self.bound(action).iter().map(Key::to_string).collect();
+        keys.join(", ")
+    }
+    // The action bound to `key`. Escape pauses unless it is bound to
+    // something else.
+    pub fn action(&self, key: Key) -> Option<GameAction> {
+        Self::ACTIONS
+            .iter()
+            .map(|&(action, _)| action)
+            .find(|&action| self.bound(action).contains(&key))
+            .or((key == Key::Escape).then_some(GameAction::Pause))
+    }
+    // Makes `key` the main key of `action`, in place of its first one. A key
+    // another action already uses is refused, and that action returned.
+    pub fn bind(&mut self, action: GameAction, key: Key) -> Result<(), GameAction> {
+        if let Some(other) = self.action(key).filter(|&other| other != action) {
+            if self.bound(other).contains(&key) {
+                return Err(other);
+            }
+        }
+        let keys = self.bound_mut(action);
+        keys.retain(|&k| k != key);
+        match keys.first_mut() {
+            Some(first) => *first = key,
+            None => keys.push(key),
+        }
+        Ok(())
+    }
+    // A key bound to two actions, if there is one.
+    pub fn conflict(&self) -> Option<(Key, GameAction, GameAction)> {
+        Self::ACTIONS
+            .iter()
+            .enumerate()
+            .find_map(|(i, &(action, _))| {
+                self.bound(action).iter().find_map(|&key| {
+                    Self::ACTIONS[i + 1..]
+                        .iter()
+                        .find(|&&(other, _)| self.bound(other).contains(&key))
+                        .map(|&(other, _)| (key, action, other))
+                })
+            })
+    }
+    pub fn name(action: GameAction) -> &'static str {
+        Self::ACTIONS
+            .iter()
+            .find(|&&(a, _)| a == action)
+            .map_or("", |&(_, name)| name)
+    }
+    // The bindings as a `[keys]` table.
+    fn to_table(&self) -> toml::Table {
+        Self::ACTIONS
+            .iter()
+            .map(|&(action, name)| {
+                let keys = self.bound(action).iter();
+                let keys = keys.map(|key| toml::Value::String(key.to_string()));
+                (name.to_string(), toml::Value::Array(keys.collect()))
+            })
+            .collect()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Glyphs {
+    pub player: char,
+    pub partner: char,
+    pub ghost: char,
+    pub wall: char,
+    pub push_box: char,
+    pub slippery_box: char,
+    pub button: char,
+    pub door: char,
+    pub win_pad: char,
+    pub plate: char,
+    pub hold_door: char,
+    pub step_pad: char,
+    pub phase_box: char,
+    pub phase_pickup: char,
+    pub undo_pickup: char,
+    pub gem: char,
+    // Warps are secret, so drawn as bare floor unless given a glyph here.
+    pub warp: char,
+}
+
+impl Default for Glyphs {
+    fn default() -> Self {
+        Self {
+            player: PLAYER_GLYPH,
+            partner: 'Y',
+            ghost: PLAYER_GLYPH.to_ascii_lowercase(),
+            wall: TileType::Wall1.glyph(),
+            push_box: TileType::PushBox.glyph(),
+            slippery_box: TileType::SlipperyBox.glyph(),
+            button: TileType::Button(0).glyph(),
+            door: TileType::Door(None, false).glyph(),
+            win_pad: TileType::WinPad.glyph(),
+            plate: TileType::Plate.glyph(),
+            hold_door: TileType::HoldDoor(false).glyph(),
+            step_pad: TileType::StepPad(0, 0, 1).glyph(),
+            phase_box: TileType::PhaseBox.glyph(),
+            phase_pickup: TileType::Pickup(Item::Phase).glyph(),
+            undo_pickup: TileType::Pickup(Item::Undo).glyph(),
+            gem: TileType::Gem.glyph(),
+            warp: TileType::Warp(0).glyph(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SoundConfig {
+    #[serde(deserialize_with = "parsed")]
+    pub output: SoundOutput,
+    pub volume: SoundVolumes,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self {
+            output: SoundOutput::Bell,
+            volume: SoundVolumes::default(),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy)]
+#[serde(default, deny_unknown_fields)]
+pub struct SoundVolumes {
+    pub step: u8,
+    pub bump: u8,
+    pub push: u8,
+    pub button: u8,
+    pub door: u8,
+    pub win: u8,
+    pub music: u8,
+}
+
+impl Default for SoundVolumes {
+    fn default() -> Self {
+        Self {
+            step: 0,
+            bump: 0,
+            push: 0,
+            button: 50,
+            door: 50,
+            win: 80,
+            music: 40,
+        }
+    }
+}
+
+impl Glyphs {
+    pub fn tile(&self, tile_type: TileType) -> char {
+        match tile_type {
+            TileType::Wall1 => self.wall,
+            TileType::PushBox => self.push_box,
+            TileType::SlipperyBox => self.slippery_box,
+            TileType::Button(_) => self.button,
+            TileType::Door(..) => self.door,
+            TileType::WinPad => self.win_pad,
+            TileType::Plate => self.plate,
+            TileType::HoldDoor(_) => self.hold_door,
+            TileType::StepPad(..) => self.step_pad,
+            TileType::PhaseBox => self.phase_box,
+            TileType::Pickup(Item::Phase) => self.phase_pickup,
+            TileType::Pickup(Item::Undo) => self.undo_pickup,
+            TileType::Gem => self.gem,
+            TileType::Warp(_) => self.warp,
+            TileType::Empty | TileType::Custom(_) => tile_type.glyph(),
+        }
+    }
+}
+
+fn parsed<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    let text = String::deserialize(deserializer)?;
+    text.parse().map_err(serde::de::Error::custom)
+}
+
+// One key or a list of them.
+fn parsed_list<'de, D, T>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+    let texts = match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(text) => vec![text],
+        OneOrMany::Many(texts) => texts,
+    };
+    texts
+        .iter()
+        .map(|text| text.parse().map_err(serde::de::Error::custom))
+        .collect()
+}
+
+fn parsed_option<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: FromStr<Err = String>,
+{
+    parsed(deserializer).map(Some)
+}
+
+pub fn config_path() -> PathBuf {
+    config_dir().join(CONFIG_FILE)
+}
+
+// Reads the config file at `path`, or else the one of `profile` (a missing
+// file is the same as an empty one), and applies the `KEY=VALUE` overrides
+// on top.
+pub fn load(
+    path: Option<PathBuf>,
+    profile: &Profile,
+    overrides: &[String],
+) -> Result<Config, ConfigError> {
+    let explicit = path.is_some();
+    let path = path.unwrap_or_else(|| profile.config_path());
+    let text = match fs::read_to_string(&path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !explicit => String::new(),
+        Err(e) => return Err(ConfigError::Io(path, e)),
+    };
+    let mut table: toml::Table = text
+        .parse()
+        .map_err(|e: toml::de::Error| ConfigError::Parse(format!("{}: {}", path.display(), e)))?;
+    for item in overrides {
+        let (key, value) = item
+            .split_once('=')
+            .ok_or_else(|| ConfigError::Parse(format!("`{}` is not KEY=VALUE", item)))?;
+        let single: toml::Table = format!("{} = {}", key.trim(), value.trim())
+            .parse()
+            .map_err(|e: toml::de::Error| ConfigError::Parse(format!("{}: {}", item, e)))?;
+        merge(&mut table, single);
+    }
+    let mut config: Config = table
+        .try_into()
+        .map_err(|e: toml::de::Error| ConfigError::Parse(e.to_string()))?;
+    if let Some((key, first, second)) = config.keys.conflict() {
+        return Err(ConfigError::Parse(format!(
+            "`{}` is bound to both {} and {}",
+            key,
+            Keys::name(first),
+            Keys::name(second)
+        )));
+    }
+    config.strings =
+        Strings::load(&config.language).map_err(|e| ConfigError::Parse(e.to_string()))?;
+    Ok(config)
+}
+
+// Writes `keys` into the `[keys]` table of the config file at `path`,
+// keeping its other settings.
+pub fn save_keys(path: &Path, keys: &Keys) -> Result<(), ConfigError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(ConfigError::Io(path.to_path_buf(), e)),
+    };
+    let mut table: toml::Table = text
+        .parse()
+        .map_err(|e: toml::de::Error| ConfigError::Parse(format!("{}: {}", path.display(), e)))?;
+    table.insert("keys".to_string(), toml::Value::Table(keys.to_table()));
+    let text = toml::to_string(&table).map_err(|e| ConfigError::Parse(e.to_string()))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| ConfigError::Io(dir.to_path_buf(), e))?;
+    }
+    fs::write(path, text).map_err(|e| ConfigError::Io(path.to_path_buf(), e))
+}
+
+// Copies `from` into `into`, replacing values but merging tables, so that
+// overriding `keys.up` keeps the rest of `[keys]`.
+fn merge(into: &mut toml::Table, from: toml::Table) {
+    for (key, value) in from {
+        match (into.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(table)) => {
+                merge(existing, table)
+            }
+            (_, value) => {
+                into.insert(key, value);
+            }
+        }
+    }
+}
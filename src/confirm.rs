@@ -0,0 +1,113 @@
+// Guards restart and quit so one stray key can't throw away a level in
+// progress. With `confirm = "prompt"` (the default) the first press asks on
+// the status line and only `y` or Enter goes through; with `"hold"` the key
+// has to be held down for a moment instead. A level nothing has been done in
+// yet is never asked about.
+use crate::renderer::Key;
+use box_pushing_core::action::GameAction;
+use std::time::{Duration, Instant};
+
+// How long a key has to be held to confirm.
+const HOLD_TIME: Duration = Duration::from_millis(1000);
+// Presses further apart than this start the hold over. Terminals wait about
+// half a second before they start repeating a held key.
+const HOLD_GAP: Duration = Duration::from_millis(600);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ConfirmMode {
+    Off,
+    Prompt,
+    Hold,
+}
+
+impl std::str::FromStr for ConfirmMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "prompt" => Ok(Self::Prompt),
+            "hold" => Ok(Self::Hold),
+            _ => Err(format!(
+                "unknown confirm mode `{}` (off, prompt or hold)",
+                s
+            )),
+        }
+    }
+}
+
+pub enum Confirmed {
+    // Go ahead with the action.
+    Act(GameAction),
+    // The action is waiting on the player; ask again or keep asking.
+    Asking(GameAction),
+    // A pending action was called off by some other key.
+    Cancelled,
+    // Nothing to confirm; handle the key as usual.
+    Pass,
+}
+
+pub struct Confirmation {
+    mode: ConfirmMode,
+    // The action waiting, when it was first asked for and when last pressed.
+    pending: Option<(GameAction, Instant, Instant)>,
+}
+
+impl Confirmation {
+    pub fn new(mode: ConfirmMode) -> Self {
+        Self {
+            mode,
+            pending: None,
+        }
+    }
+    pub fn needs_confirming(action: GameAction) -> bool {
+        matches!(action, GameAction::Restart | GameAction::Menu)
+    }
+    // Decides what a press of `key` (bound to `action`, if anything) does.
+    // `at_risk` is whether the level has progress that would be lost.
+    pub fn filter(
+        &mut self,
+        key: Key,
+        action: Option<GameAction>,
+        at_risk: bool,
+        now: Instant,
+    ) -> Confirmed {
+        match (self.mode, self.pending.take()) {
+            (ConfirmMode::Prompt, Some((pending, ..))) => {
+                return match key {
+                    Key::Char('y' | 'Y') | Key::Enter => Confirmed::Act(pending),
+                    _ => Confirmed::Cancelled,
+                };
+            }
+            (ConfirmMode::Hold, Some((pending, first, last)))
+                if action == Some(pending) && now - last < HOLD_GAP =>
+            {
+                if now - first >= HOLD_TIME {
+                    return Confirmed::Act(pending);
+                }
+                self.pending = Some((pending, first, now));
+                return Confirmed::Asking(pending);
+            }
+            _ => (),
+        }
+        match action {
+            Some(action) if Self::needs_confirming(action) => {
+                if self.mode == ConfirmMode::Off || !at_risk {
+                    return Confirmed::Act(action);
+                }
+                self.pending = Some((action, now, now));
+                Confirmed::Asking(action)
+            }
+            _ => Confirmed::Pass,
+        }
+    }
+    pub fn is_pending(&self) -> bool {
+        self.pending.is_some()
+    }
+    pub fn clear(&mut self) {
+        self.pending = None;
+    }
+    pub fn mode(&self) -> ConfirmMode {
+        self.mode
+    }
+}
@@ -0,0 +1,117 @@
+// The controls screen: every action with the keys bound to it. Enter on an
+// action waits for a key and makes it that action's main key; a key another
+// action already uses is refused. Escape leaves, writing the bindings to the
+// config file if any changed.
+use crate::{
+    config::{self, Keys},
+    i18n::Strings,
+    renderer::{Key, Renderer},
+};
+use box_pushing_core::action::GameAction;
+use std::path::Path;
+
+const LIST_TOP: i32 = 3;
+const KEYS_COLUMN: i32 = 24;
+
+pub fn action_label(strings: &Strings, action: GameAction) -> &str {
+    strings.get(match action {
+        GameAction::MoveUp => "action.move_up",
+        GameAction::MoveDown => "action.move_down",
+        GameAction::MoveLeft => "action.move_left",
+        GameAction::MoveRight => "action.move_right",
+        GameAction::PartnerUp => "action.partner_up",
+        GameAction::PartnerDown => "action.partner_down",
+        GameAction::PartnerLeft => "action.partner_left",
+        GameAction::PartnerRight => "action.partner_right",
+        GameAction::Run => "action.run",
+        GameAction::Grapple => "action.grapple",
+        GameAction::UsePhase => "action.use_phase",
+        GameAction::UseUndo => "action.use_undo",
+        GameAction::Restart => "action.restart",
+        GameAction::Undo => "action.undo",
+        GameAction::Redo => "action.redo",
+        GameAction::Pause => "action.pause",
+        GameAction::Menu => "action.menu",
+        GameAction::ShareCode => "action.share_code",
+        GameAction::ExportSolution => "action.export_solution",
+        GameAction::ExportBoard => "action.export_board",
+        GameAction::Editor => "action.editor",
+        GameAction::Hint => "action.hint",
+        GameAction::Mute => "action.mute",
+        GameAction::Help => "action.help",
+        GameAction::Describe => "action.describe",
+        GameAction::Practice => "action.practice",
+    })
+}
+
+fn draw(window: &dyn Renderer, strings: &Strings, keys: &Keys, selected: usize, message: &str) {
+    window.clear();
+    window.print(1, 2, strings.get("controls.title"));
+    for (i, &(action, _)) in Keys::ACTIONS.iter().enumerate() {
+        let row = LIST_TOP + i as i32;
+        let marker = if i == selected { '>' } else { ' ' };
+        window.print(
+            row,
+            2,
+            &format!("{} {}", marker, action_label(strings, action)),
+        );
+        window.print(row, KEYS_COLUMN, &keys.describe(action));
+    }
+    let bottom = LIST_TOP + Keys::ACTIONS.len() as i32 + 1;
+    window.print(bottom, 2, message);
+    window.print(bottom + 1, 2, strings.get("controls.help"));
+    window.refresh();
+}
+
+// Runs the screen until it is left. Returns a message for the menu if the
+// bindings were saved or could not be.
+pub fn run(
+    window: &dyn Renderer,
+    strings: &Strings,
+    keys: &mut Keys,
+    path: &Path,
+) -> Option<String> {
+    let mut selected = 0;
+    let mut message = String::new();
+    let mut changed = false;
+    loop {
+        draw(window, strings, keys, selected, &message);
+        match window.read_key() {
+            Some(Key::Up) => selected = selected.checked_sub(1).unwrap_or(Keys::ACTIONS.len() - 1),
+            Some(Key::Down) => selected = (selected + 1) % Keys::ACTIONS.len(),
+            Some(Key::Enter) => {
+                let action = Keys::ACTIONS[selected].0;
+                let label = action_label(strings, action);
+                draw(
+                    window,
+                    strings,
+                    keys,
+                    selected,
+                    &strings.fill("controls.press_key", &[("action", &label)]),
+                );
+                message = match window.read_key() {
+                    Some(Key::Escape | Key::Click(..)) | None => String::new(),
+                    Some(key) => match keys.bind(action, key) {
+                        Ok(()) => {
+                            changed = true;
+                            String::new()
+                        }
+                        Err(other) => strings.fill(
+                            "controls.conflict",
+                            &[("key", &key), ("action", &action_label(strings, other))],
+                        ),
+                    },
+                };
+            }
+            Some(Key::Escape) | None => break,
+            _ => (),
+        }
+    }
+    if !changed {
+        return None;
+    }
+    Some(match config::save_keys(path, keys) {
+        Ok(()) => strings.fill("controls.saved", &[("path", &path.display())]),
+        Err(e) => e.to_string(),
+    })
+}
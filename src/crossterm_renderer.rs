@@ -0,0 +1,158 @@
+use crate::renderer::{Key, Renderer, Style};
+use crossterm::{
+    cursor,
+    event::{
+        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+        MouseButton, MouseEvent, MouseEventKind,
+    },
+    queue,
+    style::{Attribute, Color, Print, SetAttribute, SetForegroundColor},
+    terminal::{self, ClearType},
+};
+use std::{
+    cell::Cell,
+    io::{self, Write},
+    time::{Duration, Instant},
+};
+
+pub struct CrosstermRenderer {
+    timeout_ms: Cell<i32>,
+    colors: bool,
+}
+
+impl CrosstermRenderer {
+    pub fn new(colors: bool) -> io::Result<Self> {
+        terminal::enable_raw_mode()?;
+        let mut out = io::stdout();
+        queue!(
+            out,
+            terminal::EnterAlternateScreen,
+            EnableMouseCapture,
+            cursor::Hide
+        )?;
+        out.flush()?;
+        Ok(Self {
+            timeout_ms: Cell::new(-1),
+            colors,
+        })
+    }
+}
+
+impl Drop for CrosstermRenderer {
+    fn drop(&mut self) {
+        let mut out = io::stdout();
+        let _ = queue!(
+            out,
+            SetAttribute(Attribute::Reset),
+            cursor::Show,
+            DisableMouseCapture,
+            terminal::LeaveAlternateScreen
+        );
+        let _ = out.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+// Drawing errors are dropped, the same as curses return codes.
+impl Renderer for CrosstermRenderer {
+    fn clear(&self) {
+        let _ = queue!(io::stdout(), terminal::Clear(ClearType::All));
+    }
+    fn clear_line(&self, y: i32) {
+        let _ = queue!(
+            io::stdout(),
+            cursor::MoveTo(0, y.max(0) as u16),
+            terminal::Clear(ClearType::UntilNewLine)
+        );
+    }
+    fn print(&self, y: i32, x: i32, text: &str) {
+        if y < 0 || x < 0 {
+            return;
+        }
+        let _ = queue!(
+            io::stdout(),
+            cursor::MoveTo(x as u16, y as u16),
+            Print(text)
+        );
+    }
+    fn put_char(&self, y: i32, x: i32, c: char) {
+        let mut buf = [0; 4];
+        self.print(y, x, c.encode_utf8(&mut buf));
+    }
+    fn set_style(&self, style: Style) {
+        let style = match style {
+            Style::Cursor => style,
+            _ if !self.colors => Style::Normal,
+            _ => style,
+        };
+        let (color, attribute) = match style {
+            Style::Normal => (Color::Reset, Attribute::Reset),
+            Style::Wall => (Color::White, Attribute::Reset),
+            Style::PushBox | Style::ClosedDoor => (Color::Yellow, Attribute::Bold),
+            Style::Button => (Color::Red, Attribute::Reset),
+            Style::OpenDoor => (Color::Yellow, Attribute::Dim),
+            Style::WinPad => (Color::Blue, Attribute::Reset),
+            Style::Cursor => (Color::Reset, Attribute::Reverse),
+            Style::Ghost => (Color::DarkGrey, Attribute::Dim),
+            Style::Stuck => (Color::Red, Attribute::Bold),
+        };
+        let _ = queue!(
+            io::stdout(),
+            SetAttribute(Attribute::Reset),
+            SetForegroundColor(color),
+            SetAttribute(attribute)
+        );
+    }
+    fn refresh(&self) {
+        let _ = io::stdout().flush();
+    }
+    fn height(&self) -> i32 {
+        terminal::size().map_or(24, |(_, rows)| rows as i32)
+    }
+    fn set_timeout(&self, ms: i32) {
+        self.timeout_ms.set(ms);
+    }
+    fn bell(&self) {
+        let mut out = io::stdout();
+        let _ = out.write_all(b"\x07");
+        let _ = out.flush();
+    }
+    fn read_key(&self) -> Option<Key> {
+        let timeout = self.timeout_ms.get();
+        let deadline = Instant::now() + Duration::from_millis(timeout.max(0) as u64);
+        loop {
+            if timeout >= 0 {
+                let left = deadline.saturating_duration_since(Instant::now());
+                if !event::poll(left).ok()? {
+                    return None;
+                }
+            }
+            let (code, kind) = match event::read().ok()? {
+                Event::Key(KeyEvent { code, kind, .. }) => (code, kind),
+                Event::Mouse(MouseEvent {
+                    kind: MouseEventKind::Down(MouseButton::Left),
+                    row,
+                    column,
+                    ..
+                }) => return Some(Key::Click(row as i32, column as i32)),
+                _ => continue,
+            };
+            if kind == KeyEventKind::Release {
+                continue;
+            }
+            let key = match code {
+                KeyCode::Up => Key::Up,
+                KeyCode::Down => Key::Down,
+                KeyCode::Left => Key::Left,
+                KeyCode::Right => Key::Right,
+                KeyCode::Enter => Key::Enter,
+                KeyCode::Backspace => Key::Backspace,
+                KeyCode::Esc => Key::Escape,
+                KeyCode::Char(c) => Key::Char(c),
+                KeyCode::F(n) => Key::Function(n),
+                _ => continue,
+            };
+            return Some(key);
+        }
+    }
+}
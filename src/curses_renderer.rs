@@ -0,0 +1,116 @@
+use crate::renderer::{Key, Renderer, Style};
+use pancurses::{curs_set, endwin, initscr, noecho, Input, Window};
+
+pub struct CursesRenderer {
+    window: Window,
+    colors: bool,
+}
+
+impl CursesRenderer {
+    pub fn new(colors: bool) -> Self {
+        let window = initscr();
+        let colors = colors && pancurses::has_colors();
+        if colors {
+            pancurses::start_color();
+            pancurses::init_pair(1, pancurses::COLOR_WHITE, pancurses::COLOR_BLACK);
+            pancurses::init_pair(2, pancurses::COLOR_RED, pancurses::COLOR_BLACK);
+            pancurses::init_pair(4, pancurses::COLOR_BLUE, pancurses::COLOR_BLACK);
+            pancurses::init_pair(5, pancurses::COLOR_YELLOW, pancurses::COLOR_BLACK);
+        }
+        window.keypad(true);
+        pancurses::mousemask(
+            pancurses::BUTTON1_PRESSED | pancurses::BUTTON1_CLICKED,
+            None,
+        );
+        noecho();
+        curs_set(0);
+        Self { window, colors }
+    }
+}
+
+impl Drop for CursesRenderer {
+    fn drop(&mut self) {
+        endwin();
+    }
+}
+
+impl Renderer for CursesRenderer {
+    fn clear(&self) {
+        self.window.clear();
+    }
+    fn clear_line(&self, y: i32) {
+        self.window.mv(y, 0);
+        self.window.clrtoeol();
+    }
+    fn print(&self, y: i32, x: i32, text: &str) {
+        self.window.mvprintw(y, x, text);
+    }
+    fn put_char(&self, y: i32, x: i32, c: char) {
+        self.window.mvaddch(y, x, c);
+    }
+    fn set_style(&self, style: Style) {
+        self.window.attrset(pancurses::A_NORMAL);
+        if style == Style::Cursor {
+            self.window.attrset(pancurses::A_REVERSE);
+            return;
+        }
+        if !self.colors {
+            return;
+        }
+        let (pair, attribute) = match style {
+            Style::Normal | Style::Cursor => return,
+            Style::Wall => (1, pancurses::A_NORMAL),
+            Style::PushBox | Style::ClosedDoor => (5, pancurses::A_BOLD),
+            Style::Button => (2, pancurses::A_NORMAL),
+            Style::OpenDoor => (5, pancurses::A_DIM),
+            Style::WinPad => (4, pancurses::A_NORMAL),
+            Style::Ghost => (1, pancurses::A_DIM),
+            Style::Stuck => (2, pancurses::A_BOLD),
+        };
+        self.window.attrset(pancurses::COLOR_PAIR(pair) | attribute);
+    }
+    fn refresh(&self) {
+        self.window.refresh();
+    }
+    fn height(&self) -> i32 {
+        self.window.get_max_y()
+    }
+    fn set_timeout(&self, ms: i32) {
+        self.window.timeout(ms);
+    }
+    fn bell(&self) {
+        pancurses::beep();
+    }
+    fn read_key(&self) -> Option<Key> {
+        loop {
+            let key = match self.window.getch()? {
+                Input::KeyUp => Key::Up,
+                Input::KeyDown => Key::Down,
+                Input::KeyLeft => Key::Left,
+                Input::KeyRight => Key::Right,
+                Input::KeyEnter | Input::Character('\n') => Key::Enter,
+                Input::KeyBackspace | Input::Character('\u{7f}') => Key::Backspace,
+                Input::Character('\u{1b}') => Key::Escape,
+                Input::Character(c) => Key::Char(c),
+                Input::KeyF1 => Key::Function(1),
+                Input::KeyF2 => Key::Function(2),
+                Input::KeyF3 => Key::Function(3),
+                Input::KeyF4 => Key::Function(4),
+                Input::KeyF5 => Key::Function(5),
+                Input::KeyF6 => Key::Function(6),
+                Input::KeyF7 => Key::Function(7),
+                Input::KeyF8 => Key::Function(8),
+                Input::KeyF9 => Key::Function(9),
+                Input::KeyF10 => Key::Function(10),
+                Input::KeyF11 => Key::Function(11),
+                Input::KeyF12 => Key::Function(12),
+                Input::KeyMouse => match pancurses::getmouse() {
+                    Ok(event) => Key::Click(event.y, event.x),
+                    Err(_) => continue,
+                },
+                _ => continue,
+            };
+            return Some(key);
+        }
+    }
+}
@@ -0,0 +1,117 @@
+// The `:` command line for trying things out while building levels or
+// chasing a bug, available in play when started with `--debug`:
+//
+//   level N         load level N
+//   teleport Y X    put the player at row Y, column X
+//   give key N      open the doors of id N, as if their buttons were held
+//   noclip          walk through walls and boxes; again to stop
+//
+// What is typed shows on the status line, and the result replaces it.
+use crate::renderer::Key;
+use box_pushing_core::{
+    game::{GameContext, LevelChange, LevelId},
+    map::Id,
+    pos::Pos,
+};
+use std::str::FromStr;
+
+pub enum Command {
+    Level(u32),
+    Teleport(Pos),
+    GiveKey(Id),
+    Noclip,
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = s.split_whitespace().collect();
+        let number = |word: &str| {
+            word.parse::<i32>()
+                .map_err(|_| format!("`{}` is not a number", word))
+        };
+        match words[..] {
+            ["level", n] => Ok(Self::Level(number(n)?.max(0) as u32)),
+            ["teleport", y, x] => Ok(Self::Teleport(Pos::new(number(y)?, number(x)?))),
+            ["give", "key", id] => Ok(Self::GiveKey(
+                id.parse().map_err(|_| format!("bad id `{}`", id))?,
+            )),
+            ["noclip"] => Ok(Self::Noclip),
+            _ => Err(format!(
+                "unknown command `{}` (level, teleport, give key, noclip)",
+                s.trim()
+            )),
+        }
+    }
+}
+
+impl Command {
+    // Carries out the command, returning what to show on the status line.
+    pub fn run(self, game: &mut GameContext) -> String {
+        match self {
+            Self::Level(level) => match game.goto_level(LevelId::Index(level)) {
+                LevelChange::Loaded(loaded) => format!("loaded level {}", loaded),
+                LevelChange::CampaignFinished => "no levels".to_string(),
+            },
+            Self::Teleport(pos) => match game.teleport(pos) {
+                Ok(()) => format!("teleported to {}", pos),
+                Err(e) => e,
+            },
+            Self::GiveKey(id) => {
+                if game.map_data.force_open(id, &mut game.events) {
+                    format!("opened doors {}", id)
+                } else {
+                    format!("no doors with id {}", id)
+                }
+            }
+            Self::Noclip => {
+                game.noclip = !game.noclip;
+                format!("noclip {}", if game.noclip { "on" } else { "off" })
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Console {
+    // What has been typed, while the command line is open.
+    typed: Option<String>,
+}
+
+impl Console {
+    // Handles `key` if it opens the command line or goes to it, returning
+    // whether it did.
+    pub fn handle(&mut self, game: &mut GameContext, key: Key) -> bool {
+        let Some(typed) = self.typed.as_mut() else {
+            if key != Key::Char(':') {
+                return false;
+            }
+            self.typed = Some(String::new());
+            game.status = Some(":".to_string());
+            return true;
+        };
+        match key {
+            Key::Char(c) => typed.push(c),
+            Key::Backspace => {
+                typed.pop();
+            }
+            Key::Enter => {
+                let line = self.typed.take().unwrap_or_default();
+                game.status = Some(match line.parse::<Command>() {
+                    Ok(command) => command.run(game),
+                    Err(e) => e,
+                });
+                return true;
+            }
+            Key::Escape => {
+                self.typed = None;
+                game.status = None;
+                return true;
+            }
+            _ => (),
+        }
+        game.status = Some(format!(":{}", typed));
+        true
+    }
+}
@@ -0,0 +1,152 @@
+// Describe mode, for playing with a screen reader: with `describe = true`
+// the bottom line says in words what is next to the player after every move,
+// such as "wall north, box east on button 2, win pad 4 south". The describe
+// key says it again, and without describe mode puts it on the status line.
+use crate::{config::Config, i18n::Strings, renderer::Renderer};
+use box_pushing_core::{
+    describe::Surroundings,
+    game::GameContext,
+    map::{Item, TileType},
+    pos::Pos,
+    Direction,
+};
+
+fn direction_name(strings: &Strings, direction: Direction) -> &str {
+    strings.get(match direction {
+        Direction::Up => "describe.north",
+        Direction::Right => "describe.east",
+        Direction::Down => "describe.south",
+        Direction::Left => "describe.west",
+    })
+}
+
+// What a tile is called, or None for bare floor.
+fn tile_name(strings: &Strings, tile_type: TileType) -> Option<String> {
+    let id_text = |id: Option<u32>| id.map_or("?".to_string(), |id| id.to_string());
+    let key = match tile_type {
+        TileType::Empty | TileType::Warp(_) => return None,
+        TileType::Button(id) => {
+            return Some(strings.fill("describe.button", &[("id", &id)]));
+        }
+        TileType::StepPad(id, ..) => {
+            return Some(strings.fill("describe.step_pad", &[("id", &id)]));
+        }
+        TileType::Door(id, open) => {
+            let key = if open {
+                "describe.open_door"
+            } else {
+                "describe.closed_door"
+            };
+            return Some(strings.fill(key, &[("id", &id_text(id))]));
+        }
+        TileType::Custom(glyph) => {
+            return Some(strings.fill("describe.custom", &[("glyph", &glyph)]));
+        }
+        TileType::Wall1 => "describe.wall",
+        TileType::PushBox => "describe.box",
+        TileType::SlipperyBox => "describe.slippery_box",
+        TileType::PhaseBox => "describe.phase_box",
+        TileType::WinPad => "describe.win_pad",
+        TileType::Plate => "describe.plate",
+        TileType::HoldDoor(false) => "describe.hold_door",
+        TileType::HoldDoor(true) => "describe.open_hold_door",
+        TileType::Pickup(Item::Phase) => "item.phase",
+        TileType::Pickup(Item::Undo) => "item.undo",
+        TileType::Gem => "describe.gem",
+    };
+    Some(strings.get(key).to_string())
+}
+
+// The steps to the goal along one axis, if it is off that way at all.
+fn steps(strings: &Strings, count: i32, forward: Direction) -> Option<String> {
+    let direction = if count < 0 {
+        forward.opposite()
+    } else {
+        forward
+    };
+    (count != 0).then(|| {
+        strings.fill(
+            "describe.steps",
+            &[
+                ("count", &count.abs()),
+                ("direction", &direction_name(strings, direction)),
+            ],
+        )
+    })
+}
+
+pub fn describe(config: &Config, game: &GameContext) -> String {
+    let strings = &config.strings;
+    let player = game.player.pos();
+    let surroundings = Surroundings::new(&game.map_data, player);
+    let mut parts = Vec::new();
+    for (direction, cell) in surroundings.sides {
+        let floor = tile_name(strings, cell.floor_type());
+        let object = cell
+            .object
+            .and_then(|tile| tile_name(strings, tile.tile_type));
+        let thing = match (object, floor) {
+            (Some(object), Some(floor)) => {
+                strings.fill("describe.on", &[("thing", &object), ("floor", &floor)])
+            }
+            (Some(thing), None) | (None, Some(thing)) => thing,
+            (None, None) => continue,
+        };
+        parts.push(strings.fill(
+            "describe.side",
+            &[
+                ("thing", &thing),
+                ("direction", &direction_name(strings, direction)),
+            ],
+        ));
+    }
+    if let Some(partner) = &game.partner {
+        let beside = Direction::ALL
+            .into_iter()
+            .find(|&direction| player + direction == partner.pos());
+        if let Some(direction) = beside {
+            parts.push(strings.fill(
+                "describe.partner",
+                &[("direction", &direction_name(strings, direction))],
+            ));
+        }
+    }
+    match surroundings.goal {
+        Some(Pos { y: 0, x: 0 }) => parts.push(strings.get("describe.on_goal").to_string()),
+        Some(Pos { y, x }) => {
+            let steps: Vec<String> = [
+                steps(strings, y, Direction::Down),
+                steps(strings, x, Direction::Right),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            parts.push(strings.fill("describe.goal", &[("steps", &steps.join(" "))]));
+        }
+        None => (),
+    }
+    if parts.is_empty() {
+        return strings.get("describe.nothing").to_string();
+    }
+    parts.join(", ")
+}
+
+// The bottom line, where describe mode keeps the description.
+fn row(window: &dyn Renderer) -> i32 {
+    window.height() - 1
+}
+
+pub fn draw(window: &dyn Renderer, config: &Config, game: &GameContext) {
+    window.print(row(window), 0, &describe(config, game));
+}
+
+// Says the description again. A screen reader reads out what changes, so in
+// describe mode the line is blanked first and the next redraw puts it back.
+pub fn announce(window: &dyn Renderer, config: &Config, game: &mut GameContext) {
+    if config.describe {
+        window.clear_line(row(window));
+        window.refresh();
+    } else {
+        game.status = Some(describe(config, game));
+    }
+}
@@ -0,0 +1,233 @@
+// Drawing for the game state kept in box_pushing_core.
+use crate::{
+    config::{Config, Glyphs},
+    renderer::{Renderer, Style},
+    TOP_PADDING,
+};
+use box_pushing_core::{
+    actor::Actor,
+    deadlock::Deadlocks,
+    game::GameContext,
+    map::{MapData, Tile, TileType},
+    pos::Pos,
+};
+
+pub fn tile_style(tile_type: TileType) -> Style {
+    match tile_type {
+        TileType::Empty | TileType::Warp(_) => Style::Normal,
+        TileType::Wall1 | TileType::PhaseBox => Style::Wall,
+        TileType::PushBox | TileType::SlipperyBox => Style::PushBox,
+        TileType::Button(_) => Style::Button,
+        TileType::Door(_, false) => Style::ClosedDoor,
+        TileType::Door(_, true) => Style::OpenDoor,
+        TileType::WinPad => Style::WinPad,
+        TileType::Custom(_) => Style::Normal,
+        TileType::Pickup(_) | TileType::Gem => Style::WinPad,
+        TileType::Plate | TileType::StepPad(..) => Style::Button,
+        TileType::HoldDoor(false) => Style::ClosedDoor,
+        TileType::HoldDoor(true) => Style::OpenDoor,
+    }
+}
+
+pub fn draw_tile(window: &dyn Renderer, glyphs: &Glyphs, tile: &Tile) {
+    draw_tile_styled(window, glyphs, tile, tile_style(tile.tile_type));
+}
+
+fn draw_tile_styled(window: &dyn Renderer, glyphs: &Glyphs, tile: &Tile, style: Style) {
+    let Pos { y, x } = tile.pos();
+    window.set_style(style);
+    window.put_char(y + TOP_PADDING, x, glyphs.tile(tile.tile_type));
+    window.set_style(Style::Normal);
+}
+
+pub fn draw_map(window: &dyn Renderer, glyphs: &Glyphs, map: &MapData) {
+    for tile in map.tiles() {
+        // Phase boxes blink with the turns, faint while they can be walked
+        // through.
+        if tile.tile_type == TileType::PhaseBox && !map.phase_boxes_solid() {
+            draw_tile_styled(window, glyphs, &tile, Style::Ghost);
+        } else {
+            draw_tile(window, glyphs, &tile);
+        }
+    }
+}
+
+pub fn draw_actor(window: &dyn Renderer, glyph: char, actor: &Actor) {
+    window.put_char(actor.y + TOP_PADDING, actor.x, glyph);
+}
+
+pub fn draw_game(window: &dyn Renderer, config: &Config, game: &GameContext) {
+    let map = &game.map_data;
+    let strings = &config.strings;
+    draw_map(window, &config.glyphs, map);
+    window.set_style(Style::Stuck);
+    for Pos { y, x } in Deadlocks::new(map).stuck_boxes(map) {
+        window.put_char(y + TOP_PADDING, x, config.glyphs.push_box);
+    }
+    window.set_style(Style::Normal);
+    draw_actor(window, config.glyphs.player, &game.player);
+    if let Some(partner) = &game.partner {
+        draw_actor(window, config.glyphs.partner, partner);
+    }
+    let flavor = game.flavor(&strings.language).unwrap_or("");
+    window.print(
+        TOP_PADDING - 1,
+        0,
+        &strings.fill("hud.level", &[("level", &game.level), ("flavor", &flavor)]),
+    );
+    if let Some(status) = &game.status {
+        window.print(TOP_PADDING - 2, 0, status);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Snapshots of drawn levels, kept in tests/snapshots. Each holds the
+    // glyphs drawn and, under them, a letter for each cell's style (the
+    // codes `spectate` sends). Run with UPDATE_SNAPSHOTS=1 to write them
+    // afresh after a change meant to alter the drawing.
+    use super::draw_game;
+    use crate::{
+        config::Config,
+        renderer::{Key, Renderer, Style},
+        spectate::style_code,
+    };
+    use box_pushing_core::{game::GameContext, level_file::parse_levels, lurd, map};
+    use std::{
+        cell::{Cell, RefCell},
+        collections::BTreeMap,
+        env, fs,
+        path::PathBuf,
+    };
+
+    // A renderer that records the glyph and style of every cell drawn.
+    #[derive(Default)]
+    struct Canvas {
+        cells: RefCell<BTreeMap<(i32, i32), (char, Style)>>,
+        style: Cell<Option<Style>>,
+    }
+
+    impl Canvas {
+        fn text(&self) -> String {
+            let cells = self.cells.borrow();
+            // From the first row drawn on, since the top of the screen is left
+            // for the scores.
+            let top = cells.keys().map(|&(y, _)| y).min().unwrap_or(0);
+            let height = cells.keys().map(|&(y, _)| y + 1).max().unwrap_or(0);
+            let width = cells.keys().map(|&(_, x)| x + 1).max().unwrap_or(0);
+            let grid = |show: &dyn Fn(char, Style) -> char| -> String {
+                (top..height)
+                    .map(|y| {
+                        let row: String = (0..width)
+                            .map(|x| cells.get(&(y, x)).map_or(' ', |&(c, s)| show(c, s)))
+                            .collect();
+                        format!("{}\n", row.trim_end())
+                    })
+                    .collect()
+            };
+            let glyphs = grid(&|c, _| c);
+            let styles = grid(&|c, s| if c == ' ' { ' ' } else { style_code(s) });
+            format!("{}\n{}", glyphs, styles)
+        }
+    }
+
+    impl Renderer for Canvas {
+        fn clear(&self) {
+            self.cells.borrow_mut().clear();
+        }
+        fn clear_line(&self, y: i32) {
+            self.cells.borrow_mut().retain(|&(row, _), _| row != y);
+        }
+        fn print(&self, y: i32, x: i32, text: &str) {
+            for (i, c) in text.chars().enumerate() {
+                self.put_char(y, x + i as i32, c);
+            }
+        }
+        fn put_char(&self, y: i32, x: i32, c: char) {
+            let style = self.style.get().unwrap_or(Style::Normal);
+            self.cells.borrow_mut().insert((y, x), (c, style));
+        }
+        fn set_style(&self, style: Style) {
+            self.style.set(Some(style));
+        }
+        fn refresh(&self) {}
+        fn height(&self) -> i32 {
+            24
+        }
+        fn set_timeout(&self, _ms: i32) {}
+        fn bell(&self) {}
+        fn read_key(&self) -> Option<Key> {
+            None
+        }
+    }
+
+    fn play(game: &mut GameContext, moves: &str) {
+        for direction in lurd::parse(moves).unwrap() {
+            game.player_movement(direction);
+            game.update_all();
+        }
+    }
+
+    fn render(game: &GameContext) -> String {
+        let canvas = Canvas::default();
+        draw_game(&canvas, &Config::default(), game);
+        canvas.text()
+    }
+
+    fn assert_snapshot(name: &str, actual: &str) {
+        let path: PathBuf = [env!("CARGO_MANIFEST_DIR"), "tests", "snapshots"]
+            .iter()
+            .collect::<PathBuf>()
+            .join(format!("{}.txt", name));
+        if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, actual).unwrap();
+            return;
+        }
+        let expected = fs::read_to_string(&path).unwrap_or_else(|_| {
+            panic!(
+                "no snapshot {}; run with UPDATE_SNAPSHOTS=1 to write it",
+                path.display()
+            )
+        });
+        assert!(
+            expected == actual,
+            "{} changed; drawn now:\n{}",
+            path.display(),
+            actual
+        );
+    }
+
+    fn game(level: &str) -> GameContext {
+        GameContext::new(parse_levels(level).unwrap(), None)
+    }
+
+    #[test]
+    fn builtin_levels_at_the_start() {
+        for (level, map) in map::get_maps().into_iter().enumerate() {
+            let game = GameContext::new(vec![map], None);
+            assert_snapshot(&format!("builtin_{}", level), &render(&game));
+        }
+    }
+
+    #[test]
+    fn box_on_a_button_and_open_door() {
+        let mut game = GameContext::new(vec![map::get_maps().remove(2)], None);
+        play(&mut game, "rrrrrrdrUluRRRRRRRRRRRRRRdrdu");
+        assert_snapshot("box_on_button", &render(&game));
+    }
+
+    #[test]
+    fn stuck_box_in_a_corner() {
+        let mut game = game("BBBBBB\nB#   B\nBP@  B\nB    B\nBBBBBB\n");
+        play(&mut game, "RRurD");
+        assert_snapshot("stuck_box", &render(&game));
+    }
+
+    #[test]
+    fn co_op_plates_and_partner() {
+        let mut game = game("BBBBBBBBBB\nB=  P  |#B\nB        B\nBBBBBBBBBB\n");
+        game.add_partner();
+        assert_snapshot("co_op", &render(&game));
+    }
+}
@@ -0,0 +1,81 @@
+// The level editor screen. Arrows move the cursor, any level-file character
+// places that tile, space clears, R and L rotate clockwise and back, M
+// mirrors left to right and V top to bottom, Enter plays the edited level and
+// Esc throws the edit away.
+use crate::{
+    config::Glyphs,
+    draw::tile_style,
+    i18n::Strings,
+    renderer::{Key, Renderer, Style},
+    TOP_PADDING,
+};
+use box_pushing_core::{
+    editor::Editor,
+    game::{GameContext, GameState},
+    level_file,
+    pos::Pos,
+    transform::Transform,
+    Direction,
+};
+
+pub fn draw_editor(window: &dyn Renderer, glyphs: &Glyphs, strings: &Strings, editor: &Editor) {
+    for (y, row) in editor.rows.iter().enumerate() {
+        for (x, &c) in row.iter().enumerate() {
+            let (style, glyph) = cell_look(glyphs, c);
+            window.set_style(style);
+            window.put_char(y as i32 + TOP_PADDING, x as i32, glyph);
+        }
+    }
+    let Pos { y, x } = editor.cursor;
+    window.set_style(Style::Cursor);
+    window.put_char(
+        y + TOP_PADDING,
+        x,
+        cell_look(glyphs, editor.at(editor.cursor)).1,
+    );
+    window.set_style(Style::Normal);
+    window.print(TOP_PADDING - 1, 0, strings.get("editor.help"));
+}
+
+// How a grid character looks in play.
+fn cell_look(glyphs: &Glyphs, c: char) -> (Style, char) {
+    match level_file::tile_from_char(c) {
+        Some(tile_type) => (tile_style(tile_type), glyphs.tile(tile_type)),
+        None if c == 'P' => (Style::Normal, glyphs.player),
+        None => (Style::Normal, ' '),
+    }
+}
+
+pub fn handle_key(game: &mut GameContext, key: Key) {
+    let GameState::Editor(editor) = &mut game.state else {
+        return;
+    };
+    let transform = match key {
+        Key::Char('R') => Transform::RotateCw,
+        Key::Char('L') => Transform::RotateCcw,
+        Key::Char('M') => Transform::MirrorHorizontal,
+        Key::Char('V') => Transform::MirrorVertical,
+        Key::Up => return editor.move_cursor(Direction::Up),
+        Key::Down => return editor.move_cursor(Direction::Down),
+        Key::Left => return editor.move_cursor(Direction::Left),
+        Key::Right => return editor.move_cursor(Direction::Right),
+        Key::Char(' ' | '.') | Key::Backspace => return editor.place(' '),
+        Key::Char(c) if c == 'P' || level_file::tile_from_char(c).is_some() => {
+            return editor.place(c);
+        }
+        Key::Click(y, x) => {
+            editor.cursor = Pos::new(y - TOP_PADDING, x).max(Pos::ZERO);
+            return;
+        }
+        Key::Enter => return game.apply_edit(),
+        Key::Escape => {
+            game.status = None;
+            game.state = GameState::Playing;
+            return;
+        }
+        Key::Char(_) | Key::Function(_) => return,
+    };
+    if let Err(e) = editor.transform(transform) {
+        game.status = Some(e.to_string());
+    }
+}
@@ -0,0 +1,186 @@
+// Downloads a level pack as a tar archive and installs it under the user's
+// level directory. The archive holds a `manifest.txt` and its level files,
+// either at the top level or inside a single directory.
+use crate::pack::{Manifest, PackError, MANIFEST_FILE};
+use box_pushing_core::paths::levels_dir;
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    io::Read,
+    path::{Component, Path, PathBuf},
+};
+
+const MAX_PACK_BYTES: u64 = 8 * 1024 * 1024;
+
+#[derive(Debug)]
+pub enum FetchError {
+    Http(String),
+    Archive(String),
+    Invalid(PackError),
+    Io(std::io::Error),
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Http(e) => write!(f, "download failed: {}", e),
+            Self::Archive(e) => write!(f, "bad pack archive: {}", e),
+            Self::Invalid(e) => write!(f, "invalid pack: {}", e),
+            Self::Io(e) => write!(f, "could not install pack: {}", e),
+        }
+    }
+}
+
+impl From<std::io::Error> for FetchError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+// Returns the directory the pack was installed into.
+pub fn fetch_pack(url: &str) -> Result<PathBuf, FetchError> {
+    let bytes = ureq::get(url)
+        .call()
+        .map_err(|e| FetchError::Http(e.to_string()))?
+        .body_mut()
+        .with_config()
+        .limit(MAX_PACK_BYTES)
+        .read_to_vec()
+        .map_err(|e| FetchError::Http(e.to_string()))?;
+    let files = read_archive(&bytes)?;
+    install_pack(&files, &levels_dir())
+}
+
+// Reads every regular file in the archive into memory, keyed by file name.
+fn read_archive(bytes: &[u8]) -> Result<HashMap<String, Vec<u8>>, FetchError> {
+    let archive_err = |e: &dyn fmt::Display| FetchError::Archive(e.to_string());
+    let mut archive = tar::Archive::new(bytes);
+    let mut files = HashMap::new();
+    let mut top_dirs = Vec::new();
+    for entry in archive.entries().map_err(|e| archive_err(&e))? {
+        let mut entry = entry.map_err(|e| archive_err(&e))?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let path = entry.path().map_err(|e| archive_err(&e))?.into_owned();
+        let parts: Vec<String> = path
+            .components()
+            .map(|c| match c {
+                Component::Normal(p) => Ok(p.to_string_lossy().into_owned()),
+                _ => Err(archive_err(&format!("unsafe path {}", path.display()))),
+            })
+            .collect::<Result<_, _>>()?;
+        let (dir, name) = match parts.as_slice() {
+            [name] => (None, name.clone()),
+            [dir, name] => (Some(dir.clone()), name.clone()),
+            _ => return Err(archive_err(&format!("unexpected path {}", path.display()))),
+        };
+        if !top_dirs.contains(&dir) {
+            top_dirs.push(dir);
+        }
+        let mut contents = Vec::new();
+        entry
+            .read_to_end(&mut contents)
+            .map_err(|e| archive_err(&format!("{}: {}", name, e)))?;
+        files.insert(name, contents);
+    }
+    if top_dirs.len() > 1 {
+        return Err(archive_err(&"files are spread over several directories"));
+    }
+    Ok(files)
+}
+
+// Installs the pack in its own directory under `root`.
+fn install_pack(files: &HashMap<String, Vec<u8>>, root: &Path) -> Result<PathBuf, FetchError> {
+    // Everything but the music is text.
+    let text = |name: &str| {
+        files
+            .get(name)
+            .and_then(|contents| String::from_utf8(contents.clone()).ok())
+    };
+    let manifest_text =
+        text(MANIFEST_FILE).ok_or_else(|| FetchError::Archive(format!("no {}", MANIFEST_FILE)))?;
+    let manifest = Manifest::parse(&manifest_text).map_err(FetchError::Invalid)?;
+    manifest.validate(text).map_err(FetchError::Invalid)?;
+    if let Some(missing) = manifest.music.iter().find(|f| !files.contains_key(*f)) {
+        return Err(FetchError::Invalid(PackError::Level {
+            file: missing.clone(),
+            msg: "missing from pack".to_string(),
+        }));
+    }
+
+    let dir = root.join(dir_name(&manifest.name));
+    fs::create_dir_all(&dir)?;
+    fs::write(dir.join(MANIFEST_FILE), &manifest_text)?;
+    // The manifest only accepts plain file names, so these can't escape `dir`.
+    for world in &manifest.worlds {
+        fs::write(dir.join(&world.file), &files[&world.file])?;
+        if let Some(story) = &world.story {
+            fs::write(dir.join(story), &files[story])?;
+        }
+    }
+    for tile in &manifest.tiles {
+        fs::write(dir.join(&tile.script), &files[&tile.script])?;
+    }
+    for track in &manifest.music {
+        fs::write(dir.join(track), &files[track])?;
+    }
+    Ok(dir)
+}
+
+// Turns a pack name into something safe to use as a directory name.
+fn dir_name(pack_name: &str) -> String {
+    let name: String = pack_name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    match name.trim_matches('_') {
+        "" => "pack".to_string(),
+        trimmed => trimmed.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{install_pack, read_archive};
+    use crate::pack::MANIFEST_FILE;
+    use std::{env, fs};
+
+    // A pack archive of `files`, all inside one directory.
+    fn archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(Vec::new());
+        for (name, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, format!("pack/{}", name), contents.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn installs_the_stories_with_the_levels() {
+        let bytes = archive(&[
+            (
+                MANIFEST_FILE,
+                "name: Story Pack\nlevels: world1.txt\nstory: world1.story\n",
+            ),
+            ("world1.txt", "BBBBB\nBP.#B\nBBBBB\n"),
+            ("world1.story", "Once upon a time.\n"),
+        ]);
+        let root = env::temp_dir().join(format!("fetch-test-{}", std::process::id()));
+        let dir = install_pack(&read_archive(&bytes).unwrap(), &root).unwrap();
+        let story = fs::read_to_string(dir.join("world1.story"));
+        fs::remove_dir_all(&root).unwrap();
+        assert_eq!(story.unwrap(), "Once upon a time.\n");
+    }
+}
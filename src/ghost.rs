@@ -0,0 +1,91 @@
+// Timed runs of a level and the ghost that replays one. A run is every move
+// of a win with when it was made; the fastest run of each level is kept, and
+// with `ghost = true` in the config it is raced as a dim second player that
+// makes the same moves at the same times. Runs are saved as one
+// `MILLIS MOVE` line per move, MILLIS counted from the start of the level and
+// MOVE a LURD character.
+use box_pushing_core::{actor::Actor, events::EventQueue, lurd, map::MapData, pos::Pos};
+use std::time::Duration;
+
+#[derive(Clone, Default)]
+pub struct Run {
+    pub moves: Vec<(Duration, char)>,
+}
+
+impl Run {
+    // Pairs each LURD move with the time it was made.
+    pub fn new(moves: &str, times: &[Duration]) -> Self {
+        Self {
+            moves: times.iter().copied().zip(moves.chars()).collect(),
+        }
+    }
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut moves = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let bad = || format!("line {}: expected MILLIS MOVE, got `{}`", i + 1, line);
+            let (millis, step) = line.split_once(' ').ok_or_else(bad)?;
+            let millis = millis.parse().map_err(|_| bad())?;
+            let mut chars = step.trim().chars();
+            let (Some(step), None) = (chars.next(), chars.next()) else {
+                return Err(bad());
+            };
+            if lurd::move_direction(step).is_none() {
+                return Err(bad());
+            }
+            moves.push((Duration::from_millis(millis), step));
+        }
+        Ok(Self { moves })
+    }
+    pub fn to_text(&self) -> String {
+        self.moves
+            .iter()
+            .map(|(time, step)| format!("{} {}\n", time.as_millis(), step))
+            .collect()
+    }
+}
+
+// A run being replayed against the clock on its own copy of the level.
+pub struct Ghost {
+    map: MapData,
+    actor: Actor,
+    run: Run,
+    next: usize,
+}
+
+impl Ghost {
+    // `map` is the level as it is at the start.
+    pub fn new(map: MapData, run: Run) -> Self {
+        Self {
+            actor: Actor::player(map.player_spawn),
+            map,
+            run,
+            next: 0,
+        }
+    }
+    // Makes every move due by `elapsed`, returning whether the ghost moved.
+    pub fn advance(&mut self, elapsed: Duration) -> bool {
+        let mut events = EventQueue::new();
+        let start = self.next;
+        while let Some(&(time, step)) = self.run.moves.get(self.next) {
+            if time > elapsed {
+                break;
+            }
+            if let Some(direction) = lurd::move_direction(step) {
+                self.map.move_actor(&mut self.actor, direction, &mut events);
+                self.map.update_button_status(&self.actor, &mut events);
+            }
+            self.next += 1;
+        }
+        self.next != start
+    }
+    pub fn finished(&self) -> bool {
+        self.next >= self.run.moves.len()
+    }
+    pub fn pos(&self) -> Pos {
+        self.actor.pos()
+    }
+}
@@ -0,0 +1,144 @@
+// The help screen: the controls as they are bound now, what each tile looks
+// like in the configured glyphs and colours, and the rules. Everything is
+// read from the config as the screen opens, so it always matches the game.
+// Up and Down scroll; any other key goes back.
+use crate::{
+    config::{Config, Keys},
+    controls::action_label,
+    draw::tile_style,
+    renderer::{Key, Renderer, Style},
+};
+use box_pushing_core::{
+    action::GameAction,
+    map::{Item, TileType},
+};
+
+const LIST_TOP: i32 = 3;
+const KEYS_COLUMN: i32 = 26;
+// Where the meaning of a tile goes, after its glyph.
+const LEGEND_COLUMN: i32 = 6;
+
+// The tiles in the legend, with what they mean. Warps are secret, and custom
+// tiles belong to their packs.
+const TILES: [(TileType, &str); 15] = [
+    (TileType::Wall1, "help.tile.wall"),
+    (TileType::PushBox, "help.tile.box"),
+    (TileType::SlipperyBox, "help.tile.slippery_box"),
+    (TileType::Button(0), "help.tile.button"),
+    (TileType::Door(None, false), "help.tile.closed_door"),
+    (TileType::Door(None, true), "help.tile.open_door"),
+    (TileType::WinPad, "help.tile.win_pad"),
+    (TileType::StepPad(0, 0, 1), "help.tile.step_pad"),
+    (TileType::PhaseBox, "help.tile.phase_box"),
+    (TileType::Plate, "help.tile.plate"),
+    (TileType::HoldDoor(false), "help.tile.hold_door"),
+    (TileType::HoldDoor(true), "help.tile.open_hold_door"),
+    (TileType::Pickup(Item::Phase), "help.tile.phase_pickup"),
+    (TileType::Pickup(Item::Undo), "help.tile.undo_pickup"),
+    (TileType::Gem, "help.tile.gem"),
+];
+
+const RULES: [&str; 5] = [
+    "help.rule.win",
+    "help.rule.push",
+    "help.rule.buttons",
+    "help.rule.stuck",
+    "help.rule.undo",
+];
+
+// Text on the screen at a column, after a glyph drawn in its style if there
+// is one.
+struct Line {
+    glyph: Option<(char, Style)>,
+    column: i32,
+    text: String,
+}
+
+impl Line {
+    fn text(column: i32, text: &str) -> Self {
+        Self {
+            glyph: None,
+            column,
+            text: text.to_string(),
+        }
+    }
+    fn legend(glyph: char, style: Style, text: &str) -> Self {
+        Self {
+            glyph: Some((glyph, style)),
+            column: LEGEND_COLUMN,
+            text: text.to_string(),
+        }
+    }
+}
+
+// Every row of the screen, each one or more lines.
+fn rows(config: &Config) -> Vec<Vec<Line>> {
+    let strings = &config.strings;
+    let glyphs = &config.glyphs;
+    let mut rows = vec![vec![Line::text(2, strings.get("help.controls"))]];
+    for &(action, _) in &Keys::ACTIONS {
+        rows.push(vec![
+            Line::text(4, action_label(strings, action)),
+            Line::text(KEYS_COLUMN, &config.keys.describe(action)),
+        ]);
+    }
+    rows.push(Vec::new());
+    rows.push(vec![Line::text(2, strings.get("help.tiles"))]);
+    let player = strings.get("help.tile.player");
+    rows.push(vec![Line::legend(glyphs.player, Style::Normal, player)]);
+    for (tile_type, key) in TILES {
+        let (glyph, style) = (glyphs.tile(tile_type), tile_style(tile_type));
+        rows.push(vec![Line::legend(glyph, style, strings.get(key))]);
+    }
+    rows.push(Vec::new());
+    rows.push(vec![Line::text(2, strings.get("help.rules"))]);
+    for key in RULES {
+        let text = strings.fill(
+            key,
+            &[
+                ("win_pad", &glyphs.win_pad),
+                ("undo", &config.keys.describe(GameAction::Undo)),
+            ],
+        );
+        rows.push(vec![Line::text(4, &text)]);
+    }
+    rows
+}
+
+fn draw(window: &dyn Renderer, config: &Config, rows: &[Vec<Line>], top: usize, shown: usize) {
+    window.clear();
+    window.print(1, 2, config.strings.get("help.title"));
+    for (i, row) in rows.iter().skip(top).take(shown).enumerate() {
+        let y = LIST_TOP + i as i32;
+        for line in row {
+            if let Some((glyph, style)) = line.glyph {
+                window.set_style(style);
+                window.put_char(y, 4, glyph);
+                window.set_style(Style::Normal);
+            }
+            window.print(y, line.column, &line.text);
+        }
+    }
+    window.print(
+        LIST_TOP + shown as i32 + 1,
+        2,
+        config.strings.get("help.footer"),
+    );
+    window.refresh();
+}
+
+pub fn show(window: &dyn Renderer, config: &Config) {
+    let rows = rows(config);
+    // Room for the title above and the footer below.
+    let shown = ((window.height() - LIST_TOP - 2).max(1) as usize).min(rows.len());
+    let last_top = rows.len() - shown;
+    let mut top = 0;
+    loop {
+        draw(window, config, &rows, top, shown);
+        match window.read_key() {
+            Some(Key::Up) => top = top.saturating_sub(1),
+            Some(Key::Down) => top = (top + 1).min(last_top),
+            _ => return,
+        }
+    }
+}
@@ -0,0 +1,147 @@
+// Hints from the solver. The hint key solves the level from where the player
+// stands and marks the box to push next with an arrow the way it should go,
+// until the player moves or a few seconds pass. Each level allows
+// `hints_per_level` of them (from the config), counted until the level is
+// left, so restarting doesn't give them back. Assist mode has no limit.
+//
+// From the pause menu the same key plays the whole solution instead, from
+// where the player stands, and then offers to restart the level so they can
+// try it themselves. That doesn't use up hints.
+use crate::{
+    config::Config,
+    menu::Menu,
+    playback::run_playback,
+    renderer::{Renderer, Style},
+    TOP_PADDING,
+};
+use box_pushing_core::{
+    action::GameAction,
+    deadlock::Deadlocks,
+    game::{GameContext, GameState},
+    pos::Pos,
+    solver::{self, Hint, SolveResult},
+    Direction,
+};
+use std::time::{Duration, Instant};
+
+// How long a hint stays marked if the player doesn't move.
+const SHOWN_FOR: Duration = Duration::from_secs(3);
+
+#[derive(Default)]
+pub struct Hints {
+    // The box to push, which way, and when the hint was given.
+    shown: Option<(Pos, Direction, Instant)>,
+}
+
+impl Hints {
+    // Solves the level for a hint, returning what to say on the status line.
+    pub fn ask(&mut self, config: &Config, game: &mut GameContext) -> String {
+        let strings = &config.strings;
+        if game.partner.is_some() {
+            return strings.get("hint.coop").to_string();
+        }
+        let allowed = config.hints_per_level;
+        if game.hints_left(allowed) == Some(0) {
+            return strings.fill("hint.used_up", &[("hints", &allowed)]);
+        }
+        // A lost level needs no search, and the stuck boxes say why.
+        let deadlocks = Deadlocks::new(&game.map_data);
+        if deadlocks.is_map_lost(&game.map_data, game.player.pos()) {
+            return match deadlocks.stuck_boxes(&game.map_data).is_empty() {
+                true => strings.get("hint.unsolvable").to_string(),
+                false => strings.get("hint.stuck").to_string(),
+            };
+        }
+        let hint = solver::hint(
+            &game.map_data,
+            game.player.pos(),
+            solver::DEFAULT_MAX_STATES,
+        );
+        let message = match hint {
+            Hint::Push { at, direction } => {
+                game.count_hint();
+                self.shown = Some((at, direction, Instant::now()));
+                match game.hints_left(allowed) {
+                    Some(left) => strings.fill("hint.push", &[("left", &left)]),
+                    None => strings.get("hint.push_assist").to_string(),
+                }
+            }
+            Hint::WalkToWin => strings.get("hint.walk").to_string(),
+            Hint::Unsolvable => strings.get("hint.unsolvable").to_string(),
+            Hint::GaveUp => strings.get("hint.gave_up").to_string(),
+        };
+        log::debug!("hint with {:?} left: {}", game.hints_left(allowed), message);
+        message
+    }
+    pub fn is_shown(&self) -> bool {
+        self.shown.is_some()
+    }
+    pub fn hide(&mut self) {
+        self.shown = None;
+    }
+    // Hides a hint that has been up long enough, returning whether it did.
+    pub fn expire(&mut self) -> bool {
+        let expired = self
+            .shown
+            .is_some_and(|(.., since)| since.elapsed() >= SHOWN_FOR);
+        if expired {
+            self.shown = None;
+        }
+        expired
+    }
+    // Marks the box to push, over whatever was drawn there.
+    pub fn draw(&self, window: &dyn Renderer) {
+        let Some((Pos { y, x }, direction, _)) = self.shown else {
+            return;
+        };
+        let arrow = match direction {
+            Direction::Up => '^',
+            Direction::Down => 'v',
+            Direction::Left => '<',
+            Direction::Right => '>',
+        };
+        window.set_style(Style::Cursor);
+        window.put_char(y + TOP_PADDING, x, arrow);
+        window.set_style(Style::Normal);
+    }
+}
+
+// Plays the solver's solution from the current position, then asks whether
+// to restart the level or carry on from where the player left off.
+pub fn show_me(window: &dyn Renderer, config: &Config, game: &mut GameContext) {
+    let strings = &config.strings;
+    if game.partner.is_some() {
+        game.status = Some(strings.get("hint.coop").to_string());
+        return;
+    }
+    let mut map = game.map_data.clone();
+    map.player_spawn = game.player.pos();
+    let moves = match solver::solve(&map, solver::DEFAULT_MAX_STATES).result {
+        SolveResult::Solved(moves) => moves,
+        SolveResult::Unsolvable => {
+            game.status = Some(strings.get("hint.unsolvable").to_string());
+            return;
+        }
+        SolveResult::GaveUp => {
+            game.status = Some(strings.get("hint.gave_up").to_string());
+            return;
+        }
+    };
+    log::debug!("showing a solution of {} moves", moves.len());
+    run_playback(
+        window,
+        config,
+        map,
+        &moves,
+        Some(strings.get("show_me.watching")),
+    );
+    let choices = [
+        strings.get("show_me.restart"),
+        strings.get("show_me.resume"),
+    ];
+    let restart = Menu::new(strings.get("show_me.done"), &choices).run(window) == Some(0);
+    game.state = GameState::Playing;
+    if restart {
+        game.perform(GameAction::Restart);
+    }
+}
@@ -0,0 +1,92 @@
+// Hot-seat play: two people share the keyboard and take turns at the same
+// levels, handing over after each level won or, if asked, every so many
+// moves. Each player's moves and levels are counted, and the two are
+// compared once the game is left.
+use crate::{i18n::Strings, renderer::Renderer};
+
+pub struct HotSeat {
+    pub names: [String; 2],
+    // Moves per turn, or None to hand over only after a level is won.
+    every: Option<usize>,
+    turn: usize,
+    moves_this_turn: usize,
+    pub moves: [usize; 2],
+    pub levels: [usize; 2],
+}
+
+impl HotSeat {
+    pub fn new(names: [String; 2], every: Option<usize>) -> Self {
+        Self {
+            names,
+            every: every.filter(|&n| n > 0),
+            turn: 0,
+            moves_this_turn: 0,
+            moves: [0; 2],
+            levels: [0; 2],
+        }
+    }
+    // Whose turn it is, as an index into `names`.
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+    fn pass(&mut self) {
+        self.turn = 1 - self.turn;
+        self.moves_this_turn = 0;
+    }
+    // Counts a move for whoever's turn it is. Returns whether their turn is
+    // now over.
+    pub fn moved(&mut self) -> bool {
+        self.moves[self.turn] += 1;
+        self.moves_this_turn += 1;
+        match self.every {
+            Some(every) if self.moves_this_turn >= every => {
+                self.pass();
+                true
+            }
+            _ => false,
+        }
+    }
+    // Credits the level to whoever's turn it is and hands over.
+    pub fn won(&mut self) {
+        self.levels[self.turn] += 1;
+        self.pass();
+    }
+    // The player ahead: more levels, then fewer moves once any were won.
+    // None for a draw.
+    pub fn leader(&self) -> Option<&str> {
+        let mut order = self.levels[0].cmp(&self.levels[1]);
+        if self.levels[0] > 0 {
+            order = order.then(self.moves[1].cmp(&self.moves[0]));
+        }
+        match order {
+            std::cmp::Ordering::Greater => Some(&self.names[0]),
+            std::cmp::Ordering::Less => Some(&self.names[1]),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+// The two players side by side until any key is pressed.
+pub fn show_scoreboard(window: &dyn Renderer, strings: &Strings, seat: &HotSeat) {
+    window.clear();
+    window.print(1, 2, strings.get("hotseat.title"));
+    for (i, name) in seat.names.iter().enumerate() {
+        let line = strings.fill(
+            "hotseat.score",
+            &[
+                ("name", name),
+                ("levels", &seat.levels[i]),
+                ("moves", &seat.moves[i]),
+            ],
+        );
+        window.print(3 + i as i32, 2, &line);
+    }
+    let verdict = match seat.leader() {
+        Some(name) => strings.fill("hotseat.leader", &[("name", &name)]),
+        None => strings.get("hotseat.draw").to_string(),
+    };
+    window.print(6, 2, &verdict);
+    window.print(8, 2, strings.get("stats.help"));
+    window.refresh();
+    window.read_key();
+}
@@ -0,0 +1,491 @@
+// Translated UI text. Screens look their text up by key rather than writing
+// it out, so it can be shown in the language picked with `language` in the
+// config. English is built in; other languages are TOML files of
+// `key = "text"` lines, either shipped in `lang/` or dropped into `lang/` in
+// the config directory (which wins). A key a translation leaves out falls
+// back to English, and `{name}` in a string is replaced by the argument of
+// that name.
+use box_pushing_core::paths::config_dir;
+use std::{collections::BTreeMap, fmt, fs, io};
+
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+const SHIPPED: [(&str, &str); 1] = [("fr", include_str!("../lang/fr.toml"))];
+
+const ENGLISH: [(&str, &str); 271] = [
+    ("menu.play", "Play"),
+    ("menu.play_pack", "Play level pack"),
+    ("menu.play_code", "Play level code"),
+    ("menu.random", "Random levels"),
+    ("menu.watch", "Watch solution"),
+    ("menu.fetch", "Fetch level pack"),
+    ("menu.controls", "Controls"),
+    ("menu.stats", "Statistics"),
+    ("menu.profile", "Profile: {name}"),
+    ("menu.online", "Online leaderboard"),
+    ("menu.multiplayer", "Two players"),
+    ("menu.quit", "Quit"),
+    ("menu.select_level", "Select a level"),
+    ("multi.coop", "Co-op on one keyboard"),
+    ("multi.hotseat", "Hot seat: take turns"),
+    ("multi.versus", "Versus race on one keyboard"),
+    ("multi.host", "Host a network co-op game"),
+    ("multi.host_versus", "Host a network versus race"),
+    ("multi.join", "Join a network game"),
+    ("prompt.best_of", "Best of how many rounds (blank: 3): "),
+    ("versus.you", "You"),
+    ("versus.them", "Them"),
+    (
+        "versus.score",
+        "{first} {first_wins} - {second_wins} {second}  (best of {best_of})",
+    ),
+    ("versus.round_won", "Round to {name}! press any key"),
+    ("versus.series_won", "The series goes to {name}!"),
+    (
+        "versus.final",
+        "{first} {first_wins} - {second_wins} {second}",
+    ),
+    (
+        "prompt.host_addr",
+        "Listen on, or RELAY/ROOM (blank: every address): ",
+    ),
+    ("prompt.join_addr", "Host address, RELAY/ROOM or RELAY/: "),
+    ("relay.rooms", "Rooms waiting at {relay}"),
+    ("relay.no_rooms", "no rooms are waiting"),
+    ("net.waiting", "Waiting for a player to join on {addr}..."),
+    ("net.cancel", "press any key to give up"),
+    ("net.failed", "network game failed: {error}"),
+    ("net.lost", "the other player has left"),
+    ("spectate.waiting", "Watching {addr}; press any key to stop"),
+    ("spectate.ended", "the broadcast has ended; press any key"),
+    ("prompt.player_name", "Name of player {player}: "),
+    (
+        "prompt.turn_moves",
+        "Moves per turn (blank: one level each): ",
+    ),
+    ("msg.bad_number", "`{text}` is not a number"),
+    ("hotseat.default_name", "Player {player}"),
+    ("hotseat.turn", "{name} to play ({moves} moves so far)"),
+    ("hotseat.your_turn", "{name}'s turn"),
+    ("hotseat.title", "Hot seat results"),
+    ("hotseat.score", "{name}: {levels} levels, {moves} moves"),
+    ("hotseat.leader", "{name} wins!"),
+    ("hotseat.draw", "It is a draw!"),
+    ("menu.level_packs", "Level packs"),
+    ("menu.no_packs", "No level packs installed"),
+    ("menu.level_style", "Level style"),
+    ("style.plain", "Plain"),
+    ("style.mirrored", "Mirrored"),
+    ("style.rotational", "Rotational"),
+    ("prompt.level_code", "Level code: "),
+    ("prompt.seed", "Seed (blank for random): "),
+    ("prompt.level_number", "Level number: "),
+    ("prompt.moves", "LURD moves or file: "),
+    ("prompt.pack_url", "Pack URL: "),
+    ("msg.bad_seed", "`{seed}` is not a seed"),
+    ("msg.generating", "Generating..."),
+    ("msg.no_generated_levels", "No levels found for seed {seed}"),
+    ("msg.no_such_level", "there is no level {level}"),
+    ("msg.pack_installed", "Installed pack into {dir}"),
+    ("pack.title", "{name} - {stars} stars"),
+    ("pack.world_locked", "{world} (locked: needs {stars} stars)"),
+    ("pack.world_open", "{world} ({done} completed)"),
+    ("pack.locked", "{world} is locked"),
+    ("pack.save_failed", "could not save progress: {error}"),
+    ("story.continue", "Press a key to go on, or Escape to skip"),
+    ("stats.title", "Statistics"),
+    ("stats.moves", "Moves"),
+    ("stats.pushes", "Pushes"),
+    ("stats.restarts", "Restarts"),
+    ("stats.undos", "Undos"),
+    ("stats.levels_completed", "Levels completed"),
+    ("stats.time_played", "Time played"),
+    ("stats.hardest", "Most restarted and undone levels"),
+    (
+        "stats.level_heat",
+        "{world} level {level}: {restarts} restarts, {undos} undos",
+    ),
+    ("stats.help", "press any key to go back"),
+    (
+        "resume.title",
+        "Level {level} was left part-way through ({moves} moves)",
+    ),
+    (
+        "recover.title",
+        "The game did not exit cleanly; level {level} was autosaved ({moves} moves)",
+    ),
+    ("resume.continue", "Carry on where you left off"),
+    ("resume.discard", "Start afresh"),
+    ("profile.title", "Profiles"),
+    ("profile.new", "New profile..."),
+    ("prompt.profile", "Profile name: "),
+    ("profile.export", "Export this profile..."),
+    ("profile.import", "Import into this profile..."),
+    ("prompt.export_file", "Export to file: "),
+    ("prompt.import_file", "Import from file: "),
+    ("profile.exported", "profile exported to {file}"),
+    (
+        "profile.imported",
+        "profile imported, {levels} records improved",
+    ),
+    (
+        "profile.imported_settings",
+        "profile imported, {levels} records improved; settings apply on restart",
+    ),
+    (
+        "profile.transfer_failed",
+        "profile transfer failed: {error}",
+    ),
+    ("msg.profile_switched", "playing as {name}"),
+    ("msg.profile_failed", "could not switch profile: {error}"),
+    ("select.rating", "Rating level..."),
+    ("select.no_solution", "no solution found"),
+    ("select.completed", "[done]"),
+    ("select.locked", "[locked]"),
+    ("select.score", "score {score}"),
+    ("spawn.title", "Start where?"),
+    ("spawn.usual", "At the usual start"),
+    ("select.title_score", "{title} - pack score {score}"),
+    (
+        "select.locked_level",
+        "level {level} is locked; win level {needed} first",
+    ),
+    ("hud.level", "level {level}: {flavor}"),
+    (
+        "hud.paused",
+        "paused: {resume} to resume, {show} to watch it solved, {quit} to quit",
+    ),
+    (
+        "hud.level_complete",
+        "level {level} complete! press any key",
+    ),
+    ("hud.best", "best: {moves} moves, {pushes} pushes, {time}"),
+    ("hud.par", "par: {moves} moves, {pushes} pushes"),
+    ("hud.grapples", "grapples: {left}"),
+    ("hud.undos", "undos: {left}"),
+    ("hud.gems", "gems: {gems}/{all}"),
+    (
+        "hud.score",
+        "score: {total} (gems {gems}, par bonus {par}, time bonus {time})",
+    ),
+    ("hud.item", "{item} x{count} [{key}]"),
+    ("item.phase", "phase"),
+    ("item.undo", "extra undo"),
+    (
+        "hud.result",
+        "won in {moves} moves, {pushes} pushes, {time}",
+    ),
+    ("hud.new_record", "new personal best: {records}!"),
+    ("record.moves", "moves"),
+    ("record.pushes", "pushes"),
+    ("record.time", "time"),
+    ("record.score", "score"),
+    ("board.moves", "fewest moves"),
+    ("board.time", "fastest"),
+    ("board.entry", "{rank}. {player} {score}"),
+    ("online.no_server", "no leaderboard_url set in the config"),
+    ("online.fetching", "Fetching rankings..."),
+    ("online.title", "Online leaderboard: level {level}"),
+    ("online.empty", "no runs yet"),
+    ("online.failed", "could not fetch the leaderboard: {error}"),
+    ("hud.game_over", "every level complete! press any key"),
+    ("status.level_code", "level code: {code}"),
+    ("status.solution_saved", "solution saved to {path}"),
+    ("status.solution_failed", "could not save solution: {error}"),
+    ("status.board_saved", "board saved to {path}"),
+    ("status.board_failed", "could not save board: {error}"),
+    (
+        "status.no_solution",
+        "complete a level to export its solution",
+    ),
+    ("event.button_pressed", "button {id} pressed"),
+    ("event.door_opened", "door {id} opened"),
+    ("event.door_closed", "door {id} closed"),
+    ("event.item_picked_up", "picked up: {item}"),
+    ("event.gem_collected", "gem collected"),
+    ("event.warped", "a secret warp! on to level {level}"),
+    (
+        "event.hold_opened",
+        "the plates are held: the hold doors open",
+    ),
+    (
+        "event.hold_closed",
+        "a plate was let go: the hold doors close",
+    ),
+    ("event.level_won", "level {level} won"),
+    ("refused.no_path", "can't walk there"),
+    ("refused.nothing_to_grapple", "nothing to grapple"),
+    ("refused.no_grapples", "no grapples left"),
+    ("refused.nothing_to_phase", "nothing to phase through"),
+    ("refused.no_phases", "no phases left"),
+    ("refused.no_undos", "no undos left"),
+    ("refused.no_extra_undos", "no extra undos left"),
+    ("refused.nothing_to_undo", "nothing to undo"),
+    ("refused.nothing_to_redo", "nothing to redo"),
+    ("status.reloaded", "reloaded"),
+    ("status.reload_failed", "reload failed: {error}"),
+    (
+        "level_error.no_levels",
+        "cannot load level {level}: there are no levels",
+    ),
+    (
+        "level_error.missing",
+        "cannot load level {level}: there are only {count} levels",
+    ),
+    (
+        "level_error.needs_partner",
+        "cannot load level {level}: it needs two players",
+    ),
+    (
+        "playback.status",
+        "playback: step {step}/{total} ({state}, {ms}ms/step)",
+    ),
+    ("playback.solved", "solved"),
+    ("playback.paused", "paused"),
+    ("playback.playing", "playing"),
+    (
+        "playback.verified",
+        "verified: wins in {moves} moves, {time}",
+    ),
+    ("playback.unverified", "not verified: {error}"),
+    ("controls.title", "Controls"),
+    (
+        "controls.help",
+        "Enter to change the highlighted action's key, Esc to leave",
+    ),
+    (
+        "controls.press_key",
+        "press a key for {action} (Esc cancels)",
+    ),
+    ("controls.conflict", "`{key}` is already used for {action}"),
+    ("controls.saved", "controls saved to {path}"),
+    (
+        "confirm.prompt",
+        "{action}? Press y to confirm, any other key to cancel",
+    ),
+    ("confirm.hold", "{action}: keep holding {key} to confirm"),
+    ("action.move_up", "Move up"),
+    ("action.move_down", "Move down"),
+    ("action.move_left", "Move left"),
+    ("action.move_right", "Move right"),
+    ("action.partner_up", "Player 2 up"),
+    ("action.partner_down", "Player 2 down"),
+    ("action.partner_left", "Player 2 left"),
+    ("action.partner_right", "Player 2 right"),
+    ("action.run", "Run (then a direction)"),
+    ("action.grapple", "Grapple (then a direction)"),
+    ("action.use_phase", "Use a phase (then a direction)"),
+    ("action.use_undo", "Use an extra undo"),
+    ("action.restart", "Restart level"),
+    ("action.undo", "Undo"),
+    ("action.redo", "Redo"),
+    ("action.pause", "Pause"),
+    ("action.menu", "Quit to menu"),
+    ("action.share_code", "Show level code"),
+    ("action.export_solution", "Export solution"),
+    ("action.export_board", "Export board"),
+    ("action.editor", "Level editor"),
+    ("action.hint", "Hint"),
+    ("action.mute", "Mute sound"),
+    ("action.help", "Help"),
+    ("action.describe", "Describe surroundings"),
+    ("action.practice", "Practice"),
+    ("describe.north", "north"),
+    ("describe.east", "east"),
+    ("describe.south", "south"),
+    ("describe.west", "west"),
+    ("describe.side", "{thing} {direction}"),
+    ("describe.on", "{thing} on {floor}"),
+    ("describe.partner", "partner {direction}"),
+    ("describe.goal", "win pad {steps}"),
+    ("describe.steps", "{count} {direction}"),
+    ("describe.on_goal", "on the win pad"),
+    ("describe.nothing", "open floor all around"),
+    ("describe.wall", "wall"),
+    ("describe.box", "box"),
+    ("describe.slippery_box", "slippery box"),
+    ("describe.phase_box", "phase box"),
+    ("describe.button", "button {id}"),
+    ("describe.step_pad", "step pad {id}"),
+    ("describe.closed_door", "closed door {id}"),
+    ("describe.open_door", "open door {id}"),
+    ("describe.win_pad", "win pad"),
+    ("describe.plate", "plate"),
+    ("describe.hold_door", "hold door"),
+    ("describe.open_hold_door", "open hold door"),
+    ("describe.gem", "gem"),
+    ("describe.custom", "{glyph} tile"),
+    ("help.title", "Help"),
+    ("help.controls", "Controls"),
+    ("help.tiles", "Tiles"),
+    ("help.rules", "Rules"),
+    (
+        "help.footer",
+        "Up and Down to scroll, any other key to go back",
+    ),
+    ("help.tile.player", "you"),
+    ("help.tile.wall", "wall"),
+    ("help.tile.box", "box"),
+    (
+        "help.tile.slippery_box",
+        "slippery box, which slides until stopped",
+    ),
+    ("help.tile.button", "button, numbered in level files"),
+    ("help.tile.closed_door", "closed door"),
+    ("help.tile.open_door", "open door"),
+    ("help.tile.win_pad", "win pad"),
+    ("help.tile.step_pad", "step pad, pressed on the right move"),
+    (
+        "help.tile.phase_box",
+        "phase box, passable after an odd move",
+    ),
+    ("help.tile.plate", "plate, held down by a player (co-op)"),
+    (
+        "help.tile.hold_door",
+        "hold door, open while every plate is held",
+    ),
+    ("help.tile.open_hold_door", "open hold door"),
+    ("help.tile.phase_pickup", "phase power-up"),
+    ("help.tile.undo_pickup", "extra undo power-up"),
+    ("help.tile.gem", "gem, worth points in scoring mode"),
+    (
+        "help.rule.win",
+        "Step onto a win pad ({win_pad}) to finish the level.",
+    ),
+    (
+        "help.rule.push",
+        "Walk into a box to push it; boxes can't be pulled.",
+    ),
+    (
+        "help.rule.buttons",
+        "A box or player on a button opens the doors that go with it.",
+    ),
+    (
+        "help.rule.stuck",
+        "A box that can never move again turns red.",
+    ),
+    (
+        "help.rule.undo",
+        "Made a mistake? {undo} takes back a move.",
+    ),
+    ("sound.muted", "sound off"),
+    ("sound.unmuted", "sound on"),
+    (
+        "hint.push",
+        "hint: push the marked box the way its arrow points ({left} hints left)",
+    ),
+    (
+        "hint.walk",
+        "hint: no more pushing needed, head for the win pad",
+    ),
+    (
+        "hint.unsolvable",
+        "hint: there is no way to win from here; try undoing",
+    ),
+    (
+        "hint.stuck",
+        "hint: the red boxes can never move again, so there is no way to win; try undoing",
+    ),
+    ("hint.gave_up", "hint: no solution found in time"),
+    (
+        "hint.used_up",
+        "no hints left on this level ({hints} per level)",
+    ),
+    ("hint.coop", "hints are only for one player"),
+    (
+        "hint.push_assist",
+        "hint: push the marked box the way its arrow points",
+    ),
+    (
+        "assist.stuck",
+        "that box can never move again; undo to free it",
+    ),
+    ("hud.assisted", "{result} [assisted: no records]"),
+    (
+        "practice.help",
+        "practice: arrows move, space adds a box or picks one up and sets it down, backspace takes it away, P puts the player here, Enter plays on",
+    ),
+    ("hud.practicing", "[practice]"),
+    ("practice.networked", "no practice in a networked game"),
+    ("hud.practice", "{result} [practice: no records]"),
+    ("show_me.watching", "watching the solver: q to stop"),
+    ("show_me.done", "now try it yourself?"),
+    ("show_me.restart", "restart the level"),
+    ("show_me.resume", "carry on from where I was"),
+    (
+        "editor.help",
+        "arrows move, type a tile to place, space clears, R/L rotate, M/V mirror, \
+         Enter plays, Esc cancels",
+    ),
+];
+
+#[derive(Debug)]
+pub enum LanguageError {
+    Unknown(String),
+    Io(String, io::Error),
+    Parse(String, String),
+}
+
+impl fmt::Display for LanguageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Unknown(language) => write!(f, "unknown language `{}`", language),
+            Self::Io(language, e) => write!(f, "language `{}`: {}", language, e),
+            Self::Parse(language, msg) => write!(f, "language `{}`: {}", language, msg),
+        }
+    }
+}
+
+pub struct Strings {
+    pub language: String,
+    table: BTreeMap<String, String>,
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        Self {
+            language: DEFAULT_LANGUAGE.to_string(),
+            table: BTreeMap::new(),
+        }
+    }
+}
+
+impl Strings {
+    pub fn load(language: &str) -> Result<Self, LanguageError> {
+        if language == DEFAULT_LANGUAGE {
+            return Ok(Self::default());
+        }
+        let path = config_dir().join("lang").join(format!("{}.toml", language));
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => SHIPPED
+                .iter()
+                .find(|(name, _)| *name == language)
+                .map(|(_, text)| text.to_string())
+                .ok_or_else(|| LanguageError::Unknown(language.to_string()))?,
+            Err(e) => return Err(LanguageError::Io(language.to_string(), e)),
+        };
+        let table = toml::from_str(&text).map_err(|e: toml::de::Error| {
+            LanguageError::Parse(language.to_string(), e.to_string())
+        })?;
+        Ok(Self {
+            language: language.to_string(),
+            table,
+        })
+    }
+    // The text for `key`, or the key itself if even English lacks it.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.table
+            .get(key)
+            .map(String::as_str)
+            .or_else(|| ENGLISH.iter().find(|(k, _)| *k == key).map(|(_, v)| *v))
+            .unwrap_or(key)
+    }
+    pub fn fill(&self, key: &str, args: &[(&str, &dyn fmt::Display)]) -> String {
+        let mut text = self.get(key).to_string();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{}}}", name), &value.to_string());
+        }
+        text
+    }
+}
@@ -0,0 +1,84 @@
+// Holds key presses that come faster than the game takes them. Keys are
+// played one per tick, the first straight away, so a quick burst of moves
+// plays out in order instead of being lost while the screen redraws. Past
+// `input_buffer` waiting keys (from the config) further presses are dropped.
+//
+// With `one_key = true` nothing is queued at all: a press is ignored while
+// the one before it is still waiting or was played less than `min_move_ms`
+// ago, so shaky or doubled presses don't turn into extra moves.
+use crate::renderer::Key;
+use std::{
+    collections::VecDeque,
+    time::{Duration, Instant},
+};
+
+pub struct InputBuffer {
+    keys: VecDeque<Key>,
+    capacity: usize,
+    period: Duration,
+    last_played: Option<Instant>,
+    // Drop presses that come too soon instead of queueing them.
+    one_at_a_time: bool,
+}
+
+impl InputBuffer {
+    pub fn new(capacity: usize, period: Duration) -> Self {
+        Self {
+            keys: VecDeque::new(),
+            capacity: capacity.max(1),
+            period,
+            last_played: None,
+            one_at_a_time: false,
+        }
+    }
+    // Takes one key at a time, at most one per `min_gap`.
+    pub fn one_at_a_time(min_gap: Duration) -> Self {
+        Self {
+            one_at_a_time: true,
+            ..Self::new(1, min_gap)
+        }
+    }
+    // Returns false if the buffer was full and the key was dropped.
+    pub fn push(&mut self, key: Key, now: Instant) -> bool {
+        let too_soon = self
+            .last_played
+            .is_some_and(|last| now < last + self.period);
+        if self.one_at_a_time && (too_soon || !self.keys.is_empty()) {
+            log::debug!("still settling; ignored {}", key);
+            return false;
+        }
+        if self.keys.len() >= self.capacity {
+            log::debug!("input buffer full; dropped {}", key);
+            return false;
+        }
+        self.keys.push_back(key);
+        true
+    }
+    // The next key, if one is waiting and a tick has passed since the last.
+    pub fn pop_due(&mut self, now: Instant) -> Option<Key> {
+        if self
+            .last_played
+            .is_some_and(|last| now < last + self.period)
+        {
+            return None;
+        }
+        let key = self.keys.pop_front()?;
+        self.last_played = Some(now);
+        Some(key)
+    }
+    pub fn clear(&mut self) {
+        self.keys.clear();
+    }
+    // Milliseconds until the next waiting key is due, or -1 if none is
+    // waiting, in the renderer's timeout terms.
+    pub fn timeout_ms(&self, now: Instant) -> i32 {
+        match (self.keys.is_empty(), self.last_played) {
+            (true, _) => -1,
+            (false, None) => 0,
+            (false, Some(last)) => {
+                let wait = (last + self.period).saturating_duration_since(now);
+                wait.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32
+            }
+        }
+    }
+}
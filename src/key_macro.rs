@@ -0,0 +1,171 @@
+// Records key presses to a file and plays them back, for reproducing bugs
+// and making demos. `--record-keys FILE` writes a `DELAY_MS KEY` line for
+// every key read, with the time since the one before; `--replay-keys FILE`
+// presses the keys of such a file with the same timing and then hands back
+// to the keyboard. Keys are named as in the config, and a line with no
+// delay is pressed straight away, so stub renderer input replays too.
+use crate::renderer::{Key, Renderer, Style};
+use std::{
+    cell::{Cell, RefCell},
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+    thread,
+    time::{Duration, Instant},
+};
+
+pub fn parse(text: &str) -> Result<VecDeque<(Duration, Key)>, String> {
+    let mut keys = VecDeque::new();
+    for (number, line) in text.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        // The key name is everything after the delay, so a space can be one.
+        let (delay, name) = match line.split_once(' ') {
+            Some((ms, name)) if ms.parse::<u64>().is_ok() => (ms.parse().unwrap(), name),
+            _ => (0, line.trim()),
+        };
+        let key = name
+            .parse()
+            .map_err(|e| format!("line {}: {}", number + 1, e))?;
+        keys.push_back((Duration::from_millis(delay), key));
+    }
+    Ok(keys)
+}
+
+// Wraps `window` to replay and then record keys, as the paths ask.
+pub fn wrap(
+    window: Box<dyn Renderer>,
+    record: Option<&Path>,
+    replay: Option<&Path>,
+) -> Result<Box<dyn Renderer>, String> {
+    let mut window = window;
+    if let Some(path) = replay {
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        let keys = parse(&text).map_err(|e| format!("{}: {}", path.display(), e))?;
+        window = Box::new(Replayer::new(window, keys));
+    }
+    if let Some(path) = record {
+        let file = File::create(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        window = Box::new(Recorder::new(window, file));
+    }
+    Ok(window)
+}
+
+pub struct Recorder {
+    inner: Box<dyn Renderer>,
+    file: RefCell<File>,
+    last: Cell<Instant>,
+}
+
+impl Recorder {
+    pub fn new(inner: Box<dyn Renderer>, file: File) -> Self {
+        Self {
+            inner,
+            file: RefCell::new(file),
+            last: Cell::new(Instant::now()),
+        }
+    }
+    fn write(&self, key: Key) -> io::Result<()> {
+        let now = Instant::now();
+        let delay = now - self.last.replace(now);
+        let mut file = self.file.borrow_mut();
+        writeln!(file, "{} {}", delay.as_millis(), key)?;
+        // Flushed as it goes, so a crash still leaves the keys that led to it.
+        file.flush()
+    }
+}
+
+pub struct Replayer {
+    inner: Box<dyn Renderer>,
+    keys: RefCell<VecDeque<(Duration, Key)>>,
+    // When the previous key was pressed.
+    last: Cell<Instant>,
+    timeout_ms: Cell<i32>,
+}
+
+impl Replayer {
+    pub fn new(inner: Box<dyn Renderer>, keys: VecDeque<(Duration, Key)>) -> Self {
+        Self {
+            inner,
+            keys: RefCell::new(keys),
+            last: Cell::new(Instant::now()),
+            timeout_ms: Cell::new(-1),
+        }
+    }
+}
+
+// Everything but reading keys goes straight to the wrapped renderer.
+macro_rules! delegate_drawing {
+    () => {
+        fn clear(&self) {
+            self.inner.clear();
+        }
+        fn clear_line(&self, y: i32) {
+            self.inner.clear_line(y);
+        }
+        fn print(&self, y: i32, x: i32, text: &str) {
+            self.inner.print(y, x, text);
+        }
+        fn put_char(&self, y: i32, x: i32, c: char) {
+            self.inner.put_char(y, x, c);
+        }
+        fn set_style(&self, style: Style) {
+            self.inner.set_style(style);
+        }
+        fn refresh(&self) {
+            self.inner.refresh();
+        }
+        fn height(&self) -> i32 {
+            self.inner.height()
+        }
+    };
+}
+
+impl Renderer for Recorder {
+    delegate_drawing!();
+
+    fn set_timeout(&self, ms: i32) {
+        self.inner.set_timeout(ms);
+    }
+    fn bell(&self) {
+        self.inner.bell();
+    }
+    fn read_key(&self) -> Option<Key> {
+        let key = self.inner.read_key()?;
+        if let Err(e) = self.write(key) {
+            log::warn!("could not record key {}: {}", key, e);
+        }
+        Some(key)
+    }
+}
+
+impl Renderer for Replayer {
+    delegate_drawing!();
+
+    fn set_timeout(&self, ms: i32) {
+        self.timeout_ms.set(ms);
+        self.inner.set_timeout(ms);
+    }
+    fn bell(&self) {
+        self.inner.bell();
+    }
+    fn read_key(&self) -> Option<Key> {
+        let mut keys = self.keys.borrow_mut();
+        let Some(&(delay, key)) = keys.front() else {
+            return self.inner.read_key();
+        };
+        let due = self.last.get() + delay;
+        let now = Instant::now();
+        let timeout = self.timeout_ms.get();
+        if timeout >= 0 && now + Duration::from_millis(timeout as u64) < due {
+            thread::sleep(Duration::from_millis(timeout as u64));
+            return None;
+        }
+        thread::sleep(due.saturating_duration_since(now));
+        keys.pop_front();
+        self.last.set(Instant::now());
+        Some(key)
+    }
+}
@@ -0,0 +1,37 @@
+// Steadies held keys. Terminals report a held key as a stream of presses at
+// whatever rate they like, so a press of the same key arriving soon after the
+// last one counts as the key still being held, and those repeats are let
+// through at most once per `repeat_ms` (from the config). Outside of play
+// and the editor, such as on the level-complete screen, repeats are dropped
+// entirely so a held arrow can't skip past it. Scripted input through the
+// stub renderer arrives all at once, so it wants `repeat_ms = 0`.
+use crate::renderer::Key;
+use std::time::{Duration, Instant};
+
+// Presses of one key closer together than this are a hold, not taps.
+const HOLD_GAP: Duration = Duration::from_millis(150);
+
+pub struct KeyRepeat {
+    rate: Duration,
+    // The held key, when it last got through and when it was last seen.
+    held: Option<(Key, Instant, Instant)>,
+}
+
+impl KeyRepeat {
+    pub fn new(rate: Duration) -> Self {
+        Self { rate, held: None }
+    }
+    // Whether a press of `key` at `now` should be acted on. `repeats` is
+    // false while repeats are being dropped.
+    pub fn accept(&mut self, key: Key, now: Instant, repeats: bool) -> bool {
+        if let Some((held, accepted, seen)) = self.held {
+            let held = held == key && now - seen < HOLD_GAP;
+            if held && (!repeats || now - accepted < self.rate) {
+                self.held = Some((key, accepted, now));
+                return false;
+            }
+        }
+        self.held = Some((key, now, now));
+        true
+    }
+}
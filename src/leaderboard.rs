@@ -0,0 +1,183 @@
+// The best runs of each level by anyone playing on this machine, whatever
+// their profile, kept in `leaderboard.txt` in the data directory as one
+// `pack-id/world-file: level player moves millis` line per run. Only runs in
+// a level's top few by moves or by time are kept.
+use crate::{i18n::Strings, play::format_time};
+use box_pushing_core::paths::data_dir;
+use std::{collections::BTreeMap, fs, io, path::PathBuf, time::Duration};
+
+const LEADERBOARD_FILE: &str = "leaderboard.txt";
+// How many runs each board shows.
+pub const TOP: usize = 5;
+
+#[derive(Clone)]
+pub struct Entry {
+    pub player: String,
+    pub moves: usize,
+    pub time: Duration,
+}
+
+#[derive(Default)]
+pub struct Leaderboard {
+    path: PathBuf,
+    // Whose runs are being added.
+    player: String,
+    runs: BTreeMap<String, BTreeMap<usize, Vec<Entry>>>,
+}
+
+fn parse_entry(text: &str) -> Option<(usize, Entry)> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let &[level, player, moves, millis] = words.as_slice() else {
+        return None;
+    };
+    let entry = Entry {
+        player: player.to_string(),
+        moves: moves.parse().ok()?,
+        time: Duration::from_millis(millis.parse().ok()?),
+    };
+    Some((level.parse().ok()?, entry))
+}
+
+fn world_key(pack_id: &str, world_file: &str) -> String {
+    format!("{}/{}", pack_id, world_file)
+}
+
+// Where the first `TOP` of `runs` are, fewest moves or fastest first, ties
+// going to the other measure.
+fn ranked(runs: &[Entry], by_time: bool) -> Vec<usize> {
+    let mut ranked: Vec<usize> = (0..runs.len()).collect();
+    match by_time {
+        true => ranked.sort_by_key(|&i| (runs[i].time, runs[i].moves)),
+        false => ranked.sort_by_key(|&i| (runs[i].moves, runs[i].time)),
+    }
+    ranked.truncate(TOP);
+    ranked
+}
+
+impl Leaderboard {
+    // The leaderboard, with runs added from now on credited to `player`. A
+    // missing or unreadable one is empty.
+    pub fn load(player: &str) -> Self {
+        let path = data_dir().join(LEADERBOARD_FILE);
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        let mut board = Self {
+            path,
+            player: player.to_string(),
+            ..Self::default()
+        };
+        for line in text.lines() {
+            let Some((key, entry)) = line.rsplit_once(':') else {
+                continue;
+            };
+            if let Some((level, entry)) = parse_entry(entry) {
+                let world = board.runs.entry(key.trim().to_string()).or_default();
+                world.entry(level).or_default().push(entry);
+            }
+        }
+        board
+    }
+    pub fn save(&self) -> io::Result<()> {
+        let mut text = String::new();
+        for (key, levels) in &self.runs {
+            for (level, entries) in levels {
+                for entry in entries {
+                    text.push_str(&format!(
+                        "{}: {} {} {} {}\n",
+                        key,
+                        level,
+                        entry.player,
+                        entry.moves,
+                        entry.time.as_millis()
+                    ));
+                }
+            }
+        }
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, text)
+    }
+    pub fn player(&self) -> &str {
+        &self.player
+    }
+    fn runs(&self, pack_id: &str, world_file: &str, level: usize) -> &[Entry] {
+        self.runs
+            .get(&world_key(pack_id, world_file))
+            .and_then(|levels| levels.get(&level))
+            .map_or(&[], Vec::as_slice)
+    }
+    // Adds the player's run, dropping any that fall off both boards.
+    pub fn add(
+        &mut self,
+        pack_id: &str,
+        world_file: &str,
+        level: usize,
+        moves: usize,
+        time: Duration,
+    ) {
+        let entry = Entry {
+            player: self.player.clone(),
+            moves,
+            time,
+        };
+        let world = self.runs.entry(world_key(pack_id, world_file)).or_default();
+        let runs = world.entry(level).or_default();
+        runs.push(entry);
+        let mut keep = vec![false; runs.len()];
+        for i in ranked(runs, false).into_iter().chain(ranked(runs, true)) {
+            keep[i] = true;
+        }
+        let mut keep = keep.into_iter();
+        runs.retain(|_| keep.next().unwrap_or_default());
+    }
+    // The level's board by moves, or by time.
+    pub fn board(
+        &self,
+        pack_id: &str,
+        world_file: &str,
+        level: usize,
+        by_time: bool,
+    ) -> Vec<&Entry> {
+        let runs = self.runs(pack_id, world_file, level);
+        ranked(runs, by_time)
+            .into_iter()
+            .map(|i| &runs[i])
+            .collect()
+    }
+}
+
+// The level's boards by moves and by time, a line per run.
+pub fn describe_boards(
+    strings: &Strings,
+    leaderboard: &Leaderboard,
+    pack_id: &str,
+    world_file: &str,
+    level: usize,
+) -> [Vec<String>; 2] {
+    [false, true].map(|by_time| {
+        let board = leaderboard.board(pack_id, world_file, level, by_time);
+        board_lines(strings, &board, by_time)
+    })
+}
+
+// One line per run of a board, numbered.
+pub fn board_lines(strings: &Strings, board: &[&Entry], by_time: bool) -> Vec<String> {
+    board
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let score = match by_time {
+                true => format_time(entry.time),
+                false => entry.moves.to_string(),
+            };
+            strings.fill(
+                "board.entry",
+                &[
+                    ("rank", &(i + 1)),
+                    ("player", &entry.player),
+                    ("score", &score),
+                ],
+            )
+        })
+        .collect()
+}
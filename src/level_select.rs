@@ -0,0 +1,223 @@
+use crate::{
+    i18n::Strings,
+    leaderboard::{describe_boards, Leaderboard},
+    renderer::{Key, Renderer},
+};
+use box_pushing_core::{
+    difficulty::{estimate, Difficulty},
+    map::MapData,
+    thumbnail::thumbnail,
+};
+
+const THUMB_WIDTH: usize = 30;
+const THUMB_HEIGHT: usize = 8;
+// Each entry takes the thumbnail's rows plus a blank separator line.
+const ENTRY_HEIGHT: i32 = THUMB_HEIGHT as i32 + 1;
+const LIST_TOP: i32 = 3;
+const LABEL_WIDTH: i32 = 40;
+
+pub struct LevelSelect<'a> {
+    strings: &'a Strings,
+    title: String,
+    maps: &'a [MapData],
+    thumbnails: Vec<Vec<String>>,
+    // Filled in as entries are selected, since solving every level up front
+    // could take a while.
+    difficulties: Vec<Option<Option<Difficulty>>>,
+    selected: usize,
+    // Levels already won, and the last level that may be played.
+    completed: Vec<bool>,
+    unlocked: usize,
+    // Each level's leaderboards, by moves and by time, on a line each.
+    leaders: Vec<[String; 2]>,
+    // Each level's best score and the pack's total, in scoring mode.
+    scores: Option<(Vec<Option<u32>>, u32)>,
+    // Digits typed so far to jump to a level by number.
+    typed: String,
+    message: Option<String>,
+}
+
+impl<'a> LevelSelect<'a> {
+    pub fn new(strings: &'a Strings, title: &str, maps: &'a [MapData]) -> Self {
+        Self {
+            strings,
+            title: title.to_string(),
+            maps,
+            thumbnails: maps
+                .iter()
+                .map(|m| thumbnail(m, THUMB_WIDTH, THUMB_HEIGHT))
+                .collect(),
+            difficulties: vec![None; maps.len()],
+            selected: 0,
+            completed: vec![false; maps.len()],
+            unlocked: usize::MAX,
+            leaders: Vec::new(),
+            scores: None,
+            typed: String::new(),
+            message: None,
+        }
+    }
+    // Marks the levels already won, locks those past `unlocked` and starts
+    // on `resume`.
+    pub fn with_progress(mut self, completed: Vec<bool>, unlocked: usize, resume: usize) -> Self {
+        self.completed = completed;
+        self.completed.resize(self.maps.len(), false);
+        self.unlocked = unlocked;
+        self.selected = resume.min(unlocked).min(self.maps.len().saturating_sub(1));
+        self
+    }
+    // Shows the top runs of each level on `leaderboard`.
+    pub fn with_leaderboard(
+        mut self,
+        leaderboard: &Leaderboard,
+        pack_id: &str,
+        world_file: &str,
+    ) -> Self {
+        self.leaders = (0..self.maps.len())
+            .map(|level| {
+                let [moves, time] =
+                    describe_boards(self.strings, leaderboard, pack_id, world_file, level);
+                [("board.moves", moves), ("board.time", time)].map(|(title, lines)| {
+                    if lines.is_empty() {
+                        return String::new();
+                    }
+                    format!("{}: {}", self.strings.get(title), lines.join(", "))
+                })
+            })
+            .collect();
+        self
+    }
+    // Shows each level's best score, and the pack's total by the title.
+    pub fn with_scores(mut self, scores: Vec<Option<u32>>, total: u32) -> Self {
+        self.scores = Some((scores, total));
+        self
+    }
+    fn label(&self, level: usize) -> String {
+        let flavor = self.maps[level]
+            .flavor(&self.strings.language)
+            .unwrap_or("");
+        self.strings
+            .fill("hud.level", &[("level", &level), ("flavor", &flavor)])
+            .chars()
+            .take(LABEL_WIDTH as usize - 3)
+            .collect()
+    }
+    pub fn draw(&self, window: &dyn Renderer) {
+        match &self.scores {
+            Some((_, total)) => window.print(
+                1,
+                2,
+                &self.strings.fill(
+                    "select.title_score",
+                    &[("title", &self.title), ("score", total)],
+                ),
+            ),
+            None => window.print(1, 2, &self.title),
+        }
+        let visible = ((window.height() - LIST_TOP) / ENTRY_HEIGHT).max(1) as usize;
+        // Scroll so the selected entry is always on screen.
+        let first = self.selected.saturating_sub(visible - 1);
+        for (slot, level) in (first..self.maps.len()).take(visible).enumerate() {
+            let top = LIST_TOP + slot as i32 * ENTRY_HEIGHT;
+            let marker = if level == self.selected { '>' } else { ' ' };
+            window.print(top, 2, &format!("{} {}", marker, self.label(level)));
+            let difficulty = match self.difficulties[level] {
+                Some(Some(difficulty)) => difficulty.to_string(),
+                Some(None) => self.strings.get("select.no_solution").to_string(),
+                None => String::new(),
+            };
+            let tag = if level > self.unlocked {
+                self.strings.get("select.locked")
+            } else if self.completed[level] {
+                self.strings.get("select.completed")
+            } else {
+                ""
+            };
+            let score = match self
+                .scores
+                .as_ref()
+                .and_then(|(scores, _)| scores.get(level))
+            {
+                Some(Some(score)) => self.strings.fill("select.score", &[("score", score)]),
+                _ => String::new(),
+            };
+            let parts: Vec<&str> = [tag, &score, &difficulty]
+                .into_iter()
+                .filter(|part| !part.is_empty())
+                .collect();
+            window.print(top + 1, 4, &parts.join(" "));
+            for (row, line) in self.leaders.get(level).into_iter().flatten().enumerate() {
+                let line: String = line.chars().take(LABEL_WIDTH as usize - 5).collect();
+                window.print(top + 2 + row as i32, 4, &line);
+            }
+            for (row, line) in self.thumbnails[level].iter().enumerate() {
+                window.print(top + row as i32, LABEL_WIDTH, line);
+            }
+        }
+    }
+    // Returns the chosen level, or None if the screen was backed out of.
+    pub fn run(&mut self, window: &dyn Renderer) -> Option<usize> {
+        loop {
+            window.clear();
+            if self.difficulties[self.selected].is_none() {
+                self.draw(window);
+                window.print(window.height() - 1, 0, self.strings.get("select.rating"));
+                window.refresh();
+                self.difficulties[self.selected] = Some(estimate(&self.maps[self.selected]));
+                window.clear();
+            }
+            self.draw(window);
+            let bottom = window.height() - 1;
+            if !self.typed.is_empty() {
+                let prompt = self.strings.get("prompt.level_number");
+                window.print(bottom, 0, &format!("{}{}", prompt, self.typed));
+            } else if let Some(message) = &self.message {
+                window.print(bottom, 0, message);
+            }
+            window.refresh();
+            let key = window.read_key();
+            self.message = None;
+            if !self.typed.is_empty() {
+                self.edit_number(key);
+                continue;
+            }
+            match key {
+                Some(Key::Char(c)) if c.is_ascii_digit() => self.typed.push(c),
+                Some(Key::Up) => self.selected = self.selected.saturating_sub(1),
+                Some(Key::Down) => self.selected = (self.selected + 1).min(self.maps.len() - 1),
+                Some(Key::Enter) if self.selected > self.unlocked => {
+                    let message = self.strings.fill(
+                        "select.locked_level",
+                        &[("level", &self.selected), ("needed", &self.unlocked)],
+                    );
+                    self.message = Some(message);
+                }
+                Some(Key::Enter) => return Some(self.selected),
+                Some(Key::Char('q')) => return None,
+                _ => (),
+            }
+        }
+    }
+    // Handles a key while a level number is being typed. Enter jumps to the
+    // level, Escape gives up on it.
+    fn edit_number(&mut self, key: Option<Key>) {
+        match key {
+            Some(Key::Char(c)) if c.is_ascii_digit() => self.typed.push(c),
+            Some(Key::Backspace) => {
+                self.typed.pop();
+            }
+            Some(Key::Enter) => {
+                let typed = std::mem::take(&mut self.typed);
+                match typed.parse::<usize>() {
+                    Ok(level) if level < self.maps.len() => self.selected = level,
+                    _ => {
+                        let message = self.strings.fill("msg.no_such_level", &[("level", &typed)]);
+                        self.message = Some(message);
+                    }
+                }
+            }
+            Some(Key::Escape) => self.typed.clear(),
+            _ => (),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+// Logging goes to a file, never the terminal, where it would scribble over
+// the game. The level comes from RUST_LOG (e.g. RUST_LOG=debug to trace every
+// move and event) and defaults to info.
+use box_pushing_core::paths::data_dir;
+use env_logger::{Env, Target};
+use std::{
+    fs::{self, File},
+    io,
+    path::PathBuf,
+};
+
+const LOG_FILE: &str = "button_trial.log";
+
+pub fn log_path() -> PathBuf {
+    data_dir().join(LOG_FILE)
+}
+
+// Starts logging to `path`, replacing the previous run's log.
+pub fn init(path: Option<PathBuf>) -> io::Result<()> {
+    let path = path.unwrap_or_else(log_path);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let file = File::create(&path)?;
+    env_logger::Builder::from_env(Env::default().default_filter_or("info"))
+        .target(Target::Pipe(Box::new(file)))
+        .write_style(env_logger::WriteStyle::Never)
+        .init();
+    log::info!(
+        "{} {} started",
+        env!("CARGO_PKG_NAME"),
+        env!("CARGO_PKG_VERSION")
+    );
+    Ok(())
+}
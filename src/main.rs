@@ -1,72 +1,1212 @@
-use crate::map::init_tile_colors;
-use pancurses::{curs_set, endwin, initscr, napms, noecho, Input};
+use crate::{
+    cli::Args,
+    config::{Config, ConfigError},
+    hotseat::HotSeat,
+    i18n::Strings,
+    leaderboard::Leaderboard,
+    level_select::LevelSelect,
+    netplay::{NetError, NetLink},
+    pack::{Pack, PackError},
+    play::{level_error, play, Record, TwoPlayer},
+    profile::Profile,
+    progress::{Progress, CAMPAIGN},
+    recovery::Lock,
+    renderer::{Backend, Renderer},
+    replay::Replay,
+    stats::Stats,
+    suspend::Suspended,
+    versus::Series,
+};
+use box_pushing_core::{
+    game::{GameContext, LevelError},
+    generator::{self, Symmetry},
+    headless::Simulation,
+    hot_reload::LevelWatcher,
+    level_file::{self, load_level_file},
+    lint, lurd,
+    map::{self, MapData, Par},
+    share_code,
+    solver::{self, SolveResult},
+};
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-pub mod game;
-pub mod map;
+pub mod cli;
+pub mod config;
+pub mod confirm;
+pub mod controls;
+pub mod crossterm_renderer;
+#[cfg(feature = "curses")]
+pub mod curses_renderer;
+pub mod debug_console;
+pub mod describe;
+pub mod draw;
+pub mod editor;
+pub mod fetch;
+pub mod ghost;
+pub mod help;
+pub mod hint;
+pub mod hotseat;
+pub mod i18n;
+pub mod input_buffer;
+pub mod key_macro;
+pub mod key_repeat;
+pub mod leaderboard;
+pub mod level_select;
+pub mod logging;
+pub mod menu;
+pub mod music;
+pub mod netplay;
+pub mod online;
+pub mod pack;
+pub mod play;
+pub mod playback;
+pub mod practice;
+pub mod profile;
+pub mod progress;
+pub mod recovery;
+pub mod relay;
+pub mod renderer;
+pub mod replay;
+pub mod scheduler;
+pub mod sound;
+pub mod spectate;
+pub mod stats;
+pub mod story;
+pub mod stub_renderer;
+pub mod suspend;
+pub mod transfer;
+pub mod versus;
 
 pub const TOP_PADDING: i32 = 5;
 
-#[derive(Clone, Copy)]
-pub enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
+// Generated levels come in a short run, sorted easiest first.
+const GENERATED_SET_SIZE: u64 = 5;
+
+// String keys of the main menu entries.
+const MAIN_MENU: [&str; 12] = [
+    "menu.play",
+    "menu.play_pack",
+    "menu.play_code",
+    "menu.random",
+    "menu.watch",
+    "menu.fetch",
+    "menu.controls",
+    "menu.stats",
+    "menu.profile",
+    "menu.online",
+    "menu.multiplayer",
+    "menu.quit",
+];
+
+// String keys of the two-player modes.
+const MULTIPLAYER_MENU: [&str; 6] = [
+    "multi.coop",
+    "multi.hotseat",
+    "multi.versus",
+    "multi.host",
+    "multi.host_versus",
+    "multi.join",
+];
+// Versus races are best of this many rounds unless asked otherwise.
+const DEFAULT_BEST_OF: u32 = 3;
+
+fn main() {
+    let args = cli::parse_args(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
+    });
+    if let Err(e) = logging::init(args.log_file.clone()) {
+        eprintln!("could not open the log file: {}", e);
+    }
+    if let Some(addr) = &args.relay {
+        if let Err(e) = relay::serve(addr) {
+            eprintln!("could not relay on {}: {}", addr, e);
+            process::exit(1);
+        }
+        return;
+    }
+    if let Some(url) = &args.fetch_url {
+        match fetch::fetch_pack(url) {
+            Ok(dir) => println!("installed pack into {}", dir.display()),
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+
+    if let Some(target) = &args.export_builtin {
+        let text = level_file::levels_to_text(&map::get_maps());
+        if target == "-" {
+            print!("{}", text);
+        } else if let Err(e) = fs::write(target, text) {
+            eprintln!("{}: {}", target, e);
+            process::exit(1);
+        }
+        return;
+    }
+    if let Some(transforms) = &args.transforms {
+        let Some(path) = &args.level_file else {
+            eprintln!("--transform needs a level file");
+            process::exit(2);
+        };
+        let mut maps = load_level_file(path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path.display(), e);
+            process::exit(1);
+        });
+        for map in &mut maps {
+            for &transform in transforms {
+                map.transform(transform);
+            }
+        }
+        print!("{}", level_file::levels_to_text(&maps));
+        return;
+    }
+    if let Some(path) = &args.lint_file {
+        let maps = load_level_file(path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path.display(), e);
+            process::exit(1);
+        });
+        let mut problems = 0;
+        for (level, map) in maps.iter().enumerate() {
+            for lint in lint::lint_level(map) {
+                println!("{}: level {} {}", path.display(), level, lint);
+                problems += 1;
+            }
+        }
+        if problems > 0 {
+            process::exit(1);
+        }
+        return;
+    }
+    if let Some(path) = &args.par_path {
+        let max_states = args.max_states.unwrap_or(solver::DEFAULT_MAX_STATES);
+        match write_pars(path, max_states) {
+            Ok(0) => (),
+            Ok(unsolved) => {
+                eprintln!("{} levels were left without a par", unsolved);
+                process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("{}: {}", path.display(), e);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(path) = &args.solve_file {
+        let maps = load_level_file(path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path.display(), e);
+            process::exit(1);
+        });
+        let max_states = args.max_states.unwrap_or(solver::DEFAULT_MAX_STATES);
+        let mut unsolved = 0;
+        for (level, map) in maps.into_iter().enumerate() {
+            if args.level.is_some_and(|only| only as usize != level) {
+                continue;
+            }
+            if solve_level(level, map, max_states).is_none() {
+                unsolved += 1;
+            }
+        }
+        if unsolved > 0 {
+            process::exit(1);
+        }
+        return;
+    }
+
+    let level_path = args.level_file.clone();
+    let map_list = match &level_path {
+        Some(path) => load_level_file(path).unwrap_or_else(|e| {
+            eprintln!("{}: {}", path.display(), e);
+            process::exit(1);
+        }),
+        None => map::get_maps(),
+    };
+
+    if let Some(file) = &args.headless_file {
+        let level = args.level.unwrap_or(0);
+        let moves = lurd::load(file).unwrap_or_else(|e| {
+            eprintln!("{}: {}", file, e);
+            process::exit(1);
+        });
+        let Some(map) = map_list.get(level as usize).cloned() else {
+            eprintln!("there is no level {}", level);
+            process::exit(1);
+        };
+        let simulation = Simulation::run(map, &moves);
+        for (i, step) in simulation.steps.iter().enumerate() {
+            println!("step {}: {}", i + 1, step);
+        }
+        for line in simulation.board() {
+            println!("{}", line);
+        }
+        if simulation.won() {
+            println!("won in {} moves", simulation.steps.len());
+        } else {
+            println!("not won after {} moves", simulation.steps.len());
+            process::exit(1);
+        }
+        return;
+    }
+
+    let mut profile = match &args.profile {
+        Some(name) => Profile::new(name).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            process::exit(2);
+        }),
+        None => Profile::current(),
+    };
+    let mut config = load_config(&args, &profile).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(2);
+    });
+    if args.profile.is_some() {
+        if let Err(e) = profile.select() {
+            eprintln!("could not switch to profile {}: {}", profile.name(), e);
+        }
+    }
+    let backend = args.renderer.or(config.renderer).unwrap_or_default();
+
+    if let Some(target) = &args.export_replay {
+        let level = args.level.unwrap_or(0) as usize;
+        let Some(map) = map_list.get(level) else {
+            eprintln!("there is no level {}", level);
+            process::exit(1);
+        };
+        let progress = Progress::load(profile.progress_path());
+        let campaign = campaign_key(&args.level_file);
+        let Some(run) = progress.fastest_run(CAMPAIGN, &campaign, level) else {
+            eprintln!("level {} has not been won by {}", level, profile.name());
+            process::exit(1);
+        };
+        let text = Replay::new(map, run).to_text();
+        if target == "-" {
+            print!("{}", text);
+        } else if let Err(e) = fs::write(target, text) {
+            eprintln!("{}: {}", target, e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(addr) = &args.spectate {
+        let window = init_window(&args, backend, &config);
+        if let Err(e) = spectate::spectate(&*window, &config.strings, addr) {
+            drop(window);
+            eprintln!("could not watch {}: {}", addr, e);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if let Some(file) = &args.playback_file {
+        let (level, moves, verdict) = match fs::read_to_string(file) {
+            Ok(text) if Replay::is_replay(&text) => {
+                let replay = Replay::parse(&text).unwrap_or_else(|e| {
+                    eprintln!("{}: {}", file, e);
+                    process::exit(1);
+                });
+                let level = match args.level {
+                    Some(level) => level as usize,
+                    None => replay.find_level(&map_list).unwrap_or_else(|| {
+                        eprintln!("{}: none of these levels was played", file);
+                        process::exit(1);
+                    }),
+                };
+                let verdict = match map_list.get(level).map(|map| replay.verify(map)) {
+                    Some(Ok(time)) => config.strings.fill(
+                        "playback.verified",
+                        &[
+                            ("moves", &replay.run.moves.len()),
+                            ("time", &play::format_time(time)),
+                        ],
+                    ),
+                    Some(Err(e)) => config.strings.fill("playback.unverified", &[("error", &e)]),
+                    None => String::new(),
+                };
+                (level, replay.moves(), Some(verdict))
+            }
+            _ => {
+                let moves = lurd::load(file).unwrap_or_else(|e| {
+                    eprintln!("{}: {}", file, e);
+                    process::exit(1);
+                });
+                (args.level.unwrap_or(0) as usize, moves, None)
+            }
+        };
+        let Some(map) = map_list.get(level).cloned() else {
+            eprintln!("there is no level {}", level);
+            process::exit(1);
+        };
+        let window = init_window(&args, backend, &config);
+        playback::run_playback(&*window, &config, map, &moves, verdict.as_deref());
+        return;
+    }
+
+    let renderer = init_window(&args, backend, &config);
+    let window = renderer.as_ref();
+    let map_list = &map_list;
+    let mut solutions = Vec::new();
+    let mut message = None;
+    loop {
+        let (lock, crashed) = Lock::acquire(&profile);
+        let (solved, next) = run_menus(
+            window,
+            &args,
+            &mut config,
+            &profile,
+            map_list,
+            message,
+            crashed,
+        );
+        lock.release();
+        solutions.extend(solved);
+        let Some(next) = next else {
+            break;
+        };
+        // A profile that can't be switched to leaves the current one in use.
+        let switched = load_config(&args, &next)
+            .map_err(|e| e.to_string())
+            .and_then(|loaded| {
+                next.select()
+                    .map_err(|e| config.strings.fill("msg.profile_failed", &[("error", &e)]))?;
+                Ok(loaded)
+            });
+        message = Some(match switched {
+            Ok(loaded) => {
+                config = loaded;
+                profile = next;
+                config
+                    .strings
+                    .fill("msg.profile_switched", &[("name", &profile.name())])
+            }
+            Err(e) => e,
+        });
+    }
+
+    drop(renderer);
+
+    if args.export_solutions {
+        for (level, lurd) in solutions {
+            println!("level {}: {}", level, lurd);
+        }
+    }
 }
 
-impl Direction {
-    fn get_vec2_move(&self) -> (i32, i32) {
-        match self {
-            Self::Up => (-1, 0),
-            Self::Down => (1, 0),
-            Self::Left => (0, -1),
-            Self::Right => (0, 1),
+// Runs the main menu for `profile` until it is quit, or another profile is
+// chosen to switch to. Returns the solutions of the levels won either way.
+fn run_menus(
+    window: &dyn Renderer,
+    args: &Args,
+    config: &mut Config,
+    profile: &Profile,
+    map_list: &[MapData],
+    message: Option<String>,
+    crashed: bool,
+) -> (Vec<(u32, String)>, Option<Profile>) {
+    let level_path = &args.level_file;
+    let strings = &config.strings;
+
+    // Campaign progress is kept per level file, or for the built-in levels.
+    let mut progress = Progress::load(profile.progress_path());
+    let mut stats = Stats::load(profile.stats_path());
+    let mut leaderboard = Leaderboard::load(profile.name());
+    let campaign = campaign_key(level_path);
+
+    // After a crash the autosave is offered back first, then any level left
+    // part-way through.
+    let mut solutions = Vec::new();
+    let autosave = profile.autosave_path();
+    if !crashed {
+        suspend::discard(&autosave);
+    }
+    for (saved, title) in [
+        (autosave, "recover.title"),
+        (profile.suspended_path(), "resume.title"),
+    ] {
+        let Some(found) = Suspended::load(&saved) else {
+            continue;
+        };
+        match found.and_then(|found| Ok((found.game()?, found.world))) {
+            Ok((game, world)) => match offer_resume(window, strings, title, &game) {
+                Some(true) => {
+                    suspend::discard(&saved);
+                    let music = world
+                        .as_ref()
+                        .and_then(|(pack_id, _)| {
+                            pack::installed_packs()
+                                .into_iter()
+                                .find(|p| p.id() == *pack_id)
+                        })
+                        .map_or_else(Vec::new, |pack| pack.music_paths());
+                    let record = world.as_ref().map(|(pack_id, world_file)| Record {
+                        progress: &mut progress,
+                        leaderboard: &mut leaderboard,
+                        pack_id,
+                        world_file,
+                        music: &music,
+                    });
+                    let solved = play(
+                        window,
+                        config,
+                        game,
+                        record,
+                        &mut stats,
+                        Some(profile),
+                        None,
+                    );
+                    solutions.extend(solved);
+                }
+                Some(false) => suspend::discard(&saved),
+                // Backing out leaves the choice for next time.
+                None => (),
+            },
+            Err(e) => {
+                log::warn!("could not restore {}: {}", saved.display(), e);
+                suspend::discard(&saved);
+            }
+        }
+    }
+
+    let main_menu = MAIN_MENU.map(|key| strings.fill(key, &[("name", &profile.name())]));
+    let main_menu = main_menu.each_ref().map(String::as_str);
+    let mut menu = menu::Menu::new("button trial", &main_menu);
+    menu.message = message;
+    loop {
+        match menu.run(window) {
+            Some(0) => {
+                let title = strings.get("menu.select_level");
+                let completed = (0..map_list.len())
+                    .map(|level| progress.is_completed(CAMPAIGN, &campaign, level))
+                    .collect();
+                // Debugging opens every level.
+                let unlocked = match config.debug {
+                    true => usize::MAX,
+                    false => progress.highest_unlocked(CAMPAIGN, &campaign),
+                };
+                let resume = progress.resume_level(CAMPAIGN, &campaign);
+                let mut select = LevelSelect::new(strings, title, map_list)
+                    .with_progress(completed, unlocked, resume)
+                    .with_leaderboard(&leaderboard, CAMPAIGN, &campaign);
+                if config.scoring {
+                    let scores = (0..map_list.len())
+                        .map(|level| progress.score(CAMPAIGN, &campaign, level))
+                        .collect();
+                    select = select.with_scores(scores, progress.pack_score(CAMPAIGN));
+                }
+                let Some(level) = select.run(window) else {
+                    continue;
+                };
+                let Some(spawn) = choose_spawn(window, strings, &map_list[level]) else {
+                    continue;
+                };
+                let watcher = level_path.as_deref().map(LevelWatcher::new);
+                let game = match start_game(map_list.to_vec(), watcher, level, spawn, false) {
+                    Ok(game) => game,
+                    Err(e) => {
+                        menu.message = Some(level_error(strings, e));
+                        continue;
+                    }
+                };
+                progress.set_resume(CAMPAIGN, &campaign, level);
+                menu.message = save_progress(strings, &progress);
+                let record = Record {
+                    progress: &mut progress,
+                    leaderboard: &mut leaderboard,
+                    pack_id: CAMPAIGN,
+                    world_file: &campaign,
+                    music: &[],
+                };
+                solutions.extend(play(
+                    window,
+                    config,
+                    game,
+                    Some(record),
+                    &mut stats,
+                    Some(profile),
+                    None,
+                ));
+            }
+            Some(1) => {
+                let packs = pack::installed_packs();
+                if packs.is_empty() {
+                    menu.message = Some(strings.get("menu.no_packs").to_string());
+                    continue;
+                }
+                let names: Vec<&str> = packs.iter().map(|p| p.manifest.name.as_str()).collect();
+                let title = strings.get("menu.level_packs");
+                if let Some(choice) = menu::Menu::new(title, &names).run(window) {
+                    solutions.extend(play_pack(
+                        window,
+                        config,
+                        &mut progress,
+                        &mut leaderboard,
+                        &mut stats,
+                        profile,
+                        &packs[choice],
+                    ));
+                }
+            }
+            Some(2) => {
+                let Some(code) = menu::prompt_line(window, strings.get("prompt.level_code")) else {
+                    continue;
+                };
+                match share_code::decode(&code) {
+                    Ok(map) => solutions.extend(play(
+                        window,
+                        config,
+                        GameContext::new(vec![map], None),
+                        None,
+                        &mut stats,
+                        Some(profile),
+                        None,
+                    )),
+                    Err(e) => menu.message = Some(e.to_string()),
+                }
+            }
+            Some(3) => {
+                let Some(seed) = menu::prompt_line(window, strings.get("prompt.seed")) else {
+                    continue;
+                };
+                let seed = match seed.trim() {
+                    "" => SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |d| d.as_secs()),
+                    text => match text.parse() {
+                        Ok(seed) => seed,
+                        Err(_) => {
+                            menu.message = Some(strings.fill("msg.bad_seed", &[("seed", &text)]));
+                            continue;
+                        }
+                    },
+                };
+                let styles = ["style.plain", "style.mirrored", "style.rotational"]
+                    .map(|key| strings.get(key));
+                let title = strings.get("menu.level_style");
+                let Some(style) = menu::Menu::new(title, &styles).run(window) else {
+                    continue;
+                };
+                let symmetry = [Symmetry::None, Symmetry::Horizontal, Symmetry::Rotational][style];
+                window.clear();
+                window.print(window.height() - 1, 0, strings.get("msg.generating"));
+                window.refresh();
+                let maps = generator::generate_set(seed, GENERATED_SET_SIZE, symmetry);
+                if maps.is_empty() {
+                    menu.message =
+                        Some(strings.fill("msg.no_generated_levels", &[("seed", &seed)]));
+                } else {
+                    solutions.extend(play(
+                        window,
+                        config,
+                        GameContext::new(maps, None),
+                        None,
+                        &mut stats,
+                        Some(profile),
+                        None,
+                    ));
+                }
+            }
+            Some(4) => {
+                let Some(level) = menu::prompt_line(window, strings.get("prompt.level_number"))
+                else {
+                    continue;
+                };
+                let Some(map) = level
+                    .trim()
+                    .parse()
+                    .ok()
+                    .and_then(|l: usize| map_list.get(l))
+                else {
+                    menu.message =
+                        Some(strings.fill("msg.no_such_level", &[("level", &level.trim())]));
+                    continue;
+                };
+                let Some(moves) = menu::prompt_line(window, strings.get("prompt.moves")) else {
+                    continue;
+                };
+                match lurd::load(moves.trim()) {
+                    Ok(moves) => playback::run_playback(window, config, map.clone(), &moves, None),
+                    Err(e) => menu.message = Some(e),
+                }
+            }
+            Some(5) => {
+                let Some(url) = menu::prompt_line(window, strings.get("prompt.pack_url")) else {
+                    continue;
+                };
+                menu.message = Some(match fetch::fetch_pack(&url) {
+                    Ok(dir) => strings.fill("msg.pack_installed", &[("dir", &dir.display())]),
+                    Err(e) => e.to_string(),
+                });
+            }
+            Some(6) => {
+                let path = args
+                    .config_file
+                    .clone()
+                    .unwrap_or_else(|| profile.config_path());
+                menu.message = controls::run(window, strings, &mut config.keys, &path);
+            }
+            Some(7) => stats::show(window, strings, &stats),
+            Some(8) => {
+                let profiles = Profile::list();
+                let mut names: Vec<&str> = profiles.iter().map(Profile::name).collect();
+                names.extend(
+                    ["profile.new", "profile.export", "profile.import"].map(|key| strings.get(key)),
+                );
+                let title = strings.get("profile.title");
+                let Some(choice) = menu::Menu::new(title, &names).run(window) else {
+                    continue;
+                };
+                let chosen = match choice.checked_sub(profiles.len()) {
+                    None => profiles[choice].clone(),
+                    Some(1) => {
+                        let Some(file) =
+                            menu::prompt_line(window, strings.get("prompt.export_file"))
+                        else {
+                            continue;
+                        };
+                        let file = file.trim();
+                        menu.message = Some(match transfer::export(profile, Path::new(file)) {
+                            Ok(()) => strings.fill("profile.exported", &[("file", &file)]),
+                            Err(e) => strings.fill("profile.transfer_failed", &[("error", &e)]),
+                        });
+                        continue;
+                    }
+                    Some(2) => {
+                        let Some(file) =
+                            menu::prompt_line(window, strings.get("prompt.import_file"))
+                        else {
+                            continue;
+                        };
+                        menu.message =
+                            Some(match transfer::import(profile, Path::new(file.trim())) {
+                                Ok(imported) => {
+                                    progress = Progress::load(profile.progress_path());
+                                    stats = Stats::load(profile.stats_path());
+                                    let key = match imported.settings {
+                                        true => "profile.imported_settings",
+                                        false => "profile.imported",
+                                    };
+                                    strings.fill(key, &[("levels", &imported.levels_improved)])
+                                }
+                                Err(e) => strings.fill("profile.transfer_failed", &[("error", &e)]),
+                            });
+                        continue;
+                    }
+                    _ => {
+                        let Some(name) = menu::prompt_line(window, strings.get("prompt.profile"))
+                        else {
+                            continue;
+                        };
+                        match Profile::new(&name) {
+                            Ok(chosen) => chosen,
+                            Err(e) => {
+                                menu.message = Some(e);
+                                continue;
+                            }
+                        }
+                    }
+                };
+                if chosen != *profile {
+                    return (solutions, Some(chosen));
+                }
+            }
+            Some(9) => {
+                let Some(server) = &config.leaderboard_url else {
+                    menu.message = Some(strings.get("online.no_server").to_string());
+                    continue;
+                };
+                let Some(level) = menu::prompt_line(window, strings.get("prompt.level_number"))
+                else {
+                    continue;
+                };
+                let Some((level, map)) = level
+                    .trim()
+                    .parse()
+                    .ok()
+                    .and_then(|l: usize| Some((l, map_list.get(l)?)))
+                else {
+                    menu.message =
+                        Some(strings.fill("msg.no_such_level", &[("level", &level.trim())]));
+                    continue;
+                };
+                if let Err(e) = online::show(window, strings, server, level, map) {
+                    menu.message = Some(strings.fill("online.failed", &[("error", &e)]));
+                }
+            }
+            Some(10) => {
+                if let Some(message) = run_multiplayer(window, config, map_list, &mut stats) {
+                    menu.message = Some(message);
+                }
+            }
+            _ => break,
         }
     }
+    (solutions, None)
 }
 
-fn main() {
-    let window = initscr();
-    if pancurses::has_colors() {
-        pancurses::start_color();
-        init_tile_colors();
-    }
-    window.keypad(true);
-    noecho();
-    curs_set(0);
-    window.printw("Test");
-    let player = game::Player {
-        y: 5,
-        x: 5,
-        glyph: 'X',
+// Picks a two-player mode and a level of the campaign to start at, and plays
+// it; a game joined over the network plays the host's levels instead.
+// Two-player games are nobody's own, so they are neither recorded nor
+// autosaved. Returns a message for the menu if the game could not start.
+fn run_multiplayer(
+    window: &dyn Renderer,
+    config: &Config,
+    map_list: &[MapData],
+    stats: &mut Stats,
+) -> Option<String> {
+    let strings = &config.strings;
+    let modes = MULTIPLAYER_MENU.map(|key| strings.get(key));
+    let mode = menu::Menu::new(strings.get("menu.multiplayer"), &modes).run(window)?;
+    let net_failed = |e: &dyn std::fmt::Display| strings.fill("net.failed", &[("error", e)]);
+    let sides = || ["versus.you", "versus.them"].map(|key| strings.get(key).to_string());
+    if mode == 5 {
+        let addr = menu::prompt_line(window, strings.get("prompt.join_addr"))?;
+        let joined = match relay::split_room(addr.trim()) {
+            Some((relay, room)) => {
+                let room = match room {
+                    "" => pick_room(window, strings, relay)?,
+                    room => Ok(room.to_string()),
+                };
+                room.and_then(|room| relay::join_room(relay, &room))
+                    .and_then(NetLink::join_stream)
+            }
+            None => NetLink::join(addr.trim()),
+        };
+        let (mut link, maps, level) = match joined {
+            Ok(joined) => joined,
+            Err(e) => return Some(net_failed(&e)),
+        };
+        if let Some(best_of) = link.best_of() {
+            let series = Series::new(sides(), best_of);
+            let message = versus::run(window, config, &maps, level, series, Some(&mut link));
+            link.close();
+            return message;
+        }
+        let game = match start_game(maps, None, level, None, true) {
+            Ok(game) => game,
+            Err(e) => return Some(level_error(strings, e)),
+        };
+        play(
+            window,
+            config,
+            game,
+            None,
+            stats,
+            None,
+            Some(TwoPlayer::Remote(&mut link)),
+        );
+        link.close();
+        return None;
+    }
+    let mut seat = match mode {
+        1 => match ask_hot_seat(window, strings)? {
+            Ok(seat) => Some(seat),
+            Err(message) => return Some(message),
+        },
+        _ => None,
+    };
+    let best_of = match mode {
+        2 | 4 => {
+            let text = menu::prompt_line(window, strings.get("prompt.best_of"))?;
+            match text.trim() {
+                "" => Some(DEFAULT_BEST_OF),
+                text => match text.parse() {
+                    Ok(best_of) => Some(best_of),
+                    Err(_) => return Some(strings.fill("msg.bad_number", &[("text", &text)])),
+                },
+            }
+        }
+        _ => None,
+    };
+    let title = strings.get("menu.select_level");
+    let level = LevelSelect::new(strings, title, map_list).run(window)?;
+    let mut link = None;
+    if mode >= 3 {
+        let addr = menu::prompt_line(window, strings.get("prompt.host_addr"))?;
+        let addr = match addr.trim() {
+            "" => "0.0.0.0",
+            addr => addr,
+        };
+        let waited = match relay::split_room(addr) {
+            Some((relay, room)) => relay::host_room(window, strings, relay, room),
+            None => netplay::wait_for_partner(window, strings, addr),
+        };
+        let stream = match waited {
+            Ok(stream) => stream?,
+            Err(e) => return Some(net_failed(&e)),
+        };
+        match NetLink::host(stream, map_list, level, best_of) {
+            Ok(hosted) => link = Some(hosted),
+            Err(e) => return Some(net_failed(&e)),
+        }
+    }
+    if let Some(best_of) = best_of {
+        let names = match link {
+            Some(_) => sides(),
+            None => {
+                [1, 2].map(|player| strings.fill("hotseat.default_name", &[("player", &player)]))
+            }
+        };
+        let series = Series::new(names, best_of);
+        let message = versus::run(window, config, map_list, level, series, link.as_mut());
+        if let Some(link) = &mut link {
+            link.close();
+        }
+        return message;
+    }
+    // Hot-seat players take turns alone.
+    let game = match start_game(map_list.to_vec(), None, level, None, seat.is_none()) {
+        Ok(game) => game,
+        Err(e) => return Some(level_error(strings, e)),
+    };
+    let two_player = match (&mut seat, &mut link) {
+        (Some(seat), _) => Some(TwoPlayer::HotSeat(seat)),
+        (_, Some(link)) => Some(TwoPlayer::Remote(link)),
+        _ => None,
+    };
+    play(window, config, game, None, stats, None, two_player);
+    if let Some(link) = &mut link {
+        link.close();
+    }
+    if let Some(seat) = &seat {
+        hotseat::show_scoreboard(window, strings, seat);
+    }
+    None
+}
+
+// Lists the rooms waiting at `relay` to pick one from. None if backed out
+// of.
+fn pick_room(
+    window: &dyn Renderer,
+    strings: &Strings,
+    relay: &str,
+) -> Option<Result<String, NetError>> {
+    let rooms = match relay::list_rooms(relay) {
+        Ok(rooms) if rooms.is_empty() => {
+            return Some(Err(NetError::Refused(
+                strings.get("relay.no_rooms").to_string(),
+            )))
+        }
+        Ok(rooms) => rooms,
+        Err(e) => return Some(Err(e)),
+    };
+    let names: Vec<&str> = rooms.iter().map(String::as_str).collect();
+    let title = strings.fill("relay.rooms", &[("relay", &relay)]);
+    let choice = menu::Menu::new(&title, &names).run(window)?;
+    Some(Ok(rooms[choice].clone()))
+}
+
+// Asks the hot-seat players' names and how long a turn is. None if backed
+// out of, or why the answers won't do.
+fn ask_hot_seat(window: &dyn Renderer, strings: &Strings) -> Option<Result<HotSeat, String>> {
+    let mut names = [1, 2].map(|player| {
+        let prompt = strings.fill("prompt.player_name", &[("player", &player)]);
+        menu::prompt_line(window, &prompt).map(|name| name.trim().to_string())
+    });
+    for (i, name) in names.iter_mut().enumerate() {
+        if name.as_deref() == Some("") {
+            *name = Some(strings.fill("hotseat.default_name", &[("player", &(i + 1))]));
+        }
+    }
+    let [Some(first), Some(second)] = names else {
+        return None;
+    };
+    let every = menu::prompt_line(window, strings.get("prompt.turn_moves"))?;
+    let every = match every.trim() {
+        "" => None,
+        text => match text.parse() {
+            Ok(every) => Some(every),
+            Err(_) => return Some(Err(strings.fill("msg.bad_number", &[("text", &text)]))),
+        },
+    };
+    Some(Ok(HotSeat::new([first, second], every)))
+}
+
+fn load_config(args: &Args, profile: &Profile) -> Result<Config, ConfigError> {
+    let mut config = config::load(args.config_file.clone(), profile, &args.config_overrides)?;
+    config.debug = args.debug;
+    Ok(config)
+}
+
+// The campaign's world in the progress file: the level file played, or the
+// built-in levels.
+fn campaign_key(level_path: &Option<PathBuf>) -> String {
+    match level_path {
+        Some(path) => path.display().to_string(),
+        None => "builtin".to_string(),
+    }
+}
+
+// Asks whether to carry on with a saved game, or None if the question was
+// backed out of.
+fn offer_resume(
+    window: &dyn Renderer,
+    strings: &Strings,
+    title: &str,
+    game: &GameContext,
+) -> Option<bool> {
+    let title = strings.fill(
+        title,
+        &[("level", &game.level), ("moves", &game.moves.len())],
+    );
+    let entries = ["resume.continue", "resume.discard"].map(|key| strings.get(key));
+    menu::Menu::new(&title, &entries)
+        .run(window)
+        .map(|choice| choice == 0)
+}
+
+// Saves `progress`, returning a message for the menu if it could not be.
+fn save_progress(strings: &Strings, progress: &Progress) -> Option<String> {
+    let e = progress.save().err()?;
+    Some(strings.fill("pack.save_failed", &[("error", &e)]))
+}
+
+// Prints a solution of `map`, checked by playing it, or why there is none.
+// Returns the par of the solution, if one was found.
+fn solve_level(level: usize, map: MapData, max_states: usize) -> Option<Par> {
+    if map.needs_two_players() {
+        println!(
+            "level {}: needs two players, which the solver can't play",
+            level
+        );
+        return None;
+    }
+    let solution = solver::solve(&map, max_states);
+    let states = solution.stats.states_explored;
+    let moves = match solution.result {
+        SolveResult::Solved(moves) => moves,
+        SolveResult::Unsolvable => {
+            println!("level {}: unsolvable ({} states)", level, states);
+            return None;
+        }
+        SolveResult::GaveUp => {
+            println!("level {}: gave up after {} states", level, states);
+            return None;
+        }
     };
+    let simulation = Simulation::run(map, &moves);
+    if !simulation.won() {
+        println!("level {}: the solution found does not win", level);
+        return None;
+    }
+    println!(
+        "level {}: {} ({} moves, {} pushes, {} states)",
+        level,
+        simulation.lurd(),
+        moves.len(),
+        solution.pushes,
+        states
+    );
+    Some(Par {
+        moves: moves.len(),
+        pushes: solution.pushes,
+    })
+}
 
-    let mut game = game::GameContext {
-        player,
-        map_data: None,
-        map_list: map::get_maps(),
-        level: 0,
+// Solves every level of the level file at `path`, or of each world of the
+// pack directory at `path`, and writes the pars found into the files.
+// Returns how many levels were left without one.
+fn write_pars(path: &Path, max_states: usize) -> Result<usize, PackError> {
+    let files = match path.is_dir() {
+        true => {
+            let pack = Pack::load(path)?;
+            let mut files = Vec::new();
+            for world in &pack.manifest.worlds {
+                files.push((pack.dir.join(&world.file), pack.world_maps(world)?));
+            }
+            files
+        }
+        false => {
+            let maps = load_level_file(path).map_err(|e| PackError::Level {
+                file: path.display().to_string(),
+                msg: e.to_string(),
+            })?;
+            vec![(path.to_path_buf(), maps)]
+        }
     };
-    game.load_current_level();
+    let mut unsolved = 0;
+    for (file, maps) in files {
+        println!("{}:", file.display());
+        let pars: Vec<Option<Par>> = maps
+            .into_iter()
+            .enumerate()
+            .map(|(level, map)| solve_level(level, map, max_states))
+            .collect();
+        unsolved += pars.iter().filter(|par| par.is_none()).count();
+        let text = fs::read_to_string(&file)?;
+        fs::write(&file, level_file::with_pars(&text, &pars))?;
+    }
+    Ok(unsolved)
+}
+
+fn start_game(
+    map_list: Vec<MapData>,
+    watcher: Option<LevelWatcher>,
+    level: usize,
+    spawn: Option<String>,
+    partner: bool,
+) -> Result<GameContext, LevelError> {
+    let mut game = GameContext::new(map_list, watcher);
+    game.level = level as u32;
+    game.spawn = spawn;
+    if partner {
+        game.add_partner();
+    }
+    game.load_current_level()?;
+    Ok(game)
+}
+
+// For a level with named spawn points, asks which to start at: Some(None)
+// for the usual one. None if the question was backed out of.
+fn choose_spawn(window: &dyn Renderer, strings: &Strings, map: &MapData) -> Option<Option<String>> {
+    if map.spawns.is_empty() {
+        return Some(None);
+    }
+    let names: Vec<&str> = map.spawns.keys().map(String::as_str).collect();
+    let entries: Vec<&str> = [strings.get("spawn.usual")]
+        .into_iter()
+        .chain(names.iter().copied())
+        .collect();
+    let choice = menu::Menu::new(strings.get("spawn.title"), &entries).run(window)?;
+    Some(choice.checked_sub(1).map(|i| names[i].to_string()))
+}
 
+fn init_window(args: &Args, backend: Backend, config: &Config) -> Box<dyn Renderer> {
+    let window = renderer::open(backend, config.theme).unwrap_or_else(|e| {
+        eprintln!("could not set up the terminal: {}", e);
+        process::exit(1);
+    });
+    let window = key_macro::wrap(
+        window,
+        args.record_keys.as_deref(),
+        args.replay_keys.as_deref(),
+    )
+    .unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    match &args.broadcast {
+        Some(addr) => match spectate::Broadcaster::new(window, addr) {
+            Ok(broadcaster) => Box::new(broadcaster),
+            Err(e) => {
+                eprintln!("could not broadcast on {}: {}", addr, e);
+                process::exit(1);
+            }
+        },
+        None => window,
+    }
+}
+
+// Lets the player pick an unlocked world from the pack, recording each
+// completed level in the saved progress and on the leaderboard.
+fn play_pack(
+    window: &dyn Renderer,
+    config: &Config,
+    progress: &mut Progress,
+    leaderboard: &mut Leaderboard,
+    stats: &mut Stats,
+    profile: &Profile,
+    pack: &Pack,
+) -> Vec<(u32, String)> {
+    let strings = &config.strings;
+    let pack_id = pack.id();
+    let mut message = None;
+    let mut solutions = Vec::new();
     loop {
-        window.clear();
-        game.draw_all(&window);
-        window.refresh();
-        let k = window.getch();
-        match k {
-            Some(Input::KeyRight) => game.player_movement(Direction::Right),
-            Some(Input::KeyUp) => game.player_movement(Direction::Up),
-            Some(Input::KeyLeft) => game.player_movement(Direction::Left),
-            Some(Input::KeyDown) => game.player_movement(Direction::Down),
-            Some(Input::Character('r')) => game.load_current_level(),
-            Some(Input::Character('q')) => break,
-            _ => (),
-        };
-        game.update_all();
-        napms(20);
-    }
-
-    window.refresh();
-    endwin();
+        let stars = progress.stars(&pack_id);
+        let entries: Vec<String> = pack
+            .manifest
+            .worlds
+            .iter()
+            .map(|world| {
+                if stars < world.required_stars {
+                    strings.fill(
+                        "pack.world_locked",
+                        &[("world", &world.name()), ("stars", &world.required_stars)],
+                    )
+                } else {
+                    let done = progress.completed_in_world(&pack_id, &world.file);
+                    strings.fill(
+                        "pack.world_open",
+                        &[("world", &world.name()), ("done", &done)],
+                    )
+                }
+            })
+            .collect();
+        let entries: Vec<&str> = entries.iter().map(String::as_str).collect();
+        let title = strings.fill(
+            "pack.title",
+            &[("name", &pack.manifest.name), ("stars", &stars)],
+        );
+        let mut world_menu = menu::Menu::new(&title, &entries);
+        world_menu.message = message.take();
+        let Some(choice) = world_menu.run(window) else {
+            return solutions;
+        };
+        let world = &pack.manifest.worlds[choice];
+        if stars < world.required_stars {
+            message = Some(strings.fill("pack.locked", &[("world", &world.name())]));
+            continue;
+        }
+        // The story plays on the way into a world nothing is done in yet.
+        if progress.completed_in_world(&pack_id, &world.file) == 0 {
+            match pack.story_pages(world) {
+                Ok(pages) => story::show(window, config, &pages),
+                Err(e) => log::warn!("{}", e),
+            }
+        }
+        match pack.world_maps(world) {
+            Ok(maps) => {
+                let mut select = LevelSelect::new(strings, world.name(), &maps).with_leaderboard(
+                    leaderboard,
+                    &pack_id,
+                    &world.file,
+                );
+                if config.scoring {
+                    let scores = (0..maps.len())
+                        .map(|level| progress.score(&pack_id, &world.file, level))
+                        .collect();
+                    select = select.with_scores(scores, progress.pack_score(&pack_id));
+                }
+                let Some(level) = select.run(window) else {
+                    continue;
+                };
+                let Some(spawn) = choose_spawn(window, strings, &maps[level]) else {
+                    continue;
+                };
+                let game = match start_game(maps, None, level, spawn, false) {
+                    Ok(game) => game,
+                    Err(e) => {
+                        message = Some(level_error(strings, e));
+                        continue;
+                    }
+                };
+                let music = pack.music_paths();
+                let record = Record {
+                    progress,
+                    leaderboard,
+                    pack_id: &pack_id,
+                    world_file: &world.file,
+                    music: &music,
+                };
+                solutions.extend(play(
+                    window,
+                    config,
+                    game,
+                    Some(record),
+                    stats,
+                    Some(profile),
+                    None,
+                ));
+            }
+            Err(e) => message = Some(e.to_string()),
+        }
+    }
 }
@@ -0,0 +1,72 @@
+use crate::renderer::{Key, Renderer};
+
+pub struct Menu {
+    title: String,
+    entries: Vec<String>,
+    selected: usize,
+    pub message: Option<String>,
+}
+
+impl Menu {
+    pub fn new(title: &str, entries: &[&str]) -> Self {
+        Self {
+            title: title.to_string(),
+            entries: entries.iter().map(|e| e.to_string()).collect(),
+            selected: 0,
+            message: None,
+        }
+    }
+    pub fn draw(&self, window: &dyn Renderer) {
+        window.print(1, 2, &self.title);
+        for (i, entry) in self.entries.iter().enumerate() {
+            let marker = if i == self.selected { '>' } else { ' ' };
+            window.print(3 + i as i32, 2, &format!("{} {}", marker, entry));
+        }
+        if let Some(message) = &self.message {
+            window.print(4 + self.entries.len() as i32, 2, message);
+        }
+    }
+    // Blocks until an entry is chosen. Returns None if the menu was backed out of.
+    pub fn run(&mut self, window: &dyn Renderer) -> Option<usize> {
+        loop {
+            window.clear();
+            self.draw(window);
+            window.refresh();
+            match window.read_key() {
+                Some(Key::Up) => {
+                    self.selected = self
+                        .selected
+                        .checked_sub(1)
+                        .unwrap_or(self.entries.len() - 1);
+                }
+                Some(Key::Down) => self.selected = (self.selected + 1) % self.entries.len(),
+                Some(Key::Enter) => {
+                    self.message = None;
+                    return Some(self.selected);
+                }
+                Some(Key::Char('q')) => return None,
+                _ => (),
+            }
+        }
+    }
+}
+
+// Reads a line of text at the bottom of the screen. Escape cancels.
+pub fn prompt_line(window: &dyn Renderer, prompt: &str) -> Option<String> {
+    let mut line = String::new();
+    let row = window.height() - 1;
+    loop {
+        window.clear_line(row);
+        window.print(row, 0, &format!("{}{}", prompt, line));
+        window.refresh();
+        match window.read_key() {
+            Some(Key::Enter) => return Some(line),
+            Some(Key::Escape) => return None,
+            Some(Key::Backspace) => {
+                line.pop();
+            }
+            Some(Key::Char(c)) if !c.is_control() => line.push(c),
+            _ => (),
+        }
+    }
+}
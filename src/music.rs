@@ -0,0 +1,152 @@
+// Level pack music. A pack's manifest can list tracks (see `pack`), and
+// level N loops track N of them, wrapping round. When the track changes
+// between levels the old one fades out as the new one fades in. It is only
+// heard in builds with the `sound` feature; `music` in `[sound.volume]` sets
+// how loud, and the mute key silences it along with the sound effects.
+use crate::{config::SoundConfig, sound::SoundOutput};
+use std::path::PathBuf;
+
+pub struct Music {
+    tracks: Vec<PathBuf>,
+    // The track playing now, as an index into `tracks`.
+    current: Option<usize>,
+    #[cfg(feature = "sound")]
+    player: Option<player::Player>,
+}
+
+impl Music {
+    pub fn new(tracks: &[PathBuf], sound: &SoundConfig) -> Self {
+        let wanted = !tracks.is_empty() && sound.output != SoundOutput::Off;
+        #[cfg(feature = "sound")]
+        let player = if wanted && sound.volume.music > 0 {
+            player::Player::open(sound.volume.music)
+        } else {
+            None
+        };
+        #[cfg(not(feature = "sound"))]
+        if wanted {
+            log::warn!("built without the sound feature, so there is no music");
+        }
+        Self {
+            tracks: tracks.to_vec(),
+            current: None,
+            #[cfg(feature = "sound")]
+            player,
+        }
+    }
+    // Starts the track for `level`, crossfading from the one before if it
+    // is a different track.
+    pub fn play_level(&mut self, level: u32) {
+        if self.tracks.is_empty() {
+            return;
+        }
+        let track = level as usize % self.tracks.len();
+        if self.current == Some(track) {
+            return;
+        }
+        self.current = Some(track);
+        log::debug!("music {}", self.tracks[track].display());
+        #[cfg(feature = "sound")]
+        if let Some(player) = &mut self.player {
+            player.switch_to(&self.tracks[track]);
+        }
+    }
+    pub fn set_muted(&mut self, muted: bool) {
+        #[cfg(feature = "sound")]
+        if let Some(player) = &mut self.player {
+            player.set_muted(muted);
+        }
+        #[cfg(not(feature = "sound"))]
+        let _ = muted;
+    }
+}
+
+#[cfg(feature = "sound")]
+mod player {
+    use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink, Source};
+    use std::{fs::File, io::BufReader, path::Path, thread, time::Duration};
+
+    const CROSSFADE: Duration = Duration::from_millis(1500);
+    // How many volume steps the old track is faded out in.
+    const FADE_STEPS: u32 = 30;
+
+    pub struct Player {
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+        sink: Option<Sink>,
+        volume: f32,
+        muted: bool,
+    }
+
+    impl Player {
+        pub fn open(volume: u8) -> Option<Self> {
+            match OutputStream::try_default() {
+                Ok((stream, handle)) => Some(Self {
+                    _stream: stream,
+                    handle,
+                    sink: None,
+                    volume: volume as f32 / 100.0,
+                    muted: false,
+                }),
+                Err(e) => {
+                    log::warn!("no sound device, so no music: {}", e);
+                    None
+                }
+            }
+        }
+        pub fn switch_to(&mut self, path: &Path) {
+            if let Some(old) = self.sink.take() {
+                self.fade_out(old);
+            }
+            let track = File::open(path)
+                .map_err(|e| e.to_string())
+                .and_then(|file| {
+                    Decoder::new_looped(BufReader::new(file)).map_err(|e| e.to_string())
+                });
+            let track = match track {
+                Ok(track) => track,
+                Err(e) => {
+                    log::warn!("could not play {}: {}", path.display(), e);
+                    return;
+                }
+            };
+            let sink = match Sink::try_new(&self.handle) {
+                Ok(sink) => sink,
+                Err(e) => {
+                    log::warn!("could not play music: {}", e);
+                    return;
+                }
+            };
+            sink.set_volume(self.volume);
+            if self.muted {
+                sink.pause();
+            }
+            sink.append(track.fade_in(CROSSFADE));
+            self.sink = Some(sink);
+        }
+        // Turns `sink` down to nothing over the crossfade, on its own thread
+        // so the game doesn't wait for it. Dropping it at the end stops it.
+        fn fade_out(&self, sink: Sink) {
+            if self.muted {
+                return;
+            }
+            let volume = self.volume;
+            thread::spawn(move || {
+                for step in 1..=FADE_STEPS {
+                    thread::sleep(CROSSFADE / FADE_STEPS);
+                    sink.set_volume(volume * (1.0 - step as f32 / FADE_STEPS as f32));
+                }
+            });
+        }
+        pub fn set_muted(&mut self, muted: bool) {
+            self.muted = muted;
+            if let Some(sink) = &self.sink {
+                if muted {
+                    sink.pause();
+                } else {
+                    sink.play();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,380 @@
+// Co-op over the network: one game hosts, the other joins over TCP, and each
+// plays one of the two players on the same levels. The host sends the levels
+// when the other joins, then the two games exchange actions as JSON lines.
+// To keep both games in step, the host decides the order everything happens
+// in: it carries out its own actions as they come and sends them on, while
+// the joining game sends its actions to the host and only carries them out
+// once the host sends them back. So both games see the same actions in the
+// same order, and as the rules are deterministic, they stay the same.
+//
+// A versus race (see `versus`) is hosted the same way, but each game plays
+// its own copy of the level, so moves are carried out at once and sent on
+// for the other game's copy. The host judges who finished each round first,
+// and moves are marked with their round so a late one can't spill into the
+// next.
+use crate::{i18n::Strings, renderer::Renderer};
+use box_pushing_core::{action::GameAction, map::MapData, share_code, Direction};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+const NET_VERSION: u32 = 1;
+pub const DEFAULT_PORT: u16 = 7878;
+// How often waiting for the other game checks for a key to give up on.
+const WAIT_POLL_MS: i32 = 100;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Deserialize)]
+enum Message {
+    // From the host on joining: the levels as share codes and where to start.
+    Hello {
+        version: u32,
+        levels: Vec<String>,
+        level: usize,
+        // How many rounds a versus race is best of; none for co-op.
+        #[serde(default)]
+        best_of: Option<u32>,
+    },
+    Action(GameAction),
+    Race {
+        round: u32,
+        action: GameAction,
+    },
+    // The host's verdict on a round of a race.
+    RoundWon {
+        round: u32,
+        host: bool,
+    },
+    // The other game left.
+    Bye,
+}
+
+#[derive(Debug)]
+pub enum NetError {
+    Io(io::Error),
+    Protocol(String),
+    Version(u32),
+    // The relay turned the request down.
+    Refused(String),
+}
+
+impl fmt::Display for NetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Protocol(e) => write!(f, "the other game sent nonsense: {}", e),
+            Self::Version(v) => write!(f, "the other game speaks version {}", v),
+            Self::Refused(reason) => write!(f, "the relay refused: {}", reason),
+        }
+    }
+}
+
+impl From<io::Error> for NetError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+// Adds `port` to an address given without one.
+pub fn with_port(addr: &str, port: u16) -> String {
+    match addr.contains(':') {
+        true => addr.to_string(),
+        false => format!("{}:{}", addr, port),
+    }
+}
+
+fn send(stream: &mut TcpStream, message: &Message) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).map_err(io::Error::other)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+// Reads messages off `stream` on another thread. The channel closes when the
+// connection does.
+fn spawn_reader(stream: TcpStream) -> Receiver<Result<Message, String>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let message = serde_json::from_str(&line).map_err(|e| e.to_string());
+            if sender.send(message).is_err() {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+// Whether the other game needs to know about an action. Pausing, leaving and
+// the like only matter to the game they happen in.
+fn shared(action: GameAction) -> bool {
+    action.direction().is_some()
+        || action.partner_direction().is_some()
+        || matches!(
+            action,
+            GameAction::Undo | GameAction::Redo | GameAction::Restart
+        )
+}
+
+pub struct NetLink {
+    stream: TcpStream,
+    incoming: Receiver<Result<Message, String>>,
+    host: bool,
+    closed: bool,
+    best_of: Option<u32>,
+    round: u32,
+    // Who won the round, by the host's word: whether it was the host.
+    verdict: Option<bool>,
+}
+
+impl NetLink {
+    fn new(stream: TcpStream, host: bool) -> io::Result<Self> {
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            incoming: spawn_reader(stream.try_clone()?),
+            stream,
+            host,
+            closed: false,
+            best_of: None,
+            round: 0,
+            verdict: None,
+        })
+    }
+    // Hosts `levels`, starting at `level`, for the game that connected on
+    // `stream`: a co-op game, or a versus race that is best of `best_of`.
+    pub fn host(
+        stream: TcpStream,
+        levels: &[MapData],
+        level: usize,
+        best_of: Option<u32>,
+    ) -> io::Result<Self> {
+        let mut link = Self::new(stream, true)?;
+        link.best_of = best_of;
+        let hello = Message::Hello {
+            version: NET_VERSION,
+            levels: levels.iter().map(share_code::encode).collect(),
+            level,
+            best_of,
+        };
+        send(&mut link.stream, &hello)?;
+        Ok(link)
+    }
+    // Joins the game hosted at `addr`, returning its levels and the one to
+    // start at.
+    pub fn join(addr: &str) -> Result<(Self, Vec<MapData>, usize), NetError> {
+        let addr = with_port(addr, DEFAULT_PORT);
+        let socket = std::net::ToSocketAddrs::to_socket_addrs(&addr)?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, addr.clone()))?;
+        Self::join_stream(TcpStream::connect_timeout(&socket, CONNECT_TIMEOUT)?)
+    }
+    // Joins the game hosting on the other end of `stream`.
+    pub fn join_stream(stream: TcpStream) -> Result<(Self, Vec<MapData>, usize), NetError> {
+        let mut link = Self::new(stream, false)?;
+        let hello = link
+            .incoming
+            .recv_timeout(CONNECT_TIMEOUT)
+            .map_err(|_| NetError::Protocol("no levels sent".to_string()))?;
+        let Ok(Message::Hello {
+            version,
+            levels,
+            level,
+            best_of,
+        }) = hello
+        else {
+            return Err(NetError::Protocol("no levels sent".to_string()));
+        };
+        if version != NET_VERSION {
+            return Err(NetError::Version(version));
+        }
+        let maps = levels
+            .iter()
+            .map(|code| share_code::decode(code))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| NetError::Protocol(e.to_string()))?;
+        link.best_of = best_of;
+        Ok((link, maps, level))
+    }
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+    pub fn is_host(&self) -> bool {
+        self.host
+    }
+    // How many rounds the race is best of, if this is a versus race.
+    pub fn best_of(&self) -> Option<u32> {
+        self.best_of
+    }
+    fn send_message(&mut self, message: &Message) {
+        if self.closed {
+            return;
+        }
+        if let Err(e) = send(&mut self.stream, message) {
+            log::warn!("lost the other game: {}", e);
+            self.closed = true;
+        }
+    }
+    fn send(&mut self, action: GameAction) {
+        let message = match self.best_of {
+            Some(_) => Message::Race {
+                round: self.round,
+                action,
+            },
+            None => Message::Action(action),
+        };
+        self.send_message(&message);
+    }
+    // Tells the joining game who won the round, as the host.
+    pub fn send_verdict(&mut self, host_won: bool) {
+        let round = self.round;
+        self.send_message(&Message::RoundWon {
+            round,
+            host: host_won,
+        });
+    }
+    // The host's verdict on the round, once it has come.
+    pub fn take_verdict(&mut self) -> Option<bool> {
+        self.verdict.take()
+    }
+    // Moves on to the next round of the race.
+    pub fn next_round(&mut self) {
+        self.round += 1;
+        self.verdict = None;
+    }
+    // Takes an action of the player at this keyboard, whose moves move their
+    // own player whichever keys made them. Returns the action to carry out
+    // now, if any: the host's are, while the joining game waits for its own
+    // to come back. In a race every action is carried out at once.
+    pub fn local(&mut self, action: GameAction) -> Option<GameAction> {
+        if matches!(
+            action,
+            GameAction::Run
+                | GameAction::Grapple
+                | GameAction::UsePhase
+                | GameAction::UseUndo
+                | GameAction::Editor
+        ) {
+            return None;
+        }
+        if !shared(action) || self.closed {
+            return Some(action);
+        }
+        // In a race each game's own level copy has only player one.
+        let partner = !self.host && self.best_of.is_none();
+        let action = match action.direction().or(action.partner_direction()) {
+            Some(direction) => move_action(direction, partner),
+            None => action,
+        };
+        self.send(action);
+        (self.host || self.best_of.is_some()).then_some(action)
+    }
+    // The next action from the other game to carry out, if one has come.
+    pub fn poll(&mut self) -> Option<GameAction> {
+        loop {
+            let message = match self.incoming.try_recv() {
+                Ok(message) => message,
+                Err(TryRecvError::Empty) => return None,
+                Err(TryRecvError::Disconnected) => {
+                    self.closed = true;
+                    return None;
+                }
+            };
+            match message {
+                // The joining game only moves the partner.
+                Ok(Message::Action(action)) if self.host && action.direction().is_some() => {
+                    log::warn!("ignoring a move of the host's player");
+                }
+                Ok(Message::Action(action)) if shared(action) => {
+                    if self.host {
+                        self.send(action);
+                    }
+                    return Some(action);
+                }
+                Ok(Message::Race { round, action }) if shared(action) => {
+                    if round == self.round {
+                        return Some(action);
+                    }
+                    log::debug!("dropping a move from round {}", round);
+                }
+                Ok(Message::RoundWon { round, host }) if !self.host && round == self.round => {
+                    // Nothing after the verdict belongs to this round.
+                    self.verdict = Some(host);
+                    return None;
+                }
+                Ok(Message::Bye) => {
+                    self.closed = true;
+                    return None;
+                }
+                Ok(_) => log::warn!("ignoring an unexpected message"),
+                Err(e) => log::warn!("ignoring a bad message: {}", e),
+            }
+        }
+    }
+    // Tells the other game this one is leaving.
+    pub fn close(&mut self) {
+        if !self.closed {
+            let _ = send(&mut self.stream, &Message::Bye);
+            self.closed = true;
+        }
+    }
+}
+
+// The move of player one, or of the partner, in `direction`.
+fn move_action(direction: Direction, partner: bool) -> GameAction {
+    match (direction, partner) {
+        (Direction::Up, false) => GameAction::MoveUp,
+        (Direction::Down, false) => GameAction::MoveDown,
+        (Direction::Left, false) => GameAction::MoveLeft,
+        (Direction::Right, false) => GameAction::MoveRight,
+        (Direction::Up, true) => GameAction::PartnerUp,
+        (Direction::Down, true) => GameAction::PartnerDown,
+        (Direction::Left, true) => GameAction::PartnerLeft,
+        (Direction::Right, true) => GameAction::PartnerRight,
+    }
+}
+
+// Listens on `addr` until another game joins, or returns None if a key is
+// pressed first.
+pub fn wait_for_partner(
+    window: &dyn Renderer,
+    strings: &Strings,
+    addr: &str,
+) -> Result<Option<TcpStream>, NetError> {
+    let addr = with_port(addr, DEFAULT_PORT);
+    let listener = TcpListener::bind(&addr)?;
+    listener.set_nonblocking(true)?;
+    window.clear();
+    window.print(1, 2, &strings.fill("net.waiting", &[("addr", &addr)]));
+    window.print(3, 2, strings.get("net.cancel"));
+    window.refresh();
+    window.set_timeout(WAIT_POLL_MS);
+    let joined = loop {
+        match listener.accept() {
+            Ok((stream, peer)) => {
+                log::info!("{} joined", peer);
+                break Some(stream);
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => (),
+            Err(e) => {
+                window.set_timeout(-1);
+                return Err(e.into());
+            }
+        }
+        if window.read_key().is_some() {
+            break None;
+        }
+    };
+    window.set_timeout(-1);
+    if let Some(stream) = &joined {
+        stream.set_nonblocking(false)?;
+    }
+    Ok(joined)
+}
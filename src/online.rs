@@ -0,0 +1,157 @@
+// Online leaderboards, for when `leaderboard_url` is set in the config. Each
+// win of a level is checked against its replay (see `replay`) and then sent
+// to the server in the background; the leaderboard screen fetches a level's
+// rankings from it. Levels are named by their replay hash, so the same level
+// ranks together whichever file or pack it was played from. The server
+// takes:
+//
+//   POST URL/submit          {"level": HASH, "player": NAME, "moves": N,
+//                             "time_ms": N, "replay": REPLAY_FILE_TEXT}
+//   GET  URL/levels/HASH     {"by_moves": [RUN...], "by_time": [RUN...]}
+//
+// where each RUN is {"player": NAME, "moves": N, "time_ms": N}.
+use crate::{
+    ghost::Run,
+    i18n::Strings,
+    leaderboard::{board_lines, Entry},
+    renderer::Renderer,
+    replay::{level_hash, Replay},
+};
+use box_pushing_core::map::MapData;
+use serde::{Deserialize, Serialize};
+use std::{
+    thread::{self, JoinHandle},
+    time::Duration,
+};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+// Columns given to each board on the leaderboard screen.
+const BOARD_WIDTH: i32 = 32;
+
+#[derive(Serialize)]
+struct Submission {
+    level: String,
+    player: String,
+    moves: usize,
+    time_ms: u64,
+    replay: String,
+}
+
+#[derive(Deserialize)]
+struct RankedRun {
+    player: String,
+    moves: usize,
+    time_ms: u64,
+}
+
+#[derive(Deserialize)]
+struct Rankings {
+    by_moves: Vec<RankedRun>,
+    by_time: Vec<RankedRun>,
+}
+
+fn agent() -> ureq::Agent {
+    ureq::Agent::config_builder()
+        .timeout_global(Some(TIMEOUT))
+        .build()
+        .into()
+}
+
+fn level_url(server: &str, map: &MapData) -> String {
+    let hash = level_hash(map);
+    format!("{}/levels/{:016x}", server.trim_end_matches('/'), hash)
+}
+
+// Sends `player`'s winning run of `map` to the server on another thread, as
+// long as its replay holds up. Failures are only logged.
+pub fn submit(server: &str, player: &str, map: &MapData, run: Run) -> Option<JoinHandle<()>> {
+    let replay = Replay::new(map, run);
+    let time = match replay.verify(map) {
+        Ok(time) => time,
+        Err(e) => {
+            log::warn!("not submitting a run that fails its replay: {}", e);
+            return None;
+        }
+    };
+    let submission = Submission {
+        level: format!("{:016x}", replay.level_hash),
+        player: player.to_string(),
+        moves: replay.run.moves.len(),
+        time_ms: time.as_millis() as u64,
+        replay: replay.to_text(),
+    };
+    let url = format!("{}/submit", server.trim_end_matches('/'));
+    Some(thread::spawn(move || {
+        let sent = serde_json::to_string(&submission)
+            .map_err(|e| e.to_string())
+            .and_then(|body| {
+                agent()
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .send(body)
+                    .map_err(|e| e.to_string())
+            });
+        match sent {
+            Ok(_) => log::info!("submitted a run of level {}", submission.level),
+            Err(e) => log::warn!("could not submit to {}: {}", url, e),
+        }
+    }))
+}
+
+// The level's rankings from the server, by moves and by time.
+fn fetch(server: &str, map: &MapData) -> Result<[Vec<Entry>; 2], String> {
+    let text = agent()
+        .get(&level_url(server, map))
+        .call()
+        .map_err(|e| e.to_string())?
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| e.to_string())?;
+    let rankings: Rankings = serde_json::from_str(&text).map_err(|e| e.to_string())?;
+    Ok([rankings.by_moves, rankings.by_time].map(|runs| {
+        runs.into_iter()
+            .map(|run| Entry {
+                player: run.player,
+                moves: run.moves,
+                time: Duration::from_millis(run.time_ms),
+            })
+            .collect()
+    }))
+}
+
+// Shows the level's online rankings until any key is pressed, or returns
+// why they could not be fetched.
+pub fn show(
+    window: &dyn Renderer,
+    strings: &Strings,
+    server: &str,
+    level: usize,
+    map: &MapData,
+) -> Result<(), String> {
+    window.clear();
+    window.print(1, 2, strings.get("online.fetching"));
+    window.refresh();
+    let boards = fetch(server, map)?;
+    window.clear();
+    window.print(1, 2, &strings.fill("online.title", &[("level", &level)]));
+    for (column, (title, board)) in ["board.moves", "board.time"]
+        .into_iter()
+        .zip(&boards)
+        .enumerate()
+    {
+        let x = 2 + column as i32 * BOARD_WIDTH;
+        window.print(3, x, strings.get(title));
+        let board: Vec<&Entry> = board.iter().collect();
+        let lines = board_lines(strings, &board, column == 1);
+        if lines.is_empty() {
+            window.print(4, x, strings.get("online.empty"));
+        }
+        for (row, line) in lines.iter().enumerate() {
+            window.print(4 + row as i32, x, line);
+        }
+    }
+    window.print(window.height() - 1, 2, strings.get("stats.help"));
+    window.refresh();
+    window.read_key();
+    Ok(())
+}
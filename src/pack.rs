@@ -0,0 +1,333 @@
+// Level packs: a directory holding a `manifest.txt` and the level files it
+// lists. Each level file is a world; a world can require a number of stars
+// (levels completed anywhere in the pack) before it unlocks.
+//
+//   ; comment
+//   name: Example Pack
+//   levels: world1.txt
+//   levels: world2.txt requires 8
+//   story: world2.story
+//   tile: ~ ice.rhai
+//   players: 2
+//   music: theme.ogg
+//   music: cave.wav
+//
+// A `tile` line gives a glyph the pack's levels can use for a custom tile,
+// and the script that says how it behaves. `players: 2` makes a co-op pack,
+// whose levels may use the co-op tiles; a solo pack with them is refused.
+// `music` lines list tracks to loop behind the levels, taking turns level by
+// level; they are only heard in builds with the `sound` feature.
+// A `story` line gives a text file to show as an interlude before the world
+// on the `levels` line above it (see `story`).
+use crate::story;
+use box_pushing_core::{
+    level_file::{self, parse_levels_with},
+    map::MapData,
+    paths::levels_dir,
+    scripting::TileScripts,
+};
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+pub const MANIFEST_FILE: &str = "manifest.txt";
+
+#[derive(Debug)]
+pub enum PackError {
+    Io(std::io::Error),
+    Manifest { line: usize, msg: String },
+    Level { file: String, msg: String },
+}
+
+impl fmt::Display for PackError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Manifest { line, msg } => write!(f, "{} line {}: {}", MANIFEST_FILE, line, msg),
+            Self::Level { file, msg } => write!(f, "{}: {}", file, msg),
+        }
+    }
+}
+
+impl From<std::io::Error> for PackError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[derive(Clone)]
+pub struct World {
+    pub file: String,
+    pub required_stars: usize,
+    // The interlude shown before the world's levels, if any.
+    pub story: Option<String>,
+}
+
+impl World {
+    pub fn name(&self) -> &str {
+        self.file
+            .rsplit_once('.')
+            .map_or(&self.file, |(stem, _)| stem)
+    }
+}
+
+#[derive(Clone)]
+pub struct CustomTile {
+    pub glyph: char,
+    pub script: String,
+}
+
+#[derive(Clone)]
+pub struct Manifest {
+    pub name: String,
+    pub worlds: Vec<World>,
+    pub tiles: Vec<CustomTile>,
+    // 2 for a co-op pack, otherwise 1.
+    pub players: u32,
+    pub music: Vec<String>,
+}
+
+impl Manifest {
+    pub fn parse(text: &str) -> Result<Self, PackError> {
+        let mut name = None;
+        let mut worlds = Vec::new();
+        let mut tiles = Vec::new();
+        let mut players = 1;
+        let mut music = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let err = |msg: String| PackError::Manifest { line: i + 1, msg };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(':')
+                .ok_or_else(|| err("expected `key: value`".to_string()))?;
+            let value = value.trim();
+            match key.trim() {
+                "name" => name = Some(value.to_string()),
+                "levels" => worlds.push(parse_world(value).map_err(err)?),
+                "tile" => tiles.push(parse_tile(value).map_err(err)?),
+                "story" => {
+                    if !is_plain_file_name(value) {
+                        return Err(err(format!("`{}` is not a file in the pack", value)));
+                    }
+                    let Some(world) = worlds.last_mut() else {
+                        return Err(err("`story` comes before any `levels`".to_string()));
+                    };
+                    if world.story.is_some() {
+                        return Err(err(format!("{} already has a story", world.file)));
+                    }
+                    world.story = Some(value.to_string());
+                }
+                "players" => {
+                    players = match value {
+                        "1" => 1,
+                        "2" => 2,
+                        _ => return Err(err(format!("`{}` players is not 1 or 2", value))),
+                    }
+                }
+                "music" => {
+                    if !is_plain_file_name(value) {
+                        return Err(err(format!("`{}` is not a file in the pack", value)));
+                    }
+                    music.push(value.to_string());
+                }
+                other => return Err(err(format!("unknown key `{}`", other))),
+            }
+        }
+        let name = name.ok_or(PackError::Manifest {
+            line: 0,
+            msg: "missing `name`".to_string(),
+        })?;
+        if worlds.is_empty() {
+            return Err(PackError::Manifest {
+                line: 0,
+                msg: "pack lists no level files".to_string(),
+            });
+        }
+        Ok(Self {
+            name,
+            worlds,
+            tiles,
+            players,
+            music,
+        })
+    }
+    // Compiles the custom tile scripts, using `read` to fetch a file's
+    // contents by name. None if the pack has no custom tiles.
+    pub fn tile_scripts<F>(&self, mut read: F) -> Result<Option<Arc<TileScripts>>, PackError>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        if self.tiles.is_empty() {
+            return Ok(None);
+        }
+        let mut scripts = TileScripts::new();
+        for CustomTile { glyph, script } in &self.tiles {
+            let script_err = |msg: String| PackError::Level {
+                file: script.clone(),
+                msg,
+            };
+            let source = read(script).ok_or_else(|| script_err("missing from pack".to_string()))?;
+            scripts
+                .add(*glyph, &source)
+                .map_err(|e| script_err(e.to_string()))?;
+        }
+        Ok(Some(Arc::new(scripts)))
+    }
+    // Refuses co-op levels in a solo pack.
+    fn check_players(&self, maps: &[MapData]) -> Result<(), String> {
+        let coop = maps.iter().position(MapData::needs_two_players);
+        match (coop, self.players) {
+            (Some(level), 1) => Err(format!(
+                "level {} has co-op tiles, but the pack isn't marked `players: 2`",
+                level
+            )),
+            _ => Ok(()),
+        }
+    }
+    // Checks that every listed level file and tile script is present and
+    // parses, using `read` to fetch a file's contents by name.
+    pub fn validate<F>(&self, mut read: F) -> Result<(), PackError>
+    where
+        F: FnMut(&str) -> Option<String>,
+    {
+        let scripts = self.tile_scripts(&mut read)?;
+        for World { file, story, .. } in &self.worlds {
+            if let Some(story) = story {
+                if read(story).is_none() {
+                    return Err(PackError::Level {
+                        file: story.clone(),
+                        msg: "missing from pack".to_string(),
+                    });
+                }
+            }
+            let level_err = |msg: String| PackError::Level {
+                file: file.clone(),
+                msg,
+            };
+            let text = read(file).ok_or_else(|| level_err("missing from pack".to_string()))?;
+            let maps =
+                parse_levels_with(&text, scripts.as_ref()).map_err(|e| level_err(e.to_string()))?;
+            self.check_players(&maps).map_err(level_err)?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_world(value: &str) -> Result<World, String> {
+    let mut words = value.split_whitespace();
+    let file = words.next().unwrap_or("");
+    if !is_plain_file_name(file) {
+        return Err(format!("`{}` is not a file in the pack", file));
+    }
+    let required_stars = match (words.next(), words.next(), words.next()) {
+        (None, ..) => 0,
+        (Some("requires"), Some(n), None) => n
+            .parse()
+            .map_err(|_| format!("`{}` is not a star count", n))?,
+        _ => return Err("expected `levels: FILE [requires STARS]`".to_string()),
+    };
+    Ok(World {
+        file: file.to_string(),
+        required_stars,
+        story: None,
+    })
+}
+
+fn parse_tile(value: &str) -> Result<CustomTile, String> {
+    let (glyph, script) = value
+        .split_once(char::is_whitespace)
+        .ok_or("expected `tile: GLYPH FILE`")?;
+    let mut chars = glyph.chars();
+    let (Some(glyph), None) = (chars.next(), chars.next()) else {
+        return Err(format!("`{}` is not a single character", glyph));
+    };
+    // The glyph must not already mean something in a level file.
+    if level_file::tile_from_char(glyph).is_some() || "P.;:".contains(glyph) {
+        return Err(format!("`{}` is already a level file character", glyph));
+    }
+    let script = script.trim();
+    if !is_plain_file_name(script) {
+        return Err(format!("`{}` is not a file in the pack", script));
+    }
+    Ok(CustomTile {
+        glyph,
+        script: script.to_string(),
+    })
+}
+
+// Level file names in a manifest must stay inside the pack directory.
+pub fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && !name.starts_with('.') && !name.contains(['/', '\\', ':'])
+}
+
+pub struct Pack {
+    pub dir: PathBuf,
+    pub manifest: Manifest,
+}
+
+impl Pack {
+    pub fn load(dir: &Path) -> Result<Self, PackError> {
+        let manifest = Manifest::parse(&fs::read_to_string(dir.join(MANIFEST_FILE))?)?;
+        Ok(Self {
+            dir: dir.to_path_buf(),
+            manifest,
+        })
+    }
+    // Stable key for this pack in saved progress.
+    pub fn id(&self) -> String {
+        self.dir
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+    pub fn music_paths(&self) -> Vec<PathBuf> {
+        self.manifest
+            .music
+            .iter()
+            .map(|f| self.dir.join(f))
+            .collect()
+    }
+    // The pages of the interlude before `world`, or none if it has no story.
+    pub fn story_pages(&self, world: &World) -> Result<Vec<String>, PackError> {
+        let Some(file) = &world.story else {
+            return Ok(Vec::new());
+        };
+        let text = fs::read_to_string(self.dir.join(file)).map_err(|e| PackError::Level {
+            file: file.clone(),
+            msg: e.to_string(),
+        })?;
+        Ok(story::pages(&text))
+    }
+    pub fn world_maps(&self, world: &World) -> Result<Vec<MapData>, PackError> {
+        let read = |name: &str| fs::read_to_string(self.dir.join(name)).ok();
+        let scripts = self.manifest.tile_scripts(read)?;
+        let level_err = |msg: String| PackError::Level {
+            file: world.file.clone(),
+            msg,
+        };
+        let text =
+            fs::read_to_string(self.dir.join(&world.file)).map_err(|e| level_err(e.to_string()))?;
+        let maps =
+            parse_levels_with(&text, scripts.as_ref()).map_err(|e| level_err(e.to_string()))?;
+        self.manifest.check_players(&maps).map_err(level_err)?;
+        Ok(maps)
+    }
+}
+
+// All packs in the user's level directory, skipping anything that fails to load.
+pub fn installed_packs() -> Vec<Pack> {
+    let Ok(entries) = fs::read_dir(levels_dir()) else {
+        return Vec::new();
+    };
+    let mut packs: Vec<Pack> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| Pack::load(&e.path()).ok())
+        .collect();
+    packs.sort_by(|a, b| a.manifest.name.cmp(&b.manifest.name));
+    packs
+}
@@ -0,0 +1,845 @@
+// Runs a game as a state machine: each `GameState` reads keys and draws the
+// screen its own way, and the game ends when it goes back to the main menu.
+use crate::{
+    config::Config,
+    confirm::{ConfirmMode, Confirmation, Confirmed},
+    controls,
+    debug_console::Console,
+    describe, draw, editor,
+    ghost::{Ghost, Run},
+    help,
+    hint::{self, Hints},
+    hotseat::HotSeat,
+    i18n::Strings,
+    input_buffer::InputBuffer,
+    key_repeat::KeyRepeat,
+    leaderboard::{describe_boards, Leaderboard},
+    music::Music,
+    netplay::NetLink,
+    online, practice,
+    profile::Profile,
+    progress::{world_key, Best, NewRecords, Progress},
+    renderer::{Key, Renderer, Style},
+    scheduler::FrameScheduler,
+    sound::Speaker,
+    stats::Stats,
+    suspend::{self, Suspended},
+    TOP_PADDING,
+};
+use box_pushing_core::{
+    action::GameAction,
+    events::{GameEvent, Refusal},
+    game::{GameContext, GameState, LevelError, TICK_MS},
+    level_file, lurd,
+    map::Item,
+    pos::Pos,
+    score::Score,
+    share_code,
+};
+use std::{
+    io,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+// Columns given to each leaderboard on the level-complete screen.
+const BOARD_WIDTH: i32 = 28;
+
+// Where the wins of a game are recorded: the saved progress, and the world
+// in it being played. Games of level codes or random levels have none.
+pub struct Record<'a> {
+    pub progress: &'a mut Progress,
+    pub leaderboard: &'a mut Leaderboard,
+    pub pack_id: &'a str,
+    pub world_file: &'a str,
+    // The pack's music tracks, if it has any.
+    pub music: &'a [PathBuf],
+}
+
+impl Record<'_> {
+    fn best(&self, level: u32) -> Option<Best> {
+        self.progress
+            .best(self.pack_id, self.world_file, level as usize)
+    }
+    // The ghost to race on `game`'s level as it is now, if the config asks
+    // for one and the level has been won before.
+    fn ghost(&self, config: &Config, game: &GameContext) -> Option<Ghost> {
+        if !config.ghost {
+            return None;
+        }
+        let run = self
+            .progress
+            .fastest_run(self.pack_id, self.world_file, game.level as usize)?;
+        Some(Ghost::new(game.map_data.clone(), run))
+    }
+    fn player(&self) -> &str {
+        self.leaderboard.player()
+    }
+    fn boards(&self, strings: &Strings, level: u32) -> [Vec<String>; 2] {
+        let (pack_id, world_file) = (self.pack_id, self.world_file);
+        describe_boards(
+            strings,
+            self.leaderboard,
+            pack_id,
+            world_file,
+            level as usize,
+        )
+    }
+    // Unlocks the level a warp led to, resuming there next time, and saves.
+    fn warp(&mut self, level: u32) -> io::Result<()> {
+        let (pack_id, world_file, level) = (self.pack_id, self.world_file, level as usize);
+        self.progress.mark_warped(pack_id, world_file, level);
+        self.progress.set_resume(pack_id, world_file, level);
+        self.progress.save()
+    }
+    // Marks `level` won, resuming at the one after it next time.
+    fn complete(&mut self, level: usize, levels: usize) {
+        let (pack_id, world_file) = (self.pack_id, self.world_file);
+        self.progress.mark_completed(pack_id, world_file, level);
+        let next = (level + 1).min(levels.saturating_sub(1));
+        self.progress.set_resume(pack_id, world_file, next);
+    }
+    // An assisted win, or one LURD can't write down, completes the level and
+    // saves, but sets no records and goes on no leaderboard.
+    fn win_assisted(&mut self, level: u32, levels: usize) -> io::Result<()> {
+        self.complete(level as usize, levels);
+        self.progress.save()
+    }
+    // Completes `level` and saves, keeping `run` if it was the fastest yet
+    // and `score` if it was the highest, and adding it to the leaderboard.
+    // Returns the records `result` set.
+    fn win(
+        &mut self,
+        level: u32,
+        result: Best,
+        score: Option<Score>,
+        run: &Run,
+        levels: usize,
+    ) -> (NewRecords, io::Result<()>) {
+        let (pack_id, world_file, level) = (self.pack_id, self.world_file, level as usize);
+        self.complete(level, levels);
+        let mut records = self
+            .progress
+            .record_best(pack_id, world_file, level, result);
+        if let Some(score) = score {
+            records.score = self
+                .progress
+                .record_score(pack_id, world_file, level, score.total());
+        }
+        let mut saved = self.progress.save();
+        if records.time {
+            saved = saved.and(
+                self.progress
+                    .save_fastest_run(pack_id, world_file, level, run),
+            );
+        }
+        self.leaderboard
+            .add(pack_id, world_file, level, result.moves, result.time);
+        (records, saved.and(self.leaderboard.save()))
+    }
+}
+
+// Who else is playing a two-player game: someone taking turns at the same
+// keyboard, or another game over the network.
+pub enum TwoPlayer<'a> {
+    HotSeat(&'a mut HotSeat),
+    Remote(&'a mut NetLink),
+}
+
+// The personal best of the level being played and how it was last won, for
+// the HUD, and the leaderboards by moves and by time for the level-complete
+// screen.
+#[derive(Default)]
+struct Scores {
+    best: Option<Best>,
+    won: Option<(Best, NewRecords)>,
+    // What the last win scored, in scoring mode.
+    score: Option<Score>,
+    boards: [Vec<String>; 2],
+    // Whose turn it is in a hot-seat game.
+    turn: Option<String>,
+}
+
+// Runs the game until the player leaves it, returning the LURD solution of
+// every level won along the way. With a profile to save to, leaving
+// part-way through a level suspends it to be resumed later, and the level
+// is autosaved every `autosave_moves` moves in case the game crashes. A
+// hot-seat game hands the keyboard over as its turns run out, and a networked
+// one carries out the other game's actions as they arrive.
+pub fn play(
+    window: &dyn Renderer,
+    config: &Config,
+    mut game: GameContext,
+    mut record: Option<Record>,
+    stats: &mut Stats,
+    saves: Option<&Profile>,
+    two_player: Option<TwoPlayer>,
+) -> Vec<(u32, String)> {
+    let (mut seat, mut link) = match two_player {
+        Some(TwoPlayer::HotSeat(seat)) => (Some(seat), None),
+        Some(TwoPlayer::Remote(link)) => (None, Some(link)),
+        None => (None, None),
+    };
+    // When time played was last added to the stats.
+    let mut session = Instant::now();
+    // Assist mode lifts every undo limit.
+    game.assist = config.assist;
+    game.history
+        .set_limit(config.undo_limit.filter(|_| !config.assist));
+    let mut scheduler = FrameScheduler::new(Duration::from_millis(TICK_MS));
+    let mut repeat = KeyRepeat::new(Duration::from_millis(config.repeat_ms));
+    let mut buffer = if config.one_key {
+        InputBuffer::one_at_a_time(Duration::from_millis(config.min_move_ms.max(TICK_MS)))
+    } else {
+        InputBuffer::new(config.input_buffer, Duration::from_millis(TICK_MS))
+    };
+    let mut confirm = Confirmation::new(config.confirm);
+    let mut console = Console::default();
+    let mut solutions = Vec::new();
+    let mut left_mid_level = false;
+    // Where the levels come from, for filing their solutions.
+    let world = record
+        .as_ref()
+        .map(|record| world_key(record.pack_id, record.world_file));
+    let mut moves_since_autosave = 0;
+    // The level being timed and when it was started, and when each of its
+    // moves so far was made.
+    let mut timer = (game.level, Instant::now());
+    let mut move_times: Vec<Duration> = Vec::new();
+    let mut ghost = record.as_ref().and_then(|r| r.ghost(config, &game));
+    let mut hints = Hints::default();
+    let mut speaker = Speaker::new(config.sound.output, config.sound.volume);
+    let mut music = Music::new(record.as_ref().map_or(&[], |r| r.music), &config.sound);
+    music.play_level(game.level);
+    // Wins still being sent to the online leaderboard.
+    let mut submissions = Vec::new();
+    let mut scores = Scores {
+        best: record.as_ref().and_then(|r| r.best(game.level)),
+        ..Scores::default()
+    };
+    if let Some(seat) = &seat {
+        game.status = Some(turn_message(config, seat));
+    }
+
+    while game.state != GameState::MainMenu {
+        if game.level != timer.0 {
+            timer = (game.level, Instant::now());
+            move_times.clear();
+            scores.best = record.as_ref().and_then(|r| r.best(game.level));
+            ghost = record.as_ref().and_then(|r| r.ghost(config, &game));
+            hints = Hints::default();
+            music.play_level(game.level);
+        }
+        // Nothing moves on its own outside of play.
+        let racing = ghost.as_ref().is_some_and(|ghost| !ghost.finished());
+        // The other game's actions are looked for every tick.
+        let ticking = game.needs_ticks() || racing || link.is_some() || hints.is_shown();
+        scheduler.set_ticking(game.state == GameState::Playing && ticking);
+        if scheduler.take_redraw() {
+            window.clear();
+            draw_state(window, config, &game, &scores, ghost.as_ref());
+            if game.state == GameState::Playing {
+                hints.draw(window);
+            }
+            window.refresh();
+        }
+        window.set_timeout(earliest(
+            scheduler.timeout_ms(),
+            buffer.timeout_ms(Instant::now()),
+        ));
+        if let Some(key) = window.read_key() {
+            let repeats = matches!(
+                game.state,
+                GameState::Playing | GameState::Editor(_) | GameState::Arranging(_)
+            );
+            if repeat.accept(key, Instant::now(), repeats) {
+                buffer.push(key, Instant::now());
+            }
+        }
+        if let Some(key) = buffer.pop_due(Instant::now()) {
+            let was_playing = game.state == GameState::Playing;
+            let in_level = matches!(game.state, GameState::Playing | GameState::Paused);
+            let action = config.keys.action(key);
+            let show_me = game.state == GameState::Paused
+                && link.is_none()
+                && action == Some(GameAction::Hint);
+            if show_me {
+                hint::show_me(window, config, &mut game);
+            } else if in_level && action == Some(GameAction::Help) {
+                help::show(window, config);
+            } else if in_level && action == Some(GameAction::Describe) {
+                describe::announce(window, config, &mut game);
+            } else if in_level && action == Some(GameAction::Mute) {
+                let muted = speaker.toggle_mute();
+                music.set_muted(muted);
+                let message = if muted {
+                    "sound.muted"
+                } else {
+                    "sound.unmuted"
+                };
+                game.status = Some(config.strings.get(message).to_string());
+            } else if !(config.debug
+                && game.state == GameState::Playing
+                && console.handle(&mut game, key))
+            {
+                handle_key(
+                    config,
+                    &mut game,
+                    &mut confirm,
+                    key,
+                    &mut hints,
+                    link.as_deref_mut(),
+                    world.as_deref(),
+                );
+            }
+            // Keys meant for play don't carry over to whatever comes next.
+            if was_playing && game.state != GameState::Playing {
+                buffer.clear();
+                confirm.clear();
+            }
+            left_mid_level = in_level && game.state == GameState::MainMenu;
+            scheduler.mark_dirty();
+        }
+        if scheduler.tick_due() {
+            let ghost_moved = ghost
+                .as_mut()
+                .is_some_and(|ghost| ghost.advance(timer.1.elapsed()));
+            if game.tick() || ghost_moved || hints.expire() {
+                scheduler.mark_dirty();
+            }
+        }
+        if game.state != GameState::Playing {
+            continue;
+        }
+        if let Some(link) = link.as_deref_mut() {
+            let was_open = !link.is_closed();
+            while let Some(action) = link.poll() {
+                game.perform(action);
+                scheduler.mark_dirty();
+            }
+            if was_open && link.is_closed() {
+                game.status = Some(config.strings.get("net.lost").to_string());
+                scheduler.mark_dirty();
+            }
+        }
+        // Undo and restart take moves back; new ones are timed as they come.
+        move_times.truncate(game.moves.len());
+        let now = timer.1.elapsed();
+        move_times.resize(game.moves.len(), now);
+        let mut save_error = None;
+        let won = game.update_all();
+        if let Some(level) = won {
+            buffer.clear();
+            let moves = &game.moves;
+            let result = Best {
+                moves: moves.len(),
+                pushes: moves.chars().filter(char::is_ascii_uppercase).count(),
+                time: timer.1.elapsed(),
+            };
+            // Grapples and phases are moves too, though not in LURD.
+            let map = &game.map_data;
+            scores.score = config
+                .scoring
+                .then(|| Score::new(map.gems().0, map.turn(), map.par, result.time));
+            let mut records = NewRecords::default();
+            // A win in practice counts for nothing, not even completing the
+            // level, and is submitted nowhere.
+            if !game.practice {
+                solutions.extend(game.last_solution.clone());
+                // Only a win written down in LURD can be replayed, so any
+                // other sets no records either, like an assisted one.
+                let solution = game.last_solution.as_ref().filter(|_| !config.assist);
+                match (record.as_mut(), solution) {
+                    (Some(record), None) => {
+                        save_error = record.win_assisted(level, game.map_list.len()).err();
+                    }
+                    (Some(record), Some((_, moves))) => {
+                        let run = Run::new(moves, &move_times);
+                        let saved;
+                        (records, saved) =
+                            record.win(level, result, scores.score, &run, game.map_list.len());
+                        save_error = saved.err();
+                        scores.best = record.best(level);
+                        scores.boards = record.boards(&config.strings, level);
+                        if let (Some(server), Some(map)) =
+                            (&config.leaderboard_url, game.map_list.get(level as usize))
+                        {
+                            submissions.extend(online::submit(server, record.player(), map, run));
+                        }
+                    }
+                    (None, _) => (),
+                }
+            }
+            scores.won = Some((result, records));
+        }
+        let mut turn_over = false;
+        for event in game.events.drain() {
+            if let GameEvent::PlayerMoved { .. } = event {
+                moves_since_autosave += 1;
+                hints.hide();
+                if let Some(seat) = seat.as_mut() {
+                    turn_over |= seat.moved();
+                }
+            }
+            if let (GameEvent::Warped { to, .. }, Some(record)) = (event, record.as_mut()) {
+                save_error = save_error.or(record.warp(to).err());
+            }
+            // Like progress, the stats leave out whatever happens in practice.
+            if !game.practice {
+                stats.count(event);
+                if let Some(record) = &record {
+                    let world = world_key(record.pack_id, record.world_file);
+                    stats.count_level(&world, game.level as usize, event);
+                }
+            }
+            speaker.hear(event);
+            show_event(config, &mut game, event);
+        }
+        speaker.play(window);
+        if let Some(seat) = seat.as_mut() {
+            if won.is_some() {
+                seat.won();
+                turn_over = true;
+            }
+            if turn_over {
+                game.status = Some(turn_message(config, seat));
+            }
+            let turn = seat.turn();
+            scores.turn = Some(config.strings.fill(
+                "hotseat.turn",
+                &[("name", &seat.names[turn]), ("moves", &seat.moves[turn])],
+            ));
+        }
+        if let (Some(profile), true) = (
+            saves,
+            config.autosave_moves > 0 && moves_since_autosave >= config.autosave_moves,
+        ) {
+            moves_since_autosave = 0;
+            if let Err(e) = Suspended::new(&game, record.as_ref()).save(&profile.autosave_path()) {
+                log::warn!("could not autosave: {}", e);
+            }
+        }
+        if won.is_some() {
+            save_error = save_error.or(save_stats(stats, &mut session).err());
+        }
+        if let Some(e) = save_error {
+            game.status = Some(config.strings.fill("pack.save_failed", &[("error", &e)]));
+        }
+    }
+
+    window.set_timeout(-1);
+    for submission in submissions {
+        let _ = submission.join();
+    }
+    if let Err(e) = save_stats(stats, &mut session) {
+        log::warn!("could not save stats: {}", e);
+    }
+    let Some(profile) = saves else {
+        return solutions;
+    };
+    // Leaving normally, the autosave is no longer needed.
+    suspend::discard(&profile.autosave_path());
+    if left_mid_level && !game.moves.is_empty() {
+        let suspended = Suspended::new(&game, record.as_ref());
+        if let Err(e) = suspended.save(&profile.suspended_path()) {
+            log::warn!("could not suspend the level: {}", e);
+        }
+    }
+    solutions
+}
+
+// Adds the time played since `session` to the stats and saves them.
+fn save_stats(stats: &mut Stats, session: &mut Instant) -> io::Result<()> {
+    stats.time_played += session.elapsed();
+    *session = Instant::now();
+    stats.save()
+}
+
+// The sooner of two renderer timeouts, where -1 means none.
+fn earliest(a: i32, b: i32) -> i32 {
+    match (a, b) {
+        (-1, t) | (t, -1) => t,
+        (a, b) => a.min(b),
+    }
+}
+
+fn handle_key(
+    config: &Config,
+    game: &mut GameContext,
+    confirm: &mut Confirmation,
+    key: Key,
+    hints: &mut Hints,
+    link: Option<&mut NetLink>,
+    world: Option<&str>,
+) {
+    // The editor reads raw keys, since typing a tile's character places it.
+    if let GameState::Editor(_) = game.state {
+        return editor::handle_key(game, key);
+    }
+    if let GameState::Arranging(_) = game.state {
+        return practice::handle_key(config, game, key);
+    }
+    let action = config.keys.action(key);
+    match game.state {
+        GameState::Playing => {
+            let was_asking = confirm.is_pending();
+            match confirm.filter(key, action, !game.moves.is_empty(), Instant::now()) {
+                Confirmed::Act(action) => {
+                    game.status = None;
+                    handle_playing(config, game, action, hints, link, world);
+                }
+                Confirmed::Asking(action) => game.status = Some(ask(config, confirm, action)),
+                Confirmed::Cancelled => game.status = None,
+                Confirmed::Pass => {
+                    if was_asking {
+                        game.status = None;
+                    }
+                    match (key, action) {
+                        // Walking to a click happens in this game only.
+                        (Key::Click(y, x), _) if link.is_none() => {
+                            game.walk_to(Pos::new(y - TOP_PADDING, x))
+                        }
+                        (_, Some(action)) => {
+                            handle_playing(config, game, action, hints, link, world)
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        }
+        GameState::Paused => match action {
+            Some(GameAction::Pause) => game.state = GameState::Playing,
+            Some(GameAction::Menu) => game.state = GameState::MainMenu,
+            _ => (),
+        },
+        GameState::LevelComplete { .. } => game.next_level(),
+        GameState::GameOver => game.state = GameState::MainMenu,
+        GameState::Editor(_) | GameState::Arranging(_) | GameState::MainMenu => (),
+    }
+}
+
+// The status line question for an action waiting to be confirmed.
+fn ask(config: &Config, confirm: &Confirmation, action: GameAction) -> String {
+    let strings = &config.strings;
+    let label = controls::action_label(strings, action);
+    match confirm.mode() {
+        ConfirmMode::Hold => strings.fill(
+            "confirm.hold",
+            &[("action", &label), ("key", &config.keys.describe(action))],
+        ),
+        _ => strings.fill("confirm.prompt", &[("action", &label)]),
+    }
+}
+
+// In a networked game the action goes to the other game first, which may
+// mean carrying it out later or not at all. Exported solutions are filed
+// under `world`.
+fn handle_playing(
+    config: &Config,
+    game: &mut GameContext,
+    action: GameAction,
+    hints: &mut Hints,
+    link: Option<&mut NetLink>,
+    world: Option<&str>,
+) {
+    let strings = &config.strings;
+    // The other game would never see the board being arranged.
+    if action == GameAction::Practice && link.is_some() {
+        game.status = Some(strings.get("practice.networked").to_string());
+        return;
+    }
+    let action = match link {
+        Some(link) => match link.local(action) {
+            Some(action) => action,
+            None => return,
+        },
+        None => action,
+    };
+    match action {
+        GameAction::ShareCode => {
+            let code = share_code::encode(&game.map_data);
+            game.status = Some(strings.fill("status.level_code", &[("code", &code)]));
+        }
+        GameAction::ExportSolution => {
+            game.status = Some(match &game.last_solution {
+                Some((level, moves)) => match lurd::save_solution(world, *level, moves) {
+                    Ok(path) => strings.fill("status.solution_saved", &[("path", &path.display())]),
+                    Err(e) => strings.fill("status.solution_failed", &[("error", &e)]),
+                },
+                None => strings.get("status.no_solution").to_string(),
+            });
+        }
+        GameAction::ExportBoard => {
+            let text = game.board_text();
+            game.status = Some(
+                match level_file::save_board(game.level, game.moves.len(), &text) {
+                    Ok(path) => strings.fill("status.board_saved", &[("path", &path.display())]),
+                    Err(e) => strings.fill("status.board_failed", &[("error", &e)]),
+                },
+            );
+        }
+        GameAction::Hint => game.status = Some(hints.ask(config, game)),
+        _ => game.perform(action),
+    }
+}
+
+// Tells the players whose turn it is now.
+fn turn_message(config: &Config, seat: &HotSeat) -> String {
+    let name = seat.names[seat.turn()].as_str();
+    config.strings.fill("hotseat.your_turn", &[("name", &name)])
+}
+
+fn item_key(item: Item) -> &'static str {
+    match item {
+        Item::Phase => "item.phase",
+        Item::Undo => "item.undo",
+    }
+}
+
+fn refusal_key(reason: Refusal) -> &'static str {
+    match reason {
+        Refusal::NoPath => "refused.no_path",
+        Refusal::NothingToGrapple => "refused.nothing_to_grapple",
+        Refusal::NoGrapplesLeft => "refused.no_grapples",
+        Refusal::NothingToPhase => "refused.nothing_to_phase",
+        Refusal::NoPhasesLeft => "refused.no_phases",
+        Refusal::NoUndosLeft => "refused.no_undos",
+        Refusal::NoExtraUndosLeft => "refused.no_extra_undos",
+        Refusal::NothingToUndo => "refused.nothing_to_undo",
+        Refusal::NothingToRedo => "refused.nothing_to_redo",
+    }
+}
+
+// Why a level couldn't be loaded, in the player's language.
+pub fn level_error(strings: &Strings, error: LevelError) -> String {
+    match error {
+        LevelError::Missing { level, count: 0 } => {
+            strings.fill("level_error.no_levels", &[("level", &level)])
+        }
+        LevelError::Missing { level, count } => strings.fill(
+            "level_error.missing",
+            &[("level", &level), ("count", &count)],
+        ),
+        LevelError::NeedsPartner { level } => {
+            strings.fill("level_error.needs_partner", &[("level", &level)])
+        }
+    }
+}
+
+// The status line reports button, door and win events as they happen.
+fn show_event(config: &Config, game: &mut GameContext, event: GameEvent) {
+    let strings = &config.strings;
+    game.status = match event {
+        GameEvent::ButtonPressed { id } => {
+            Some(strings.fill("event.button_pressed", &[("id", &id)]))
+        }
+        GameEvent::DoorOpened { id } => Some(strings.fill("event.door_opened", &[("id", &id)])),
+        GameEvent::DoorClosed { id } => Some(strings.fill("event.door_closed", &[("id", &id)])),
+        GameEvent::GemCollected => Some(strings.get("event.gem_collected").to_string()),
+        GameEvent::ItemPickedUp { item } => Some(strings.fill(
+            "event.item_picked_up",
+            &[("item", &strings.get(item_key(item)))],
+        )),
+        GameEvent::HoldDoor { open: true } => Some(strings.get("event.hold_opened").to_string()),
+        GameEvent::HoldDoor { open: false } => Some(strings.get("event.hold_closed").to_string()),
+        GameEvent::LevelWon { level } => {
+            Some(strings.fill("event.level_won", &[("level", &level)]))
+        }
+        GameEvent::Warped { to, .. } => Some(strings.fill("event.warped", &[("level", &to)])),
+        GameEvent::Refused { reason } => Some(strings.get(refusal_key(reason)).to_string()),
+        GameEvent::BoxStuck { .. } => Some(strings.get("assist.stuck").to_string()),
+        GameEvent::LoadFailed { error } => Some(level_error(strings, error)),
+        GameEvent::LevelsReloaded => Some(strings.get("status.reloaded").to_string()),
+        GameEvent::ReloadFailed => {
+            let error = game.watcher.as_ref().and_then(|w| w.error()).unwrap_or("");
+            Some(strings.fill("status.reload_failed", &[("error", &error)]))
+        }
+        _ => return,
+    };
+}
+
+pub fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!(
+        "{}:{:02}.{}",
+        secs / 60,
+        secs % 60,
+        time.subsec_millis() / 100
+    )
+}
+
+fn describe_result(config: &Config, key: &str, result: &Best) -> String {
+    config.strings.fill(
+        key,
+        &[
+            ("moves", &result.moves),
+            ("pushes", &result.pushes),
+            ("time", &format_time(result.time)),
+        ],
+    )
+}
+
+// The best line above play, and on the level-complete screen how the level
+// went, with any new records picked out.
+fn draw_scores(window: &dyn Renderer, config: &Config, game: &GameContext, scores: &Scores) {
+    let strings = &config.strings;
+    if let Some(turn) = &scores.turn {
+        window.print(0, 0, turn);
+    }
+    let mut x = 0;
+    if let Some(best) = &scores.best {
+        let line = describe_result(config, "hud.best", best);
+        window.print(0, 0, &line);
+        x = line.chars().count() as i32 + 2;
+    }
+    if let Some(par) = game.map_data.par {
+        let line = strings.fill("hud.par", &[("moves", &par.moves), ("pushes", &par.pushes)]);
+        window.print(0, x, &line);
+        x += line.chars().count() as i32 + 2;
+    }
+    if game.map_data.grapples > 0 {
+        let line = strings.fill("hud.grapples", &[("left", &game.map_data.grapples_left())]);
+        window.print(0, x, &line);
+        x += line.chars().count() as i32 + 2;
+    }
+    let (gems, all_gems) = game.map_data.gems();
+    if config.scoring && all_gems > 0 {
+        let line = strings.fill("hud.gems", &[("gems", &gems), ("all", &all_gems)]);
+        window.print(0, x, &line);
+        x += line.chars().count() as i32 + 2;
+    }
+    if game.practice {
+        let line = strings.get("hud.practicing");
+        window.print(0, x, line);
+        x += line.chars().count() as i32 + 2;
+    }
+    if let Some(left) = game.undos_left() {
+        let line = strings.fill("hud.undos", &[("left", &left)]);
+        window.print(0, x, &line);
+        x += line.chars().count() as i32 + 2;
+    }
+    // The inventory, with the key that uses each power-up.
+    for (item, action) in [
+        (Item::Phase, GameAction::UsePhase),
+        (Item::Undo, GameAction::UseUndo),
+    ] {
+        let count = game.map_data.items(item);
+        if count == 0 {
+            continue;
+        }
+        let line = strings.fill(
+            "hud.item",
+            &[
+                ("item", &strings.get(item_key(item))),
+                ("count", &count),
+                ("key", &config.keys.describe(action)),
+            ],
+        );
+        window.print(0, x, &line);
+        x += line.chars().count() as i32 + 2;
+    }
+    let (GameState::LevelComplete { .. }, Some((result, records))) = (&game.state, &scores.won)
+    else {
+        return;
+    };
+    let mut line = describe_result(config, "hud.result", result);
+    if game.practice {
+        line = strings.fill("hud.practice", &[("result", &line)]);
+    } else if config.assist {
+        line = strings.fill("hud.assisted", &[("result", &line)]);
+    }
+    window.print(1, 0, &line);
+    if let Some(score) = &scores.score {
+        let text = strings.fill(
+            "hud.score",
+            &[
+                ("total", &score.total()),
+                ("gems", &score.gems),
+                ("par", &score.par_bonus),
+                ("time", &score.time_bonus),
+            ],
+        );
+        window.print(2, 0, &text);
+    }
+    // The leaderboards go side by side under the level.
+    let top = game.map_data.bounds().bottom_right.y + TOP_PADDING + 2;
+    for (column, (title, lines)) in ["board.moves", "board.time"]
+        .into_iter()
+        .zip(&scores.boards)
+        .enumerate()
+    {
+        let x = column as i32 * BOARD_WIDTH;
+        if !lines.is_empty() {
+            window.print(top, x, strings.get(title));
+        }
+        for (row, line) in lines.iter().enumerate() {
+            window.print(top + 1 + row as i32, x, line);
+        }
+    }
+    if records.any() {
+        let broken: Vec<&str> = [
+            (records.moves, "record.moves"),
+            (records.pushes, "record.pushes"),
+            (records.time, "record.time"),
+            (records.score, "record.score"),
+        ]
+        .into_iter()
+        .filter(|&(set, _)| set)
+        .map(|(_, key)| strings.get(key))
+        .collect();
+        let text = strings.fill("hud.new_record", &[("records", &broken.join(", "))]);
+        window.set_style(Style::WinPad);
+        window.print(1, line.chars().count() as i32 + 2, &text);
+        window.set_style(Style::Normal);
+    }
+}
+
+fn draw_state(
+    window: &dyn Renderer,
+    config: &Config,
+    game: &GameContext,
+    scores: &Scores,
+    ghost: Option<&Ghost>,
+) {
+    let strings = &config.strings;
+    let banner = match &game.state {
+        GameState::Editor(editor) => {
+            editor::draw_editor(window, &config.glyphs, strings, editor);
+            if let Some(status) = &game.status {
+                window.print(TOP_PADDING - 2, 0, status);
+            }
+            return;
+        }
+        GameState::Arranging(_) => Some(strings.get("practice.help").to_string()),
+        GameState::MainMenu | GameState::Playing => None,
+        GameState::Paused => Some(strings.fill(
+            "hud.paused",
+            &[
+                ("resume", &config.keys.describe(GameAction::Pause)),
+                ("show", &config.keys.describe(GameAction::Hint)),
+                ("quit", &config.keys.describe(GameAction::Menu)),
+            ],
+        )),
+        GameState::LevelComplete { level } => {
+            Some(strings.fill("hud.level_complete", &[("level", level)]))
+        }
+        GameState::GameOver => Some(strings.get("hud.game_over").to_string()),
+    };
+    draw::draw_game(window, config, game);
+    if let Some(ghost) = ghost.filter(|ghost| ghost.pos() != game.player.pos()) {
+        let Pos { y, x } = ghost.pos();
+        window.set_style(Style::Ghost);
+        window.put_char(y + TOP_PADDING, x, config.glyphs.ghost);
+        window.set_style(Style::Normal);
+    }
+    draw_scores(window, config, game, scores);
+    if let GameState::Arranging(arranging) = &game.state {
+        practice::draw_cursor(window, config, game, arranging);
+    }
+    if config.describe && game.state == GameState::Playing {
+        describe::draw(window, config, game);
+    }
+    if let Some(banner) = banner {
+        window.print(TOP_PADDING - 3, 0, &banner);
+    }
+}
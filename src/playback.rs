@@ -0,0 +1,108 @@
+// Watches a LURD solution play out on a level. Space pauses, left/right step
+// while paused, +/- change speed, r rewinds and q leaves. A replay's
+// verdict, if there is one, shows above the status line.
+use crate::{
+    config::Config,
+    draw::draw_game,
+    renderer::{Key, Renderer},
+    TOP_PADDING,
+};
+use box_pushing_core::{
+    game::{GameContext, GameState},
+    map::MapData,
+    Direction,
+};
+
+const SPEEDS_MS: [i32; 5] = [1000, 500, 250, 100, 40];
+
+pub fn run_playback(
+    window: &dyn Renderer,
+    config: &Config,
+    map: MapData,
+    moves: &[Direction],
+    verdict: Option<&str>,
+) {
+    let mut game = GameContext::new(vec![map.clone()], None);
+    let mut step = 0;
+    let mut paused = false;
+    // Start at the first speed at least as fast as the configured one.
+    let mut speed = SPEEDS_MS
+        .iter()
+        .position(|&ms| ms <= config.animation_ms)
+        .unwrap_or(SPEEDS_MS.len() - 1);
+
+    loop {
+        window.clear();
+        draw_game(window, config, &game);
+        if let Some(verdict) = verdict {
+            window.print(TOP_PADDING - 3, 0, verdict);
+        }
+        let state = if solved(&game) {
+            "playback.solved"
+        } else if paused {
+            "playback.paused"
+        } else {
+            "playback.playing"
+        };
+        window.print(
+            TOP_PADDING - 2,
+            0,
+            &config.strings.fill(
+                "playback.status",
+                &[
+                    ("step", &step),
+                    ("total", &moves.len()),
+                    ("state", &config.strings.get(state)),
+                    ("ms", &SPEEDS_MS[speed]),
+                ],
+            ),
+        );
+        window.refresh();
+
+        let running = !paused && step < moves.len() && !solved(&game);
+        window.set_timeout(if running { SPEEDS_MS[speed] } else { -1 });
+        match window.read_key() {
+            None if running => {
+                advance(&mut game, moves[step]);
+                step += 1;
+            }
+            Some(Key::Char(' ')) => paused = !paused,
+            Some(Key::Right) if paused && step < moves.len() && !solved(&game) => {
+                advance(&mut game, moves[step]);
+                step += 1;
+            }
+            Some(Key::Left) if paused && step > 0 => {
+                step -= 1;
+                game = replay(&map, &moves[..step]);
+            }
+            Some(Key::Char('+')) => speed = (speed + 1).min(SPEEDS_MS.len() - 1),
+            Some(Key::Char('-')) => speed = speed.saturating_sub(1),
+            Some(Key::Char('r')) => {
+                step = 0;
+                game = replay(&map, &[]);
+            }
+            Some(Key::Char('q')) => break,
+            _ => (),
+        }
+    }
+    window.set_timeout(-1);
+}
+
+fn solved(game: &GameContext) -> bool {
+    game.state == GameState::GameOver
+}
+
+fn advance(game: &mut GameContext, direction: Direction) {
+    game.player_movement(direction);
+    game.update_all();
+    game.events.drain();
+}
+
+// Rebuilds the state after `moves` from a fresh copy of the level.
+fn replay(map: &MapData, moves: &[Direction]) -> GameContext {
+    let mut game = GameContext::new(vec![map.clone()], None);
+    for &direction in moves {
+        advance(&mut game, direction);
+    }
+    game
+}
@@ -0,0 +1,70 @@
+// The practice screen, where the board is arranged by hand: the board as it
+// is in play with a cursor over it. Arrows move the cursor, space adds a box
+// or picks one up and sets it down elsewhere, Backspace takes a box away, P
+// puts the player at the cursor, undo and redo work as in play, and Enter or
+// Esc plays on from there.
+use crate::{
+    config::Config,
+    renderer::{Key, Renderer, Style},
+    TOP_PADDING,
+};
+use box_pushing_core::{
+    action::GameAction,
+    game::{GameContext, GameState},
+    pos::Pos,
+    practice::Arranging,
+};
+
+// The cursor, and the box picked up if there is one, drawn over the board.
+pub fn draw_cursor(
+    window: &dyn Renderer,
+    config: &Config,
+    game: &GameContext,
+    arranging: &Arranging,
+) {
+    let glyphs = &config.glyphs;
+    window.set_style(Style::Cursor);
+    for pos in arranging.held.into_iter().chain([arranging.cursor]) {
+        let glyph = if pos == game.player.pos() {
+            glyphs.player
+        } else {
+            let cell = game.map_data.cell_at(pos);
+            cell.object.map_or(glyphs.tile(cell.floor_type()), |tile| {
+                glyphs.tile(tile.tile_type)
+            })
+        };
+        window.put_char(pos.y + TOP_PADDING, pos.x, glyph);
+    }
+    window.set_style(Style::Normal);
+}
+
+pub fn handle_key(config: &Config, game: &mut GameContext, key: Key) {
+    let GameState::Arranging(arranging) = &mut game.state else {
+        return;
+    };
+    let action = config.keys.action(key);
+    if let Some(direction) = action.and_then(GameAction::direction) {
+        arranging.move_cursor(direction, game.map_data.bounds());
+        return;
+    }
+    match key {
+        Key::Char(' ') => game.arrange_box(),
+        Key::Backspace => game.arrange_remove(),
+        Key::Char('P') => game.arrange_player(),
+        Key::Click(y, x) => {
+            let pos = Pos::new(y - TOP_PADDING, x);
+            if game.map_data.bounds().contains(pos) {
+                arranging.cursor = pos;
+            }
+        }
+        Key::Enter | Key::Escape => {
+            game.status = None;
+            game.state = GameState::Playing;
+        }
+        _ => match action {
+            Some(GameAction::Undo) => game.undo(),
+            Some(GameAction::Redo) => game.redo(),
+            _ => (),
+        },
+    }
+}
@@ -0,0 +1,108 @@
+// Player profiles, so several people sharing a machine each keep their own
+// progress, stats, suspended and autosaved levels and settings. The default
+// profile uses the usual `progress.txt`, `stats.txt`, `suspended.toml`,
+// `autosave.toml` and `config.toml`; any other keeps them in `profiles/NAME`
+// in the data directory. The profile last chosen is remembered in
+// `profile.txt`.
+use crate::config;
+use box_pushing_core::paths::data_dir;
+use std::{fs, io, path::PathBuf};
+
+const PROFILES_DIR: &str = "profiles";
+const CURRENT_FILE: &str = "profile.txt";
+const PROGRESS_FILE: &str = "progress.txt";
+const STATS_FILE: &str = "stats.txt";
+const SUSPENDED_FILE: &str = "suspended.toml";
+const AUTOSAVE_FILE: &str = "autosave.toml";
+const LOCK_FILE: &str = "running.lock";
+const CONFIG_FILE: &str = "config.toml";
+pub const DEFAULT_PROFILE: &str = "default";
+
+#[derive(Clone, PartialEq)]
+pub struct Profile {
+    name: String,
+}
+
+impl Profile {
+    // Names are kept to letters, digits, `-` and `_`, since each becomes a
+    // directory name.
+    pub fn new(name: &str) -> Result<Self, String> {
+        let name = name.trim();
+        let valid = name
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+        if name.is_empty() || !valid {
+            return Err(format!(
+                "bad profile name `{}` (letters, digits, - and _ only)",
+                name
+            ));
+        }
+        Ok(Self {
+            name: name.to_string(),
+        })
+    }
+    // The profile chosen last time, or the default one.
+    pub fn current() -> Self {
+        fs::read_to_string(data_dir().join(CURRENT_FILE))
+            .ok()
+            .and_then(|name| Self::new(&name).ok())
+            .unwrap_or_else(|| Self {
+                name: DEFAULT_PROFILE.to_string(),
+            })
+    }
+    // Every profile there is, the default one first.
+    pub fn list() -> Vec<Self> {
+        let mut names: Vec<String> = fs::read_dir(data_dir().join(PROFILES_DIR))
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .filter(|name| name != DEFAULT_PROFILE)
+            .collect();
+        names.sort();
+        std::iter::once(DEFAULT_PROFILE.to_string())
+            .chain(names)
+            .filter_map(|name| Self::new(&name).ok())
+            .collect()
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    fn is_default(&self) -> bool {
+        self.name == DEFAULT_PROFILE
+    }
+    // Where this profile's own files go.
+    pub fn dir(&self) -> PathBuf {
+        match self.is_default() {
+            true => data_dir(),
+            false => data_dir().join(PROFILES_DIR).join(&self.name),
+        }
+    }
+    pub fn progress_path(&self) -> PathBuf {
+        self.dir().join(PROGRESS_FILE)
+    }
+    pub fn stats_path(&self) -> PathBuf {
+        self.dir().join(STATS_FILE)
+    }
+    pub fn suspended_path(&self) -> PathBuf {
+        self.dir().join(SUSPENDED_FILE)
+    }
+    pub fn autosave_path(&self) -> PathBuf {
+        self.dir().join(AUTOSAVE_FILE)
+    }
+    pub fn lock_path(&self) -> PathBuf {
+        self.dir().join(LOCK_FILE)
+    }
+    pub fn config_path(&self) -> PathBuf {
+        match self.is_default() {
+            true => config::config_path(),
+            false => self.dir().join(CONFIG_FILE),
+        }
+    }
+    // Makes this the profile used from now on, creating it if it is new.
+    pub fn select(&self) -> io::Result<()> {
+        fs::create_dir_all(self.dir())?;
+        fs::write(data_dir().join(CURRENT_FILE), &self.name)
+    }
+}
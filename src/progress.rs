@@ -0,0 +1,414 @@
+// Completed levels per pack, saved to the profile's `progress.txt` as
+// one `pack-id/world-file: level level ...` line per world, plus a
+// `resume pack-id/world-file: level` line for the level last played there
+// and a `best pack-id/world-file: level moves pushes millis` line for each
+// level won, and in scoring mode a `score pack-id/world-file: level points`
+// line with the level's best score, and a `warp pack-id/world-file: level`
+// line for the furthest level a warp has reached, which unlocks it and every
+// level it skipped. The fastest run of each level is kept
+// beside it, in `runs/WORLD/LEVEL.txt` (see `ghost`).
+// The built-in campaign, or a level file played from the command line, is
+// kept as a world of the `campaign` pack.
+use crate::ghost::Run;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs, io,
+    path::PathBuf,
+    time::Duration,
+};
+
+const RESUME_PREFIX: &str = "resume ";
+const BEST_PREFIX: &str = "best ";
+const SCORE_PREFIX: &str = "score ";
+const WARP_PREFIX: &str = "warp ";
+const RUNS_DIR: &str = "runs";
+pub const CAMPAIGN: &str = "campaign";
+
+#[derive(Default)]
+pub struct Progress {
+    path: PathBuf,
+    completed: BTreeMap<String, BTreeSet<usize>>,
+    resume: BTreeMap<String, usize>,
+    bests: BTreeMap<String, BTreeMap<usize, Best>>,
+    scores: BTreeMap<String, BTreeMap<usize, u32>>,
+    warped: BTreeMap<String, usize>,
+}
+
+// How well a level was played. As a personal best each part is the best of
+// any win, not necessarily all from the same one.
+#[derive(Clone, Copy, Debug)]
+pub struct Best {
+    pub moves: usize,
+    pub pushes: usize,
+    pub time: Duration,
+}
+
+// Which parts of a win beat the personal best.
+#[derive(Clone, Copy, Default)]
+pub struct NewRecords {
+    pub moves: bool,
+    pub pushes: bool,
+    pub time: bool,
+    // Set apart from the rest, since only scoring mode keeps scores.
+    pub score: bool,
+}
+
+impl NewRecords {
+    pub fn any(self) -> bool {
+        self.moves || self.pushes || self.time || self.score
+    }
+}
+
+fn parse_best(text: &str) -> Option<(usize, Best)> {
+    let numbers: Vec<u64> = text
+        .split_whitespace()
+        .map(|n| n.parse().ok())
+        .collect::<Option<_>>()?;
+    let &[level, moves, pushes, millis] = numbers.as_slice() else {
+        return None;
+    };
+    let best = Best {
+        moves: moves as usize,
+        pushes: pushes as usize,
+        time: Duration::from_millis(millis),
+    };
+    Some((level as usize, best))
+}
+
+pub fn world_key(pack_id: &str, world_file: &str) -> String {
+    format!("{}/{}", pack_id, world_file)
+}
+
+impl Progress {
+    // Missing or unreadable progress just means nothing has been completed.
+    pub fn load(path: PathBuf) -> Self {
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        Self::parse(path, &text)
+    }
+    // Reads progress as saved, to be saved back to `path`.
+    pub fn parse(path: PathBuf, text: &str) -> Self {
+        let mut progress = Self {
+            path,
+            ..Self::default()
+        };
+        for line in text.lines() {
+            let Some((key, levels)) = line.rsplit_once(':') else {
+                continue;
+            };
+            if let Some(key) = key.strip_prefix(RESUME_PREFIX) {
+                if let Ok(level) = levels.trim().parse() {
+                    progress.resume.insert(key.trim().to_string(), level);
+                }
+                continue;
+            }
+            if let Some(key) = key.strip_prefix(BEST_PREFIX) {
+                if let Some((level, best)) = parse_best(levels) {
+                    let world = progress.bests.entry(key.trim().to_string()).or_default();
+                    world.insert(level, best);
+                }
+                continue;
+            }
+            if let Some(key) = key.strip_prefix(WARP_PREFIX) {
+                if let Ok(level) = levels.trim().parse() {
+                    progress.warped.insert(key.trim().to_string(), level);
+                }
+                continue;
+            }
+            if let Some(key) = key.strip_prefix(SCORE_PREFIX) {
+                if let Some((level, points)) = levels.trim().split_once(' ') {
+                    if let (Ok(level), Ok(points)) = (level.parse(), points.trim().parse()) {
+                        let world = progress.scores.entry(key.trim().to_string()).or_default();
+                        world.insert(level, points);
+                    }
+                }
+                continue;
+            }
+            let levels = levels
+                .split_whitespace()
+                .filter_map(|l| l.parse::<usize>().ok());
+            progress
+                .completed
+                .entry(key.trim().to_string())
+                .or_default()
+                .extend(levels);
+        }
+        progress
+    }
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, self.to_text())
+    }
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for (key, levels) in &self.completed {
+            let levels: Vec<String> = levels.iter().map(|l| l.to_string()).collect();
+            text.push_str(&format!("{}: {}\n", key, levels.join(" ")));
+        }
+        for (key, level) in &self.resume {
+            text.push_str(&format!("{}{}: {}\n", RESUME_PREFIX, key, level));
+        }
+        for (key, level) in &self.warped {
+            text.push_str(&format!("{}{}: {}\n", WARP_PREFIX, key, level));
+        }
+        for (key, levels) in &self.bests {
+            for (level, best) in levels {
+                text.push_str(&format!(
+                    "{}{}: {} {} {} {}\n",
+                    BEST_PREFIX,
+                    key,
+                    level,
+                    best.moves,
+                    best.pushes,
+                    best.time.as_millis()
+                ));
+            }
+        }
+        for (key, levels) in &self.scores {
+            for (level, points) in levels {
+                text.push_str(&format!("{}{}: {} {}\n", SCORE_PREFIX, key, level, points));
+            }
+        }
+        text
+    }
+    // Adds what `other` has to this: its completed levels, its records where
+    // they are better, and where to resume in worlds this has no place
+    // for. Returns how many levels' records improved, and the levels
+    // whose fastest time now comes from `other`.
+    pub fn merge(&mut self, other: &Progress) -> (usize, Vec<(String, usize)>) {
+        for (key, levels) in &other.completed {
+            self.completed
+                .entry(key.clone())
+                .or_default()
+                .extend(levels);
+        }
+        for (key, &level) in &other.resume {
+            self.resume.entry(key.clone()).or_insert(level);
+        }
+        for (key, &level) in &other.warped {
+            let ours = self.warped.entry(key.clone()).or_default();
+            *ours = (*ours).max(level);
+        }
+        let mut improved = 0;
+        let mut faster = Vec::new();
+        for (key, levels) in &other.bests {
+            let world = self.bests.entry(key.clone()).or_default();
+            for (&level, theirs) in levels {
+                let Some(ours) = world.get_mut(&level) else {
+                    world.insert(level, *theirs);
+                    improved += 1;
+                    faster.push((key.clone(), level));
+                    continue;
+                };
+                if theirs.time < ours.time {
+                    faster.push((key.clone(), level));
+                }
+                let merged = Best {
+                    moves: ours.moves.min(theirs.moves),
+                    pushes: ours.pushes.min(theirs.pushes),
+                    time: ours.time.min(theirs.time),
+                };
+                if (merged.moves, merged.pushes, merged.time)
+                    != (ours.moves, ours.pushes, ours.time)
+                {
+                    improved += 1;
+                }
+                *ours = merged;
+            }
+        }
+        for (key, levels) in &other.scores {
+            let world = self.scores.entry(key.clone()).or_default();
+            for (&level, &theirs) in levels {
+                let ours = world.entry(level).or_default();
+                *ours = (*ours).max(theirs);
+            }
+        }
+        (improved, faster)
+    }
+    pub fn mark_completed(&mut self, pack_id: &str, world_file: &str, level: usize) {
+        self.completed
+            .entry(world_key(pack_id, world_file))
+            .or_default()
+            .insert(level);
+    }
+    pub fn is_completed(&self, pack_id: &str, world_file: &str, level: usize) -> bool {
+        self.completed
+            .get(&world_key(pack_id, world_file))
+            .is_some_and(|levels| levels.contains(&level))
+    }
+    // Levels up to and including this one can be played: the one after the
+    // furthest completed, or the furthest warped to if that is further.
+    pub fn highest_unlocked(&self, pack_id: &str, world_file: &str) -> usize {
+        let key = world_key(pack_id, world_file);
+        let completed = self
+            .completed
+            .get(&key)
+            .and_then(|levels| levels.last())
+            .map_or(0, |last| last + 1);
+        completed.max(self.warped.get(&key).copied().unwrap_or(0))
+    }
+    // Unlocks `level` and the levels before it, which a warp skipped.
+    pub fn mark_warped(&mut self, pack_id: &str, world_file: &str, level: usize) {
+        let furthest = self
+            .warped
+            .entry(world_key(pack_id, world_file))
+            .or_default();
+        *furthest = (*furthest).max(level);
+    }
+    pub fn set_resume(&mut self, pack_id: &str, world_file: &str, level: usize) {
+        self.resume.insert(world_key(pack_id, world_file), level);
+    }
+    // The level to pick up from: the one last played, or else the first.
+    pub fn resume_level(&self, pack_id: &str, world_file: &str) -> usize {
+        self.resume
+            .get(&world_key(pack_id, world_file))
+            .copied()
+            .unwrap_or(0)
+    }
+    pub fn best(&self, pack_id: &str, world_file: &str, level: usize) -> Option<Best> {
+        self.bests
+            .get(&world_key(pack_id, world_file))?
+            .get(&level)
+            .copied()
+    }
+    // Keeps whichever parts of `result` beat the best so far, returning
+    // which did. The first win of a level sets every record.
+    pub fn record_best(
+        &mut self,
+        pack_id: &str,
+        world_file: &str,
+        level: usize,
+        result: Best,
+    ) -> NewRecords {
+        let world = self
+            .bests
+            .entry(world_key(pack_id, world_file))
+            .or_default();
+        let Some(best) = world.get_mut(&level) else {
+            world.insert(level, result);
+            return NewRecords {
+                moves: true,
+                pushes: true,
+                time: true,
+                score: false,
+            };
+        };
+        let records = NewRecords {
+            moves: result.moves < best.moves,
+            pushes: result.pushes < best.pushes,
+            time: result.time < best.time,
+            score: false,
+        };
+        best.moves = best.moves.min(result.moves);
+        best.pushes = best.pushes.min(result.pushes);
+        best.time = best.time.min(result.time);
+        records
+    }
+    pub fn score(&self, pack_id: &str, world_file: &str, level: usize) -> Option<u32> {
+        self.scores
+            .get(&world_key(pack_id, world_file))?
+            .get(&level)
+            .copied()
+    }
+    // Keeps `points` if they beat the level's best score, returning whether
+    // they did.
+    pub fn record_score(
+        &mut self,
+        pack_id: &str,
+        world_file: &str,
+        level: usize,
+        points: u32,
+    ) -> bool {
+        let world = self
+            .scores
+            .entry(world_key(pack_id, world_file))
+            .or_default();
+        if world.get(&level).is_some_and(|&best| best >= points) {
+            return false;
+        }
+        world.insert(level, points);
+        true
+    }
+    fn run_path(&self, pack_id: &str, world_file: &str, level: usize) -> PathBuf {
+        self.key_run_path(&world_key(pack_id, world_file), level)
+    }
+    // Where the fastest run of a level goes. The world key is flattened to
+    // make one directory name.
+    fn key_run_path(&self, key: &str, level: usize) -> PathBuf {
+        let world: String = key
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        let dir = self.path.parent().map(PathBuf::from).unwrap_or_default();
+        dir.join(RUNS_DIR)
+            .join(world)
+            .join(format!("{}.txt", level))
+    }
+    pub fn fastest_run(&self, pack_id: &str, world_file: &str, level: usize) -> Option<Run> {
+        let text = fs::read_to_string(self.run_path(pack_id, world_file, level)).ok()?;
+        Run::parse(&text)
+            .map_err(|e| log::warn!("bad saved run for level {}: {}", level, e))
+            .ok()
+    }
+    pub fn save_fastest_run(
+        &self,
+        pack_id: &str,
+        world_file: &str,
+        level: usize,
+        run: &Run,
+    ) -> io::Result<()> {
+        let path = self.run_path(pack_id, world_file, level);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, run.to_text())
+    }
+    // The saved text of every level's fastest run, by world key and level.
+    pub fn run_texts(&self) -> Vec<(String, usize, String)> {
+        self.bests
+            .iter()
+            .flat_map(|(key, levels)| levels.keys().map(move |&level| (key, level)))
+            .filter_map(|(key, level)| {
+                let text = fs::read_to_string(self.key_run_path(key, level)).ok()?;
+                Some((key.clone(), level, text))
+            })
+            .collect()
+    }
+    pub fn save_run_text(&self, key: &str, level: usize, text: &str) -> io::Result<()> {
+        let path = self.key_run_path(key, level);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, text)
+    }
+    pub fn completed_in_world(&self, pack_id: &str, world_file: &str) -> usize {
+        self.completed
+            .get(&world_key(pack_id, world_file))
+            .map_or(0, |levels| levels.len())
+    }
+    // The best scores of every level anywhere in the pack, added up.
+    pub fn pack_score(&self, pack_id: &str) -> u32 {
+        let prefix = format!("{}/", pack_id);
+        self.scores
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .flat_map(|(_, levels)| levels.values())
+            .sum()
+    }
+    // One star per completed level anywhere in the pack.
+    pub fn stars(&self, pack_id: &str) -> usize {
+        let prefix = format!("{}/", pack_id);
+        self.completed
+            .iter()
+            .filter(|(key, _)| key.starts_with(&prefix))
+            .map(|(_, levels)| levels.len())
+            .sum()
+    }
+}
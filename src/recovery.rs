@@ -0,0 +1,50 @@
+// Noticing that the last session didn't end cleanly. While a profile is in
+// use its directory holds `running.lock` with the game's process id, and a
+// clean exit removes it. Finding the lock at start-up with no process of that
+// id still running means the game crashed or was killed, and the level
+// autosaved every `autosave_moves` moves (see `play`) is offered back.
+use crate::profile::Profile;
+use std::{fs, path::PathBuf, process};
+
+pub struct Lock {
+    path: PathBuf,
+}
+
+// Whether the process that wrote a lock is still running. Without `/proc`
+// to ask, it is taken to be gone.
+fn still_running(pid: &str) -> bool {
+    let proc = std::path::Path::new("/proc");
+    proc.is_dir() && proc.join(pid.trim()).exists()
+}
+
+impl Lock {
+    // Marks `profile` as in use, returning whether the session before left
+    // it marked.
+    pub fn acquire(profile: &Profile) -> (Self, bool) {
+        let path = profile.lock_path();
+        let crashed = match fs::read_to_string(&path) {
+            Ok(pid) => !still_running(&pid),
+            Err(_) => false,
+        };
+        if crashed {
+            log::warn!(
+                "the last session of profile {} did not exit cleanly",
+                profile.name()
+            );
+        }
+        let written = path
+            .parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|()| fs::write(&path, process::id().to_string()));
+        if let Err(e) = written {
+            log::warn!("could not write {}: {}", path.display(), e);
+        }
+        (Self { path }, crashed)
+    }
+    // Marks the profile as no longer in use, on a clean exit.
+    pub fn release(self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            log::warn!("could not remove {}: {}", self.path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,248 @@
+// A lobby for network games, so players don't have to share their IP
+// addresses or open ports. `--relay ADDR` runs it: a host registers a room by
+// name and waits, anyone can list the rooms waiting, and joining one pairs
+// the two connections. From then on the relay passes everything between
+// them untouched, so the games talk just as they would directly (see
+// `netplay`).
+//
+// Players reach a room by giving `RELAY/ROOM` where they would give an
+// address, or `RELAY/` when joining to pick from the rooms waiting there.
+// Each connection opens with one request as a JSON line, answered the same
+// way:
+//
+//   {"Host": {"room": "fred"}}   -> "Waiting", then "Paired" once joined
+//   {"Join": {"room": "fred"}}   -> "Paired", or {"Refused": "why"}
+//   "List"                       -> {"Rooms": ["fred", ...]}
+use crate::{
+    i18n::Strings,
+    netplay::{with_port, NetError},
+    renderer::Renderer,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+    net::{Shutdown, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+pub const DEFAULT_PORT: u16 = 7880;
+// How often waiting for a room to be joined checks for a key to give up on.
+const WAIT_POLL: Duration = Duration::from_millis(100);
+// Longest request or reply line taken, against junk connections.
+const MAX_LINE: usize = 4096;
+
+#[derive(Serialize, Deserialize)]
+enum Request {
+    Host { room: String },
+    Join { room: String },
+    List,
+}
+
+#[derive(Serialize, Deserialize)]
+enum Reply {
+    Waiting,
+    Paired,
+    Rooms(Vec<String>),
+    Refused(String),
+}
+
+// Hosts waiting for someone to join, by room name.
+type Rooms = Arc<Mutex<BTreeMap<String, TcpStream>>>;
+
+fn send<T: Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message).map_err(io::Error::other)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+// Reads one line a byte at a time, so nothing after it is taken off the
+// stream: once paired, what follows belongs to the game.
+fn receive<T: for<'de> Deserialize<'de>>(stream: &mut TcpStream) -> io::Result<T> {
+    let mut line = Vec::new();
+    let mut byte = [0];
+    while line.len() < MAX_LINE {
+        if stream.read(&mut byte)? == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        if byte[0] == b'\n' {
+            return serde_json::from_slice(&line).map_err(io::Error::other);
+        }
+        line.push(byte[0]);
+    }
+    Err(io::Error::other("line too long"))
+}
+
+// Whether a waiting host is still connected.
+fn still_there(stream: &TcpStream) -> bool {
+    if stream.set_nonblocking(true).is_err() {
+        return false;
+    }
+    let there = match stream.peek(&mut [0]) {
+        Ok(0) => false,
+        Ok(_) => true,
+        Err(e) => e.kind() == io::ErrorKind::WouldBlock,
+    };
+    there && stream.set_nonblocking(false).is_ok()
+}
+
+// Passes everything from one connection to the other until either closes.
+fn pipe(mut from: TcpStream, mut to: TcpStream) {
+    let _ = io::copy(&mut from, &mut to);
+    let _ = to.shutdown(Shutdown::Both);
+    let _ = from.shutdown(Shutdown::Both);
+}
+
+fn handle(mut stream: TcpStream, rooms: Rooms) -> io::Result<()> {
+    let request: Request = receive(&mut stream)?;
+    let mut rooms_waiting = rooms.lock().unwrap_or_else(|e| e.into_inner());
+    rooms_waiting.retain(|_, host| still_there(host));
+    match request {
+        Request::List => {
+            let names = rooms_waiting.keys().cloned().collect();
+            drop(rooms_waiting);
+            send(&mut stream, &Reply::Rooms(names))
+        }
+        Request::Host { room } if rooms_waiting.contains_key(&room) => {
+            drop(rooms_waiting);
+            send(
+                &mut stream,
+                &Reply::Refused(format!("room {} is taken", room)),
+            )
+        }
+        Request::Host { room } => {
+            send(&mut stream, &Reply::Waiting)?;
+            log::info!("room {} opened", room);
+            rooms_waiting.insert(room, stream);
+            Ok(())
+        }
+        Request::Join { room } => {
+            let Some(mut host) = rooms_waiting.remove(&room) else {
+                drop(rooms_waiting);
+                return send(&mut stream, &Reply::Refused(format!("no room {}", room)));
+            };
+            drop(rooms_waiting);
+            send(&mut host, &Reply::Paired)?;
+            send(&mut stream, &Reply::Paired)?;
+            log::info!("room {} joined", room);
+            let (host_copy, guest_copy) = (host.try_clone()?, stream.try_clone()?);
+            thread::spawn(move || pipe(host_copy, guest_copy));
+            pipe(stream, host);
+            Ok(())
+        }
+    }
+}
+
+// Runs the relay on `addr` until the process is stopped.
+pub fn serve(addr: &str) -> io::Result<()> {
+    let listener = TcpListener::bind(with_port(addr, DEFAULT_PORT))?;
+    println!("relaying on {}", listener.local_addr()?);
+    let rooms = Rooms::default();
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::warn!("could not accept: {}", e);
+                continue;
+            }
+        };
+        let rooms = Arc::clone(&rooms);
+        thread::spawn(move || {
+            if let Err(e) = handle(stream, rooms) {
+                log::warn!("dropped a connection: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+// Splits a `RELAY/ROOM` address, or returns None for a plain one.
+pub fn split_room(addr: &str) -> Option<(&str, &str)> {
+    addr.split_once('/')
+}
+
+fn connect(relay: &str, request: &Request) -> Result<TcpStream, NetError> {
+    let mut stream = TcpStream::connect(with_port(relay, DEFAULT_PORT))?;
+    send(&mut stream, request)?;
+    Ok(stream)
+}
+
+pub fn list_rooms(relay: &str) -> Result<Vec<String>, NetError> {
+    let mut stream = connect(relay, &Request::List)?;
+    match receive(&mut stream)? {
+        Reply::Rooms(rooms) => Ok(rooms),
+        Reply::Refused(reason) => Err(NetError::Refused(reason)),
+        _ => Err(NetError::Protocol("unexpected reply".to_string())),
+    }
+}
+
+// Joins `room` at the relay, returning the connection to its host.
+pub fn join_room(relay: &str, room: &str) -> Result<TcpStream, NetError> {
+    let room = room.to_string();
+    let mut stream = connect(relay, &Request::Join { room })?;
+    match receive(&mut stream)? {
+        Reply::Paired => Ok(stream),
+        Reply::Refused(reason) => Err(NetError::Refused(reason)),
+        _ => Err(NetError::Protocol("unexpected reply".to_string())),
+    }
+}
+
+// Whether a read gave up for want of anything to read.
+fn is_timeout(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+    )
+}
+
+// Opens `room` at the relay and waits for someone to join it, like
+// `netplay::wait_for_partner`. Returns None if a key is pressed first.
+pub fn host_room(
+    window: &dyn Renderer,
+    strings: &Strings,
+    relay: &str,
+    room: &str,
+) -> Result<Option<TcpStream>, NetError> {
+    let mut stream = connect(
+        relay,
+        &Request::Host {
+            room: room.to_string(),
+        },
+    )?;
+    match receive(&mut stream)? {
+        Reply::Waiting => (),
+        Reply::Refused(reason) => return Err(NetError::Refused(reason)),
+        _ => return Err(NetError::Protocol("unexpected reply".to_string())),
+    }
+    window.clear();
+    let place = format!("{}/{}", relay, room);
+    window.print(1, 2, &strings.fill("net.waiting", &[("addr", &place)]));
+    window.print(3, 2, strings.get("net.cancel"));
+    window.refresh();
+    window.set_timeout(WAIT_POLL.as_millis() as i32);
+    stream.set_read_timeout(Some(WAIT_POLL))?;
+    let paired = loop {
+        match stream.peek(&mut [0]) {
+            Ok(_) => break true,
+            Err(e) if is_timeout(&e) => (),
+            Err(e) => {
+                window.set_timeout(-1);
+                return Err(e.into());
+            }
+        }
+        if window.read_key().is_some() {
+            break false;
+        }
+    };
+    window.set_timeout(-1);
+    if !paired {
+        return Ok(None);
+    }
+    stream.set_read_timeout(None)?;
+    match receive(&mut stream)? {
+        Reply::Paired => Ok(Some(stream)),
+        _ => Err(NetError::Protocol("unexpected reply".to_string())),
+    }
+}
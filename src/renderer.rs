@@ -0,0 +1,184 @@
+// The screen and keyboard as the game sees them. Every screen draws through
+// `Renderer`, so the terminal library behind it can be swapped: curses, or
+// crossterm where curses is awkward to get (Windows in particular). The stub
+// backend needs no terminal at all.
+#[cfg(feature = "curses")]
+use crate::curses_renderer::CursesRenderer;
+use crate::{crossterm_renderer::CrosstermRenderer, stub_renderer::StubRenderer};
+use std::io;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Key {
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Backspace,
+    Escape,
+    Char(char),
+    // A function key, F1 to F12.
+    Function(u8),
+    // A left click at a screen row and column.
+    Click(i32, i32),
+}
+
+// Key names as written in the config file: a single character, or one of
+// up, down, left, right, enter, backspace, escape, space and f1 to f12. The stub renderer also
+// reads clicks as `click:ROW:COLUMN`.
+impl std::str::FromStr for Key {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut chars = s.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Self::Char(c));
+        }
+        match s.to_ascii_lowercase().as_str() {
+            "up" => Ok(Self::Up),
+            "down" => Ok(Self::Down),
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "enter" => Ok(Self::Enter),
+            "backspace" => Ok(Self::Backspace),
+            "escape" | "esc" => Ok(Self::Escape),
+            "space" => Ok(Self::Char(' ')),
+            function if function.starts_with('f') => match function[1..].parse() {
+                Ok(n @ 1..=12) => Ok(Self::Function(n)),
+                _ => Err(format!("unknown key `{}`", s)),
+            },
+            click if click.starts_with("click:") => {
+                let (y, x) = click["click:".len()..]
+                    .split_once(':')
+                    .and_then(|(y, x)| Some((y.parse().ok()?, x.parse().ok()?)))
+                    .ok_or_else(|| format!("bad click `{}` (click:ROW:COLUMN)", s))?;
+                Ok(Self::Click(y, x))
+            }
+            _ => Err(format!("unknown key `{}`", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Up => write!(f, "up"),
+            Self::Down => write!(f, "down"),
+            Self::Left => write!(f, "left"),
+            Self::Right => write!(f, "right"),
+            Self::Enter => write!(f, "enter"),
+            Self::Backspace => write!(f, "backspace"),
+            Self::Escape => write!(f, "escape"),
+            Self::Char(' ') => write!(f, "space"),
+            Self::Char(c) => write!(f, "{}", c),
+            Self::Function(n) => write!(f, "f{}", n),
+            Self::Click(y, x) => write!(f, "click:{}:{}", y, x),
+        }
+    }
+}
+
+// How a cell is drawn. Backends pick the actual colours.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Style {
+    Normal,
+    Wall,
+    PushBox,
+    Button,
+    ClosedDoor,
+    OpenDoor,
+    WinPad,
+    // The editor's cursor, drawn in reverse video.
+    Cursor,
+    // The ghost of an earlier run, drawn faintly.
+    Ghost,
+    // A box that can never move again, drawn in red.
+    Stuck,
+}
+
+// Whether styles are drawn in colour or all look like normal text.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Theme {
+    Color,
+    Mono,
+}
+
+impl std::str::FromStr for Theme {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "color" => Ok(Self::Color),
+            "mono" => Ok(Self::Mono),
+            _ => Err(format!("unknown theme `{}` (color or mono)", s)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Backend {
+    Curses,
+    Crossterm,
+    Stub,
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        if cfg!(windows) || cfg!(not(feature = "curses")) {
+            Self::Crossterm
+        } else {
+            Self::Curses
+        }
+    }
+}
+
+impl std::str::FromStr for Backend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "curses" => Ok(Self::Curses),
+            "crossterm" => Ok(Self::Crossterm),
+            "stub" => Ok(Self::Stub),
+            _ => Err(format!(
+                "unknown renderer `{}` (curses, crossterm or stub)",
+                s
+            )),
+        }
+    }
+}
+
+// Drawing is buffered until `refresh`. Dropping the renderer puts the
+// terminal back the way it was found.
+pub trait Renderer {
+    fn clear(&self);
+    // Clears row `y` from column 0 to the end.
+    fn clear_line(&self, y: i32);
+    fn print(&self, y: i32, x: i32, text: &str);
+    fn put_char(&self, y: i32, x: i32, c: char);
+    fn set_style(&self, style: Style);
+    fn refresh(&self);
+    fn height(&self) -> i32;
+    // Rings the terminal bell, for sound effects without a sound device.
+    fn bell(&self);
+    // How long `read_key` waits before giving up, or -1 to wait forever.
+    fn set_timeout(&self, ms: i32);
+    // The next key press, or None if the timeout ran out first.
+    fn read_key(&self) -> Option<Key>;
+}
+
+pub fn open(backend: Backend, theme: Theme) -> io::Result<Box<dyn Renderer>> {
+    let colors = theme == Theme::Color;
+    Ok(match backend {
+        #[cfg(feature = "curses")]
+        Backend::Curses => Box::new(CursesRenderer::new(colors)),
+        #[cfg(not(feature = "curses"))]
+        Backend::Curses => {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "this build has no curses support",
+            ))
+        }
+        Backend::Crossterm => Box::new(CrosstermRenderer::new(colors)?),
+        Backend::Stub => Box::new(StubRenderer::new()),
+    })
+}
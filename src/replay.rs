@@ -0,0 +1,115 @@
+// Replay files, for sharing a run so others can watch it and check it holds
+// up. A replay is a run (see `ghost`) plus a hash of the level it was played
+// on, so it can only be checked against that level:
+//
+//   # button_trial replay
+//   level 9b1f0c2d7e4a3358
+//   50 r
+//   100 r
+//
+// `--export-replay FILE` writes the fastest run of a level, and `--playback`
+// reads replay files as well as plain LURD ones, finding the level by its
+// hash when `--level` isn't given.
+use crate::ghost::Run;
+use box_pushing_core::{headless::Simulation, lurd, map::MapData, share_code, Direction};
+use std::{fs, path::Path, time::Duration};
+
+const HEADER: &str = "# button_trial replay";
+const LEVEL_PREFIX: &str = "level ";
+
+// FNV-1a of the level's share code, which stays the same across builds and
+// machines, unlike the standard library's hasher.
+pub fn level_hash(map: &MapData) -> u64 {
+    share_code::encode(map)
+        .bytes()
+        .fold(0xcbf29ce484222325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x100000001b3)
+        })
+}
+
+pub struct Replay {
+    pub level_hash: u64,
+    pub run: Run,
+}
+
+impl Replay {
+    pub fn new(map: &MapData, run: Run) -> Self {
+        Self {
+            level_hash: level_hash(map),
+            run,
+        }
+    }
+    // Whether `text` looks like a replay rather than plain LURD moves.
+    pub fn is_replay(text: &str) -> bool {
+        text.trim_start().starts_with(HEADER)
+    }
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut level_hash = None;
+        // Blank out the header lines so the run's errors keep their line
+        // numbers.
+        let mut moves = String::new();
+        for line in text.lines() {
+            if let Some(hash) = line.trim().strip_prefix(LEVEL_PREFIX) {
+                let hash = u64::from_str_radix(hash.trim(), 16)
+                    .map_err(|_| format!("bad level hash `{}`", hash.trim()))?;
+                level_hash = Some(hash);
+            } else if !line.trim_start().starts_with('#') {
+                moves.push_str(line);
+            }
+            moves.push('\n');
+        }
+        Ok(Self {
+            level_hash: level_hash.ok_or("no level hash in the replay")?,
+            run: Run::parse(&moves)?,
+        })
+    }
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        Self::parse(&text)
+    }
+    pub fn to_text(&self) -> String {
+        format!(
+            "{}\n{}{:016x}\n{}",
+            HEADER,
+            LEVEL_PREFIX,
+            self.level_hash,
+            self.run.to_text()
+        )
+    }
+    // The first of `maps` the replay was played on.
+    pub fn find_level(&self, maps: &[MapData]) -> Option<usize> {
+        maps.iter()
+            .position(|map| level_hash(map) == self.level_hash)
+    }
+    pub fn moves(&self) -> Vec<Direction> {
+        self.run
+            .moves
+            .iter()
+            .filter_map(|&(_, step)| lurd::move_direction(step))
+            .collect()
+    }
+    // Checks the replay wins `map` with its last move, returning how long it
+    // took.
+    pub fn verify(&self, map: &MapData) -> Result<Duration, String> {
+        if level_hash(map) != self.level_hash {
+            return Err("played on a different level".to_string());
+        }
+        let times = self.run.moves.iter().map(|&(time, _)| time);
+        if times.clone().zip(times.skip(1)).any(|(a, b)| b < a) {
+            return Err("moves out of order".to_string());
+        }
+        let moves = self.moves();
+        let simulation = Simulation::run(map.clone(), &moves);
+        if !simulation.won() {
+            return Err("does not win the level".to_string());
+        }
+        if simulation.steps.len() < moves.len() {
+            return Err("moves left over after the win".to_string());
+        }
+        Ok(self
+            .run
+            .moves
+            .last()
+            .map_or(Duration::ZERO, |&(time, _)| time))
+    }
+}
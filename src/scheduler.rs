@@ -0,0 +1,64 @@
+// Decides how long the play loop may sleep. The loop wakes for a key press
+// or the next timer deadline, whichever comes first; with no timer running it
+// waits on input alone, so an idle game costs nothing and a key is handled
+// the moment it arrives. The screen is only redrawn after something changed.
+use std::time::{Duration, Instant};
+
+pub struct FrameScheduler {
+    period: Duration,
+    next_tick: Option<Instant>,
+    redraw: bool,
+}
+
+impl FrameScheduler {
+    pub fn new(period: Duration) -> Self {
+        Self {
+            period,
+            next_tick: None,
+            redraw: true,
+        }
+    }
+    // Starts or stops the tick timer. Starting it again counts a whole
+    // period from now.
+    pub fn set_ticking(&mut self, ticking: bool) {
+        match (ticking, self.next_tick) {
+            (true, None) => self.next_tick = Some(Instant::now() + self.period),
+            (false, Some(_)) => self.next_tick = None,
+            _ => (),
+        }
+    }
+    pub fn mark_dirty(&mut self) {
+        self.redraw = true;
+    }
+    // Whether the screen needs drawing, clearing the request.
+    pub fn take_redraw(&mut self) -> bool {
+        std::mem::take(&mut self.redraw)
+    }
+    // How long to wait for input, in the renderer's terms: milliseconds
+    // until the next tick (rounded up, so the wait never ends early), or -1
+    // to wait forever.
+    pub fn timeout_ms(&self) -> i32 {
+        let Some(next_tick) = self.next_tick else {
+            return -1;
+        };
+        let wait = next_tick.saturating_duration_since(Instant::now());
+        wait.as_micros().div_ceil(1000).min(i32::MAX as u128) as i32
+    }
+    // Whether a tick is due, moving the deadline on if so. After a long
+    // stall it starts counting again rather than catching up.
+    pub fn tick_due(&mut self) -> bool {
+        let Some(next_tick) = self.next_tick else {
+            return false;
+        };
+        let now = Instant::now();
+        if now < next_tick {
+            return false;
+        }
+        let mut next = next_tick + self.period;
+        if next <= now {
+            next = now + self.period;
+        }
+        self.next_tick = Some(next);
+        true
+    }
+}
@@ -0,0 +1,193 @@
+// Sound effects for what happens in play. `[sound]` in the config picks the
+// output: `bell` rings the terminal bell, `tones` plays short beeps through
+// the sound device (in builds with the `sound` feature) and `off` keeps
+// quiet. Each sound has its own volume from 0 to 100, 0 muting it; the bell
+// can't be turned down, so it rings for anything above 0. The mute key
+// silences everything until it is pressed again.
+//
+// One move can set off several events at once, such as a push that presses
+// a button; only the most important of them is heard.
+use crate::{config::SoundVolumes, renderer::Renderer};
+use box_pushing_core::events::GameEvent;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SoundOutput {
+    Off,
+    Bell,
+    Tones,
+}
+
+impl std::str::FromStr for SoundOutput {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "off" => Ok(Self::Off),
+            "bell" => Ok(Self::Bell),
+            "tones" => Ok(Self::Tones),
+            _ => Err(format!("unknown sound output `{}` (off, bell or tones)", s)),
+        }
+    }
+}
+
+// Least important first.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum Sound {
+    Step,
+    Bump,
+    Push,
+    Button,
+    Door,
+    Win,
+}
+
+impl Sound {
+    pub fn for_event(event: GameEvent) -> Option<Self> {
+        Some(match event {
+            GameEvent::PlayerMoved { .. } => Self::Step,
+            GameEvent::MoveBlocked { .. } => Self::Bump,
+            GameEvent::BoxPushed { .. } | GameEvent::Grappled { .. } => Self::Push,
+            GameEvent::ButtonPressed { .. }
+            | GameEvent::ItemPickedUp { .. }
+            | GameEvent::GemCollected => Self::Button,
+            GameEvent::DoorOpened { .. }
+            | GameEvent::DoorClosed { .. }
+            | GameEvent::HoldDoor { .. } => Self::Door,
+            GameEvent::LevelWon { .. } | GameEvent::Warped { .. } => Self::Win,
+            GameEvent::LevelRestarted
+            | GameEvent::MoveUndone
+            | GameEvent::Refused { .. }
+            | GameEvent::BoxStuck { .. }
+            | GameEvent::LoadFailed { .. }
+            | GameEvent::LevelsReloaded
+            | GameEvent::ReloadFailed => return None,
+        })
+    }
+    fn volume(self, volumes: &SoundVolumes) -> u8 {
+        match self {
+            Self::Step => volumes.step,
+            Self::Bump => volumes.bump,
+            Self::Push => volumes.push,
+            Self::Button => volumes.button,
+            Self::Door => volumes.door,
+            Self::Win => volumes.win,
+        }
+    }
+    // The pitch in Hz and length in milliseconds of the beep for it.
+    #[cfg(feature = "sound")]
+    fn tone(self) -> (f32, u64) {
+        match self {
+            Self::Step => (220.0, 30),
+            Self::Bump => (110.0, 60),
+            Self::Push => (330.0, 50),
+            Self::Button => (523.0, 90),
+            Self::Door => (392.0, 150),
+            Self::Win => (784.0, 400),
+        }
+    }
+}
+
+pub struct Speaker {
+    output: SoundOutput,
+    volumes: SoundVolumes,
+    muted: bool,
+    // The sound that will be played at the end of this frame.
+    pending: Option<Sound>,
+    #[cfg(feature = "sound")]
+    tones: Option<tones::Tones>,
+}
+
+impl Speaker {
+    pub fn new(output: SoundOutput, volumes: SoundVolumes) -> Self {
+        #[cfg(feature = "sound")]
+        let tones = match output {
+            SoundOutput::Tones => tones::Tones::open(),
+            _ => None,
+        };
+        #[cfg(not(feature = "sound"))]
+        if output == SoundOutput::Tones {
+            log::warn!("built without the sound feature, so there are no tones");
+        }
+        Self {
+            output,
+            volumes,
+            muted: false,
+            pending: None,
+            #[cfg(feature = "sound")]
+            tones,
+        }
+    }
+    // Flips muting, returning whether it is now muted.
+    pub fn toggle_mute(&mut self) -> bool {
+        self.muted = !self.muted;
+        self.muted
+    }
+    // Notes an event, to be heard at the next `play` if nothing more
+    // important happens first.
+    pub fn hear(&mut self, event: GameEvent) {
+        let Some(sound) = Sound::for_event(event) else {
+            return;
+        };
+        if sound.volume(&self.volumes) > 0 {
+            self.pending = self.pending.max(Some(sound));
+        }
+    }
+    pub fn play(&mut self, window: &dyn Renderer) {
+        let Some(sound) = self.pending.take() else {
+            return;
+        };
+        if self.muted {
+            return;
+        }
+        match self.output {
+            SoundOutput::Off => (),
+            SoundOutput::Bell => window.bell(),
+            #[cfg(feature = "sound")]
+            SoundOutput::Tones => {
+                if let Some(tones) = &self.tones {
+                    tones.play(sound, sound.volume(&self.volumes));
+                }
+            }
+            #[cfg(not(feature = "sound"))]
+            SoundOutput::Tones => (),
+        }
+        log::trace!("sound {:?}", sound);
+    }
+}
+
+#[cfg(feature = "sound")]
+mod tones {
+    use super::Sound;
+    use rodio::{source::SineWave, OutputStream, OutputStreamHandle, Source};
+    use std::time::Duration;
+
+    // The stream has to be kept for as long as anything is to be heard.
+    pub struct Tones {
+        _stream: OutputStream,
+        handle: OutputStreamHandle,
+    }
+
+    impl Tones {
+        pub fn open() -> Option<Self> {
+            match OutputStream::try_default() {
+                Ok((stream, handle)) => Some(Self {
+                    _stream: stream,
+                    handle,
+                }),
+                Err(e) => {
+                    log::warn!("no sound device, so no tones: {}", e);
+                    None
+                }
+            }
+        }
+        pub fn play(&self, sound: Sound, volume: u8) {
+            let (pitch, ms) = sound.tone();
+            let beep = SineWave::new(pitch)
+                .take_duration(Duration::from_millis(ms))
+                .amplify(volume as f32 / 100.0 * 0.3);
+            if let Err(e) = self.handle.play_raw(beep) {
+                log::warn!("could not play a tone: {}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,253 @@
+// Watching someone else play, for streaming or coaching. `--broadcast ADDR`
+// makes a game take viewers on ADDR and send each of them its screen every
+// time it is redrawn; `--spectate ADDR` connects to such a game and shows
+// what its player sees, live, with no way to touch it. A frame is one JSON
+// line holding the screen's rows of text and, for each row, a letter per
+// column naming its style:
+//
+//   {"text": ["level 0: Welcome", ...], "styles": ["nnnn...", ...]}
+use crate::{
+    i18n::Strings,
+    netplay::{with_port, NetError},
+    renderer::{Key, Renderer, Style},
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cell::{Cell, RefCell},
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    sync::mpsc::{self, TryRecvError},
+    thread,
+    time::Duration,
+};
+
+pub const DEFAULT_PORT: u16 = 7879;
+// How long a viewer that stops reading may hold up the game.
+const WRITE_TIMEOUT: Duration = Duration::from_millis(200);
+// How often the spectator checks for a key to stop watching.
+const POLL_MS: i32 = 50;
+
+const STYLE_CODES: [(Style, char); 10] = [
+    (Style::Normal, 'n'),
+    (Style::Wall, 'w'),
+    (Style::PushBox, 'b'),
+    (Style::Button, 'u'),
+    (Style::ClosedDoor, 'c'),
+    (Style::OpenDoor, 'o'),
+    (Style::WinPad, 'p'),
+    (Style::Cursor, 'r'),
+    (Style::Ghost, 'g'),
+    (Style::Stuck, 's'),
+];
+
+pub fn style_code(style: Style) -> char {
+    STYLE_CODES
+        .iter()
+        .find(|&&(s, _)| s == style)
+        .map_or('n', |&(_, code)| code)
+}
+
+fn code_style(code: char) -> Style {
+    STYLE_CODES
+        .iter()
+        .find(|&&(_, c)| c == code)
+        .map_or(Style::Normal, |&(style, _)| style)
+}
+
+#[derive(Serialize, Deserialize, PartialEq)]
+struct Frame {
+    text: Vec<String>,
+    styles: Vec<String>,
+}
+
+// Wraps the game's renderer, keeping a copy of the screen to send to viewers.
+pub struct Broadcaster {
+    inner: Box<dyn Renderer>,
+    listener: TcpListener,
+    viewers: RefCell<Vec<TcpStream>>,
+    screen: RefCell<Vec<Vec<(char, Style)>>>,
+    style: Cell<Style>,
+    // The last frame sent as JSON, so an unchanged screen isn't sent again.
+    sent: RefCell<String>,
+}
+
+impl Broadcaster {
+    pub fn new(inner: Box<dyn Renderer>, addr: &str) -> io::Result<Self> {
+        let addr = with_port(addr, DEFAULT_PORT);
+        let listener = TcpListener::bind(&addr)?;
+        listener.set_nonblocking(true)?;
+        log::info!("broadcasting on {}", addr);
+        Ok(Self {
+            inner,
+            listener,
+            viewers: RefCell::new(Vec::new()),
+            screen: RefCell::new(Vec::new()),
+            style: Cell::new(Style::Normal),
+            sent: RefCell::new(String::new()),
+        })
+    }
+    fn set(&self, y: i32, x: i32, c: char) {
+        let (Ok(y), Ok(x)) = (usize::try_from(y), usize::try_from(x)) else {
+            return;
+        };
+        let mut screen = self.screen.borrow_mut();
+        if screen.len() <= y {
+            screen.resize(y + 1, Vec::new());
+        }
+        let row = &mut screen[y];
+        if row.len() <= x {
+            row.resize(x + 1, (' ', Style::Normal));
+        }
+        row[x] = (c, self.style.get());
+    }
+    fn frame(&self) -> Frame {
+        let screen = self.screen.borrow();
+        Frame {
+            text: screen
+                .iter()
+                .map(|row| row.iter().map(|&(c, _)| c).collect())
+                .collect(),
+            styles: screen
+                .iter()
+                .map(|row| row.iter().map(|&(_, style)| style_code(style)).collect())
+                .collect(),
+        }
+    }
+    // Takes on new viewers and sends the screen to every viewer that hasn't
+    // seen it yet, dropping any that have gone.
+    fn send(&self) {
+        let mut viewers = self.viewers.borrow_mut();
+        let mut joined = Vec::new();
+        while let Ok((stream, peer)) = self.listener.accept() {
+            log::info!("{} is watching", peer);
+            if stream.set_nonblocking(false).is_ok()
+                && stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_ok()
+            {
+                joined.push(stream);
+            }
+        }
+        let Ok(mut line) = serde_json::to_string(&self.frame()) else {
+            return;
+        };
+        line.push('\n');
+        let changed = *self.sent.borrow() != line;
+        if changed {
+            viewers.retain_mut(|viewer| viewer.write_all(line.as_bytes()).is_ok());
+        }
+        joined.retain_mut(|viewer| viewer.write_all(line.as_bytes()).is_ok());
+        viewers.extend(joined);
+        *self.sent.borrow_mut() = line;
+    }
+}
+
+impl Renderer for Broadcaster {
+    fn clear(&self) {
+        self.screen.borrow_mut().clear();
+        self.inner.clear();
+    }
+    fn clear_line(&self, y: i32) {
+        if let Some(row) = self.screen.borrow_mut().get_mut(y.max(0) as usize) {
+            row.clear();
+        }
+        self.inner.clear_line(y);
+    }
+    fn print(&self, y: i32, x: i32, text: &str) {
+        for (i, c) in text.chars().enumerate() {
+            self.set(y, x + i as i32, c);
+        }
+        self.inner.print(y, x, text);
+    }
+    fn put_char(&self, y: i32, x: i32, c: char) {
+        self.set(y, x, c);
+        self.inner.put_char(y, x, c);
+    }
+    fn set_style(&self, style: Style) {
+        self.style.set(style);
+        self.inner.set_style(style);
+    }
+    fn refresh(&self) {
+        self.send();
+        self.inner.refresh();
+    }
+    fn height(&self) -> i32 {
+        self.inner.height()
+    }
+    fn set_timeout(&self, ms: i32) {
+        self.inner.set_timeout(ms);
+    }
+    fn bell(&self) {
+        self.inner.bell();
+    }
+    fn read_key(&self) -> Option<Key> {
+        self.inner.read_key()
+    }
+}
+
+fn draw_frame(window: &dyn Renderer, frame: &Frame) {
+    window.clear();
+    for (y, (text, styles)) in frame.text.iter().zip(&frame.styles).enumerate() {
+        for (x, (c, code)) in text.chars().zip(styles.chars()).enumerate() {
+            window.set_style(code_style(code));
+            window.put_char(y as i32, x as i32, c);
+        }
+    }
+    window.set_style(Style::Normal);
+    window.refresh();
+}
+
+// Shows the game broadcasting at `addr` until a key is pressed, or until it
+// stops and the key after that.
+pub fn spectate(window: &dyn Renderer, strings: &Strings, addr: &str) -> Result<(), NetError> {
+    let addr = with_port(addr, DEFAULT_PORT);
+    let stream = TcpStream::connect(&addr)?;
+    let (sender, frames) = mpsc::channel();
+    thread::spawn(move || {
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            match serde_json::from_str::<Frame>(&line) {
+                Ok(frame) => {
+                    if sender.send(frame).is_err() {
+                        break;
+                    }
+                }
+                Err(e) => log::warn!("ignoring a bad frame: {}", e),
+            }
+        }
+    });
+    window.clear();
+    window.print(1, 2, &strings.fill("spectate.waiting", &[("addr", &addr)]));
+    window.refresh();
+    window.set_timeout(POLL_MS);
+    loop {
+        if window.read_key().is_some() {
+            break;
+        }
+        // Only the newest frame is worth drawing.
+        let mut newest = None;
+        loop {
+            match frames.try_recv() {
+                Ok(frame) => newest = Some(frame),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    if let Some(frame) = &newest {
+                        draw_frame(window, frame);
+                    }
+                    let row = window.height() - 1;
+                    window.clear_line(row);
+                    window.print(row, 0, strings.get("spectate.ended"));
+                    window.refresh();
+                    window.set_timeout(-1);
+                    window.read_key();
+                    return Ok(());
+                }
+            }
+        }
+        if let Some(frame) = &newest {
+            draw_frame(window, frame);
+        }
+    }
+    window.set_timeout(-1);
+    Ok(())
+}
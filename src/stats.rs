@@ -0,0 +1,208 @@
+// Lifetime totals for a profile, counted from game events and kept in its
+// `stats.txt` as `name value` lines, with time played in milliseconds. The
+// restarts and undos of each level are counted too, as
+// `level pack-id/world-file: level restarts undos` lines, so the levels
+// where a player struggles stand out.
+use crate::{i18n::Strings, renderer::Renderer};
+use box_pushing_core::events::GameEvent;
+use std::{collections::BTreeMap, fs, io, path::PathBuf, time::Duration};
+
+const LEVEL_PREFIX: &str = "level ";
+// How many of the most restarted and undone levels the statistics screen
+// shows, and the widest their bars get.
+const HEAT_ROWS: usize = 8;
+const HEAT_WIDTH: u64 = 20;
+
+#[derive(Clone, Copy, Default)]
+pub struct LevelStats {
+    pub restarts: u64,
+    pub undos: u64,
+}
+
+impl LevelStats {
+    fn heat(self) -> u64 {
+        self.restarts + self.undos
+    }
+}
+
+#[derive(Default)]
+pub struct Stats {
+    path: PathBuf,
+    pub moves: u64,
+    pub pushes: u64,
+    pub restarts: u64,
+    pub undos: u64,
+    pub levels_completed: u64,
+    pub time_played: Duration,
+    // By world key and level.
+    pub levels: BTreeMap<(String, usize), LevelStats>,
+}
+
+fn parse_level(line: &str) -> Option<((String, usize), LevelStats)> {
+    let (key, counts) = line.strip_prefix(LEVEL_PREFIX)?.rsplit_once(':')?;
+    let numbers: Vec<u64> = counts
+        .split_whitespace()
+        .map(|n| n.parse().ok())
+        .collect::<Option<_>>()?;
+    let &[level, restarts, undos] = numbers.as_slice() else {
+        return None;
+    };
+    let level_stats = LevelStats { restarts, undos };
+    Some(((key.trim().to_string(), level as usize), level_stats))
+}
+
+impl Stats {
+    // Missing or unreadable stats start from zero.
+    pub fn load(path: PathBuf) -> Self {
+        let text = fs::read_to_string(&path).unwrap_or_default();
+        Self::parse(path, &text)
+    }
+    // Reads stats as saved, to be saved back to `path`.
+    pub fn parse(path: PathBuf, text: &str) -> Self {
+        let mut stats = Self {
+            path,
+            ..Self::default()
+        };
+        for line in text.lines() {
+            if let Some((level, level_stats)) = parse_level(line) {
+                stats.levels.insert(level, level_stats);
+                continue;
+            }
+            let Some((name, value)) = line.split_once(' ') else {
+                continue;
+            };
+            let Ok(value) = value.trim().parse() else {
+                continue;
+            };
+            match name {
+                "moves" => stats.moves = value,
+                "pushes" => stats.pushes = value,
+                "restarts" => stats.restarts = value,
+                "undos" => stats.undos = value,
+                "levels_completed" => stats.levels_completed = value,
+                "time_played_ms" => stats.time_played = Duration::from_millis(value),
+                _ => (),
+            }
+        }
+        stats
+    }
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&self.path, self.to_text())
+    }
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "moves {}\npushes {}\nrestarts {}\nundos {}\nlevels_completed {}\ntime_played_ms {}\n",
+            self.moves,
+            self.pushes,
+            self.restarts,
+            self.undos,
+            self.levels_completed,
+            self.time_played.as_millis()
+        );
+        for ((key, level), counts) in &self.levels {
+            text.push_str(&format!(
+                "{}{}: {} {} {}\n",
+                LEVEL_PREFIX, key, level, counts.restarts, counts.undos
+            ));
+        }
+        text
+    }
+    // Takes the larger of each total, so importing the same stats twice
+    // doesn't count them twice.
+    pub fn merge(&mut self, other: &Stats) {
+        self.moves = self.moves.max(other.moves);
+        self.pushes = self.pushes.max(other.pushes);
+        self.restarts = self.restarts.max(other.restarts);
+        self.undos = self.undos.max(other.undos);
+        self.levels_completed = self.levels_completed.max(other.levels_completed);
+        self.time_played = self.time_played.max(other.time_played);
+        for (level, theirs) in &other.levels {
+            let ours = self.levels.entry(level.clone()).or_default();
+            ours.restarts = ours.restarts.max(theirs.restarts);
+            ours.undos = ours.undos.max(theirs.undos);
+        }
+    }
+    pub fn count(&mut self, event: GameEvent) {
+        match event {
+            GameEvent::PlayerMoved { .. } => self.moves += 1,
+            GameEvent::BoxPushed { .. } => self.pushes += 1,
+            GameEvent::LevelRestarted => self.restarts += 1,
+            GameEvent::MoveUndone => self.undos += 1,
+            GameEvent::LevelWon { .. } => self.levels_completed += 1,
+            _ => (),
+        }
+    }
+    // Counts a restart or undo against the level it happened on, in the
+    // world `world_key`.
+    pub fn count_level(&mut self, world_key: &str, level: usize, event: GameEvent) {
+        if !matches!(event, GameEvent::LevelRestarted | GameEvent::MoveUndone) {
+            return;
+        }
+        let counts = self
+            .levels
+            .entry((world_key.to_string(), level))
+            .or_default();
+        match event {
+            GameEvent::LevelRestarted => counts.restarts += 1,
+            _ => counts.undos += 1,
+        }
+    }
+}
+
+// Shows the totals until any key is pressed.
+pub fn show(window: &dyn Renderer, strings: &Strings, stats: &Stats) {
+    let secs = stats.time_played.as_secs();
+    let time = format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60);
+    let rows: [(&str, &dyn std::fmt::Display); 6] = [
+        ("stats.moves", &stats.moves),
+        ("stats.pushes", &stats.pushes),
+        ("stats.restarts", &stats.restarts),
+        ("stats.undos", &stats.undos),
+        ("stats.levels_completed", &stats.levels_completed),
+        ("stats.time_played", &time),
+    ];
+    window.clear();
+    window.print(1, 2, strings.get("stats.title"));
+    let bottom = 4 + rows.len() as i32;
+    for (i, (key, value)) in rows.into_iter().enumerate() {
+        window.print(3 + i as i32, 2, strings.get(key));
+        window.print(3 + i as i32, 24, &value.to_string());
+    }
+    // The levels restarted and undone in most, each with a bar scaled to the
+    // worst.
+    let mut hottest: Vec<(&(String, usize), &LevelStats)> = stats.levels.iter().collect();
+    hottest.sort_by_key(|(_, counts)| std::cmp::Reverse(counts.heat()));
+    hottest.truncate(HEAT_ROWS);
+    let bottom = match hottest.first() {
+        Some((_, worst)) => {
+            window.print(bottom, 2, strings.get("stats.hardest"));
+            let worst = worst.heat().max(1);
+            for (i, ((world, level), counts)) in hottest.iter().enumerate() {
+                let bar = "#".repeat((counts.heat() * HEAT_WIDTH).div_ceil(worst) as usize);
+                let label = strings.fill(
+                    "stats.level_heat",
+                    &[
+                        ("world", world),
+                        ("level", level),
+                        ("restarts", &counts.restarts),
+                        ("undos", &counts.undos),
+                    ],
+                );
+                let row = bottom + 1 + i as i32;
+                window.print(
+                    row,
+                    2,
+                    &format!("{:<width$} {}", bar, label, width = HEAT_WIDTH as usize),
+                );
+            }
+            bottom + hottest.len() as i32 + 2
+        }
+        None => bottom,
+    };
+    window.print(bottom, 2, strings.get("stats.help"));
+    window.refresh();
+    window.read_key();
+}
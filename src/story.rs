@@ -0,0 +1,82 @@
+// Story interludes between the worlds of a level pack. A story file is plain
+// text split into pages by lines of `---`. Each page types itself out a
+// letter at a time, or all at once with `reduced_motion`; a key shows the
+// rest of the page at once, and once it is all there a key turns to the next.
+// Escape skips whatever is left.
+use crate::{
+    config::Config,
+    i18n::Strings,
+    renderer::{Key, Renderer},
+};
+
+const PAGE_SEPARATOR: &str = "---";
+// How long each letter takes to appear.
+const LETTER_MS: i32 = 30;
+const TEXT_TOP: i32 = 2;
+
+pub fn pages(text: &str) -> Vec<String> {
+    let mut pages = vec![String::new()];
+    for line in text.lines() {
+        if line.trim() == PAGE_SEPARATOR {
+            pages.push(String::new());
+            continue;
+        }
+        let page = pages.last_mut().unwrap();
+        page.push_str(line);
+        page.push('\n');
+    }
+    pages
+        .into_iter()
+        .map(|page| page.trim_matches('\n').to_string())
+        .filter(|page| !page.trim().is_empty())
+        .collect()
+}
+
+// Draws the first `shown` letters of `page`, with the prompt once it is all
+// there.
+fn draw(window: &dyn Renderer, strings: &Strings, page: &str, shown: usize) {
+    window.clear();
+    let mut left = shown;
+    let mut row = TEXT_TOP;
+    for line in page.lines() {
+        let part: String = line.chars().take(left).collect();
+        window.print(row, 2, &part);
+        // The line break counts as a letter too.
+        left = left.saturating_sub(line.chars().count() + 1);
+        row += 1;
+    }
+    if shown >= page.chars().count() {
+        window.print(row + 1, 2, strings.get("story.continue"));
+    }
+    window.refresh();
+}
+
+// Shows one page, typed out unless `typed` is false. False if the player
+// skipped the story.
+fn show_page(window: &dyn Renderer, strings: &Strings, page: &str, typed: bool) -> bool {
+    let total = page.chars().count();
+    let mut shown = if typed { 0 } else { total };
+    window.set_timeout(LETTER_MS);
+    while shown < total {
+        draw(window, strings, page, shown);
+        match window.read_key() {
+            None => shown += 1,
+            Some(Key::Escape) => {
+                window.set_timeout(-1);
+                return false;
+            }
+            Some(_) => shown = total,
+        }
+    }
+    window.set_timeout(-1);
+    draw(window, strings, page, total);
+    !matches!(window.read_key(), Some(Key::Escape) | None)
+}
+
+pub fn show(window: &dyn Renderer, config: &Config, pages: &[String]) {
+    for page in pages {
+        if !show_page(window, &config.strings, page, !config.reduced_motion) {
+            break;
+        }
+    }
+}
@@ -0,0 +1,109 @@
+// A renderer with no terminal behind it, for running the game in CI or
+// scripts: the screen is kept in memory and keys are read from stdin, one
+// key name per line as written in the config (`up`, `enter`, `q`, ...).
+// A key read with a timeout set arrives once the timeout has run out, so
+// timed game logic gets to run between keys. When stdin runs out reads time
+// out until the game would wait forever; then the final screen is printed to
+// stdout and the game exits.
+use crate::renderer::{Key, Renderer, Style};
+use std::{
+    cell::{Cell, RefCell},
+    io::{self, BufRead},
+    process, thread,
+    time::Duration,
+};
+
+const WIDTH: usize = 80;
+const HEIGHT: usize = 24;
+
+pub struct StubRenderer {
+    screen: RefCell<Vec<Vec<char>>>,
+    timeout_ms: Cell<i32>,
+}
+
+impl Default for StubRenderer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StubRenderer {
+    pub fn new() -> Self {
+        Self {
+            screen: RefCell::new(vec![vec![' '; WIDTH]; HEIGHT]),
+            timeout_ms: Cell::new(-1),
+        }
+    }
+    // The screen as text, with trailing blanks trimmed.
+    pub fn contents(&self) -> String {
+        let screen = self.screen.borrow();
+        let lines: Vec<String> = screen
+            .iter()
+            .map(|row| row.iter().collect::<String>().trim_end().to_string())
+            .collect();
+        lines.join("\n").trim_end().to_string()
+    }
+}
+
+impl Renderer for StubRenderer {
+    fn clear(&self) {
+        for row in self.screen.borrow_mut().iter_mut() {
+            row.fill(' ');
+        }
+    }
+    fn clear_line(&self, y: i32) {
+        if let Some(row) = self.screen.borrow_mut().get_mut(y.max(0) as usize) {
+            row.fill(' ');
+        }
+    }
+    fn print(&self, y: i32, x: i32, text: &str) {
+        for (i, c) in text.chars().enumerate() {
+            self.put_char(y, x + i as i32, c);
+        }
+    }
+    fn put_char(&self, y: i32, x: i32, c: char) {
+        if y < 0 || x < 0 {
+            return;
+        }
+        let mut screen = self.screen.borrow_mut();
+        if let Some(cell) = screen
+            .get_mut(y as usize)
+            .and_then(|row| row.get_mut(x as usize))
+        {
+            *cell = c;
+        }
+    }
+    fn set_style(&self, _style: Style) {}
+    fn refresh(&self) {}
+    fn height(&self) -> i32 {
+        HEIGHT as i32
+    }
+    fn set_timeout(&self, ms: i32) {
+        self.timeout_ms.set(ms);
+    }
+    fn bell(&self) {}
+    fn read_key(&self) -> Option<Key> {
+        let timeout = self.timeout_ms.get();
+        if timeout >= 0 {
+            thread::sleep(Duration::from_millis(timeout as u64));
+        }
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let name = line.trim();
+            if name.is_empty() {
+                continue;
+            }
+            match name.parse() {
+                Ok(key) => return Some(key),
+                Err(e) => eprintln!("{}", e),
+            }
+        }
+        if timeout >= 0 {
+            return None;
+        }
+        println!("{}", self.contents());
+        process::exit(0);
+    }
+}
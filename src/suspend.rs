@@ -0,0 +1,61 @@
+// A level left part-way through, kept in the profile's `suspended.toml` so
+// the next launch can offer to pick it up exactly where it was: boxes,
+// doors, the player, the moves so far and their undo history. The game is
+// stored as a core save, along with the pack and world it came from so its
+// win still counts once resumed.
+use crate::play::Record;
+use box_pushing_core::{
+    game::{GameContext, GameState},
+    save,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs, io, path::Path};
+
+#[derive(Serialize, Deserialize)]
+pub struct Suspended {
+    // Where the game's wins are recorded; none for level codes and random
+    // levels.
+    pub world: Option<(String, String)>,
+    game: String,
+}
+
+impl Suspended {
+    pub fn new(game: &GameContext, record: Option<&Record>) -> Self {
+        Self {
+            world: record.map(|r| (r.pack_id.to_string(), r.world_file.to_string())),
+            game: save::to_string(game),
+        }
+    }
+    // Reads the suspended game at `path`, if there is one.
+    pub fn load(path: &Path) -> Option<Result<Self, String>> {
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return None,
+            Err(e) => return Some(Err(e.to_string())),
+        };
+        Some(toml::from_str(&text).map_err(|e| e.to_string()))
+    }
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let text = toml::to_string(self).map_err(io::Error::other)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, text)
+    }
+    // The game, back in play.
+    pub fn game(&self) -> Result<GameContext, String> {
+        let mut game = save::from_str(&self.game).map_err(|e| e.to_string())?;
+        game.state = GameState::Playing;
+        game.status = None;
+        Ok(game)
+    }
+}
+
+// Forgets the suspended game at `path`, once resumed or turned down.
+pub fn discard(path: &Path) {
+    if let Err(e) = fs::remove_file(path) {
+        if e.kind() != io::ErrorKind::NotFound {
+            log::warn!("could not remove {}: {}", path.display(), e);
+        }
+    }
+}
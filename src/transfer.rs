@@ -0,0 +1,116 @@
+// Moving a profile to another machine: everything it keeps (progress and
+// records, fastest runs, stats and settings) is exported to one TOML file,
+// which can be imported into a profile elsewhere. Importing merges rather
+// than overwrites: completed levels are joined, each record keeps the better
+// of the two, stats keep the larger totals, and settings are only taken if
+// the profile has none of its own yet.
+use crate::{profile::Profile, progress::Progress, stats::Stats};
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::Path};
+
+const EXPORT_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize)]
+struct Export {
+    version: u32,
+    // The profile it came from, for reference only.
+    profile: String,
+    progress: String,
+    stats: String,
+    config: Option<String>,
+    #[serde(default)]
+    runs: Vec<ExportedRun>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ExportedRun {
+    world: String,
+    level: usize,
+    run: String,
+}
+
+#[derive(Debug)]
+pub enum TransferError {
+    Io(io::Error),
+    Format(String),
+    Version(u32),
+}
+
+impl fmt::Display for TransferError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Format(e) => write!(f, "not a profile export: {}", e),
+            Self::Version(v) => write!(f, "profile export version {} is not supported", v),
+        }
+    }
+}
+
+impl From<io::Error> for TransferError {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+// What an import changed.
+pub struct Imported {
+    pub levels_improved: usize,
+    pub settings: bool,
+}
+
+pub fn export(profile: &Profile, path: &Path) -> Result<(), TransferError> {
+    let progress = Progress::load(profile.progress_path());
+    let export = Export {
+        version: EXPORT_VERSION,
+        profile: profile.name().to_string(),
+        progress: progress.to_text(),
+        stats: Stats::load(profile.stats_path()).to_text(),
+        config: fs::read_to_string(profile.config_path()).ok(),
+        runs: progress
+            .run_texts()
+            .into_iter()
+            .map(|(world, level, run)| ExportedRun { world, level, run })
+            .collect(),
+    };
+    let text = toml::to_string(&export).map_err(|e| TransferError::Format(e.to_string()))?;
+    fs::write(path, text)?;
+    Ok(())
+}
+
+pub fn import(profile: &Profile, path: &Path) -> Result<Imported, TransferError> {
+    let text = fs::read_to_string(path)?;
+    let export: Export = toml::from_str(&text).map_err(|e| TransferError::Format(e.to_string()))?;
+    if export.version != EXPORT_VERSION {
+        return Err(TransferError::Version(export.version));
+    }
+
+    let mut progress = Progress::load(profile.progress_path());
+    let theirs = Progress::parse(profile.progress_path(), &export.progress);
+    let (levels_improved, faster) = progress.merge(&theirs);
+    for run in &export.runs {
+        if faster.contains(&(run.world.clone(), run.level)) {
+            progress.save_run_text(&run.world, run.level, &run.run)?;
+        }
+    }
+    progress.save()?;
+
+    let mut stats = Stats::load(profile.stats_path());
+    stats.merge(&Stats::parse(profile.stats_path(), &export.stats));
+    stats.save()?;
+
+    let config_path = profile.config_path();
+    let settings = match &export.config {
+        Some(config) if !config_path.exists() => {
+            if let Some(dir) = config_path.parent() {
+                fs::create_dir_all(dir)?;
+            }
+            fs::write(&config_path, config)?;
+            true
+        }
+        _ => false,
+    };
+    Ok(Imported {
+        levels_improved,
+        settings,
+    })
+}
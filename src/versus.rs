@@ -0,0 +1,251 @@
+// Versus races: two players race the same level side by side, each on their
+// own copy of it, and whoever reaches a win pad first takes the round. Rounds
+// go through the levels in turn until one player has won most of a best-of
+// series. At one keyboard player one plays the left side with the move keys
+// and player two the right side with the partner keys (WASD); over the
+// network each game plays its own side on the left, and the other game's
+// moves arrive through the `NetLink`.
+use crate::{
+    config::Config,
+    draw,
+    netplay::NetLink,
+    renderer::{Key, Renderer, Style},
+};
+use box_pushing_core::{
+    action::GameAction,
+    game::{GameContext, TICK_MS},
+    map::MapData,
+};
+
+// Columns between the two sides.
+const GAP: i32 = 4;
+
+pub struct Series {
+    pub names: [String; 2],
+    pub wins: [u32; 2],
+    best_of: u32,
+}
+
+impl Series {
+    pub fn new(names: [String; 2], best_of: u32) -> Self {
+        Self {
+            names,
+            wins: [0; 2],
+            best_of: best_of.max(1),
+        }
+    }
+    // The player who has won most of the rounds, if either has.
+    pub fn winner(&self) -> Option<usize> {
+        (0..2).find(|&i| self.wins[i] > self.best_of / 2)
+    }
+}
+
+// Draws onto one side of the screen, `left` columns in.
+struct Panel<'a> {
+    window: &'a dyn Renderer,
+    left: i32,
+}
+
+impl Renderer for Panel<'_> {
+    // The sides share the screen, which is cleared and refreshed as a whole.
+    fn clear(&self) {}
+    fn clear_line(&self, _y: i32) {}
+    fn print(&self, y: i32, x: i32, text: &str) {
+        self.window.print(y, x + self.left, text);
+    }
+    fn put_char(&self, y: i32, x: i32, c: char) {
+        self.window.put_char(y, x + self.left, c);
+    }
+    fn set_style(&self, style: Style) {
+        self.window.set_style(style);
+    }
+    fn refresh(&self) {}
+    fn height(&self) -> i32 {
+        self.window.height()
+    }
+    fn set_timeout(&self, ms: i32) {
+        self.window.set_timeout(ms);
+    }
+    fn bell(&self) {
+        self.window.bell();
+    }
+    fn read_key(&self) -> Option<Key> {
+        self.window.read_key()
+    }
+}
+
+fn draw(
+    window: &dyn Renderer,
+    config: &Config,
+    series: &Series,
+    boards: &[GameContext; 2],
+    banner: Option<&str>,
+) {
+    let strings = &config.strings;
+    window.clear();
+    window.print(
+        0,
+        0,
+        &strings.fill(
+            "versus.score",
+            &[
+                ("first", &series.names[0]),
+                ("second", &series.names[1]),
+                ("first_wins", &series.wins[0]),
+                ("second_wins", &series.wins[1]),
+                ("best_of", &series.best_of),
+            ],
+        ),
+    );
+    if let Some(banner) = banner {
+        window.print(1, 0, banner);
+    }
+    let width = boards[0].map_data.bounds().bottom_right.x + 1;
+    for (i, board) in boards.iter().enumerate() {
+        let panel = Panel {
+            window,
+            left: i as i32 * (width + GAP),
+        };
+        panel.print(2, 0, &series.names[i]);
+        draw::draw_game(&panel, config, board);
+    }
+    window.refresh();
+}
+
+// Whether an action is carried out on a side. Pausing, the editor and the
+// like have no place in a race.
+fn plays(action: GameAction) -> bool {
+    action.direction().is_some()
+        || matches!(
+            action,
+            GameAction::Undo | GameAction::Redo | GameAction::Restart
+        )
+}
+
+// Runs one round until someone wins it, returning which side did, or None
+// if it was left or the other game went away.
+fn race(
+    window: &dyn Renderer,
+    config: &Config,
+    series: &Series,
+    boards: &mut [GameContext; 2],
+    mut link: Option<&mut NetLink>,
+) -> Option<usize> {
+    // Over the network the other side moves on its own time.
+    window.set_timeout(match link {
+        Some(_) => TICK_MS as i32,
+        None => -1,
+    });
+    loop {
+        draw(window, config, series, boards, None);
+        let action = window.read_key().and_then(|key| config.keys.action(key));
+        if action == Some(GameAction::Menu) {
+            return None;
+        }
+        match (action, link.as_deref_mut()) {
+            (Some(action), Some(link)) => {
+                if let Some(action) = link.local(action).filter(|&a| plays(a)) {
+                    boards[0].perform(action);
+                }
+            }
+            (Some(action), None) => {
+                if let Some(direction) = action.direction() {
+                    boards[0].player_movement(direction);
+                } else if let Some(direction) = action.partner_direction() {
+                    boards[1].player_movement(direction);
+                }
+            }
+            (None, _) => (),
+        }
+        if let Some(link) = link.as_deref_mut() {
+            while let Some(action) = link.poll() {
+                boards[1].perform(action);
+            }
+            if link.is_closed() {
+                return None;
+            }
+        }
+        let finished = boards.each_mut().map(|board| {
+            let won = board.update_all().is_some();
+            board.events.drain();
+            won
+        });
+        let first = finished.iter().position(|&won| won);
+        // Over the network the host judges; the joining game is the one on
+        // the right as far as the host is concerned.
+        let winner = match link.as_deref_mut() {
+            None => first,
+            Some(link) if link.is_host() => {
+                if let Some(side) = first {
+                    link.send_verdict(side == 0);
+                }
+                first
+            }
+            Some(link) => link.take_verdict().map(usize::from),
+        };
+        if winner.is_some() {
+            window.set_timeout(-1);
+            return winner;
+        }
+    }
+}
+
+// Plays rounds from `first_level` on until the series is won. Returns a
+// message for the menu if it ended early because the other game left.
+pub fn run(
+    window: &dyn Renderer,
+    config: &Config,
+    maps: &[MapData],
+    first_level: usize,
+    mut series: Series,
+    mut link: Option<&mut NetLink>,
+) -> Option<String> {
+    let strings = &config.strings;
+    if maps.is_empty() {
+        return None;
+    }
+    let mut round = 0;
+    while series.winner().is_none() {
+        let level = (first_level + round) % maps.len();
+        let mut boards = [0, 1].map(|_| GameContext::new(vec![maps[level].clone()], None));
+        let Some(winner) = race(window, config, &series, &mut boards, link.as_deref_mut()) else {
+            window.set_timeout(-1);
+            return match link.as_deref().is_some_and(NetLink::is_closed) {
+                true => Some(strings.get("net.lost").to_string()),
+                false => None,
+            };
+        };
+        series.wins[winner] += 1;
+        round += 1;
+        let banner = strings.fill("versus.round_won", &[("name", &series.names[winner])]);
+        draw(window, config, &series, &boards, Some(&banner));
+        window.read_key();
+        if let Some(link) = link.as_deref_mut() {
+            link.next_round();
+        }
+    }
+    let winner = series.winner().map_or("", |i| series.names[i].as_str());
+    window.clear();
+    window.print(
+        1,
+        2,
+        &strings.fill("versus.series_won", &[("name", &winner)]),
+    );
+    window.print(
+        3,
+        2,
+        &strings.fill(
+            "versus.final",
+            &[
+                ("first", &series.names[0]),
+                ("second", &series.names[1]),
+                ("first_wins", &series.wins[0]),
+                ("second_wins", &series.wins[1]),
+            ],
+        ),
+    );
+    window.print(5, 2, strings.get("stats.help"));
+    window.refresh();
+    window.read_key();
+    None
+}
@@ -0,0 +1,105 @@
+// The game in a browser: a thin wasm-bindgen wrapper around the core that
+// web/index.html drives and draws as text. Build it with
+//
+//   wasm-pack build web --target web
+//
+// and serve the `web` directory. A level shared as `index.html?code=...`
+// opens that level; without a code the built-in levels are played.
+use box_pushing_core::{
+    action::GameAction,
+    events::GameEvent,
+    game::{GameContext, GameState},
+    map, share_code,
+    thumbnail::thumbnail,
+};
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+pub struct WebGame {
+    game: GameContext,
+}
+
+#[wasm_bindgen]
+impl WebGame {
+    #[wasm_bindgen(constructor)]
+    pub fn new(code: Option<String>) -> Result<WebGame, JsError> {
+        let maps = match code {
+            Some(code) => {
+                vec![share_code::decode(&code).map_err(|e| JsError::new(&e.to_string()))?]
+            }
+            None => map::get_maps(),
+        };
+        Ok(Self {
+            game: GameContext::new(maps, None),
+        })
+    }
+    // Takes a `KeyboardEvent.key` name. Keys that mean nothing are ignored.
+    pub fn key(&mut self, key: &str) {
+        let game = &mut self.game;
+        if let GameState::LevelComplete { .. } = game.state {
+            game.next_level();
+            return;
+        }
+        if game.state != GameState::Playing {
+            return;
+        }
+        let action = match key {
+            "ArrowUp" => GameAction::MoveUp,
+            "ArrowDown" => GameAction::MoveDown,
+            "ArrowLeft" => GameAction::MoveLeft,
+            "ArrowRight" => GameAction::MoveRight,
+            "w" => GameAction::PartnerUp,
+            "s" => GameAction::PartnerDown,
+            "a" => GameAction::PartnerLeft,
+            "d" => GameAction::PartnerRight,
+            "r" => GameAction::Restart,
+            "z" => GameAction::Undo,
+            "y" => GameAction::Redo,
+            "g" => GameAction::Grapple,
+            "1" => GameAction::UsePhase,
+            "2" => GameAction::UseUndo,
+            _ => return,
+        };
+        game.perform(action);
+        game.update_all();
+        for event in game.events.drain() {
+            if let GameEvent::ButtonPressed { .. }
+            | GameEvent::DoorOpened { .. }
+            | GameEvent::DoorClosed { .. }
+            | GameEvent::ItemPickedUp { .. }
+            | GameEvent::GemCollected
+            | GameEvent::Warped { .. }
+            | GameEvent::HoldDoor { .. }
+            | GameEvent::Refused { .. }
+            | GameEvent::LevelWon { .. } = event
+            {
+                game.status = Some(event.to_string());
+            }
+        }
+    }
+    // The board as text, one row per line, with the player drawn on it.
+    pub fn board(&self) -> String {
+        let mut map = self.game.map_data.clone();
+        map.player_spawn = self.game.player.pos();
+        thumbnail(&map, usize::MAX, usize::MAX).join("\n")
+    }
+    // The line shown above the board: a banner, the last event or the flavor text.
+    pub fn status(&self) -> String {
+        match self.game.state {
+            GameState::LevelComplete { level } => {
+                format!("level {} complete! press any key", level)
+            }
+            GameState::GameOver => "every level complete!".to_string(),
+            _ => self.game.status.clone().unwrap_or_else(|| {
+                let flavor = self.game.flavor("").unwrap_or("");
+                format!("level {}: {}", self.game.level, flavor)
+            }),
+        }
+    }
+    // A code for the current level as it starts, for building a share link.
+    pub fn share_code(&self) -> String {
+        let game = &self.game;
+        let map = game.map_list.get(game.level as usize);
+        share_code::encode(map.unwrap_or(&game.map_data))
+    }
+}